@@ -1,17 +1,27 @@
 #![allow(non_snake_case)]
 
 use objc2_core_foundation::{
-    CFAllocator, CFArray, CFIndex, CFOptionFlags, CFRetained, CFString, CFType, Type,
+    CFAllocator, CFArray, CFDictionary, CFIndex, CFOptionFlags, CFRetained, CFString, CFType, Type,
+    CFURL,
+};
+use std::{
+    cell::UnsafeCell,
+    ffi::c_void,
+    marker::{PhantomData, PhantomPinned},
+    ptr::NonNull,
 };
-use std::{ffi::c_void, ptr::NonNull};
 
+/// Opaque `MDQueryRef`. Not `Send`/`Sync` by itself; see `MDQuery` for the thread-safety
+/// argument.
 #[repr(C)]
-pub(super) struct CoreMDQuery([u8; 0]);
+pub(super) struct CoreMDQuery([u8; 0], PhantomData<(*const UnsafeCell<()>, PhantomPinned)>);
 
 unsafe impl Type for CoreMDQuery {}
 
+/// Opaque `MDItemRef`. Not `Send`/`Sync` by itself; see `MDItem` for the thread-safety
+/// argument.
 #[repr(C)]
-pub(super) struct CoreMDItem([u8; 0]);
+pub(super) struct CoreMDItem([u8; 0], PhantomData<(*const UnsafeCell<()>, PhantomPinned)>);
 
 unsafe impl Type for CoreMDItem {}
 
@@ -42,18 +52,19 @@ pub(super) unsafe extern "C-unwind" fn MDQueryCreate(
     ret.map(|ret| unsafe { CFRetained::from_raw(ret) })
 }
 
+// https://developer.apple.com/documentation/coreservices/1427106-mditemcreatewithurl?language=objc
 #[inline]
-pub(super) unsafe extern "C-unwind" fn MDItemCreate(
+pub(super) unsafe extern "C-unwind" fn MDItemCreateWithURL(
     allocator: Option<&CFAllocator>,
-    path: &CFString,
+    url: &CFURL,
 ) -> Option<CFRetained<CoreMDItem>> {
     extern "C-unwind" {
-        fn MDItemCreate(
+        fn MDItemCreateWithURL(
             allocator: Option<&CFAllocator>,
-            path: Option<&CFString>,
+            url: Option<&CFURL>,
         ) -> Option<NonNull<CoreMDItem>>;
     }
-    let ret = unsafe { MDItemCreate(allocator, Some(path)) };
+    let ret = unsafe { MDItemCreateWithURL(allocator, Some(url)) };
     ret.map(|ret| unsafe { CFRetained::from_raw(ret) })
 }
 
@@ -80,8 +91,132 @@ pub(super) unsafe extern "C-unwind" fn MDItemCopyAttribute(
     ret.map(|ret| unsafe { CFRetained::from_raw(ret) })
 }
 
+// https://developer.apple.com/documentation/coreservices/1427080-mditemcopyattributes?language=objc
+#[inline]
+pub(super) unsafe extern "C-unwind" fn MDItemCopyAttributes(
+    item: &CoreMDItem,
+    names: &CFArray,
+) -> Option<CFRetained<CFDictionary>> {
+    extern "C-unwind" {
+        fn MDItemCopyAttributes(
+            item: &CoreMDItem,
+            names: &CFArray,
+        ) -> Option<NonNull<CFDictionary>>;
+    }
+    let ret = unsafe { MDItemCopyAttributes(item, names) };
+    ret.map(|ret| unsafe { CFRetained::from_raw(ret) })
+}
+
+// https://developer.apple.com/documentation/coreservices/1413096-mdquerycopyvaluesofattribute?language=objc
+#[inline]
+pub(super) unsafe extern "C-unwind" fn MDQueryCopyValuesOfAttribute(
+    query: &CoreMDQuery,
+    name: &CFString,
+) -> Option<CFRetained<CFArray>> {
+    extern "C-unwind" {
+        fn MDQueryCopyValuesOfAttribute(
+            query: &CoreMDQuery,
+            name: &CFString,
+        ) -> Option<NonNull<CFArray>>;
+    }
+    let ret = unsafe { MDQueryCopyValuesOfAttribute(query, name) };
+    ret.map(|ret| unsafe { CFRetained::from_raw(ret) })
+}
+
+// MDSchema.h: the attributes declared by the schema of all installed metadata importers.
+#[inline]
+pub(super) unsafe extern "C-unwind" fn MDSchemaCopyAllAttributes() -> Option<CFRetained<CFArray>> {
+    extern "C-unwind" {
+        fn MDSchemaCopyAllAttributes() -> Option<NonNull<CFArray>>;
+    }
+    let ret = unsafe { MDSchemaCopyAllAttributes() };
+    ret.map(|ret| unsafe { CFRetained::from_raw(ret) })
+}
+
+// MDSchema.h: the localized display name of an attribute.
+#[inline]
+pub(super) unsafe extern "C-unwind" fn MDSchemaCopyDisplayNameForAttribute(
+    name: &CFString,
+) -> Option<CFRetained<CFString>> {
+    extern "C-unwind" {
+        fn MDSchemaCopyDisplayNameForAttribute(name: &CFString) -> Option<NonNull<CFString>>;
+    }
+    let ret = unsafe { MDSchemaCopyDisplayNameForAttribute(name) };
+    ret.map(|ret| unsafe { CFRetained::from_raw(ret) })
+}
+
+// MDSchema.h: the localized description of an attribute.
+#[inline]
+pub(super) unsafe extern "C-unwind" fn MDSchemaCopyDisplayDescriptionForAttribute(
+    name: &CFString,
+) -> Option<CFRetained<CFString>> {
+    extern "C-unwind" {
+        fn MDSchemaCopyDisplayDescriptionForAttribute(name: &CFString)
+            -> Option<NonNull<CFString>>;
+    }
+    let ret = unsafe { MDSchemaCopyDisplayDescriptionForAttribute(name) };
+    ret.map(|ret| unsafe { CFRetained::from_raw(ret) })
+}
+
+// MDSchema.h: the type and multiplicity of an attribute, keyed by `kMDAttributeType` and
+// `kMDAttributeMultiValued`.
+#[inline]
+pub(super) unsafe extern "C-unwind" fn MDSchemaCopyMetaAttributesForAttribute(
+    name: &CFString,
+) -> Option<CFRetained<CFDictionary>> {
+    extern "C-unwind" {
+        fn MDSchemaCopyMetaAttributesForAttribute(name: &CFString)
+            -> Option<NonNull<CFDictionary>>;
+    }
+    let ret = unsafe { MDSchemaCopyMetaAttributesForAttribute(name) };
+    ret.map(|ret| unsafe { CFRetained::from_raw(ret) })
+}
+
+// https://developer.apple.com/documentation/coreservices/mdquerybatchingparams?language=objc
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub(super) struct MDQueryBatchingParams {
+    pub first_max_num: usize,
+    pub first_max_ms: usize,
+    pub progress_max_num: usize,
+    pub progress_max_ms: usize,
+    pub update_max_num: usize,
+    pub update_max_ms: usize,
+}
+
 #[link(name = "CoreServices", kind = "framework")]
 extern "C" {
+    // https://developer.apple.com/documentation/coreservices/kmdqueryprogressnotification?language=objc
+    pub(super) static kMDQueryProgressNotification: &'static CFString;
+
+    // https://developer.apple.com/documentation/coreservices/kmdquerydidfinishnotification?language=objc
+    pub(super) static kMDQueryDidFinishNotification: &'static CFString;
+
+    // https://developer.apple.com/documentation/coreservices/kmdqueryresultcontentrelevance?language=objc
+    pub(super) static kMDQueryResultContentRelevance: &'static CFString;
+
+    // MDSchema.h: the key of the CFTypeID of an attribute's values in its meta attributes.
+    pub(super) static kMDAttributeType: &'static CFString;
+
+    // MDSchema.h: the key of whether an attribute holds an array in its meta attributes.
+    pub(super) static kMDAttributeMultiValued: &'static CFString;
+
+    // https://developer.apple.com/documentation/coreservices/kmdquerydidupdatenotification?language=objc
+    #[cfg(feature = "async")]
+    pub(super) static kMDQueryDidUpdateNotification: &'static CFString;
+
+    // https://developer.apple.com/documentation/coreservices/kmdqueryupdateaddeditems?language=objc
+    #[cfg(feature = "async")]
+    pub(super) static kMDQueryUpdateAddedItems: &'static CFString;
+
+    // https://developer.apple.com/documentation/coreservices/kmdqueryupdatechangeditems?language=objc
+    #[cfg(feature = "async")]
+    pub(super) static kMDQueryUpdateChangedItems: &'static CFString;
+
+    // https://developer.apple.com/documentation/coreservices/kmdqueryupdateremoveditems?language=objc
+    #[cfg(feature = "async")]
+    pub(super) static kMDQueryUpdateRemovedItems: &'static CFString;
+
     // https://developer.apple.com/documentation/coreservices/1413048-mdquerysetsearchscope?language=objc
     pub(super) fn MDQuerySetSearchScope(
         query: &CoreMDQuery,
@@ -92,12 +227,31 @@ extern "C" {
     // https://developer.apple.com/documentation/coreservices/1413085-mdquerysetmaxcount?language=objc
     pub(super) fn MDQuerySetMaxCount(query: &CoreMDQuery, max_count: CFIndex);
 
+    // https://developer.apple.com/documentation/coreservices/1413070-mdquerysetbatchingparameters?language=objc
+    pub(super) fn MDQuerySetBatchingParameters(query: &CoreMDQuery, params: MDQueryBatchingParams);
+
     // https://developer.apple.com/documentation/coreservices/1413099-mdqueryexecute?language=objc
     pub(super) fn MDQueryExecute(query: &CoreMDQuery, option_flags: CFOptionFlags) -> bool;
 
+    // https://developer.apple.com/documentation/coreservices/1413029-mdquerystop?language=objc
+    pub(super) fn MDQueryStop(query: &CoreMDQuery);
+
+    // https://developer.apple.com/documentation/coreservices/1413074-mdquerydisableupdates?language=objc
+    pub(super) fn MDQueryDisableUpdates(query: &CoreMDQuery);
+
+    // https://developer.apple.com/documentation/coreservices/1413027-mdqueryenableupdates?language=objc
+    pub(super) fn MDQueryEnableUpdates(query: &CoreMDQuery);
+
     // https://developer.apple.com/documentation/coreservices/1413008-mdquerygetresultcount?language=objc
     pub(super) fn MDQueryGetResultCount(query: &CoreMDQuery) -> CFIndex;
 
     // https://developer.apple.com/documentation/coreservices/1413055-mdquerygetresultatindex?language=objc
     pub(super) fn MDQueryGetResultAtIndex(query: &CoreMDQuery, index: CFIndex) -> *const c_void;
+
+    // https://developer.apple.com/documentation/coreservices/1413093-mdquerygetattributevalueofresult?language=objc
+    pub(super) fn MDQueryGetAttributeValueOfResultAtIndex(
+        query: &CoreMDQuery,
+        name: &CFString,
+        index: CFIndex,
+    ) -> *const c_void;
 }
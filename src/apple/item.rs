@@ -1,20 +1,116 @@
-use super::{api::*, MDItemKey};
+use super::api::*;
+#[cfg(feature = "nsmetadata")]
+use super::as_object;
+use super::names::{attribute_name, key_name};
+use super::query::cf_array_of_strings;
+#[cfg(feature = "uttype")]
+use super::UTType;
+use crate::model::{absolutize, icloud_directory, PACKAGE_CONTENT_TYPES};
+use crate::{
+    ContentKind, DocumentInfo, FinderLabel, ImageOrientation, MDAttributeValue, MDItemKey,
+    VideoInfo,
+};
 use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+#[cfg(feature = "nsmetadata")]
+use objc2::{msg_send, rc::autoreleasepool, rc::Retained, runtime::AnyObject};
 use objc2_core_foundation::{
-    CFArray, CFArrayGetCount, CFArrayGetValueAtIndex, CFIndex, CFRetained, CFString, ConcreteType,
+    CFArray, CFArrayGetCount, CFArrayGetValueAtIndex, CFBoolean, CFDate, CFDictionary,
+    CFDictionaryGetValue, CFIndex, CFNumber, CFNumberIsFloatType, CFRetained, CFString, CFType,
+    CFURLBookmarkResolutionOptions, CFURLCopyFileSystemPath, CFURLCreateBookmarkDataFromFile,
+    CFURLCreateByResolvingBookmarkData, CFURLCreateFromFileSystemRepresentation, CFURLPathStyle,
+    ConcreteType, CFURL,
 };
 use std::{
+    ffi::{c_void, CStr, OsStr},
+    fmt,
+    hash::{Hash, Hasher},
+    mem,
+    os::unix::ffi::OsStrExt,
     path::{Path, PathBuf},
-    ptr::NonNull,
+    ptr::{self, NonNull},
+    sync::OnceLock,
+    time::Duration,
 };
 
 /// A wrapper around macOS Metadata Item (MDItem).
 /// Provides access to file and directory metadata through the Spotlight metadata framework.
-pub struct MDItem(CFRetained<CoreMDItem>);
+///
+/// The path, display name, content type and content type tree are cached after they are
+/// first read. Call [`refresh`](Self::refresh) to re-read them after the file changes.
+///
+/// Two items are equal if they have the same [`path`](Self::path). Items without a path,
+/// such as Mail messages, are only equal to themselves and their clones.
+#[derive(Clone)]
+pub struct MDItem {
+    item: ItemSource,
+    cache: AttributeCache,
+}
+
+/// The Spotlight object the attributes of an `MDItem` are read from.
+#[derive(Clone)]
+enum ItemSource {
+    /// An `MDItemRef` from Core Services.
+    Core(CFRetained<CoreMDItem>),
+    /// An `NSMetadataItem` returned by an `NSMetadataQuery`.
+    #[cfg(feature = "nsmetadata")]
+    NsMetadata(Retained<AnyObject>),
+}
+
+// SAFETY: An `MDItemRef` is an immutable CoreFoundation object: this wrapper only reads
+// attributes from it and never mutates it, and MDItem's copy functions are documented as
+// thread-safe. An `NSMetadataItem` is only handed out once its query has stopped, after
+// which its attributes no longer change, and is only read from as well. Retain counts
+// are atomic, so cloning (retaining) and dropping (releasing) from any thread is sound.
+// The attribute cache is made of `OnceLock`s, which are themselves `Send` and `Sync`.
+unsafe impl Send for MDItem {}
+unsafe impl Sync for MDItem {}
+
+impl PartialEq for MDItem {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.path(), other.path()) {
+            (Some(path), Some(other_path)) => path == other_path,
+            (None, None) => self.item.as_ptr() == other.item.as_ptr(),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for MDItem {}
+
+/// Shows the path, which items are compared by.
+impl fmt::Debug for MDItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MDItem").field("path", &self.path()).finish()
+    }
+}
+
+impl Hash for MDItem {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self.path() {
+            Some(path) => path.hash(state),
+            None => self.item.as_ptr().hash(state),
+        }
+    }
+}
+
+/// Lazily populated values of frequently read attributes.
+#[derive(Clone, Default)]
+struct AttributeCache {
+    path: OnceLock<Option<PathBuf>>,
+    display_name: OnceLock<Option<String>>,
+    content_type: OnceLock<Option<String>>,
+    content_type_tree: OnceLock<Option<Vec<String>>>,
+}
 
 impl MDItem {
     /// Creates a new MDItem from a file path.
     ///
+    /// Absolute paths are used as given. Relative paths are canonicalized, which resolves
+    /// them against the current directory and follows symlinks. Use
+    /// [`from_path_unresolved`](Self::from_path_unresolved) to never touch the filesystem
+    /// before creating the item.
+    ///
     /// # Arguments
     /// * `path` - A path to a file or directory
     ///
@@ -24,11 +120,90 @@ impl MDItem {
     /// # Errors
     /// * Returns an error if the path is invalid or if the MDItem creation fails
     pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let path = path.as_ref().canonicalize()?;
-        let path_str = CFString::from_str(path.to_str().ok_or(anyhow!("Invalid path"))?);
+        let path = path.as_ref();
+        if path.is_absolute() {
+            Self::create(path)
+        } else {
+            Self::create(&path.canonicalize()?)
+        }
+    }
+
+    /// Creates a new MDItem from a file path without resolving symlinks.
+    ///
+    /// Relative paths are made absolute lexically: they are joined to the current directory
+    /// and `.` and `..` components are removed without consulting the filesystem. A
+    /// symlink therefore yields an item for the link itself rather than its destination.
+    ///
+    /// # Arguments
+    /// * `path` - A path to a file or directory
+    ///
+    /// # Returns
+    /// * `Result<Self>` - A new MDItem instance or an error
+    ///
+    /// # Errors
+    /// * Returns an error if the current directory is unavailable, the path is invalid or
+    ///   the MDItem creation fails
+    pub fn from_path_unresolved<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::create(&absolutize(path.as_ref())?)
+    }
+
+    /// Creates a new MDItem from a file URL.
+    ///
+    /// This is useful when a `CFURL` is already at hand, e.g. from a file picker or a drop
+    /// operation, and avoids converting it to a path string first.
+    ///
+    /// # Arguments
+    /// * `url` - A file URL pointing to a file or directory
+    ///
+    /// # Returns
+    /// * `Result<Self>` - A new MDItem instance or an error
+    ///
+    /// # Errors
+    /// * Returns an error if the MDItem creation fails
+    pub fn from_url(url: &CFURL) -> Result<Self> {
         let item =
-            unsafe { MDItemCreate(None, &path_str) }.ok_or(anyhow!("Failed to create MDItem"))?;
-        Ok(Self(item))
+            unsafe { MDItemCreateWithURL(None, url) }.ok_or(anyhow!("Failed to create MDItem"))?;
+        Ok(Self::from_retained(item))
+    }
+
+    /// Creates an MDItem from an absolute path.
+    fn create(path: &Path) -> Result<Self> {
+        let path = path.as_os_str().as_bytes();
+        let url = unsafe {
+            CFURLCreateFromFileSystemRepresentation(
+                None,
+                path.as_ptr(),
+                path.len() as CFIndex,
+                false,
+            )
+        }
+        .ok_or(anyhow!("Invalid path"))?;
+        Self::from_url(&url)
+    }
+
+    /// Wraps an item returned by Spotlight, which need not correspond to a file.
+    pub(super) fn from_retained(item: CFRetained<CoreMDItem>) -> Self {
+        Self {
+            item: ItemSource::Core(item),
+            cache: AttributeCache::default(),
+        }
+    }
+
+    /// Wraps an `NSMetadataItem` returned by a stopped `NSMetadataQuery`.
+    #[cfg(feature = "nsmetadata")]
+    pub(super) fn from_ns_metadata_item(item: Retained<AnyObject>) -> Self {
+        Self {
+            item: ItemSource::NsMetadata(item),
+            cache: AttributeCache::default(),
+        }
+    }
+
+    /// Clears the cached attribute values, so they are read again on next access.
+    ///
+    /// Use this when the underlying file is known to have changed, e.g. after it was
+    /// renamed.
+    pub fn refresh(&mut self) {
+        self.cache = AttributeCache::default();
     }
 
     /// Retrieves all available attribute names for this MDItem.
@@ -36,19 +211,9 @@ impl MDItem {
     /// # Returns
     /// * `Vec<String>` - A vector of attribute name strings
     pub fn get_attribute_names(&self) -> Vec<String> {
-        unsafe { MDItemCopyAttributeNames(&self.0) }
-            .map(|array| unsafe {
-                let count = CFArrayGetCount(&array) as usize;
-                let mut names = Vec::with_capacity(count);
-                for i in 0..count {
-                    let name_ptr = CFArrayGetValueAtIndex(&array, i as CFIndex);
-                    if let Some(cf_name) = NonNull::new(name_ptr as *mut CFString) {
-                        let name = cf_name.as_ref().to_string();
-                        names.push(name);
-                    }
-                }
-                names
-            })
+        self.item
+            .copy_attribute_names()
+            .map(|array| cf_array_to_strings(&array))
             .unwrap_or_default()
     }
 
@@ -60,21 +225,139 @@ impl MDItem {
     /// # Returns
     /// * `Option<CFRetained<T>>` - The attribute value cast to the specified type, or None if not available
     pub fn get_attribute<T: Sized + ConcreteType>(&self, name: &str) -> Option<CFRetained<T>> {
-        let name = CFString::from_str(name);
-        let value = unsafe { MDItemCopyAttribute(&self.0, &name) }?;
+        let name = attribute_name(name);
+        let value = self.item.copy_attribute(&name)?;
         value.downcast::<T>().ok()
     }
 
+    /// Gets an attribute as a typed value, whatever its CoreFoundation type.
+    ///
+    /// Strings, numbers, booleans, dates and arrays of those are supported.
+    ///
+    /// # Arguments
+    /// * `key` - The attribute to retrieve
+    ///
+    /// # Returns
+    /// * `Option<MDAttributeValue>` - The attribute value, or None if not available or of
+    ///   an unsupported type
+    pub fn attribute_value(&self, key: MDItemKey) -> Option<MDAttributeValue> {
+        self.get_attribute_value(key.as_str())
+    }
+
+    /// Gets an attribute by name as a typed value.
+    ///
+    /// Works like [`attribute_value`](Self::attribute_value) for attributes without an
+    /// [`MDItemKey`], such as the names returned by
+    /// [`get_attribute_names`](Self::get_attribute_names).
+    ///
+    /// # Arguments
+    /// * `name` - The name of the attribute to retrieve
+    ///
+    /// # Returns
+    /// * `Option<MDAttributeValue>` - The attribute value, or None if not available or of
+    ///   an unsupported type
+    pub fn get_attribute_value(&self, name: &str) -> Option<MDAttributeValue> {
+        let name = attribute_name(name);
+        let value = self.item.copy_attribute(&name)?;
+        cf_type_to_value(&value)
+    }
+
+    /// Fetches several attributes in a single call.
+    ///
+    /// # Arguments
+    /// * `keys` - The attributes to retrieve
+    ///
+    /// # Returns
+    /// * `Option<CFRetained<CFDictionary>>` - The attribute values keyed by name, or None on failure
+    fn copy_attributes(&self, keys: &[MDItemKey]) -> Option<CFRetained<CFDictionary>> {
+        let names = keys.iter().map(|key| key_name(*key)).collect::<Vec<_>>();
+        let names = cf_array_of_strings(&names)?;
+        self.item.copy_attributes(&names)
+    }
+
+    /// Gets a boolean attribute that may be stored as a CFBoolean or a CFNumber.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the attribute to retrieve
+    ///
+    /// # Returns
+    /// * `Option<bool>` - The attribute value, or None if not available
+    fn get_bool_attribute(&self, name: &str) -> Option<bool> {
+        let name = attribute_name(name);
+        let value = self.item.copy_attribute(&name)?;
+        match value.downcast::<CFBoolean>() {
+            Ok(value) => Some(value.as_bool()),
+            Err(value) => value
+                .downcast::<CFNumber>()
+                .ok()
+                .and_then(|number| number.as_i64())
+                .map(|number| number != 0),
+        }
+    }
+
+    /// Gets an attribute holding an array of strings.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the attribute to retrieve
+    ///
+    /// # Returns
+    /// * `Option<Vec<String>>` - The strings in the array, or None if not available
+    fn get_string_array_attribute(&self, name: &str) -> Option<Vec<String>> {
+        self.get_attribute::<CFArray>(name)
+            .map(|array| cf_array_to_strings(&array))
+    }
+
+    /// Gets an integer attribute stored as a CFNumber.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the attribute to retrieve
+    ///
+    /// # Returns
+    /// * `Option<i64>` - The attribute value, or None if not available
+    fn get_i64_attribute(&self, name: &str) -> Option<i64> {
+        self.get_attribute::<CFNumber>(name)
+            .and_then(|number| number.as_i64())
+    }
+
+    /// Gets a floating-point attribute stored as a CFNumber.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the attribute to retrieve
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The attribute value, or None if not available
+    fn get_f64_attribute(&self, name: &str) -> Option<f64> {
+        self.get_attribute::<CFNumber>(name)
+            .and_then(|number| number.as_f64())
+    }
+
+    /// Gets a date attribute as a UTC date-time.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the attribute to retrieve
+    ///
+    /// # Returns
+    /// * `Option<DateTime<Utc>>` - The attribute value, or None if not available
+    fn get_date_attribute(&self, name: &str) -> Option<DateTime<Utc>> {
+        self.get_attribute::<CFDate>(name)
+            .and_then(|date| cf_date_to_utc(&date))
+    }
+
     /// Retrieves the file path of this MDItem.
     ///
     /// # Returns
     /// * `Option<PathBuf>` - The file path, or None if not available
     pub fn path(&self) -> Option<PathBuf> {
-        self.get_attribute::<CFString>(MDItemKey::Path.as_str())
-            .map(|path| {
-                let path_str = (*path).to_string();
-                PathBuf::from(path_str)
+        self.cache
+            .path
+            .get_or_init(|| {
+                self.get_attribute::<CFString>(MDItemKey::Path.as_str())
+                    .map(|path| {
+                        let path_str = (*path).to_string();
+                        PathBuf::from(path_str)
+                    })
             })
+            .clone()
     }
 
     /// Retrieves the display name of this MDItem.
@@ -82,13 +365,80 @@ impl MDItem {
     /// # Returns
     /// * `Option<String>` - The display name, or None if not available
     pub fn display_name(&self) -> Option<String> {
-        self.get_attribute::<CFString>(MDItemKey::DisplayName.as_str())
-            .map(|name| (*name).to_string())
+        self.cache
+            .display_name
+            .get_or_init(|| {
+                self.get_attribute::<CFString>(MDItemKey::DisplayName.as_str())
+                    .map(|name| (*name).to_string())
+            })
+            .clone()
+    }
+
+    /// Retrieves the localized Finder kind of this MDItem (e.g. "Application").
+    ///
+    /// # Returns
+    /// * `Option<String>` - The kind in the user's language, or None if not available
+    pub fn kind(&self) -> Option<String> {
+        self.get_attribute::<CFString>(MDItemKey::Kind.as_str())
+            .map(|kind| (*kind).to_string())
+    }
+
+    /// Retrieves the bundle identifier of this MDItem (e.g. "com.apple.Safari").
+    ///
+    /// # Returns
+    /// * `Option<String>` - The bundle identifier, or None if the item is not a bundle
+    pub fn bundle_identifier(&self) -> Option<String> {
+        self.get_attribute::<CFString>(MDItemKey::BundleIdentifier.as_str())
+            .map(|id| (*id).to_string())
+    }
+
+    /// Retrieves the App Store category of this MDItem (e.g. "Productivity").
+    ///
+    /// # Returns
+    /// * `Option<String>` - The category, or None if the item is not an application or its
+    ///   bundle declares no category, as is common for apps not from the App Store
+    pub fn app_store_category(&self) -> Option<String> {
+        self.get_attribute::<CFString>(MDItemKey::AppStoreCategory.as_str())
+            .map(|category| (*category).to_string())
+    }
+
+    /// Retrieves the CPU architectures this MDItem contains code for, such as "arm64" and
+    /// "x86_64" for a universal app.
+    ///
+    /// # Returns
+    /// * `Option<Vec<String>>` - The architectures, or None if the item is not an
+    ///   application or executable
+    pub fn executable_architectures(&self) -> Option<Vec<String>> {
+        self.get_string_array_attribute(MDItemKey::ExecutableArchitectures.as_str())
+    }
+
+    /// Retrieves the version string of this MDItem, such as an app's short version.
+    ///
+    /// # Returns
+    /// * `Option<String>` - The version, or None if not available
+    pub fn version(&self) -> Option<String> {
+        self.get_attribute::<CFString>(MDItemKey::Version.as_str())
+            .map(|version| (*version).to_string())
     }
 
     pub fn content_type(&self) -> Option<String> {
-        self.get_attribute::<CFString>(MDItemKey::ContentType.as_str())
-            .map(|name| (*name).to_string())
+        self.cache
+            .content_type
+            .get_or_init(|| {
+                self.get_attribute::<CFString>(MDItemKey::ContentType.as_str())
+                    .map(|name| (*name).to_string())
+            })
+            .clone()
+    }
+
+    /// Retrieves the content type of this MDItem as a `UTType`.
+    ///
+    /// # Returns
+    /// * `Option<UTType>` - The content type, or None if not available or not declared
+    #[cfg(feature = "uttype")]
+    pub fn content_type_ut(&self) -> Option<UTType> {
+        self.content_type()
+            .and_then(|content_type| UTType::from_identifier(&content_type))
     }
 
     /// Retrieves the content type tree of this MDItem.
@@ -96,95 +446,1743 @@ impl MDItem {
     /// # Returns
     /// * `Option<Vec<String>>` - The content type tree, or None if not available
     pub fn content_type_tree(&self) -> Option<Vec<String>> {
-        self.get_attribute::<CFArray>(MDItemKey::ContentTypeTree.as_str())
-            .map(|content_type_tree| {
-                let count = unsafe { CFArrayGetCount(&content_type_tree) } as usize;
-                let mut types = Vec::with_capacity(count);
-                for i in 0..count {
-                    let type_ptr =
-                        unsafe { CFArrayGetValueAtIndex(&content_type_tree, i as CFIndex) };
-                    if let Some(cf_type) = NonNull::new(type_ptr as *mut CFString) {
-                        let type_str = unsafe { cf_type.as_ref().to_string() };
-                        types.push(type_str);
-                    }
-                }
-                types
-            })
+        self.cache
+            .content_type_tree
+            .get_or_init(|| self.get_string_array_attribute(MDItemKey::ContentTypeTree.as_str()))
+            .clone()
     }
 
-    /// Checks if this MDItem is a directory.
+    /// Retrieves the authors of this MDItem's content.
     ///
     /// # Returns
-    /// * `bool` - Returns true if this is a directory, false otherwise
-    pub fn is_dir(&self) -> bool {
-        self.content_type_tree()
-            .map(|types| types.contains(&"public.folder".to_string()))
-            .unwrap_or(false)
+    /// * `Option<Vec<String>>` - The author names, or None if not available
+    pub fn authors(&self) -> Option<Vec<String>> {
+        self.get_string_array_attribute(MDItemKey::Authors.as_str())
     }
 
-    /// Checks if this MDItem is an image file.
+    /// Retrieves the text content Spotlight indexed for this MDItem.
+    ///
+    /// Not every importer stores the full text, so this may be None or only a prefix of the
+    /// document even when content queries match. The text can be very large; see
+    /// [`text_content_truncated`](Self::text_content_truncated) to limit it.
     ///
     /// # Returns
-    /// * `bool` - Returns true if this is an image file, false otherwise
-    pub fn is_image(&self) -> bool {
-        self.content_type_tree()
-            .map(|types| types.contains(&"public.image".to_string()))
-            .unwrap_or(false)
+    /// * `Option<String>` - The text content, or None if not available
+    pub fn text_content(&self) -> Option<String> {
+        self.get_attribute::<CFString>(MDItemKey::TextContent.as_str())
+            .map(|text| (*text).to_string())
     }
 
-    /// Checks if this MDItem is an application bundle.
+    /// Retrieves at most `max_len` characters of the text content of this MDItem.
+    ///
+    /// # Arguments
+    /// * `max_len` - The maximum number of characters to return
     ///
     /// # Returns
-    /// * `bool` - Returns true if this is an application bundle, false otherwise
-    pub fn is_app(&self) -> bool {
-        self.content_type()
-            .map(|content_type| content_type == "com.apple.application-bundle")
-            .unwrap_or(false)
+    /// * `Option<String>` - The possibly truncated text content, or None if not available
+    pub fn text_content_truncated(&self, max_len: usize) -> Option<String> {
+        self.text_content().map(|mut text| {
+            if let Some((end, _)) = text.char_indices().nth(max_len) {
+                text.truncate(end);
+            }
+            text
+        })
     }
 
-    /// Checks if this MDItem is a video file.
+    /// Retrieves the title, comment, copyright and languages of this MDItem.
+    ///
+    /// All four attributes are fetched from Spotlight in a single call.
     ///
     /// # Returns
-    /// * `bool` - Returns true if this is a video file, false otherwise
-    pub fn is_video(&self) -> bool {
-        self.content_type_tree()
-            .map(|types| types.contains(&"public.movie".to_string()))
-            .unwrap_or(false)
+    /// * `DocumentInfo` - The document metadata; missing attributes are None
+    pub fn document_info(&self) -> DocumentInfo {
+        let Some(values) = self.copy_attributes(&[
+            MDItemKey::Title,
+            MDItemKey::Comment,
+            MDItemKey::Copyright,
+            MDItemKey::Languages,
+        ]) else {
+            return DocumentInfo::default();
+        };
+        let string = |key: MDItemKey| {
+            cf_dictionary_value::<CFString>(&values, key.as_str()).map(|value| value.to_string())
+        };
+        DocumentInfo {
+            title: string(MDItemKey::Title),
+            comment: string(MDItemKey::Comment),
+            copyright: string(MDItemKey::Copyright),
+            languages: cf_dictionary_value::<CFArray>(&values, MDItemKey::Languages.as_str())
+                .map(|array| cf_array_to_strings(&array)),
+        }
     }
 
-    /// Checks if this MDItem is an audio file.
+    /// Retrieves the method used to protect this MDItem, such as "Password Encrypted".
     ///
     /// # Returns
-    /// * `bool` - Returns true if this is an audio file, false otherwise
-    pub fn is_audio(&self) -> bool {
-        self.content_type_tree()
-            .map(|types| types.contains(&"public.audio".to_string()))
-            .unwrap_or(false)
+    /// * `Option<String>` - The security method, or None if the item is not protected
+    pub fn security_method(&self) -> Option<String> {
+        self.get_attribute::<CFString>(MDItemKey::SecurityMethod.as_str())
+            .map(|method| (*method).to_string())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Retrieves the Finder comment of this MDItem.
+    ///
+    /// # Returns
+    /// * `Option<String>` - The Finder comment, or None if not set
+    pub fn finder_comment(&self) -> Option<String> {
+        self.get_attribute::<CFString>(MDItemKey::FinderComment.as_str())
+            .map(|comment| (*comment).to_string())
+    }
 
-    #[test]
-    fn test_get_attribute_names() {
-        let item = MDItem::from_path("/Applications/Safari.app").unwrap();
-        let names = item.get_attribute_names();
-        assert!(!names.is_empty());
+    /// Retrieves the pixel dimensions of this MDItem, if it is an image or video.
+    ///
+    /// # Returns
+    /// * `Option<(u32, u32)>` - The width and height in pixels, or None if not available
+    pub fn pixel_dimensions(&self) -> Option<(u32, u32)> {
+        let width = self.get_i64_attribute(MDItemKey::PixelWidth.as_str())?;
+        let height = self.get_i64_attribute(MDItemKey::PixelHeight.as_str())?;
+        Some((u32::try_from(width).ok()?, u32::try_from(height).ok()?))
     }
 
-    #[test]
-    fn test_get_path() {
-        let item = MDItem::from_path("/Applications/Safari.app").unwrap();
-        let path = item.path().unwrap();
-        assert_eq!(path, PathBuf::from("/Applications/Safari.app"));
+    /// Retrieves the color space of this MDItem, if it is an image.
+    ///
+    /// # Returns
+    /// * `Option<String>` - The color space (e.g. "RGB", "Gray" or "CMYK"), or None if not
+    ///   available
+    pub fn color_space(&self) -> Option<String> {
+        self.get_attribute::<CFString>(MDItemKey::ColorSpace.as_str())
+            .map(|color_space| (*color_space).to_string())
     }
 
-    #[test]
-    fn test_get_content_type_tree() {
-        let item = MDItem::from_path("/Applications/Safari.app").unwrap();
-        let content_type_tree = item.content_type_tree().unwrap();
-        assert!(!content_type_tree.is_empty());
+    /// Retrieves the number of bits per color channel of this MDItem, if it is an image.
+    ///
+    /// # Returns
+    /// * `Option<u32>` - The bits per sample (e.g. 8 or 16), or None if not available
+    pub fn bits_per_sample(&self) -> Option<u32> {
+        self.get_i64_attribute(MDItemKey::BitsPerSample.as_str())
+            .and_then(|bits| u32::try_from(bits).ok())
+    }
+
+    /// Checks whether this MDItem is an image with an alpha channel.
+    ///
+    /// # Returns
+    /// * `Option<bool>` - Whether the image has an alpha channel, or None if not available
+    pub fn has_alpha_channel(&self) -> Option<bool> {
+        self.get_bool_attribute(MDItemKey::HasAlphaChannel.as_str())
+    }
+
+    /// Retrieves the orientation of this MDItem, if it is an image or video.
+    ///
+    /// # Returns
+    /// * `Option<ImageOrientation>` - The orientation, or None if not available
+    pub fn orientation(&self) -> Option<ImageOrientation> {
+        self.get_i64_attribute(MDItemKey::Orientation.as_str())
+            .map(ImageOrientation::from_value)
+    }
+
+    /// Retrieves the dimensions, duration, codecs and bit rate of this MDItem, if it is a
+    /// video.
+    ///
+    /// All five attributes are fetched from Spotlight in a single call.
+    ///
+    /// # Returns
+    /// * `Option<VideoInfo>` - The video summary, or None if the item is not a video or
+    ///   has no pixel dimensions
+    pub fn video_info(&self) -> Option<VideoInfo> {
+        if !self.is_video() {
+            return None;
+        }
+        let values = self.copy_attributes(&[
+            MDItemKey::PixelWidth,
+            MDItemKey::PixelHeight,
+            MDItemKey::DurationSeconds,
+            MDItemKey::Codecs,
+            MDItemKey::VideoBitRate,
+        ])?;
+        let number = |key: MDItemKey| cf_dictionary_value::<CFNumber>(&values, key.as_str());
+        let dimension = |key: MDItemKey| {
+            number(key)
+                .and_then(|value| value.as_i64())
+                .and_then(|value| u32::try_from(value).ok())
+        };
+        Some(VideoInfo {
+            width: dimension(MDItemKey::PixelWidth)?,
+            height: dimension(MDItemKey::PixelHeight)?,
+            duration: number(MDItemKey::DurationSeconds)
+                .and_then(|value| value.as_f64())
+                .and_then(|secs| Duration::try_from_secs_f64(secs).ok()),
+            codecs: cf_dictionary_value::<CFArray>(&values, MDItemKey::Codecs.as_str())
+                .map(|array| cf_array_to_strings(&array)),
+            bit_rate: number(MDItemKey::VideoBitRate).and_then(|value| value.as_f64()),
+        })
+    }
+
+    /// Retrieves the playback duration of this MDItem, if it is an audio or video file.
+    ///
+    /// # Returns
+    /// * `Option<Duration>` - The duration, or None if not available
+    pub fn duration(&self) -> Option<Duration> {
+        self.get_f64_attribute(MDItemKey::DurationSeconds.as_str())
+            .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+    }
+
+    /// Retrieves the number of pages of this MDItem, if it is a paged document.
+    ///
+    /// # Returns
+    /// * `Option<u32>` - The page count, or None if not available
+    pub fn page_count(&self) -> Option<u32> {
+        self.get_i64_attribute(MDItemKey::NumberOfPages.as_str())
+            .and_then(|pages| u32::try_from(pages).ok())
+    }
+
+    /// Retrieves the names of the fonts this MDItem uses, for documents such as PDFs, or
+    /// contains, for font files.
+    ///
+    /// # Returns
+    /// * `Option<Vec<String>>` - The font names, or None if not available
+    pub fn fonts(&self) -> Option<Vec<String>> {
+        self.get_string_array_attribute(MDItemKey::Fonts.as_str())
+    }
+
+    /// Retrieves the audio sample rate of this MDItem.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The sample rate in hertz, or None if not available
+    pub fn audio_sample_rate(&self) -> Option<f64> {
+        self.get_f64_attribute(MDItemKey::AudioSampleRate.as_str())
+    }
+
+    /// Retrieves the number of audio channels of this MDItem.
+    ///
+    /// # Returns
+    /// * `Option<u32>` - The channel count, or None if not available
+    pub fn audio_channel_count(&self) -> Option<u32> {
+        self.get_i64_attribute(MDItemKey::AudioChannelCount.as_str())
+            .and_then(|channels| u32::try_from(channels).ok())
+    }
+
+    /// Retrieves the codecs used by the media streams of this MDItem.
+    ///
+    /// # Returns
+    /// * `Option<Vec<String>>` - The codec names, or None if not available
+    pub fn codecs(&self) -> Option<Vec<String>> {
+        self.get_string_array_attribute(MDItemKey::Codecs.as_str())
+    }
+
+    /// Retrieves the combined bit rate of all media streams of this MDItem.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The bit rate in kilobits per second, or None if not available
+    pub fn total_bit_rate(&self) -> Option<f64> {
+        self.get_f64_attribute(MDItemKey::TotalBitRate.as_str())
+    }
+
+    /// Retrieves the bit rate of the video stream of this MDItem.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The bit rate in kilobits per second, or None if not available
+    pub fn video_bit_rate(&self) -> Option<f64> {
+        self.get_f64_attribute(MDItemKey::VideoBitRate.as_str())
+    }
+
+    /// Retrieves the album of this MDItem, if it is a music track.
+    ///
+    /// # Returns
+    /// * `Option<String>` - The album title, or None if not available
+    pub fn album(&self) -> Option<String> {
+        self.get_attribute::<CFString>(MDItemKey::Album.as_str())
+            .map(|album| (*album).to_string())
+    }
+
+    /// Retrieves the artists of this MDItem, if it is a music track.
+    ///
+    /// Spotlight stores track artists in `kMDItemAuthors`, so this returns the same values
+    /// as [`authors`](Self::authors).
+    ///
+    /// # Returns
+    /// * `Option<Vec<String>>` - The artist names, or None if not available
+    pub fn artists(&self) -> Option<Vec<String>> {
+        self.authors()
+    }
+
+    /// Retrieves the musical genre of this MDItem.
+    ///
+    /// # Returns
+    /// * `Option<String>` - The genre, or None if not available
+    pub fn genre(&self) -> Option<String> {
+        self.get_attribute::<CFString>(MDItemKey::MusicalGenre.as_str())
+            .map(|genre| (*genre).to_string())
+    }
+
+    /// Retrieves the year this MDItem was recorded.
+    ///
+    /// # Returns
+    /// * `Option<u16>` - The recording year, or None if not available
+    pub fn recording_year(&self) -> Option<u16> {
+        self.get_i64_attribute(MDItemKey::RecordingYear.as_str())
+            .and_then(|year| u16::try_from(year).ok())
+    }
+
+    /// Retrieves the manufacturer of the camera that captured this MDItem.
+    ///
+    /// # Returns
+    /// * `Option<String>` - The camera make, or None if not available
+    pub fn camera_make(&self) -> Option<String> {
+        self.get_attribute::<CFString>(MDItemKey::AcquisitionMake.as_str())
+            .map(|make| (*make).to_string())
+    }
+
+    /// Retrieves the model of the camera that captured this MDItem.
+    ///
+    /// # Returns
+    /// * `Option<String>` - The camera model, or None if not available
+    pub fn camera_model(&self) -> Option<String> {
+        self.get_attribute::<CFString>(MDItemKey::AcquisitionModel.as_str())
+            .map(|model| (*model).to_string())
+    }
+
+    /// Retrieves the ISO speed this MDItem was captured with, if it is a photo.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The ISO speed (e.g. 400), or None if not available
+    pub fn iso_speed(&self) -> Option<f64> {
+        self.get_f64_attribute(MDItemKey::ISOSpeed.as_str())
+    }
+
+    /// Retrieves the focal length this MDItem was captured with, if it is a photo.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The focal length in millimeters, or None if not available
+    pub fn focal_length(&self) -> Option<f64> {
+        self.get_f64_attribute(MDItemKey::FocalLength.as_str())
+    }
+
+    /// Retrieves the exposure time this MDItem was captured with, if it is a photo.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The exposure time in seconds (e.g. 0.004 for 1/250 s), or None
+    ///   if not available
+    pub fn exposure_time_seconds(&self) -> Option<f64> {
+        self.get_f64_attribute(MDItemKey::ExposureTimeSeconds.as_str())
+    }
+
+    /// Retrieves the f-number this MDItem was captured with, if it is a photo.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The f-number (e.g. 2.8), or None if not available
+    pub fn f_number(&self) -> Option<f64> {
+        self.get_f64_attribute(MDItemKey::FNumber.as_str())
+    }
+
+    /// Retrieves the URLs this MDItem was downloaded from.
+    ///
+    /// # Returns
+    /// * `Option<Vec<String>>` - The download URLs, or None if not available
+    pub fn where_froms(&self) -> Option<Vec<String>> {
+        self.get_string_array_attribute(MDItemKey::WhereFroms.as_str())
+    }
+
+    /// Retrieves the date this MDItem was downloaded.
+    ///
+    /// Spotlight stores the download date as an array; this returns its first date.
+    ///
+    /// # Returns
+    /// * `Option<DateTime<Utc>>` - The download date, or None if not available
+    pub fn downloaded_date(&self) -> Option<DateTime<Utc>> {
+        self.get_attribute::<CFArray>(MDItemKey::DownloadedDate.as_str())
+            .and_then(|array| cf_array_to_dates(&array).into_iter().next())
+    }
+
+    /// Retrieves the date this MDItem was added to its current folder.
+    ///
+    /// # Returns
+    /// * `Option<DateTime<Utc>>` - The date added, or None if not available
+    pub fn date_added(&self) -> Option<DateTime<Utc>> {
+        self.get_date_attribute(MDItemKey::DateAdded.as_str())
+    }
+
+    /// Retrieves the user ID of the owner of this MDItem.
+    ///
+    /// # Returns
+    /// * `Option<u32>` - The owner's user ID, or None if not available
+    pub fn owner_uid(&self) -> Option<u32> {
+        self.get_i64_attribute(MDItemKey::FSOwnerUserID.as_str())
+            .and_then(|uid| u32::try_from(uid).ok())
+    }
+
+    /// Retrieves the group ID of the owner of this MDItem.
+    ///
+    /// # Returns
+    /// * `Option<u32>` - The owner's group ID, or None if not available
+    pub fn owner_gid(&self) -> Option<u32> {
+        self.get_i64_attribute(MDItemKey::FSOwnerGroupID.as_str())
+            .and_then(|gid| u32::try_from(gid).ok())
+    }
+
+    /// Retrieves the number of items directly contained in this MDItem, if it is a folder.
+    ///
+    /// # Returns
+    /// * `Option<u64>` - The item count, or None if not available
+    pub fn node_count(&self) -> Option<u64> {
+        self.get_i64_attribute(MDItemKey::FSNodeCount.as_str())
+            .and_then(|count| u64::try_from(count).ok())
+    }
+
+    /// Retrieves the Finder label color of this MDItem.
+    ///
+    /// # Returns
+    /// * `Option<FinderLabel>` - The label, or None if not available
+    pub fn label(&self) -> Option<FinderLabel> {
+        self.get_i64_attribute(MDItemKey::FSLabel.as_str())
+            .and_then(|value| u8::try_from(value).ok())
+            .and_then(FinderLabel::from_value)
+    }
+
+    /// Retrieves the number of times this MDItem has been opened.
+    ///
+    /// # Returns
+    /// * `Option<i64>` - The use count, or None if not available
+    pub fn use_count(&self) -> Option<i64> {
+        self.get_i64_attribute(MDItemKey::UseCount.as_str())
+    }
+
+    /// Retrieves the dates on which this MDItem was opened.
+    ///
+    /// # Returns
+    /// * `Option<Vec<DateTime<Utc>>>` - The usage dates, or None if not available
+    pub fn used_dates(&self) -> Option<Vec<DateTime<Utc>>> {
+        self.get_attribute::<CFArray>(MDItemKey::UsedDates.as_str())
+            .map(|array| cf_array_to_dates(&array))
+    }
+
+    /// Retrieves the URL of this MDItem, if it is a bookmark, history item or web archive.
+    ///
+    /// # Returns
+    /// * `Option<String>` - The URL, or None if not available
+    pub fn url(&self) -> Option<String> {
+        self.get_attribute::<CFString>(MDItemKey::Url.as_str())
+            .map(|url| (*url).to_string())
+    }
+
+    /// Retrieves the subject of this MDItem, if it is an email message.
+    ///
+    /// # Returns
+    /// * `Option<String>` - The subject, or None if not available
+    pub fn subject(&self) -> Option<String> {
+        self.get_attribute::<CFString>(MDItemKey::Subject.as_str())
+            .map(|subject| (*subject).to_string())
+    }
+
+    /// Retrieves the names of the recipients of this MDItem.
+    ///
+    /// # Returns
+    /// * `Option<Vec<String>>` - The recipient names, or None if not available
+    pub fn recipients(&self) -> Option<Vec<String>> {
+        self.get_string_array_attribute(MDItemKey::Recipients.as_str())
+    }
+
+    /// Retrieves the email addresses of the authors (senders) of this MDItem.
+    ///
+    /// # Returns
+    /// * `Option<Vec<String>>` - The sender addresses, or None if not available
+    pub fn author_email_addresses(&self) -> Option<Vec<String>> {
+        self.get_string_array_attribute(MDItemKey::AuthorEmailAddresses.as_str())
+    }
+
+    /// Retrieves the email addresses of the recipients of this MDItem.
+    ///
+    /// # Returns
+    /// * `Option<Vec<String>>` - The recipient addresses, or None if not available
+    pub fn recipient_email_addresses(&self) -> Option<Vec<String>> {
+        self.get_string_array_attribute(MDItemKey::RecipientEmailAddresses.as_str())
+    }
+
+    /// Retrieves the keywords associated with this MDItem.
+    ///
+    /// # Returns
+    /// * `Option<Vec<String>>` - The keywords, or None if not available
+    pub fn keywords(&self) -> Option<Vec<String>> {
+        self.get_string_array_attribute(MDItemKey::Keywords.as_str())
+    }
+
+    /// Retrieves the name of the application that created this MDItem's content.
+    ///
+    /// # Returns
+    /// * `Option<String>` - The creating application, or None if not available
+    pub fn creator_app(&self) -> Option<String> {
+        self.get_attribute::<CFString>(MDItemKey::Creator.as_str())
+            .map(|creator| (*creator).to_string())
+    }
+
+    /// Retrieves the Finder tags assigned to this MDItem.
+    ///
+    /// # Returns
+    /// * `Option<Vec<String>>` - The tag names, or None if the item has no tags
+    pub fn user_tags(&self) -> Option<Vec<String>> {
+        self.get_string_array_attribute(MDItemKey::UserTags.as_str())
+    }
+
+    /// Retrieves the location where this MDItem was captured, such as a geotagged photo.
+    ///
+    /// # Returns
+    /// * `Option<(f64, f64)>` - The latitude and longitude in degrees, or None if either is
+    ///   not available
+    pub fn location(&self) -> Option<(f64, f64)> {
+        let latitude = self.get_f64_attribute(MDItemKey::Latitude.as_str())?;
+        let longitude = self.get_f64_attribute(MDItemKey::Longitude.as_str())?;
+        Some((latitude, longitude))
+    }
+
+    /// Retrieves the altitude where this MDItem was captured.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The altitude in meters above sea level, or None if not available
+    pub fn altitude(&self) -> Option<f64> {
+        self.get_f64_attribute(MDItemKey::Altitude.as_str())
+    }
+
+    /// Checks if this MDItem belongs to a content kind.
+    ///
+    /// # Arguments
+    /// * `kind` - The content kind to check
+    ///
+    /// # Returns
+    /// * `bool` - Returns true if the content type tree contains any UTI of the kind and
+    ///   none of its excluded UTIs
+    #[cfg(not(feature = "uttype"))]
+    pub fn matches_kind(&self, kind: ContentKind) -> bool {
+        self.content_type_tree()
+            .map(|types| {
+                kind.content_types()
+                    .iter()
+                    .any(|uti| types.iter().any(|t| t == uti))
+                    && !kind
+                        .excluded_content_types()
+                        .iter()
+                        .any(|uti| types.iter().any(|t| t == uti))
+            })
+            .unwrap_or(false)
+    }
+
+    /// Checks if this MDItem belongs to a content kind.
+    ///
+    /// # Arguments
+    /// * `kind` - The content kind to check
+    ///
+    /// # Returns
+    /// * `bool` - Returns true if the content type conforms to any UTI of the kind and to
+    ///   none of its excluded UTIs
+    #[cfg(feature = "uttype")]
+    pub fn matches_kind(&self, kind: ContentKind) -> bool {
+        let conforms_to_any = |content_type: &UTType, utis: &[&str]| {
+            utis.iter()
+                .filter_map(|uti| UTType::from_identifier(uti))
+                .any(|uti| content_type.conforms_to(&uti))
+        };
+        self.content_type_ut().is_some_and(|content_type| {
+            conforms_to_any(&content_type, kind.content_types())
+                && !conforms_to_any(&content_type, kind.excluded_content_types())
+        })
+    }
+
+    /// Checks if this MDItem is hidden (invisible in the Finder).
+    ///
+    /// # Returns
+    /// * `bool` - Returns true if the item is hidden; items without the attribute are visible
+    pub fn is_hidden(&self) -> bool {
+        self.get_bool_attribute(MDItemKey::FSInvisible.as_str())
+            .unwrap_or(false)
+    }
+
+    /// Checks if this MDItem is a screenshot.
+    ///
+    /// # Returns
+    /// * `bool` - Returns true if the item was captured with the system screenshot tool
+    pub fn is_screenshot(&self) -> bool {
+        self.get_bool_attribute(MDItemKey::IsScreenCapture.as_str())
+            .unwrap_or(false)
+    }
+
+    /// Retrieves the screenshot type of this MDItem, such as "window" or "selection".
+    ///
+    /// # Returns
+    /// * `Option<String>` - The screenshot type, or None if not available
+    pub fn screenshot_type(&self) -> Option<String> {
+        self.get_attribute::<CFString>(MDItemKey::ScreenCaptureType.as_str())
+            .map(|capture_type| (*capture_type).to_string())
+    }
+
+    /// Checks if this MDItem is stored in iCloud Drive or another iCloud container.
+    ///
+    /// Detection is based on the item's path lying inside `~/Library/Mobile Documents`.
+    /// Items stored in iCloud but not downloaded may have incomplete metadata.
+    ///
+    /// # Returns
+    /// * `bool` - Returns true if the item is synced with iCloud
+    pub fn is_in_icloud(&self) -> bool {
+        self.path()
+            .is_some_and(|path| path.starts_with(icloud_directory()))
+    }
+
+    /// Checks if this MDItem is a directory.
+    ///
+    /// Packages such as app bundles are directories as well, use
+    /// [`is_package`](Self::is_package) to tell them apart from plain folders.
+    ///
+    /// # Returns
+    /// * `bool` - Returns true if this is a directory, false otherwise
+    pub fn is_dir(&self) -> bool {
+        self.content_type_tree()
+            .map(|types| types.contains(&"public.folder".to_string()))
+            .unwrap_or(false)
+    }
+
+    /// Checks if this MDItem is a package: a directory presented as a file, such as an app
+    /// bundle, a framework, a photo library or a document package.
+    ///
+    /// # Returns
+    /// * `bool` - Returns true if the content type tree contains `com.apple.package` or
+    ///   `com.apple.bundle`
+    pub fn is_package(&self) -> bool {
+        self.content_type_tree().is_some_and(|types| {
+            types
+                .iter()
+                .any(|uti| PACKAGE_CONTENT_TYPES.contains(&uti.as_str()))
+        })
+    }
+
+    /// Checks if this MDItem is an image file.
+    ///
+    /// # Returns
+    /// * `bool` - Returns true if this is an image file, false otherwise
+    pub fn is_image(&self) -> bool {
+        self.content_type_tree()
+            .map(|types| types.contains(&"public.image".to_string()))
+            .unwrap_or(false)
+    }
+
+    /// Checks if this MDItem is an application bundle.
+    ///
+    /// # Returns
+    /// * `bool` - Returns true if this is an application bundle, false otherwise
+    pub fn is_app(&self) -> bool {
+        self.content_type()
+            .map(|content_type| content_type == "com.apple.application-bundle")
+            .unwrap_or(false)
+    }
+
+    /// Checks if this MDItem is a video file.
+    ///
+    /// # Returns
+    /// * `bool` - Returns true if this is a video file, false otherwise
+    pub fn is_video(&self) -> bool {
+        self.content_type_tree()
+            .map(|types| types.contains(&"public.movie".to_string()))
+            .unwrap_or(false)
+    }
+
+    /// Checks if this MDItem is an audio file.
+    ///
+    /// # Returns
+    /// * `bool` - Returns true if this is an audio file, false otherwise
+    pub fn is_audio(&self) -> bool {
+        self.content_type_tree()
+            .map(|types| types.contains(&"public.audio".to_string()))
+            .unwrap_or(false)
+    }
+
+    /// Retrieves the mount point of the volume this MDItem is stored on.
+    ///
+    /// The mount point is the longest mounted path that contains the item's path, so items
+    /// on the startup volume, including its firmlinked data volume, yield `/` and items on
+    /// an external disk yield its directory below `/Volumes`.
+    ///
+    /// # Returns
+    /// * `Option<PathBuf>` - The mount point, or None if the item has no path or the mount
+    ///   table cannot be read
+    pub fn volume_path(&self) -> Option<PathBuf> {
+        let path = self.path()?;
+        mount_points()
+            .into_iter()
+            .filter(|mount_point| path.starts_with(mount_point))
+            .max_by_key(|mount_point| mount_point.as_os_str().len())
+    }
+
+    /// Checks if this MDItem is a document, i.e. content other than images, movies and
+    /// audio.
+    ///
+    /// # Returns
+    /// * `bool` - Returns true if the item matches [`ContentKind::Document`]
+    pub fn is_document(&self) -> bool {
+        self.matches_kind(ContentKind::Document)
+    }
+
+    /// Checks if this MDItem is an archive, such as a zip, gzip or tar file.
+    ///
+    /// # Returns
+    /// * `bool` - Returns true if the item matches [`ContentKind::Archive`]
+    pub fn is_archive(&self) -> bool {
+        self.matches_kind(ContentKind::Archive)
+    }
+
+    /// Checks if this MDItem is a presentation.
+    ///
+    /// # Returns
+    /// * `bool` - Returns true if the item matches [`ContentKind::Presentation`]
+    pub fn is_presentation(&self) -> bool {
+        self.matches_kind(ContentKind::Presentation)
+    }
+
+    /// Checks if this MDItem is a spreadsheet.
+    ///
+    /// # Returns
+    /// * `bool` - Returns true if the item matches [`ContentKind::Spreadsheet`]
+    pub fn is_spreadsheet(&self) -> bool {
+        self.matches_kind(ContentKind::Spreadsheet)
+    }
+
+    /// Checks if this MDItem is source code.
+    ///
+    /// # Returns
+    /// * `bool` - Returns true if the item matches [`ContentKind::SourceCode`]
+    pub fn is_source_code(&self) -> bool {
+        self.matches_kind(ContentKind::SourceCode)
+    }
+
+    /// Checks if this MDItem is a Finder alias.
+    ///
+    /// # Returns
+    /// * `bool` - Returns true if the content type tree contains `com.apple.alias-file`
+    pub fn is_alias(&self) -> bool {
+        self.content_type_tree()
+            .map(|types| types.contains(&"com.apple.alias-file".to_string()))
+            .unwrap_or(false)
+    }
+
+    /// Checks if this MDItem is a symbolic link.
+    ///
+    /// Items created with [`from_path_unresolved`](Self::from_path_unresolved) may lack a
+    /// content type, so the file itself is inspected when the content type tree does not
+    /// contain `public.symlink`.
+    ///
+    /// # Returns
+    /// * `bool` - Returns true if this is a symbolic link, false otherwise
+    pub fn is_symlink(&self) -> bool {
+        self.content_type_tree()
+            .is_some_and(|types| types.contains(&"public.symlink".to_string()))
+            || self.path().is_some_and(|path| path.is_symlink())
+    }
+
+    /// Resolves this MDItem to the path of the file it refers to.
+    ///
+    /// Finder aliases are resolved through their bookmark data, without user interaction
+    /// or mounting volumes. Other paths are canonicalized, which follows symbolic links.
+    ///
+    /// # Returns
+    /// * `Option<PathBuf>` - The target path, or None if the item has no path or the
+    ///   target cannot be found
+    pub fn resolved_path(&self) -> Option<PathBuf> {
+        let path = self.path()?;
+        if self.is_alias() {
+            resolve_alias(&path)
+        } else {
+            path.canonicalize().ok()
+        }
+    }
+}
+
+impl ItemSource {
+    fn as_ptr(&self) -> *const c_void {
+        match self {
+            Self::Core(item) => CFRetained::as_ptr(item).as_ptr() as *const c_void,
+            #[cfg(feature = "nsmetadata")]
+            Self::NsMetadata(item) => Retained::as_ptr(item) as *const c_void,
+        }
+    }
+
+    fn copy_attribute(&self, name: &CFString) -> Option<CFRetained<CFType>> {
+        match self {
+            Self::Core(item) => unsafe { MDItemCopyAttribute(item, name) },
+            #[cfg(feature = "nsmetadata")]
+            Self::NsMetadata(item) => autoreleasepool(|_| {
+                let value: *mut AnyObject =
+                    unsafe { msg_send![item, valueForAttribute: as_object(name)] };
+                // SAFETY: Attribute values are Foundation objects toll-free bridged to their
+                // CoreFoundation types, and we take our own reference.
+                NonNull::new(value.cast::<CFType>())
+                    .map(|value| unsafe { CFRetained::retain(value) })
+            }),
+        }
+    }
+
+    fn copy_attribute_names(&self) -> Option<CFRetained<CFArray>> {
+        match self {
+            Self::Core(item) => unsafe { MDItemCopyAttributeNames(item) },
+            #[cfg(feature = "nsmetadata")]
+            Self::NsMetadata(item) => autoreleasepool(|_| {
+                // An NSArray of NSStrings, toll-free bridged to a CFArray of CFStrings.
+                let names: *mut AnyObject = unsafe { msg_send![item, attributes] };
+                NonNull::new(names.cast::<CFArray>())
+                    .map(|names| unsafe { CFRetained::retain(names) })
+            }),
+        }
+    }
+
+    fn copy_attributes(&self, names: &CFArray) -> Option<CFRetained<CFDictionary>> {
+        match self {
+            Self::Core(item) => unsafe { MDItemCopyAttributes(item, names) },
+            #[cfg(feature = "nsmetadata")]
+            Self::NsMetadata(item) => autoreleasepool(|_| {
+                // An NSDictionary, toll-free bridged to a CFDictionary.
+                let values: *mut AnyObject =
+                    unsafe { msg_send![item, valuesForAttributes: as_object(names)] };
+                NonNull::new(values.cast::<CFDictionary>())
+                    .map(|values| unsafe { CFRetained::retain(values) })
+            }),
+        }
+    }
+}
+
+/// Returns the mount points of all mounted file systems.
+fn mount_points() -> Vec<PathBuf> {
+    // SAFETY: With a null buffer `getfsstat` only returns the number of file systems.
+    let count = unsafe { libc::getfsstat(ptr::null_mut(), 0, libc::MNT_NOWAIT) };
+    if count <= 0 {
+        return Vec::new();
+    }
+    // Leave room for file systems mounted between the two calls.
+    let capacity = count as usize + 8;
+    let mut mounts = Vec::<libc::statfs>::with_capacity(capacity);
+    let size = (capacity * mem::size_of::<libc::statfs>()) as libc::c_int;
+    // SAFETY: `getfsstat` writes at most `size` bytes into the owned buffer and returns the
+    // number of entries it filled in.
+    let count = unsafe { libc::getfsstat(mounts.as_mut_ptr(), size, libc::MNT_NOWAIT) };
+    if count <= 0 {
+        return Vec::new();
+    }
+    // SAFETY: The first `count` entries were initialized by `getfsstat`.
+    unsafe { mounts.set_len((count as usize).min(capacity)) };
+    mounts
+        .iter()
+        .map(|mount| {
+            let mount_point = unsafe { CStr::from_ptr(mount.f_mntonname.as_ptr()) };
+            PathBuf::from(OsStr::from_bytes(mount_point.to_bytes()))
+        })
+        .collect()
+}
+
+/// Resolves the Finder alias file at `path` to the path of its target.
+fn resolve_alias(path: &Path) -> Option<PathBuf> {
+    let path = path.as_os_str().as_bytes();
+    let url = unsafe {
+        CFURLCreateFromFileSystemRepresentation(None, path.as_ptr(), path.len() as CFIndex, false)
+    }?;
+    let bookmark = unsafe { CFURLCreateBookmarkDataFromFile(None, Some(&url), ptr::null_mut()) }?;
+    let target = unsafe {
+        CFURLCreateByResolvingBookmarkData(
+            None,
+            Some(&bookmark),
+            CFURLBookmarkResolutionOptions::CFURLBookmarkResolutionWithoutUIMask
+                | CFURLBookmarkResolutionOptions::CFURLBookmarkResolutionWithoutMountingMask,
+            None,
+            None,
+            ptr::null_mut(),
+            ptr::null_mut(),
+        )
+    }?;
+    let target = unsafe { CFURLCopyFileSystemPath(&target, CFURLPathStyle::CFURLPOSIXPathStyle) }?;
+    Some(PathBuf::from(target.to_string()))
+}
+
+/// Looks up a value of type `T` in a CFDictionary keyed by CFStrings.
+pub(super) fn cf_dictionary_value<T: ConcreteType>(
+    dictionary: &CFDictionary,
+    key: &str,
+) -> Option<CFRetained<T>> {
+    cf_dictionary_value_for_key(dictionary, &attribute_name(key))
+}
+
+/// Looks up a value of type `T` in a CFDictionary, using a CFString constant as the key.
+pub(super) fn cf_dictionary_value_for_key<T: ConcreteType>(
+    dictionary: &CFDictionary,
+    key: &CFString,
+) -> Option<CFRetained<T>> {
+    let ptr = unsafe { CFDictionaryGetValue(dictionary, key as *const CFString as *const _) };
+    let value = NonNull::new(ptr as *mut CFType)?;
+    unsafe { CFRetained::retain(value) }.downcast::<T>().ok()
+}
+
+/// Converts a CoreFoundation value into an [`MDAttributeValue`].
+pub(super) fn cf_type_to_value(value: &CFType) -> Option<MDAttributeValue> {
+    if let Some(string) = value.downcast_ref::<CFString>() {
+        Some(MDAttributeValue::String(string.to_string()))
+    } else if let Some(boolean) = value.downcast_ref::<CFBoolean>() {
+        Some(MDAttributeValue::Bool(boolean.as_bool()))
+    } else if let Some(number) = value.downcast_ref::<CFNumber>() {
+        if unsafe { CFNumberIsFloatType(number) } {
+            number.as_f64().map(MDAttributeValue::Float)
+        } else {
+            number.as_i64().map(MDAttributeValue::Integer)
+        }
+    } else if let Some(date) = value.downcast_ref::<CFDate>() {
+        cf_date_to_utc(date).map(MDAttributeValue::Date)
+    } else if let Some(array) = value.downcast_ref::<CFArray>() {
+        let count = unsafe { CFArrayGetCount(array) };
+        let values = (0..count)
+            .filter_map(|i| {
+                let ptr = unsafe { CFArrayGetValueAtIndex(array, i) };
+                let element = NonNull::new(ptr as *mut CFType)?;
+                cf_type_to_value(unsafe { element.as_ref() })
+            })
+            .collect();
+        Some(MDAttributeValue::Array(values))
+    } else {
+        None
+    }
+}
+
+/// Converts a CFDate into a UTC date-time.
+fn cf_date_to_utc(date: &CFDate) -> Option<DateTime<Utc>> {
+    date.to_system_time().map(DateTime::<Utc>::from)
+}
+
+/// Converts a CFArray of CFDates into a vector of UTC date-times, skipping elements that
+/// are not dates.
+fn cf_array_to_dates(array: &CFArray) -> Vec<DateTime<Utc>> {
+    let count = unsafe { CFArrayGetCount(array) } as usize;
+    let mut dates = Vec::with_capacity(count);
+    for i in 0..count {
+        let ptr = unsafe { CFArrayGetValueAtIndex(array, i as CFIndex) };
+        let Some(element) = NonNull::new(ptr as *mut CFType) else {
+            continue;
+        };
+        if let Some(cf_date) = unsafe { element.as_ref() }.downcast_ref::<CFDate>() {
+            dates.extend(cf_date_to_utc(cf_date));
+        }
+    }
+    dates
+}
+
+/// Converts a CFArray of CFStrings into a vector of strings, skipping elements that are
+/// not strings.
+pub(super) fn cf_array_to_strings(array: &CFArray) -> Vec<String> {
+    let count = unsafe { CFArrayGetCount(array) } as usize;
+    let mut strings = Vec::with_capacity(count);
+    for i in 0..count {
+        let ptr = unsafe { CFArrayGetValueAtIndex(array, i as CFIndex) };
+        let Some(element) = NonNull::new(ptr as *mut CFType) else {
+            continue;
+        };
+        if let Some(cf_string) = unsafe { element.as_ref() }.downcast_ref::<CFString>() {
+            strings.push(cf_string.to_string());
+        }
+    }
+    strings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a CFArray holding `values`.
+    fn cf_array(values: &[&CFType]) -> CFRetained<CFArray> {
+        let mut values = values
+            .iter()
+            .map(|value| *value as *const CFType as *const std::ffi::c_void)
+            .collect::<Vec<_>>();
+        unsafe {
+            objc2_core_foundation::CFArrayCreate(
+                None,
+                values.as_mut_ptr(),
+                values.len() as CFIndex,
+                &objc2_core_foundation::kCFTypeArrayCallBacks,
+            )
+        }
+        .unwrap()
+    }
+
+    #[test]
+    fn test_cf_array_to_strings_skips_other_types() {
+        let string = CFString::from_str("arm64");
+        let number = CFNumber::new_i32(7);
+        let array = cf_array(&[&string, &number, &string]);
+        assert_eq!(cf_array_to_strings(&array), ["arm64", "arm64"]);
+    }
+
+    #[test]
+    fn test_cf_array_to_dates_skips_other_types() {
+        let date = unsafe { objc2_core_foundation::CFDateCreate(None, 700000000.0) }.unwrap();
+        let string = CFString::from_str("2023-03-08");
+        let array = cf_array(&[&string, &date]);
+        assert_eq!(
+            cf_array_to_dates(&array),
+            cf_date_to_utc(&date).into_iter().collect::<Vec<_>>()
+        );
+        assert_eq!(cf_array_to_dates(&array).len(), 1);
+    }
+
+    #[test]
+    fn test_get_attribute_names() {
+        let item = MDItem::from_path("/Applications/Safari.app").unwrap();
+        let names = item.get_attribute_names();
+        assert!(!names.is_empty());
+    }
+
+    /// Reads an attribute with a freshly created name, bypassing the name cache.
+    fn uncached_value(item: &MDItem, name: &str) -> Option<MDAttributeValue> {
+        let name = CFString::from_str(name);
+        let value = item.item.copy_attribute(&name)?;
+        cf_type_to_value(&value)
+    }
+
+    #[test]
+    fn test_cached_names_read_same_values() {
+        let item = MDItem::from_path("/Applications/Safari.app").unwrap();
+        let mut names = MDItemKey::ALL
+            .iter()
+            .map(|key| key.as_str().to_string())
+            .collect::<Vec<_>>();
+        names.extend(item.get_attribute_names());
+        for name in &names {
+            assert!(
+                item.get_attribute_value(name) == uncached_value(&item, name),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    #[ignore = "benchmark, run with --ignored --nocapture"]
+    fn bench_cached_attribute_names() {
+        let items = crate::MDQuery::new(
+            "kMDItemFSName == \"*\"",
+            [crate::MDQueryScope::Home],
+            Some(10_000),
+        )
+        .unwrap()
+        .execute()
+        .unwrap();
+        let keys = [
+            MDItemKey::Path,
+            MDItemKey::DisplayName,
+            MDItemKey::ContentType,
+            MDItemKey::Size,
+        ];
+
+        let start = std::time::Instant::now();
+        for item in &items {
+            for key in keys {
+                std::hint::black_box(uncached_value(item, key.as_str()));
+            }
+        }
+        let uncached = start.elapsed();
+        let start = std::time::Instant::now();
+        for item in &items {
+            for key in keys {
+                std::hint::black_box(item.attribute_value(key));
+            }
+        }
+        let cached = start.elapsed();
+        println!(
+            "{} items: uncached {:?}, cached {:?}",
+            items.len(),
+            uncached,
+            cached
+        );
+    }
+
+    #[test]
+    fn test_get_path() {
+        let item = MDItem::from_path("/Applications/Safari.app").unwrap();
+        let path = item.path().unwrap();
+        assert_eq!(path, PathBuf::from("/Applications/Safari.app"));
+    }
+
+    #[test]
+    fn test_get_content_type_tree() {
+        let item = MDItem::from_path("/Applications/Safari.app").unwrap();
+        let content_type_tree = item.content_type_tree().unwrap();
+        assert!(!content_type_tree.is_empty());
+    }
+
+    #[test]
+    fn test_get_kind() {
+        let item = MDItem::from_path("/Applications/Safari.app").unwrap();
+        let kind = item.kind().unwrap();
+        assert!(!kind.is_empty());
+    }
+
+    #[test]
+    fn test_get_version() {
+        let item = MDItem::from_path("/Applications/Safari.app").unwrap();
+        let version = item.version().unwrap();
+        assert!(version
+            .split('.')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit())));
+    }
+
+    #[test]
+    fn test_get_bundle_identifier() {
+        let item = MDItem::from_path("/Applications/Safari.app").unwrap();
+        assert_eq!(
+            item.bundle_identifier(),
+            Some("com.apple.Safari".to_string())
+        );
+
+        let item = MDItem::from_path("/Applications").unwrap();
+        assert_eq!(item.bundle_identifier(), None);
+        assert_eq!(item.version(), None);
+    }
+
+    #[test]
+    fn test_authors_creator_missing() {
+        let item = MDItem::from_path("/Applications").unwrap();
+        assert_eq!(item.authors(), None);
+        assert_eq!(item.creator_app(), None);
+        assert_eq!(item.keywords(), None);
+        assert_eq!(item.where_froms(), None);
+        assert_eq!(item.downloaded_date(), None);
+        assert_eq!(item.pixel_dimensions(), None);
+        assert_eq!(item.camera_make(), None);
+        assert_eq!(item.camera_model(), None);
+        assert_eq!(item.duration(), None);
+        assert_eq!(item.album(), None);
+        assert_eq!(item.artists(), None);
+        assert_eq!(item.genre(), None);
+        assert_eq!(item.recording_year(), None);
+        assert_eq!(item.audio_sample_rate(), None);
+        assert_eq!(item.audio_channel_count(), None);
+        assert_eq!(item.codecs(), None);
+        assert_eq!(item.total_bit_rate(), None);
+        assert_eq!(item.video_bit_rate(), None);
+        assert_eq!(item.page_count(), None);
+        assert_eq!(item.subject(), None);
+        assert_eq!(item.url(), None);
+        assert!(!item.is_screenshot());
+        assert!(!item.is_in_icloud());
+        assert_eq!(item.screenshot_type(), None);
+        assert_eq!(item.recipients(), None);
+        assert_eq!(item.author_email_addresses(), None);
+        assert_eq!(item.recipient_email_addresses(), None);
+    }
+
+    #[test]
+    fn test_finder_comment() {
+        // Point MDQUERY_TEST_FINDER_COMMENT_PATH at a file with a Finder comment to run this.
+        let Some(path) = std::env::var_os("MDQUERY_TEST_FINDER_COMMENT_PATH") else {
+            return;
+        };
+        let item = MDItem::from_path(path).unwrap();
+        assert!(item
+            .finder_comment()
+            .is_some_and(|comment| !comment.is_empty()));
+    }
+
+    #[test]
+    fn test_download_origin() {
+        let Some(home) = std::env::var_os("HOME") else {
+            return;
+        };
+        let downloads = PathBuf::from(home).join("Downloads");
+        if !downloads.is_dir() {
+            return;
+        }
+        let query = crate::MDQuery::new(
+            "kMDItemWhereFroms == \"*\"",
+            [crate::MDQueryScope::from_path(downloads)],
+            Some(1),
+        )
+        .unwrap();
+        let Some(item) = query.execute().unwrap().into_iter().next() else {
+            return;
+        };
+        assert!(item.where_froms().is_some_and(|urls| !urls.is_empty()));
+        if let Some(date) = item.downloaded_date() {
+            assert!(date <= Utc::now());
+        }
+    }
+
+    /// Imports `path` with `mdimport` and waits until `read` returns a value for it.
+    fn wait_for_import<T>(path: &Path, read: impl Fn(&MDItem) -> Option<T>) -> Option<T> {
+        let _ = std::process::Command::new("mdimport").arg(path).status();
+        for _ in 0..40 {
+            if let Some(value) = MDItem::from_path(path).ok().as_ref().and_then(&read) {
+                return Some(value);
+            }
+            std::thread::sleep(Duration::from_millis(250));
+        }
+        None
+    }
+
+    #[test]
+    fn test_downloaded_date() {
+        use chrono::TimeZone;
+
+        let path =
+            std::env::temp_dir().join(format!("mdquery-download-{}.txt", std::process::id()));
+        std::fs::write(&path, "downloaded").unwrap();
+        // A binary plist holding an array with the date 2023-03-08T20:26:40Z, the way
+        // browsers record downloads.
+        let plist = concat!(
+            "62706C6973743030",
+            "A101",
+            "3341C4DC9380000000",
+            "080A",
+            "0000000000000101000000000000000200000000000000000000000000000013",
+        );
+        let written = std::process::Command::new("xattr")
+            .args(["-wx", "com.apple.metadata:kMDItemDownloadedDate", plist])
+            .arg(&path)
+            .status()
+            .unwrap();
+        assert!(written.success());
+        let date = wait_for_import(&path, MDItem::downloaded_date);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(date, Utc.with_ymd_and_hms(2023, 3, 8, 20, 26, 40).single());
+    }
+
+    #[test]
+    fn test_pixel_dimensions() {
+        // A 3x2 grayscale PNG.
+        const PNG: &[u8] = &[
+            0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48,
+            0x44, 0x52, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x02, 0x08, 0x00, 0x00, 0x00,
+            0x00, 0xb8, 0x1f, 0x39, 0xc6, 0x00, 0x00, 0x00, 0x0b, 0x49, 0x44, 0x41, 0x54, 0x78,
+            0x9c, 0x63, 0x60, 0x80, 0x00, 0x00, 0x00, 0x08, 0x00, 0x01, 0xb7, 0x58, 0x73, 0x95,
+            0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+        ];
+        let path = std::env::temp_dir().join(format!("mdquery-pixels-{}.png", std::process::id()));
+        std::fs::write(&path, PNG).unwrap();
+        let dimensions = wait_for_import(&path, MDItem::pixel_dimensions);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(dimensions, Some((3, 2)));
+    }
+
+    #[test]
+    fn test_image_technical_attributes() {
+        // A 3x2 RGBA PNG with 16 bits per channel.
+        const PNG: &[u8] = &[
+            0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48,
+            0x44, 0x52, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x02, 0x10, 0x06, 0x00, 0x00,
+            0x00, 0xcd, 0xe4, 0xba, 0x59, 0x00, 0x00, 0x00, 0x0c, 0x49, 0x44, 0x41, 0x54, 0x78,
+            0xda, 0x63, 0x60, 0x20, 0x15, 0x00, 0x00, 0x00, 0x32, 0x00, 0x01, 0x7f, 0xc1, 0x4f,
+            0xba, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+        ];
+        let path = std::env::temp_dir().join(format!("mdquery-image-{}.png", std::process::id()));
+        std::fs::write(&path, PNG).unwrap();
+        let attributes = wait_for_import(&path, |item| {
+            Some((
+                item.bits_per_sample()?,
+                item.has_alpha_channel()?,
+                item.color_space()?,
+                item.orientation()?,
+            ))
+        });
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            attributes,
+            Some((16, true, "RGB".to_string(), ImageOrientation::Landscape))
+        );
+    }
+
+    #[test]
+    fn test_exposure_attributes() {
+        // An 8x8 grayscale JPEG whose EXIF records 1/250 s at f/2.8, ISO 400 and 50 mm.
+        const JPEG: &[u8] = &[
+            0xff, 0xd8, 0xff, 0xe1, 0x00, 0x7c, 0x45, 0x78, 0x69, 0x66, 0x00, 0x00, 0x4d, 0x4d,
+            0x00, 0x2a, 0x00, 0x00, 0x00, 0x08, 0x00, 0x01, 0x87, 0x69, 0x00, 0x04, 0x00, 0x00,
+            0x00, 0x01, 0x00, 0x00, 0x00, 0x1a, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x82, 0x9a,
+            0x00, 0x05, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x5c, 0x82, 0x9d, 0x00, 0x05,
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x64, 0x88, 0x27, 0x00, 0x03, 0x00, 0x00,
+            0x00, 0x01, 0x01, 0x90, 0x00, 0x00, 0x90, 0x00, 0x00, 0x07, 0x00, 0x00, 0x00, 0x04,
+            0x30, 0x32, 0x33, 0x30, 0x92, 0x0a, 0x00, 0x05, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00,
+            0x00, 0x6c, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0xfa,
+            0x00, 0x00, 0x00, 0x1c, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00, 0x32, 0x00, 0x00,
+            0x00, 0x01, 0xff, 0xdb, 0x00, 0x43, 0x00, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01,
+            0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01,
+            0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01,
+            0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01,
+            0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01,
+            0x01, 0xff, 0xc0, 0x00, 0x0b, 0x08, 0x00, 0x08, 0x00, 0x08, 0x01, 0x01, 0x11, 0x00,
+            0xff, 0xc4, 0x00, 0x14, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0xc4, 0x00, 0x14, 0x10, 0x01,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0xff, 0xda, 0x00, 0x08, 0x01, 0x01, 0x00, 0x00, 0x3f, 0x00, 0x3f, 0xff,
+            0xd9,
+        ];
+        let path = std::env::temp_dir().join(format!("mdquery-exif-{}.jpg", std::process::id()));
+        std::fs::write(&path, JPEG).unwrap();
+        let attributes = wait_for_import(&path, |item| {
+            Some([
+                item.iso_speed()?,
+                item.focal_length()?,
+                item.exposure_time_seconds()?,
+                item.f_number()?,
+            ])
+        });
+        std::fs::remove_file(&path).unwrap();
+        let attributes = attributes.expect("the EXIF attributes were not imported");
+        for (value, expected) in attributes.into_iter().zip([400.0, 50.0, 0.004, 2.8]) {
+            assert!((value - expected).abs() < 1e-6, "{} != {}", value, expected);
+        }
+    }
+
+    #[test]
+    fn test_video_info() {
+        assert_eq!(
+            MDItem::from_path("/Applications").unwrap().video_info(),
+            None
+        );
+        let Some(home) = std::env::var_os("HOME") else {
+            return;
+        };
+        let movies = PathBuf::from(home).join("Movies");
+        if !movies.is_dir() {
+            return;
+        }
+        let query = crate::MDQuery::new(
+            "kMDItemContentTypeTree == \"public.movie\" && kMDItemPixelWidth > 0",
+            [crate::MDQueryScope::from_path(movies)],
+            Some(1),
+        )
+        .unwrap();
+        let Some(item) = query.execute().unwrap().into_iter().next() else {
+            return;
+        };
+        let info = item.video_info().unwrap();
+        assert_eq!(Some((info.width, info.height)), item.pixel_dimensions());
+        assert_eq!(info.duration, item.duration());
+    }
+
+    #[test]
+    fn test_duration() {
+        let query = crate::MDQuery::new(
+            "kMDItemDurationSeconds > 0",
+            [crate::MDQueryScope::from_path("/System/Library/Sounds")],
+            Some(1),
+        )
+        .unwrap();
+        let Some(item) = query.execute().unwrap().into_iter().next() else {
+            return;
+        };
+        assert!(item.duration().is_some_and(|duration| !duration.is_zero()));
+    }
+
+    #[test]
+    fn test_music_metadata() {
+        let Some(home) = std::env::var_os("HOME") else {
+            return;
+        };
+        let music = PathBuf::from(home).join("Music");
+        if !music.is_dir() {
+            return;
+        }
+        let query = crate::MDQuery::new(
+            "kMDItemFSName == \"*.mp3\"c && kMDItemAlbum == \"*\"",
+            [crate::MDQueryScope::from_path(music)],
+            Some(1),
+        )
+        .unwrap();
+        let Some(item) = query.execute().unwrap().into_iter().next() else {
+            return;
+        };
+        assert!(item.album().is_some());
+        if let Some(artists) = item.artists() {
+            assert!(!artists.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_owner_and_node_count() {
+        let item = MDItem::from_path("/Applications").unwrap();
+        assert_eq!(item.owner_uid(), Some(0));
+        assert!(item.node_count().is_some_and(|count| count > 0));
+
+        let item = MDItem::from_path("/Applications/Safari.app").unwrap();
+        assert_eq!(item.owner_uid(), Some(0));
+    }
+
+    #[test]
+    fn test_security_method_unencrypted() {
+        let path = std::env::temp_dir().join(format!("mdquery-plain-{}.txt", std::process::id()));
+        std::fs::write(&path, "not encrypted").unwrap();
+        let method = MDItem::from_path(&path).unwrap().security_method();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(method, None);
+    }
+
+    #[test]
+    fn test_text_content() {
+        let Some(home) = std::env::var_os("HOME") else {
+            return;
+        };
+        let path = PathBuf::from(home).join(format!("mdquery-text-{}.txt", std::process::id()));
+        std::fs::write(&path, "héllo spotlight text content").unwrap();
+        let item = MDItem::from_path(&path).unwrap();
+        let text = item.text_content();
+        let truncated = item.text_content_truncated(5);
+        std::fs::remove_file(&path).unwrap();
+
+        // The importer may not have run yet.
+        let Some(text) = text else {
+            return;
+        };
+        assert!(text.contains("spotlight"));
+        assert_eq!(truncated.as_deref(), Some("héllo"));
+    }
+
+    #[test]
+    fn test_document_info() {
+        let path = std::env::temp_dir().join(format!("mdquery-info-{}.txt", std::process::id()));
+        std::fs::write(&path, "plain text").unwrap();
+        let info = MDItem::from_path(&path).unwrap().document_info();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(info.title, None);
+        assert_eq!(info.copyright, None);
+
+        let query = crate::MDQuery::new(
+            "kMDItemContentTypeTree == \"com.adobe.pdf\" && kMDItemTitle == \"*\"",
+            [],
+            Some(1),
+        )
+        .unwrap();
+        if let Some(item) = query.execute().unwrap().into_iter().next() {
+            assert!(item.document_info().title.is_some());
+        }
+    }
+
+    #[test]
+    fn test_cached_attributes() {
+        let dir = std::env::temp_dir().join(format!("mdquery-cache-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("cached.txt");
+        std::fs::write(&path, "cached").unwrap();
+        let mut item = MDItem::from_path(&path).unwrap();
+        let cached_path = item.path();
+        let display_name = item.display_name();
+        let content_type = item.content_type();
+        let content_type_tree = item.content_type_tree();
+        assert!(cached_path.is_some());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(item.path(), cached_path);
+        assert_eq!(item.display_name(), display_name);
+        assert_eq!(item.content_type(), content_type);
+        assert_eq!(item.content_type_tree(), content_type_tree);
+
+        // Refreshing must not fail even though the file is gone.
+        item.refresh();
+        let _ = item.path();
+    }
+
+    #[test]
+    fn test_clone_across_threads() {
+        let item = MDItem::from_path("/Applications/Safari.app").unwrap();
+        let handles = (0..8)
+            .map(|_| {
+                let item = item.clone();
+                std::thread::spawn(move || (item.path(), item.display_name(), item.is_app()))
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            let (path, display_name, is_app) = handle.join().unwrap();
+            assert_eq!(path, Some(PathBuf::from("/Applications/Safari.app")));
+            assert_eq!(display_name, item.display_name());
+            assert!(is_app);
+        }
+
+        let shared = std::sync::Arc::new(item);
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| assert!(shared.content_type_tree().is_some()));
+            }
+        });
+    }
+
+    #[test]
+    // The cache only ever fills in the path that `Hash` reads, so keys stay stable.
+    #[allow(clippy::mutable_key_type)]
+    fn test_eq_and_hash() {
+        use std::collections::HashSet;
+        use std::hash::{BuildHasher, RandomState};
+
+        let query = || {
+            crate::MDQuery::new(
+                "kMDItemFSName == \"Safari.app\"",
+                [crate::MDQueryScope::from_path("/Applications")],
+                Some(1),
+            )
+            .unwrap()
+            .execute()
+            .unwrap()
+            .remove(0)
+        };
+        let first = query();
+        let second = query();
+        let from_path = MDItem::from_path("/Applications/Safari.app").unwrap();
+        assert!(first == second);
+        assert!(first == from_path);
+
+        let hasher = RandomState::new();
+        assert_eq!(hasher.hash_one(&first), hasher.hash_one(&second));
+
+        let other = MDItem::from_path("/Applications").unwrap();
+        assert!(first != other);
+        let set = [first, second, from_path, other]
+            .into_iter()
+            .collect::<HashSet<_>>();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_from_url() {
+        let path = CFString::from_str("/Applications/Safari.app");
+        let url = unsafe {
+            objc2_core_foundation::CFURLCreateWithFileSystemPath(
+                None,
+                Some(&path),
+                objc2_core_foundation::CFURLPathStyle::CFURLPOSIXPathStyle,
+                true,
+            )
+        }
+        .unwrap();
+        let item = MDItem::from_url(&url).unwrap();
+        assert_eq!(item.path(), Some(PathBuf::from("/Applications/Safari.app")));
+        assert!(item.is_app());
+    }
+
+    #[test]
+    fn test_app_store_category() {
+        let calculator = MDItem::from_path("/System/Applications/Calculator.app").unwrap();
+        if let Some(category) = calculator.app_store_category() {
+            assert!(!category.is_empty());
+        }
+        assert_eq!(
+            MDItem::from_path("/Applications")
+                .unwrap()
+                .app_store_category(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_executable_architectures() {
+        let item = MDItem::from_path("/System/Applications/Calculator.app").unwrap();
+        let architectures = item.executable_architectures().unwrap();
+        #[cfg(target_arch = "aarch64")]
+        assert!(architectures.iter().any(|arch| arch == "arm64"));
+        assert!(!architectures.is_empty());
+        assert_eq!(
+            MDItem::from_path("/Applications")
+                .unwrap()
+                .executable_architectures(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_package() {
+        let safari = MDItem::from_path("/Applications/Safari.app").unwrap();
+        assert!(safari.is_dir());
+        assert!(safari.is_package());
+        let applications = MDItem::from_path("/Applications").unwrap();
+        assert!(applications.is_dir());
+        assert!(!applications.is_package());
+    }
+
+    #[test]
+    fn test_from_path_symlink() {
+        let dir = std::env::temp_dir()
+            .canonicalize()
+            .unwrap()
+            .join(format!("mdquery-symlink-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("target.txt");
+        let link = dir.join("link.txt");
+        std::fs::write(&target, "target").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        // A relative path to the link, so that `from_path` has to resolve it.
+        let cwd = std::env::current_dir().unwrap();
+        let relative = PathBuf::from("../".repeat(cwd.components().count() - 1))
+            .join(link.strip_prefix("/").unwrap());
+
+        let resolved = MDItem::from_path(&relative).unwrap().path();
+        let unresolved = MDItem::from_path_unresolved(&relative).unwrap().path();
+        let absolute = MDItem::from_path(&link).unwrap().path();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(resolved, Some(target));
+        assert_eq!(unresolved, Some(link.clone()));
+        assert_eq!(absolute, Some(link));
+    }
+
+    #[test]
+    fn test_symlink_classification() {
+        let dir = std::env::temp_dir()
+            .canonicalize()
+            .unwrap()
+            .join(format!("mdquery-link-kind-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("target.txt");
+        let link = dir.join("link.txt");
+        std::fs::write(&target, "target").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        let link_item = MDItem::from_path_unresolved(&link).unwrap();
+        let target_item = MDItem::from_path(&target).unwrap();
+        let link_kind = (link_item.is_symlink(), link_item.is_alias());
+        let target_kind = (target_item.is_symlink(), target_item.is_alias());
+        let resolved = link_item.resolved_path();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(link_kind, (true, false));
+        assert_eq!(target_kind, (false, false));
+        assert_eq!(resolved, Some(target));
+    }
+
+    #[test]
+    fn test_date_added() {
+        let path = std::env::temp_dir().join(format!("mdquery-added-{}.plist", std::process::id()));
+        // `cp -p` keeps the modification date of the original, but not its date added.
+        let copied = std::process::Command::new("cp")
+            .arg("-p")
+            .arg("/System/Library/CoreServices/SystemVersion.plist")
+            .arg(&path)
+            .status()
+            .unwrap();
+        assert!(copied.success());
+        let dates = wait_for_import(&path, |item| {
+            let modified = item.get_date_attribute(MDItemKey::ModificationDate.as_str())?;
+            Some((item.date_added()?, modified))
+        });
+        std::fs::remove_file(&path).unwrap();
+
+        let (added, modified) = dates.expect("date added and modification date");
+        assert!(added > modified);
+        assert!(added > Utc::now() - chrono::Duration::minutes(5));
+    }
+
+    #[test]
+    fn test_debug_shows_path() {
+        let item = MDItem::from_path("/Applications/Safari.app").unwrap();
+        assert_eq!(
+            format!("{:?}", item),
+            "MDItem { path: Some(\"/Applications/Safari.app\") }"
+        );
+    }
+
+    #[test]
+    fn test_volume_path() {
+        let item = MDItem::from_path("/Applications/Safari.app").unwrap();
+        assert_eq!(item.volume_path(), Some(PathBuf::from("/")));
+        assert!(mount_points().contains(&PathBuf::from("/")));
+    }
+
+    #[test]
+    fn test_label() {
+        let path = std::env::temp_dir().join(format!("mdquery-label-{}", std::process::id()));
+        std::fs::write(&path, "label").unwrap();
+        assert!(matches!(
+            MDItem::from_path(&path).unwrap().label(),
+            None | Some(FinderLabel::None)
+        ));
+
+        // Finder stores the label index in bits 1-3 of byte 9 of the FinderInfo xattr.
+        let finder_info = format!("{}{:02X}{}", "00".repeat(9), 6 << 1, "00".repeat(22));
+        let labeled = std::process::Command::new("xattr")
+            .args(["-wx", "com.apple.FinderInfo", &finder_info])
+            .arg(&path)
+            .status()
+            .is_ok_and(|status| status.success());
+        assert!(labeled);
+        let label = wait_for_import(&path, |item| {
+            item.label().filter(|label| *label != FinderLabel::None)
+        });
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(label, Some(FinderLabel::Red));
+    }
+
+    #[test]
+    fn test_usage_statistics() {
+        let item = MDItem::from_path("/Applications/Safari.app").unwrap();
+        // Usage data depends on the machine, so only check that it is consistent.
+        if let Some(count) = item.use_count() {
+            assert!(count >= 0);
+        }
+        if let Some(dates) = item.used_dates() {
+            assert!(dates.iter().all(|date| *date <= Utc::now()));
+        }
+    }
+
+    #[test]
+    fn test_matches_kind() {
+        let item = MDItem::from_path("/Applications/Safari.app").unwrap();
+        assert!(item.matches_kind(ContentKind::Application));
+        assert!(!item.matches_kind(ContentKind::Folder));
+        assert!(!item.matches_kind(ContentKind::Image));
+
+        let item = MDItem::from_path("/Applications").unwrap();
+        assert!(item.matches_kind(ContentKind::Folder));
+    }
+
+    #[test]
+    fn test_classifiers() {
+        let dir = std::env::temp_dir().join(format!("mdquery-classify-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let classify = |name: &str| {
+            let path = dir.join(name);
+            std::fs::write(&path, "content").unwrap();
+            let item = MDItem::from_path(&path).unwrap();
+            [
+                item.is_document(),
+                item.is_archive(),
+                item.is_presentation(),
+                item.is_spreadsheet(),
+                item.is_source_code(),
+            ]
+        };
+        let notes = classify("notes.txt");
+        let archive = classify("archive.zip");
+        let slides = classify("slides.pptx");
+        let sheet = classify("sheet.xlsx");
+        let code = classify("main.c");
+        let image = classify("image.png");
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(notes, [true, false, false, false, false]);
+        assert_eq!(archive, [false, true, false, false, false]);
+        assert_eq!(slides, [true, false, true, false, false]);
+        assert_eq!(sheet, [true, false, false, true, false]);
+        assert_eq!(code, [true, false, false, false, true]);
+        assert_eq!(image, [false; 5]);
+
+        let app = MDItem::from_path("/Applications/Safari.app").unwrap();
+        assert!(!app.is_document() && !app.is_archive() && !app.is_source_code());
+    }
+
+    #[test]
+    fn test_is_hidden() {
+        let item = MDItem::from_path("/Applications/Safari.app").unwrap();
+        assert!(!item.is_hidden());
+
+        let path = std::env::temp_dir().join(format!(".mdquery-hidden-{}", std::process::id()));
+        std::fs::write(&path, "hidden").unwrap();
+        let item = MDItem::from_path(&path).unwrap();
+        let hidden = item.is_hidden();
+        std::fs::remove_file(&path).unwrap();
+        assert!(hidden);
+    }
+
+    #[test]
+    #[cfg(feature = "uttype")]
+    fn test_content_type_ut() {
+        let item = MDItem::from_path("/Applications/Safari.app").unwrap();
+        let content_type = item.content_type_ut().unwrap();
+        assert_eq!(content_type.identifier(), "com.apple.application-bundle");
+        assert!(content_type.conforms_to(&UTType::from_identifier("com.apple.bundle").unwrap()));
+        assert!(item.matches_kind(ContentKind::Application));
+        assert!(!item.matches_kind(ContentKind::Image));
     }
 }
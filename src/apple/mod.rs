@@ -1,16 +1,26 @@
 mod api;
-mod builder;
 mod item;
-mod model;
+mod names;
+#[cfg(feature = "nsmetadata")]
+mod ns_metadata;
 mod query;
+pub(crate) mod schema;
+#[cfg(feature = "uttype")]
+mod uttype;
 
 #[cfg(feature = "async")]
 mod query_async;
 
-pub use builder::*;
 pub use item::*;
-pub use model::*;
 pub use query::*;
+#[cfg(feature = "uttype")]
+pub use uttype::*;
 
 #[cfg(feature = "async")]
 pub use query_async::*;
+
+/// Returns a CoreFoundation object as the toll-free bridged Foundation object.
+#[cfg(any(feature = "nsmetadata", feature = "uttype"))]
+fn as_object<T>(value: &T) -> *const objc2::runtime::AnyObject {
+    (value as *const T).cast()
+}
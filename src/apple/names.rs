@@ -0,0 +1,99 @@
+//! Process-wide cache of the CFStrings used as attribute names.
+//!
+//! Reading an attribute needs its name as a `CFString`. Creating one allocates and copies
+//! the name, which adds up when thousands of results are hydrated, so the names of all
+//! [`MDItemKey`]s are created once and shared, and recently used custom names are kept in
+//! a small least-recently-used cache.
+
+use crate::MDItemKey;
+use objc2_core_foundation::{CFRetained, CFString};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+/// The number of custom attribute names kept by [`attribute_name`].
+const CUSTOM_NAME_CAPACITY: usize = 64;
+
+/// An immutable CFString shared between threads.
+struct SharedName(CFRetained<CFString>);
+
+// SAFETY: The strings are created by `CFString::from_str` and never mutated. Immutable
+// CoreFoundation objects may be used from any thread, and retain counts are atomic.
+unsafe impl Send for SharedName {}
+unsafe impl Sync for SharedName {}
+
+fn known_names() -> &'static HashMap<&'static str, SharedName> {
+    static KNOWN: OnceLock<HashMap<&'static str, SharedName>> = OnceLock::new();
+    KNOWN.get_or_init(|| {
+        MDItemKey::ALL
+            .iter()
+            .map(|key| (key.as_str(), SharedName(CFString::from_str(key.as_str()))))
+            .collect()
+    })
+}
+
+/// Recently used custom names, least recently used first.
+fn custom_names() -> &'static Mutex<Vec<(String, SharedName)>> {
+    static CUSTOM: OnceLock<Mutex<Vec<(String, SharedName)>>> = OnceLock::new();
+    CUSTOM.get_or_init(|| Mutex::new(Vec::with_capacity(CUSTOM_NAME_CAPACITY)))
+}
+
+/// Returns the CFString for an [`MDItemKey`], created once per process.
+pub(super) fn key_name(key: MDItemKey) -> &'static CFString {
+    &known_names()[key.as_str()].0
+}
+
+/// Returns the CFString for an attribute name.
+///
+/// Names of [`MDItemKey`]s are served from the static cache, and other names from the
+/// cache of recently used custom names, so only a name that has not been used recently
+/// allocates a new string.
+pub(super) fn attribute_name(name: &str) -> CFRetained<CFString> {
+    if let Some(known) = known_names().get(name) {
+        return known.0.clone();
+    }
+    let mut custom = custom_names()
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    if let Some(index) = custom.iter().position(|(cached, _)| cached == name) {
+        let entry = custom.remove(index);
+        let string = entry.1 .0.clone();
+        custom.push(entry);
+        return string;
+    }
+    let string = CFString::from_str(name);
+    if custom.len() == CUSTOM_NAME_CAPACITY {
+        custom.remove(0);
+    }
+    custom.push((name.to_string(), SharedName(string.clone())));
+    string
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_match_uncached() {
+        for key in MDItemKey::ALL {
+            assert_eq!(*key_name(*key), *CFString::from_str(key.as_str()));
+            assert_eq!(attribute_name(key.as_str()).to_string(), key.as_str());
+        }
+        assert_eq!(
+            attribute_name("kMDItemCustomTest").to_string(),
+            "kMDItemCustomTest"
+        );
+    }
+
+    #[test]
+    fn test_custom_names_are_evicted() {
+        for i in 0..CUSTOM_NAME_CAPACITY * 2 {
+            let name = format!("kMDItemEvictionTest{}", i);
+            assert_eq!(attribute_name(&name).to_string(), name);
+        }
+        let custom = custom_names().lock().unwrap();
+        assert!(custom.len() <= CUSTOM_NAME_CAPACITY);
+        assert!(custom.iter().any(
+            |(name, _)| name == &format!("kMDItemEvictionTest{}", CUSTOM_NAME_CAPACITY * 2 - 1)
+        ));
+    }
+}
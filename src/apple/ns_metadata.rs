@@ -0,0 +1,77 @@
+//! Query execution on top of `NSMetadataQuery`, for processes in the App Sandbox.
+
+use super::query::cf_string_array;
+use super::{as_object, MDItem};
+use anyhow::{anyhow, Result};
+use objc2::rc::{autoreleasepool, Retained};
+use objc2::runtime::AnyObject;
+use objc2::{class, msg_send};
+use objc2_core_foundation::{kCFRunLoopDefaultMode, CFRunLoopRunInMode, CFString};
+
+// `NSPredicate` and `NSMetadataQuery` live in Foundation, which objc2 does not link.
+#[link(name = "Foundation", kind = "framework")]
+extern "C" {}
+
+/// Runs `query` with `NSMetadataQuery` on the current thread's run loop and collects the
+/// results once gathering has finished.
+///
+/// # Arguments
+/// * `query` - A Spotlight query string
+/// * `scopes` - The resolved scope strings, or None to search everywhere
+/// * `max_count` - Optional maximum number of results to return
+///
+/// # Returns
+/// * `Result<Vec<MDItem>>` - The results, or an error if the query could not be started
+pub(super) fn execute(
+    query: &str,
+    scopes: Option<&[String]>,
+    max_count: Option<usize>,
+) -> Result<Vec<MDItem>> {
+    autoreleasepool(|_| {
+        let query_string = CFString::from_str(query);
+        let predicate: Option<Retained<AnyObject>> = unsafe {
+            msg_send![
+                class!(NSPredicate),
+                predicateFromMetadataQueryString: as_object(&*query_string)
+            ]
+        };
+        let predicate = predicate.ok_or(anyhow!(
+            "NSMetadataQuery create failed, check query syntax."
+        ))?;
+
+        let md_query: Retained<AnyObject> = unsafe { msg_send![class!(NSMetadataQuery), new] };
+        let _: () = unsafe { msg_send![&md_query, setPredicate: &*predicate] };
+
+        if let Some(scopes) = scopes {
+            let scopes = cf_string_array(scopes).ok_or(anyhow!(
+                "NSMetadataQuery create failed when create scope array."
+            ))?;
+            let _: () = unsafe { msg_send![&md_query, setSearchScopes: as_object(&*scopes)] };
+        }
+
+        let started: bool = unsafe { msg_send![&md_query, startQuery] };
+        if !started {
+            return Err(anyhow!("NSMetadataQuery execute failed."));
+        }
+        loop {
+            unsafe { CFRunLoopRunInMode(kCFRunLoopDefaultMode, 0.1, false) };
+            let gathering: bool = unsafe { msg_send![&md_query, isGathering] };
+            if !gathering {
+                break;
+            }
+        }
+        let _: () = unsafe { msg_send![&md_query, stopQuery] };
+
+        let count: usize = unsafe { msg_send![&md_query, resultCount] };
+        let count = max_count.map_or(count, |max_count| count.min(max_count));
+        let mut items = Vec::with_capacity(count);
+        for i in 0..count {
+            // The results are NSMetadataItems, which carry the attributes the query found.
+            let result: *mut AnyObject = unsafe { msg_send![&md_query, resultAtIndex: i] };
+            if let Some(result) = unsafe { Retained::retain(result) } {
+                items.push(MDItem::from_ns_metadata_item(result));
+            }
+        }
+        Ok(items)
+    })
+}
@@ -1,12 +1,187 @@
-use std::ptr::{self, NonNull};
-use super::{api::*, MDItemKey};
-use super::{MDItem, MDQueryBuilder, MDQueryScope};
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::ffi::c_void;
+use std::fmt;
+use std::ops::ControlFlow;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::Duration;
+use super::api::*;
+use super::item::cf_type_to_value;
+use super::names::{attribute_name, key_name};
+use super::MDItem;
+use crate::builder::text_search_query;
+use crate::results::merge_scoped;
+use crate::sort::select_top_k;
+use crate::{
+    validate_query, Backend, BuilderError, MDAttributeValue, MDItemKey, MDQueryBuilder,
+    MDQueryError, MDQueryOptions, MDQueryScope, ScopeOptions, ScopedItem, ScoredItem,
+    SortDirection,
+};
 use anyhow::{anyhow, Result};
-use objc2_core_foundation::{CFArrayCreate, CFIndex, CFRetained, CFString};
+use objc2_core_foundation::{
+    kCFRunLoopDefaultMode, kCFTypeArrayCallBacks, CFArray, CFArrayCreate, CFDictionary, CFIndex,
+    CFNotificationCenter, CFNotificationCenterAddObserver, CFNotificationCenterGetLocalCenter,
+    CFNotificationCenterRemoveObserver, CFNotificationName, CFNotificationSuspensionBehavior,
+    CFRetained, CFRunLoop, CFRunLoopGetCurrent, CFRunLoopRunInMode, CFRunLoopStop, CFString,
+    CFType,
+};
 
 /// A wrapper around macOS Spotlight search query API.
 /// Provides functionality to create and execute metadata queries.
-pub struct MDQuery(CFRetained<CoreMDQuery>);
+///
+/// `MDQuery` is `Send` and `Sync`: a query can be created on one thread and executed on
+/// another, and shared between threads that execute it concurrently. Each execution
+/// either runs the stored Spotlight query under a lock or a fresh one owned by the
+/// executing thread, so executions never interfere.
+pub struct MDQuery {
+    /// The most recently created Spotlight query, replaced on every re-execution. None
+    /// for queries on the `NSMetadataQuery` backend, which never create one.
+    md_query: Mutex<Option<CFRetained<CoreMDQuery>>>,
+    params: QueryParameters,
+    /// Whether `md_query` has been handed to `MDQueryExecute` already.
+    executed: AtomicBool,
+}
+
+// SAFETY: Apple's threading guidance (Threading Programming Guide, "Thread Safety
+// Summary") allows Core Foundation objects to be retained, released and passed between
+// threads, but not to be mutated from several threads at once. An `MDQueryRef` is
+// mutated by its setters and by `MDQueryExecute`, and posts its notifications on the run
+// loop of the thread that executed it. This wrapper upholds that:
+// * every query is fully configured in `QueryParameters::create` before it is stored or
+//   shared, and never reconfigured afterwards;
+// * the stored query is only executed while `md_query`'s mutex is held (`execute_with`),
+//   and every later execution creates a fresh query that only the executing thread uses
+//   until it replaces the stored one;
+// * asynchronous executions (`execute_async`, `watch_stream`) hand the query to the
+//   thread whose run loop receives its notifications and only use it from there;
+// * results are read with updates disabled by `MDQueryUpdateLock`, the mechanism Core
+//   Services provides for reading results while the executing thread updates them.
+// The remaining state is immutable `QueryParameters` and an `AtomicBool`.
+unsafe impl Send for MDQuery {}
+unsafe impl Sync for MDQuery {}
+
+/// The arguments a query was created with, kept so it can be rebuilt for re-execution.
+struct QueryParameters {
+    query: String,
+    /// The scopes as passed by the caller.
+    requested_scopes: Vec<MDQueryScope>,
+    /// The resolved scope strings handed to Spotlight.
+    scopes: Option<Vec<String>>,
+    max_count: Option<usize>,
+    scope_options: ScopeOptions,
+    /// Attributes whose distinct values Spotlight gathers while the query runs.
+    value_list_attributes: Vec<String>,
+    /// Attributes whose values Spotlight caches with every result, such as the relevance
+    /// for `search_text` or the sort attribute of `with_top_k`.
+    sorting_attributes: Vec<String>,
+    /// The number of results to keep, the attribute they are sorted by and the order.
+    top_k: Option<(usize, MDItemKey, SortDirection)>,
+    /// The resolved backend, never `Backend::Auto`.
+    backend: Backend,
+}
+
+impl QueryParameters {
+    /// Creates the Spotlight query to store in an `MDQuery`, or None if the query runs on
+    /// the `NSMetadataQuery` backend.
+    fn create_stored(&self) -> Result<Option<CFRetained<CoreMDQuery>>> {
+        if self.backend.is_ns_metadata() {
+            return Ok(None);
+        }
+        self.create().map(Some)
+    }
+
+    fn create(&self) -> Result<CFRetained<CoreMDQuery>> {
+        let query = CFString::from_str(&self.query);
+
+        let value_list_attributes = if self.value_list_attributes.is_empty() {
+            None
+        } else {
+            Some(cf_string_array(&self.value_list_attributes).ok_or(anyhow!(
+                "MDQuery create failed when create value list array."
+            ))?)
+        };
+
+        // Result attributes such as the relevance are only available for attributes the
+        // query sorts by.
+        let sorting_attributes = if self.sorting_attributes.is_empty() {
+            None
+        } else {
+            Some(
+                cf_string_array(&self.sorting_attributes)
+                    .ok_or(anyhow!("MDQuery create failed when create sorting array."))?,
+            )
+        };
+
+        let md_query = unsafe {
+            MDQueryCreate(
+                None, // kCFAllocatorDefault
+                &query,
+                value_list_attributes.as_deref(),
+                sorting_attributes.as_deref(),
+            )
+        }
+        .ok_or(anyhow!("MDQuery create failed, check query syntax."))?;
+
+        if let Some(scopes) = &self.scopes {
+            let scopes = cf_string_array(scopes)
+                .ok_or(anyhow!("MDQuery create failed when create scope array."))?;
+
+            unsafe {
+                MDQuerySetSearchScope(&md_query, &scopes, self.scope_options.bits());
+            }
+        }
+
+        if let Some(max_count) = self.max_count {
+            unsafe {
+                MDQuerySetMaxCount(&md_query, max_count as CFIndex);
+            }
+        }
+
+        Ok(md_query)
+    }
+}
+
+/// Returns the Spotlight query stored in an `MDQuery`.
+///
+/// # Errors
+/// Returns an error for queries on the `NSMetadataQuery` backend, which store none.
+fn stored_query(md_query: &Option<CFRetained<CoreMDQuery>>) -> Result<&CoreMDQuery> {
+    md_query.as_deref().ok_or(anyhow!(
+        "This operation is not supported by the NSMetadataQuery backend."
+    ))
+}
+
+/// Creates a CFArray of CFStrings.
+pub(super) fn cf_string_array(strings: &[String]) -> Option<CFRetained<CFArray>> {
+    let strings = strings
+        .iter()
+        .map(|string| CFString::from_str(string))
+        .collect::<Vec<_>>();
+    cf_array_of_strings(&strings.iter().map(|string| &**string).collect::<Vec<_>>())
+}
+
+/// Creates a CFArray holding the given strings.
+///
+/// The array is created with `kCFTypeArrayCallBacks`, so it retains its elements and stays
+/// valid after the strings passed in are released.
+pub(super) fn cf_array_of_strings(strings: &[&CFString]) -> Option<CFRetained<CFArray>> {
+    let mut values = strings
+        .iter()
+        .map(|string| *string as *const CFString as *const c_void)
+        .collect::<Vec<_>>();
+    unsafe {
+        CFArrayCreate(
+            None,
+            values.as_mut_ptr(),
+            values.len() as CFIndex,
+            &kCFTypeArrayCallBacks,
+        )
+    }
+}
 
 impl MDQuery {
     /// Creates a new query builder with default settings.
@@ -21,106 +196,1106 @@ impl MDQuery {
     ///
     /// # Parameters
     /// * `query` - A Spotlight query string
-    /// * `scopes` - Optional vector of search scopes to limit the query
+    /// * `scopes` - Search scopes to limit the query; none means no scope restriction
     /// * `max_count` - Optional maximum number of results to return
     ///
     /// # Returns
     /// A Result containing the MDQuery on success, or an error if query creation fails.
     pub fn new(
+        query: impl AsRef<str>,
+        scopes: impl IntoIterator<Item = MDQueryScope>,
+        max_count: Option<usize>,
+    ) -> Result<Self> {
+        Self::new_with_options(query, scopes, max_count, ScopeOptions::NONE)
+    }
+
+    /// Creates a new MDQuery, passing `scope_options` along with the search scopes.
+    ///
+    /// # Parameters
+    /// * `query` - A Spotlight query string
+    /// * `scopes` - Search scopes to limit the query; none means no scope restriction
+    /// * `max_count` - Optional maximum number of results to return
+    /// * `scope_options` - Option bits for `MDQuerySetSearchScope`; ignored without scopes
+    ///
+    /// # Returns
+    /// A Result containing the MDQuery on success, or an error if query creation fails.
+    /// Malformed query strings are rejected with a [`QuerySyntaxError`](crate::QuerySyntaxError)
+    /// giving the position of the problem.
+    pub fn new_with_options(
+        query: impl AsRef<str>,
+        scopes: impl IntoIterator<Item = MDQueryScope>,
+        max_count: Option<usize>,
+        scope_options: ScopeOptions,
+    ) -> Result<Self> {
+        Self::create(
+            Backend::Auto,
+            query.as_ref(),
+            scopes,
+            max_count,
+            scope_options,
+        )
+    }
+
+    fn create(
+        backend: Backend,
         query: &str,
-        scopes: Option<Vec<MDQueryScope>>,
+        scopes: impl IntoIterator<Item = MDQueryScope>,
         max_count: Option<usize>,
+        scope_options: ScopeOptions,
     ) -> Result<Self> {
-        let query = CFString::from_str(query);
+        Self::from_params(Self::params(
+            backend,
+            query,
+            scopes,
+            max_count,
+            scope_options,
+        )?)
+    }
 
-        let md_query = unsafe {
-            MDQueryCreate(
-                None, // kCFAllocatorDefault
-                &query, None, None,
-            )
+    /// Validates the query and resolves the scopes and backend a query is created with.
+    fn params(
+        backend: Backend,
+        query: &str,
+        scopes: impl IntoIterator<Item = MDQueryScope>,
+        max_count: Option<usize>,
+        scope_options: ScopeOptions,
+    ) -> Result<QueryParameters> {
+        validate_query(query)?;
+
+        let requested_scopes = scopes.into_iter().collect::<Vec<_>>();
+        let scopes = if requested_scopes.is_empty() {
+            None
+        } else {
+            Some(MDQueryScope::into_scope_strings(requested_scopes.clone())?)
+        };
+        Ok(QueryParameters {
+            query: query.to_string(),
+            requested_scopes,
+            scopes,
+            max_count,
+            scope_options,
+            value_list_attributes: Vec::new(),
+            sorting_attributes: Vec::new(),
+            top_k: None,
+            backend: backend.resolve(),
+        })
+    }
+
+    /// Creates the Spotlight query described by `params`.
+    fn from_params(params: QueryParameters) -> Result<Self> {
+        let md_query = params.create_stored()?;
+
+        Ok(Self {
+            md_query: Mutex::new(md_query),
+            params,
+            executed: AtomicBool::new(false),
+        })
+    }
+
+    /// Creates a new MDQuery that runs on the given backend.
+    ///
+    /// The other constructors use [`Backend::Auto`], which picks the backend permitted in
+    /// the current process.
+    ///
+    /// # Parameters
+    /// * `backend` - The Spotlight API to run the query on
+    /// * `query` - A Spotlight query string
+    /// * `scopes` - Search scopes to limit the query; none means no scope restriction
+    /// * `max_count` - Optional maximum number of results to return
+    ///
+    /// # Returns
+    /// A Result containing the MDQuery on success, or an error if query creation fails.
+    pub fn new_with_backend(
+        backend: Backend,
+        query: impl AsRef<str>,
+        scopes: impl IntoIterator<Item = MDQueryScope>,
+        max_count: Option<usize>,
+    ) -> Result<Self> {
+        Self::create(
+            backend,
+            query.as_ref(),
+            scopes,
+            max_count,
+            ScopeOptions::NONE,
+        )
+    }
+
+    /// Searches for items whose text content, display name or keywords contain a term,
+    /// ranked like the results of Spotlight search in the menu bar.
+    ///
+    /// Every word of `term` must match the start of a word of one of the three attributes,
+    /// ignoring case and diacritics, so "quart rep" finds "Quarterly Report.pdf". All
+    /// matches are gathered and sorted by the relevance Spotlight reports, most relevant
+    /// first, before `limit` is applied.
+    ///
+    /// ```no_run
+    /// use mdquery_rs::{MDQuery, MDQueryScope};
+    ///
+    /// for result in MDQuery::search_text("quarterly report", [MDQueryScope::Home], Some(10))? {
+    ///     println!("{:.3} {:?}", result.relevance, result.item.path());
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
+    /// # Parameters
+    /// * `term` - The words to search for, separated by whitespace
+    /// * `scopes` - Search scopes to limit the query; none means no scope restriction
+    /// * `limit` - Optional maximum number of results to return
+    ///
+    /// # Returns
+    /// A Result containing the matching items with their relevance, most relevant first.
+    ///
+    /// # Errors
+    /// Returns [`MDQueryError::InvalidBuilder`] if `term` has no words, or an error if the
+    /// query fails or the process can only use the `NSMetadataQuery` backend, which does
+    /// not report relevance.
+    pub fn search_text(
+        term: &str,
+        scopes: impl IntoIterator<Item = MDQueryScope>,
+        limit: Option<usize>,
+    ) -> Result<Vec<ScoredItem>> {
+        let mut params = Self::params(
+            Backend::Auto,
+            &text_search_query(term)?,
+            scopes,
+            None,
+            ScopeOptions::NONE,
+        )?;
+        params.sorting_attributes = vec![unsafe { kMDQueryResultContentRelevance }.to_string()];
+        let query = Self::from_params(params)?;
+
+        let mut items = query.execute_with(|md_query| {
+            let success =
+                unsafe { MDQueryExecute(md_query, MDQueryOptions::SYNCHRONOUS.bits() as _) };
+            if !success {
+                return Err(anyhow!("MDQuery execute failed."));
+            }
+            Ok(MDQueryUpdateLock::new(md_query).scored_results())
+        })?;
+        items.sort_by(|a, b| b.relevance.total_cmp(&a.relevance));
+        if let Some(limit) = limit {
+            items.truncate(limit);
         }
-        .ok_or(anyhow!("MDQuery create failed, check query syntax."))?;
+        Ok(items)
+    }
 
-        if let Some(scopes) = scopes {
-            let scopes = scopes
-                .into_iter()
-                .map(|scope| scope.into_scope_string())
-                .map(|scope| CFString::from_str(&scope))
-                .collect::<Vec<_>>();
+    /// Returns the backend this query runs on.
+    pub fn backend(&self) -> Backend {
+        self.params.backend
+    }
+
+    /// Requests attributes as value-list attributes, so Spotlight gathers their distinct
+    /// values while the query runs. See [`attribute_values`](Self::attribute_values).
+    ///
+    /// The query is recreated, so any earlier execution is discarded.
+    ///
+    /// # Parameters
+    /// * `keys` - The attributes to gather values of
+    ///
+    /// # Returns
+    /// A Result containing the updated MDQuery on success, or an error if query creation fails.
+    pub fn with_value_list_attributes<I>(mut self, keys: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = MDItemKey>,
+    {
+        self.params
+            .value_list_attributes
+            .extend(keys.into_iter().map(|key| key.as_str().to_string()));
+        self.md_query = Mutex::new(self.params.create_stored()?);
+        self.executed = AtomicBool::new(false);
+        Ok(self)
+    }
+
+    /// Keeps only the first `k` results in the order of an attribute, see
+    /// [`MDQueryBuilder::top_k`].
+    ///
+    /// Applies to [`execute`](Self::execute), [`execute_first`](Self::execute_first),
+    /// [`execute_one`](Self::execute_one), [`execute_with_progress`](Self::execute_with_progress)
+    /// and [`execute_with_timeout`](Self::execute_with_timeout).
+    /// [`execute_with_batches`](Self::execute_with_batches) delivers results before all of
+    /// them are gathered and therefore fails. The query is recreated, so any earlier
+    /// execution is discarded.
+    ///
+    /// # Parameters
+    /// * `k` - The number of results to keep
+    /// * `key` - The attribute to sort the results by
+    /// * `direction` - Whether the smallest or the largest values come first
+    ///
+    /// # Returns
+    /// A Result containing the updated MDQuery on success.
+    ///
+    /// # Errors
+    /// Returns [`MDQueryError::InvalidBuilder`] if `k` is 0 or the query has a max count,
+    /// which Spotlight would apply before sorting, or an error if query creation fails.
+    pub fn with_top_k(
+        mut self,
+        k: usize,
+        key: MDItemKey,
+        direction: SortDirection,
+    ) -> Result<Self> {
+        let mut errors = Vec::new();
+        if k == 0 {
+            errors.push(BuilderError::new("top_k", "k must be greater than 0"));
+        }
+        if self.params.max_count.is_some() {
+            errors.push(BuilderError::new(
+                "top_k",
+                "max_count truncates the results before they are sorted, use only top_k",
+            ));
+        }
+        if !errors.is_empty() {
+            return Err(MDQueryError::InvalidBuilder(errors).into());
+        }
+        self.params.top_k = Some((k, key, direction));
+        let key = key.as_str().to_string();
+        if !self.params.sorting_attributes.contains(&key) {
+            self.params.sorting_attributes.push(key);
+        }
+        self.md_query = Mutex::new(self.params.create_stored()?);
+        self.executed = AtomicBool::new(false);
+        Ok(self)
+    }
+
+    /// Returns the raw query string this query was created with.
+    pub fn query_string(&self) -> &str {
+        &self.params.query
+    }
+
+    /// Returns the search scopes this query was created with.
+    ///
+    /// Empty when the query was created without scopes.
+    pub fn scopes(&self) -> &[MDQueryScope] {
+        &self.params.requested_scopes
+    }
+
+    /// Executes the query and collects the results.
+    ///
+    /// Results that are not backed by a file, such as Mail messages, are included even
+    /// though their [`MDItem::path`] is `None`.
+    ///
+    /// The query can be executed any number of times. `MDQueryExecute` only runs a given
+    /// Spotlight query once and refuses to start it again, so every execution after the
+    /// first one runs a fresh query rebuilt from the stored query string, scopes and
+    /// limits. Each call therefore returns a current snapshot of the index and never
+    /// includes stale results from an earlier run.
+    ///
+    /// # Returns
+    /// A Result containing a vector of MDItem objects on success, or an error if execution fails.
+    pub fn execute(&self) -> Result<Vec<MDItem>> {
+        #[cfg(feature = "nsmetadata")]
+        if self.params.backend == Backend::NsMetadata {
+            let items = super::ns_metadata::execute(
+                &self.params.query,
+                self.params.scopes.as_deref(),
+                self.params.max_count,
+            )?;
+            return Ok(match self.params.top_k {
+                Some((k, key, direction)) => select_top_k(
+                    items
+                        .into_iter()
+                        .map(|item| (item.get_attribute_value(key.as_str()), item)),
+                    k,
+                    direction,
+                ),
+                None => items,
+            });
+        }
+        self.execute_with_options(MDQueryOptions::SYNCHRONOUS)
+    }
 
-            let scopes = unsafe {
-                CFArrayCreate(
-                    None,
-                    scopes.as_ptr() as *mut _,
-                    scopes.len() as CFIndex,
-                    ptr::null(),
-                )
+    /// Executes the query with explicit `MDQueryExecute` option flags.
+    ///
+    /// [`execute`](Self::execute) is this with [`MDQueryOptions::SYNCHRONOUS`]. Without
+    /// that flag, the query gathers asynchronously on the current thread's run loop, and
+    /// the call still returns once gathering has finished. With
+    /// [`MDQueryOptions::WANTS_UPDATES`] the query stays live after returning, so results
+    /// read later, for example with [`lock_updates`](Self::lock_updates), reflect changes
+    /// to the files. The options are ignored for the `NSMetadataQuery` backend.
+    ///
+    /// # Parameters
+    /// * `options` - The option flags to execute the query with
+    ///
+    /// # Returns
+    /// A Result containing the matching items.
+    ///
+    /// # Errors
+    /// Returns an error if the query fails to execute.
+    pub fn execute_with_options(&self, options: MDQueryOptions) -> Result<Vec<MDItem>> {
+        #[cfg(feature = "nsmetadata")]
+        if self.params.backend == Backend::NsMetadata {
+            return self.execute();
+        }
+        self.execute_with(|md_query| {
+            if options.contains(MDQueryOptions::SYNCHRONOUS) {
+                let success = unsafe { MDQueryExecute(md_query, options.bits() as _) };
+                if !success {
+                    return Err(anyhow!("MDQuery execute failed."));
+                }
+            } else {
+                Self::gather(md_query, options, &mut |_| {}, &AtomicBool::new(false))?;
             }
-            .ok_or(anyhow!("MDQuery create failed when create scope array."))?;
+            Ok(self.collect_results(md_query))
+        })
+    }
 
-            unsafe {
-                MDQuerySetSearchScope(&md_query, &scopes, 0);
+    /// Collects the results of an executed query the way [`execute`](Self::execute)
+    /// returns them, applying the top-k selection if one is set.
+    pub(super) fn collect_results(&self, md_query: &CoreMDQuery) -> Vec<MDItem> {
+        let lock = MDQueryUpdateLock::new(md_query);
+        match self.params.top_k {
+            Some((k, key, direction)) => lock.top_k_results(k, key, direction),
+            None => lock.results(),
+        }
+    }
+
+    /// Executes the query, reporting progress while Spotlight gathers the results.
+    ///
+    /// Unlike [`execute`](Self::execute), the query is run asynchronously on the calling
+    /// thread's run loop, which is run until gathering finishes. `on_progress` is called
+    /// on the calling thread with the number of results gathered so far each time
+    /// Spotlight posts `kMDQueryProgressNotification`. Short queries may finish without
+    /// any progress being reported.
+    ///
+    /// # Parameters
+    /// * `on_progress` - Called with the current result count during gathering
+    ///
+    /// # Returns
+    /// A Result containing a vector of MDItem objects on success, or an error if execution fails.
+    pub fn execute_with_progress<F: FnMut(usize)>(
+        &self,
+        mut on_progress: F,
+    ) -> Result<Vec<MDItem>> {
+        self.execute_with(|md_query| self.run_with_progress(md_query, &mut on_progress))
+    }
+
+    /// Executes the query, stopping it if gathering the results takes longer than `timeout`.
+    ///
+    /// The query runs on an internal thread while the calling thread waits. When the
+    /// timeout expires the query is stopped with `MDQueryStop`, and this method returns
+    /// once the internal thread has exited, so no work outlives the call.
+    ///
+    /// # Parameters
+    /// * `timeout` - The longest time to wait for the query to finish
+    ///
+    /// # Returns
+    /// A Result containing a vector of MDItem objects on success, or an error if execution
+    /// fails.
+    ///
+    /// # Errors
+    /// * [`MDQueryError::Timeout`] with the number of results gathered so far if the query
+    ///   did not finish in time
+    pub fn execute_with_timeout(&self, timeout: Duration) -> Result<Vec<MDItem>> {
+        self.execute_with(|md_query| {
+            let cancelled = AtomicBool::new(false);
+            let (done, finished) = mpsc::channel();
+            let executing = ExecutingQuery(md_query);
+            let gathered = thread::scope(|scope| {
+                let worker = thread::Builder::new()
+                    .name("mdquery-timeout".to_string())
+                    .spawn_scoped(scope, || {
+                        let gathered = Self::gather(
+                            executing.get(),
+                            MDQueryOptions::NONE,
+                            &mut |_| {},
+                            &cancelled,
+                        );
+                        let _ = done.send(());
+                        gathered
+                    })?;
+                if finished.recv_timeout(timeout).is_err() {
+                    cancelled.store(true, Ordering::Release);
+                }
+                worker
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })?;
+
+            if !gathered {
+                let partial_count = unsafe { MDQueryGetResultCount(md_query) } as usize;
+                return Err(MDQueryError::Timeout {
+                    timeout,
+                    partial_count,
+                }
+                .into());
             }
+            Ok(self.collect_results(md_query))
+        })
+    }
+
+    /// Executes the query, delivering the results in batches as Spotlight gathers them.
+    ///
+    /// Like [`execute_with_progress`](Self::execute_with_progress), the query runs on the
+    /// calling thread's run loop and `batch` is called on the calling thread. Each call
+    /// receives the results gathered since the previous one, in order, so the batches
+    /// concatenate to the full result set. Spotlight delivers the first batch after at
+    /// most 100 results or 100 ms, and later ones after at most 1000 results or 500 ms.
+    ///
+    /// Returning [`ControlFlow::Break`] stops the query; no further batches are delivered.
+    ///
+    /// # Parameters
+    /// * `batch` - Called with every batch of new results
+    ///
+    /// # Returns
+    /// A Result that is `Ok` once all batches were delivered or the query was stopped, or
+    /// an error if execution fails.
+    ///
+    /// # Errors
+    /// Returns [`MDQueryError::InvalidBuilder`] if a top-k selection is set, see
+    /// [`with_top_k`](Self::with_top_k), since the first results are delivered before the
+    /// top ones are known.
+    pub fn execute_with_batches<F>(&self, mut batch: F) -> Result<()>
+    where
+        F: FnMut(Vec<MDItem>) -> ControlFlow<()>,
+    {
+        if self.params.top_k.is_some() {
+            return Err(MDQueryError::InvalidBuilder(vec![BuilderError::new(
+                "top_k",
+                "not supported by execute_with_batches",
+            )])
+            .into());
         }
+        self.execute_with(|md_query| {
+            unsafe { MDQuerySetBatchingParameters(md_query, BATCHING_PARAMS) };
+            let cancelled = AtomicBool::new(false);
+            let mut delivered = 0;
+            let mut deliver = |md_query: &CoreMDQuery| {
+                if cancelled.load(Ordering::Acquire) {
+                    return;
+                }
+                let items = MDQueryUpdateLock::new(md_query).results_from(delivered);
+                if items.is_empty() {
+                    return;
+                }
+                delivered += items.len();
+                if batch(items).is_break() {
+                    cancelled.store(true, Ordering::Release);
+                }
+            };
 
-        if let Some(max_count) = max_count {
-            unsafe {
-                MDQuerySetMaxCount(&md_query, max_count as CFIndex);
+            let finished = Self::gather(
+                md_query,
+                MDQueryOptions::NONE,
+                &mut |_| deliver(md_query),
+                &cancelled,
+            )?;
+            if finished {
+                deliver(md_query);
             }
+            Ok(())
+        })
+    }
+
+    /// Runs the stored query on first use, and a freshly created one afterwards.
+    pub(super) fn execute_with<T, F>(&self, run: F) -> Result<T>
+    where
+        F: FnOnce(&CoreMDQuery) -> Result<T>,
+    {
+        self.require_core_services()?;
+        if self.executed.swap(true, Ordering::AcqRel) {
+            let md_query = self.params.create()?;
+            let result = run(&md_query)?;
+            *self.latest() = Some(md_query);
+            Ok(result)
+        } else {
+            run(stored_query(&self.latest())?)
         }
+    }
 
-        Ok(Self(md_query))
+    /// Returns the Spotlight query the next execution runs, for executions that outlive a
+    /// call: the stored query on first use, and a freshly created one afterwards.
+    #[cfg(feature = "async")]
+    pub(super) fn next_md_query(&self) -> Result<CFRetained<CoreMDQuery>> {
+        self.require_core_services()?;
+        if self.executed.swap(true, Ordering::AcqRel) {
+            let md_query = self.params.create()?;
+            *self.latest() = Some(md_query.clone());
+            Ok(md_query)
+        } else {
+            // SAFETY: The stored query is valid, and the caller takes its own reference.
+            stored_query(&self.latest())
+                .map(|md_query| unsafe { CFRetained::retain(NonNull::from(md_query)) })
+        }
     }
 
-    /// Executes the query and collects the results.
+    /// Executes the query and groups the results by the value of an attribute.
+    ///
+    /// Groups appear in the order their first item appears in the results, and the items
+    /// of each group keep the order Spotlight returned them in. Items without the
+    /// attribute are collected in a trailing `None` group.
+    ///
+    /// The Core Services query API has no grouping of its own (that is only offered by
+    /// `NSMetadataQuery`), so the attribute is read from each result after execution.
+    ///
+    /// # Parameters
+    /// * `group_by` - The attribute to group the results by
     ///
     /// # Returns
-    /// A Result containing a vector of MDItem objects on success, or an error if execution fails.
-    pub fn execute(self) -> Result<Vec<MDItem>> {
-        unsafe {
-            let success = MDQueryExecute(&self.0, MDQueryOptionsFlags::SYNCHRONOUS as _);
+    /// A Result containing the groups and their items on success, or an error if
+    /// execution fails.
+    pub fn execute_grouped(
+        &self,
+        group_by: MDItemKey,
+    ) -> Result<Vec<(Option<MDAttributeValue>, Vec<MDItem>)>> {
+        let mut groups: Vec<(Option<MDAttributeValue>, Vec<MDItem>)> = Vec::new();
+        let mut indices = HashMap::new();
+        let mut missing = Vec::new();
+        let name = group_by.as_str();
+        for item in self.execute()? {
+            match item.get_attribute_value(name) {
+                Some(value) => {
+                    let index = *indices.entry(value.clone()).or_insert_with(|| {
+                        groups.push((Some(value), Vec::new()));
+                        groups.len() - 1
+                    });
+                    groups[index].1.push(item);
+                }
+                None => missing.push(item),
+            }
+        }
+        if !missing.is_empty() {
+            groups.push((None, missing));
+        }
+        Ok(groups)
+    }
 
-            if !success {
-                return Err(anyhow!("MDQuery execute failed."));
+    /// Runs the query once per scope, each with its own result limit, and merges the results.
+    ///
+    /// Every sub-query uses this query's expression, scope options and backend, but only
+    /// the given scope. Results are merged in the order of `limits`, and an item found in
+    /// several scopes is kept once, attributed to the first scope that returned it. A scope
+    /// overlapping an earlier one can therefore contribute fewer items than its limit.
+    ///
+    /// # Parameters
+    /// * `limits` - The scopes to search and the maximum number of results from each
+    ///
+    /// # Returns
+    /// A Result containing the merged items with the index of their scope in `limits`, or
+    /// an error if creating or executing a sub-query fails.
+    pub fn execute_per_scope(&self, limits: &[(MDQueryScope, usize)]) -> Result<Vec<ScopedItem>> {
+        let results = limits
+            .iter()
+            .map(|(scope, limit)| self.scoped(scope, *limit)?.execute())
+            .collect::<Result<Vec<_>>>()?;
+        Ok(merge_scoped(results))
+    }
+
+    /// Executes the query and returns its first result.
+    ///
+    /// The query runs with a maximum count of 1, so Spotlight stops after the first match.
+    ///
+    /// # Returns
+    /// A Result containing the first matching item, or None if nothing matches.
+    pub fn execute_first(&self) -> Result<Option<MDItem>> {
+        let limited = self.limited(1)?;
+        Ok(limited.execute()?.into_iter().next())
+    }
+
+    /// Executes the query and returns its only result.
+    ///
+    /// The query runs with a maximum count of 2, whatever its own max count or top-k
+    /// selection, since a second match is enough to tell that the query is ambiguous.
+    /// Only the number of results and the single match are read from Spotlight, so no
+    /// items are created for ambiguous queries.
+    ///
+    /// # Returns
+    /// A Result containing the only matching item.
+    ///
+    /// # Errors
+    /// Returns [`MDQueryError::NotFound`] if nothing matches, [`MDQueryError::Ambiguous`]
+    /// if several items match, or an error if execution fails. The count of an ambiguous
+    /// query is a lower bound on the number of matches.
+    pub fn execute_one(&self) -> Result<MDItem> {
+        let probe = Self::create(
+            self.params.backend,
+            &self.params.query,
+            self.params.requested_scopes.clone(),
+            Some(2),
+            self.params.scope_options,
+        )?;
+        let (count, item) = if probe.params.backend.is_ns_metadata() {
+            let mut items = probe.execute()?;
+            (items.len(), items.pop())
+        } else {
+            probe.execute_with(|md_query| {
+                let success =
+                    unsafe { MDQueryExecute(md_query, MDQueryOptions::SYNCHRONOUS.bits() as _) };
+                if !success {
+                    return Err(anyhow!("MDQuery execute failed."));
+                }
+                let lock = MDQueryUpdateLock::new(md_query);
+                let count = lock.result_count();
+                let item = if count == 1 {
+                    lock.results().pop()
+                } else {
+                    None
+                };
+                Ok((count, item))
+            })?
+        };
+        match (count, item) {
+            (1, Some(item)) => Ok(item),
+            (0, _) => Err(MDQueryError::NotFound.into()),
+            (1, None) => Err(anyhow!("MDQuery result could not be read.")),
+            (count, _) => Err(MDQueryError::Ambiguous { count }.into()),
+        }
+    }
+
+    /// Creates a copy of this query returning at most `max_count` results.
+    ///
+    /// A top-k selection is narrowed instead, since a max count would apply before sorting.
+    fn limited(&self, max_count: usize) -> Result<Self> {
+        let limit = match self.params.top_k {
+            Some(_) => None,
+            None => Some(
+                self.params
+                    .max_count
+                    .map_or(max_count, |current| current.min(max_count)),
+            ),
+        };
+        let query = Self::create(
+            self.params.backend,
+            &self.params.query,
+            self.params.requested_scopes.clone(),
+            limit,
+            self.params.scope_options,
+        )?;
+        match self.params.top_k {
+            Some((k, key, direction)) => query.with_top_k(k.min(max_count), key, direction),
+            None => Ok(query),
+        }
+    }
+
+    /// Creates a copy of this query that searches only `scope`, returning at most
+    /// `max_count` results.
+    pub(super) fn scoped(&self, scope: &MDQueryScope, max_count: usize) -> Result<Self> {
+        Self::create(
+            self.params.backend,
+            &self.params.query,
+            [scope.clone()],
+            Some(max_count),
+            self.params.scope_options,
+        )
+    }
+
+    /// Returns the distinct values of an attribute across the results of the last
+    /// execution.
+    ///
+    /// Attributes requested with [`with_value_list_attributes`](Self::with_value_list_attributes)
+    /// are read from the values Spotlight gathered; others are collected from the results.
+    /// Array values, such as authors, contribute each of their elements.
+    ///
+    /// # Parameters
+    /// * `key` - The attribute to list the values of
+    ///
+    /// # Returns
+    /// A Result containing the distinct values, or an error if the query has not been
+    /// executed yet.
+    pub fn attribute_values(&self, key: MDItemKey) -> Result<Vec<MDAttributeValue>> {
+        self.require_core_services()?;
+        if !self.executed.load(Ordering::Acquire) {
+            return Err(anyhow!("MDQuery has not been executed."));
+        }
+        let name = key.as_str();
+        let latest = self.latest();
+        let md_query = stored_query(&latest)?;
+
+        if self
+            .params
+            .value_list_attributes
+            .iter()
+            .any(|attr| attr == name)
+        {
+            let cf_name = attribute_name(name);
+            if let Some(values) = unsafe { MDQueryCopyValuesOfAttribute(md_query, &cf_name) } {
+                return Ok(facet_values(cf_type_to_value(&values)));
             }
+        }
+
+        let mut seen = HashSet::new();
+        let mut values = Vec::new();
+        for item in Self::results(md_query) {
+            for value in facet_values(item.get_attribute_value(name)) {
+                if seen.insert(value.clone()) {
+                    values.push(value);
+                }
+            }
+        }
+        Ok(values)
+    }
 
-            let count = MDQueryGetResultCount(&self.0);
-            let mut items = Vec::with_capacity(count as usize);
-            for i in 0..count {
-                let item_ptr = MDQueryGetResultAtIndex(&self.0, i as _) as *mut CoreMDItem;
+    /// Counts how many results of the last execution have each value of an attribute.
+    ///
+    /// The counts are taken by enumerating the results, since Spotlight only reports the
+    /// distinct values. Array values count once for each of their elements, and results
+    /// without the attribute are not counted. Empty if the query has not been executed.
+    ///
+    /// # Parameters
+    /// * `key` - The attribute to count the values of
+    ///
+    /// # Returns
+    /// A map from each value to the number of results having it.
+    pub fn value_counts(&self, key: MDItemKey) -> HashMap<MDAttributeValue, usize> {
+        let mut counts = HashMap::new();
+        if !self.executed.load(Ordering::Acquire) {
+            return counts;
+        }
+        let name = key.as_str();
+        let Some(md_query) = &*self.latest() else {
+            return counts;
+        };
+        for item in Self::results(md_query) {
+            for value in facet_values(item.get_attribute_value(name)) {
+                *counts.entry(value).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+
+    /// Suspends result updates of the most recently executed query until the returned
+    /// lock is dropped.
+    ///
+    /// Spotlight keeps updating the results of live queries in the background. Reading
+    /// several results while holding the lock sees one consistent result set. The
+    /// enumerations done by this crate take the lock themselves.
+    ///
+    /// # Returns
+    /// An `MDQueryUpdateLock` that re-enables updates when dropped.
+    pub fn lock_updates(&self) -> MDQueryUpdateLock {
+        MDQueryUpdateLock {
+            md_query: self
+                .latest()
+                .as_deref()
+                .map(MDQueryUpdateLock::disable_updates),
+        }
+    }
+
+    /// Fails for queries on a backend other than Core Services.
+    fn require_core_services(&self) -> Result<()> {
+        if self.params.backend.is_ns_metadata() {
+            return Err(anyhow!(
+                "This operation is not supported by the NSMetadataQuery backend."
+            ));
+        }
+        Ok(())
+    }
+
+    /// Locks the most recently created Spotlight query.
+    fn latest(&self) -> MutexGuard<'_, Option<CFRetained<CoreMDQuery>>> {
+        self.md_query.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn run_with_progress(
+        &self,
+        md_query: &CoreMDQuery,
+        on_progress: &mut dyn FnMut(usize),
+    ) -> Result<Vec<MDItem>> {
+        Self::gather(
+            md_query,
+            MDQueryOptions::NONE,
+            on_progress,
+            &AtomicBool::new(false),
+        )?;
+        Ok(self.collect_results(md_query))
+    }
+
+    /// Executes a query asynchronously on the current thread's run loop and runs the run
+    /// loop until gathering finishes or `cancelled` is set. `options` must not contain
+    /// [`MDQueryOptions::SYNCHRONOUS`].
+    ///
+    /// Returns whether gathering finished; a cancelled query is stopped with `MDQueryStop`.
+    fn gather(
+        md_query: &CoreMDQuery,
+        options: MDQueryOptions,
+        on_progress: &mut dyn FnMut(usize),
+        cancelled: &AtomicBool,
+    ) -> Result<bool> {
+        let center = unsafe { CFNotificationCenterGetLocalCenter() }.ok_or(anyhow!(
+            "MDQuery execute failed to get the notification center."
+        ))?;
+        let run_loop = unsafe { CFRunLoopGetCurrent() }.ok_or(anyhow!(
+            "MDQuery execute failed to get the current run loop."
+        ))?;
+        let mut observer = ProgressObserver {
+            md_query,
+            run_loop,
+            on_progress,
+            finished: false,
+            panic: None,
+        };
+        // The observer is only accessed through this pointer while it is registered.
+        let observer_ptr: *mut ProgressObserver = &mut observer;
+        let object = md_query as *const CoreMDQuery as *const c_void;
+
+        unsafe {
+            CFNotificationCenterAddObserver(
+                &center,
+                observer_ptr as *const c_void,
+                Some(progress_callback),
+                None,
+                object,
+                CFNotificationSuspensionBehavior::DeliverImmediately,
+            );
+        }
+        let success = unsafe { MDQueryExecute(md_query, options.bits() as _) };
+        let mut finished = false;
+        if success {
+            // Notifications are delivered from within the run loop; the callback stops it
+            // once gathering has finished.
+            loop {
+                unsafe { CFRunLoopRunInMode(kCFRunLoopDefaultMode, 0.1, false) };
+                finished = unsafe { (*observer_ptr).finished };
+                if finished || unsafe { (*observer_ptr).panic.is_some() } {
+                    break;
+                }
+                if cancelled.load(Ordering::Acquire) {
+                    unsafe { MDQueryStop(md_query) };
+                    break;
+                }
+            }
+        }
+        unsafe {
+            CFNotificationCenterRemoveObserver(&center, observer_ptr as *const c_void, None, object)
+        };
+        // A panic in `on_progress` is caught before it can unwind into the notification
+        // center and resumed once the observer is no longer registered.
+        if let Some(payload) = observer.panic.take() {
+            panic::resume_unwind(payload);
+        }
+
+        if !success {
+            return Err(anyhow!("MDQuery execute failed."));
+        }
+        Ok(finished)
+    }
+
+    /// Collects the current results of an executed query.
+    pub(super) fn results(md_query: &CoreMDQuery) -> Vec<MDItem> {
+        MDQueryUpdateLock::new(md_query).results()
+    }
+}
+
+/// A query handed to the thread that executes it, see `execute_with_timeout`.
+struct ExecutingQuery<'a>(&'a CoreMDQuery);
+
+// SAFETY: Only the executing thread uses the query until it has been joined, as the
+// safety comment on `MDQuery` requires.
+unsafe impl Send for ExecutingQuery<'_> {}
+unsafe impl Sync for ExecutingQuery<'_> {}
+
+impl<'a> ExecutingQuery<'a> {
+    fn get(&self) -> &'a CoreMDQuery {
+        self.0
+    }
+}
+
+/// Keeps Spotlight from updating the results of a query while it is alive.
+///
+/// Created by [`MDQuery::lock_updates`]. Updates are disabled with
+/// `MDQueryDisableUpdates` when the lock is created and enabled again with
+/// `MDQueryEnableUpdates` when it is dropped. Locks nest: updates resume once every lock
+/// on a query has been dropped. The lock keeps its query alive, even if the
+/// [`MDQuery`] it came from is executed again or dropped. Queries on the
+/// `NSMetadataQuery` backend keep no results to lock, so their locks report none.
+pub struct MDQueryUpdateLock {
+    md_query: Option<CFRetained<CoreMDQuery>>,
+}
+
+// SAFETY: The lock only disables and enables updates and reads results, which Core
+// Services intends to be done from other threads while the query's run loop thread
+// updates the results, see the safety comment on `MDQuery`. Retaining and releasing the
+// query is thread-safe.
+unsafe impl Send for MDQueryUpdateLock {}
+unsafe impl Sync for MDQueryUpdateLock {}
+
+impl MDQueryUpdateLock {
+    fn new(md_query: &CoreMDQuery) -> Self {
+        Self {
+            md_query: Some(Self::disable_updates(md_query)),
+        }
+    }
+
+    /// Disables the updates of `md_query`, returning the reference the lock keeps.
+    fn disable_updates(md_query: &CoreMDQuery) -> CFRetained<CoreMDQuery> {
+        unsafe { MDQueryDisableUpdates(md_query) };
+        // SAFETY: `md_query` is a valid query, and the lock takes its own reference.
+        unsafe { CFRetained::retain(NonNull::from(md_query)) }
+    }
+
+    /// Returns the number of results of the locked query.
+    pub fn result_count(&self) -> usize {
+        self.md_query
+            .as_ref()
+            .map_or(0, |md_query| unsafe { MDQueryGetResultCount(md_query) }
+                as usize)
+    }
+
+    /// Collects the results of the locked query.
+    ///
+    /// # Returns
+    /// The results in the order Spotlight returned them in.
+    pub fn results(&self) -> Vec<MDItem> {
+        self.results_from(0)
+    }
+
+    /// Collects the results of the locked query, starting at index `start`.
+    ///
+    /// # Returns
+    /// The results from `start` on, in the order Spotlight returned them in. Empty if
+    /// there are no more than `start` results.
+    pub fn results_from(&self, start: usize) -> Vec<MDItem> {
+        let Some(md_query) = self.md_query.as_deref() else {
+            return Vec::new();
+        };
+        unsafe {
+            let count = MDQueryGetResultCount(md_query);
+            let mut items = Vec::with_capacity((count as usize).saturating_sub(start));
+            for i in start as CFIndex..count {
+                let item_ptr = MDQueryGetResultAtIndex(md_query, i as _) as *mut CoreMDItem;
                 if let Some(item) = NonNull::new(item_ptr) {
-                    if let Some(value) = MDItemCopyAttribute(
-                        item.as_ref(),
-                        &CFString::from_str(MDItemKey::Path.as_str()),
-                    ) {
-                        if let Ok(path_str) = value.downcast::<CFString>() {
-                            let path = (*path_str).to_string();
-                            if let Ok(item) = MDItem::from_path(&path) {
-                                items.push(item);
-                            }
-                        }
-                    }
+                    // The query owns its results, so take our own reference.
+                    items.push(MDItem::from_retained(CFRetained::retain(item)));
                 }
             }
-            Ok(items)
+            items
+        }
+    }
+
+    /// Collects the results of the locked query with their relevance, which the query must
+    /// have been created to compute. Results without a relevance get 0.
+    fn scored_results(&self) -> Vec<ScoredItem> {
+        let relevance_key = unsafe { kMDQueryResultContentRelevance };
+        (0..self.result_count())
+            .filter_map(|index| {
+                let relevance = self
+                    .result_attribute(relevance_key, index)
+                    .and_then(|value| match value {
+                        MDAttributeValue::Float(value) => Some(value),
+                        MDAttributeValue::Integer(value) => Some(value as f64),
+                        _ => None,
+                    })
+                    .unwrap_or(0.0);
+                let item = self.result_at(index)?;
+                Some(ScoredItem { relevance, item })
+            })
+            .collect()
+    }
+
+    /// Selects the first `k` results in the order of `key`, which the query must have been
+    /// created to sort by. Only the selected results are turned into items.
+    fn top_k_results(&self, k: usize, key: MDItemKey, direction: SortDirection) -> Vec<MDItem> {
+        let name = key_name(key);
+        let indices =
+            (0..self.result_count()).map(|index| (self.result_attribute(name, index), index));
+        select_top_k(indices, k, direction)
+            .into_iter()
+            .filter_map(|index| self.result_at(index))
+            .collect()
+    }
+
+    /// Returns the result at `index` of the locked query.
+    fn result_at(&self, index: usize) -> Option<MDItem> {
+        let md_query = self.md_query.as_deref()?;
+        let item_ptr = unsafe { MDQueryGetResultAtIndex(md_query, index as _) } as *mut CoreMDItem;
+        // The query owns its results, so take our own reference.
+        NonNull::new(item_ptr)
+            .map(|item| MDItem::from_retained(unsafe { CFRetained::retain(item) }))
+    }
+
+    /// Reads a cached attribute of the result at `index`, which must be one of the
+    /// attributes the query sorts by.
+    fn result_attribute(&self, name: &CFString, index: usize) -> Option<MDAttributeValue> {
+        let md_query = self.md_query.as_deref()?;
+        // The value is owned by the query, which the lock keeps alive.
+        let value =
+            unsafe { MDQueryGetAttributeValueOfResultAtIndex(md_query, name, index as CFIndex) }
+                as *const CFType;
+        unsafe { value.as_ref() }.and_then(cf_type_to_value)
+    }
+}
+
+impl Drop for MDQueryUpdateLock {
+    fn drop(&mut self) {
+        if let Some(md_query) = &self.md_query {
+            unsafe { MDQueryEnableUpdates(md_query) };
+        }
+    }
+}
+
+/// State shared with [`progress_callback`] while a query gathers results.
+struct ProgressObserver<'a> {
+    md_query: &'a CoreMDQuery,
+    run_loop: CFRetained<CFRunLoop>,
+    on_progress: &'a mut dyn FnMut(usize),
+    finished: bool,
+    /// The payload of a panic in `on_progress`, which stops the query.
+    panic: Option<Box<dyn Any + Send>>,
+}
+
+unsafe extern "C-unwind" fn progress_callback(
+    _center: *mut CFNotificationCenter,
+    observer: *mut c_void,
+    name: *const CFNotificationName,
+    _object: *const c_void,
+    _user_info: *const CFDictionary,
+) {
+    // SAFETY: The observer is registered by `run_with_progress`, which keeps it alive and
+    // removes it before returning. Notifications are delivered on the thread running it.
+    let (Some(observer), Some(name)) = (
+        unsafe { (observer as *mut ProgressObserver).as_mut() },
+        unsafe { name.as_ref() },
+    ) else {
+        return;
+    };
+    if name == unsafe { kMDQueryProgressNotification } {
+        let count = unsafe { MDQueryGetResultCount(observer.md_query) } as usize;
+        let on_progress = &mut observer.on_progress;
+        if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| on_progress(count))) {
+            observer.panic = Some(payload);
+            unsafe {
+                MDQueryStop(observer.md_query);
+                CFRunLoopStop(&observer.run_loop);
+            }
         }
+    } else if name == unsafe { kMDQueryDidFinishNotification } {
+        observer.finished = true;
+        unsafe { CFRunLoopStop(&observer.run_loop) };
     }
 }
 
-// https://developer.apple.com/documentation/coreservices/mdqueryoptionflags?language=objc
-#[repr(C)]
-struct MDQueryOptionsFlags(u32);
+/// Splits an attribute value into the values it contributes to facets.
+fn facet_values(value: Option<MDAttributeValue>) -> Vec<MDAttributeValue> {
+    match value {
+        Some(MDAttributeValue::Array(values)) => values,
+        Some(value) => vec![value],
+        None => Vec::new(),
+    }
+}
 
-#[allow(unused)]
-impl MDQueryOptionsFlags {
-    const NONE: u32 = 0;
-    const SYNCHRONOUS: u32 = 1;
-    const WANTS_UPDATES: u32 = 4;
-    const ALLOW_FS_TRANSLATIONS: u32 = 8;
+impl fmt::Debug for MDQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MDQuery")
+            .field("query", &self.params.query)
+            .field("scopes", &self.params.requested_scopes)
+            .field("max_count", &self.params.max_count)
+            .field("scope_options", &self.params.scope_options)
+            .field("backend", &self.params.backend)
+            .finish()
+    }
 }
 
+/// Batch sizes used by [`MDQuery::execute_with_batches`].
+const BATCHING_PARAMS: MDQueryBatchingParams = MDQueryBatchingParams {
+    first_max_num: 100,
+    first_max_ms: 100,
+    progress_max_num: 1000,
+    progress_max_ms: 500,
+    update_max_num: 1000,
+    update_max_ms: 1000,
+};
+
 #[cfg(test)]
 mod tests {
+    use objc2_core_foundation::{CFArrayGetCount, CFArrayGetValueAtIndex, CFGetRetainCount};
     use std::path::PathBuf;
 
     use super::*;
@@ -129,7 +1304,7 @@ mod tests {
     fn test_md_query_execute() {
         let query = MDQuery::new(
             "kMDItemFSName = \"Safari.app\"",
-            Some(vec![MDQueryScope::Custom("/Applications".into())]),
+            [MDQueryScope::Custom("/Applications".into())],
             Some(5),
         )
         .unwrap();
@@ -142,11 +1317,584 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_execute_twice() {
+        let query = MDQuery::new(
+            "kMDItemFSName = \"Safari.app\"",
+            [MDQueryScope::Custom("/Applications".into())],
+            Some(5),
+        )
+        .unwrap();
+
+        let first = query.execute().unwrap();
+        let second = query.execute().unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(
+            first.iter().map(MDItem::path).collect::<Vec<_>>(),
+            second.iter().map(MDItem::path).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_debug_includes_query() {
+        let raw = "kMDItemFSName = \"Safari.app\"";
+        let query =
+            MDQuery::new(raw, [MDQueryScope::Custom("/Applications".into())], Some(5)).unwrap();
+
+        assert_eq!(query.query_string(), raw);
+        assert_eq!(
+            query.scopes(),
+            &[MDQueryScope::Custom("/Applications".into())]
+        );
+        let debug = format!("{query:?}");
+        assert!(debug.contains(&format!("{raw:?}")));
+        assert!(debug.contains("/Applications"));
+        assert!(debug.contains("max_count: Some(5)"));
+    }
+
+    #[test]
+    fn test_execute_with_options() {
+        let query = MDQuery::new(
+            "kMDItemFSName = \"Safari.app\"",
+            [MDQueryScope::Custom("/Applications".into())],
+            None,
+        )
+        .unwrap();
+        let expected = Some(PathBuf::from("/Applications/Safari.app"));
+        for options in [
+            MDQueryOptions::SYNCHRONOUS | MDQueryOptions::ALLOW_FS_TRANSLATIONS,
+            MDQueryOptions::NONE,
+            MDQueryOptions::SYNCHRONOUS | MDQueryOptions::WANTS_UPDATES,
+        ] {
+            let items = query.execute_with_options(options).unwrap();
+            assert_eq!(items.len(), 1, "{:?}", options);
+            assert_eq!(items[0].path(), expected);
+        }
+    }
+
+    /// Also meant to be run under AddressSanitizer, which reports the use-after-free an
+    /// array without retain callbacks would cause:
+    /// `RUSTFLAGS=-Zsanitizer=address cargo +nightly test -Zbuild-std
+    /// --target aarch64-apple-darwin test_scope_array_outlives_strings`
+    #[test]
+    fn test_scope_array_outlives_strings() {
+        let names = (0..500)
+            .map(|i| format!("/tmp/mdquery-scope-{}", i))
+            .collect::<Vec<_>>();
+        let strings = names
+            .iter()
+            .map(|name| CFString::from_str(name))
+            .collect::<Vec<_>>();
+        let retain_count = CFGetRetainCount(Some(&strings[0]));
+        let array =
+            cf_array_of_strings(&strings.iter().map(|string| &**string).collect::<Vec<_>>())
+                .unwrap();
+        assert_eq!(CFGetRetainCount(Some(&strings[0])), retain_count + 1);
+        drop(strings);
+
+        assert_eq!(unsafe { CFArrayGetCount(&array) } as usize, names.len());
+        for (i, name) in names.iter().enumerate() {
+            let value = unsafe { CFArrayGetValueAtIndex(&array, i as CFIndex) } as *const CFString;
+            assert_eq!(unsafe { &*value }.to_string(), *name);
+        }
+    }
+
+    #[test]
+    fn test_many_scopes() {
+        // Distinct sibling directories, which `dedup_nested` keeps as separate scopes.
+        let base = std::env::temp_dir().join(format!("mdquery-scopes-{}", std::process::id()));
+        let dirs = (0..300)
+            .map(|i| base.join(format!("scope-{}", i)))
+            .collect::<Vec<_>>();
+        for dir in &dirs {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+        let scopes = dirs
+            .iter()
+            .map(MDQueryScope::from_path)
+            .chain([MDQueryScope::from_path("/System/Applications")]);
+        let query = MDQuery::new("kMDItemFSName == \"Calculator.app\"", scopes, None).unwrap();
+        assert_eq!(query.scopes().len(), 301);
+        assert_eq!(query.params.scopes.as_ref().map(Vec::len), Some(301));
+        let items = query.execute();
+        std::fs::remove_dir_all(&base).unwrap();
+        let items = items.unwrap();
+        assert_eq!(
+            items[0].path(),
+            Some(PathBuf::from("/System/Applications/Calculator.app"))
+        );
+    }
+
+    #[test]
+    fn test_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<MDQuery>();
+        assert_send_sync::<MDQueryUpdateLock>();
+    }
+
+    #[test]
+    fn test_execute_on_other_threads() {
+        let paths = |items: Vec<MDItem>| {
+            let mut paths = items.iter().filter_map(MDItem::path).collect::<Vec<_>>();
+            paths.sort();
+            paths
+        };
+        let query = MDQuery::new(
+            "kMDItemFSName == \"*.app\"",
+            [MDQueryScope::from_path("/System/Applications")],
+            None,
+        )
+        .unwrap();
+
+        // Created on this thread, first executed on another one.
+        let query = thread::spawn(move || {
+            assert!(!query.execute().unwrap().is_empty());
+            query
+        })
+        .join()
+        .unwrap();
+        let expected = paths(query.execute().unwrap());
+
+        // Shared by threads executing it at the same time.
+        thread::scope(|scope| {
+            let workers = (0..8)
+                .map(|_| scope.spawn(|| paths(query.execute().unwrap())))
+                .collect::<Vec<_>>();
+            for worker in workers {
+                assert_eq!(worker.join().unwrap(), expected);
+            }
+        });
+
+        // Results of the last execution stay readable from another thread.
+        let lock = query.lock_updates();
+        let count = thread::spawn(move || lock.result_count()).join().unwrap();
+        assert_eq!(count, expected.len());
+    }
+
+    #[test]
+    fn test_execute_first_and_one() {
+        let scope = [MDQueryScope::from_path("/System/Applications")];
+        let query = |raw: &str| MDQuery::new(raw, scope.clone(), None).unwrap();
+
+        let none = query("kMDItemFSName == \"NoSuchApp123456789.app\"");
+        assert!(none.execute_first().unwrap().is_none());
+        assert!(matches!(
+            none.execute_one()
+                .err()
+                .unwrap()
+                .downcast_ref::<MDQueryError>(),
+            Some(MDQueryError::NotFound)
+        ));
+
+        let one = query("kMDItemFSName == \"Calculator.app\"");
+        assert_eq!(
+            one.execute_one().unwrap().path(),
+            Some(PathBuf::from("/System/Applications/Calculator.app"))
+        );
+        assert!(one.execute_first().unwrap().is_some());
+
+        let many = query("kMDItemFSName == \"*.app\"");
+        assert!(many.execute_first().unwrap().is_some());
+        assert!(matches!(
+            many.execute_one()
+                .err()
+                .unwrap()
+                .downcast_ref::<MDQueryError>(),
+            Some(MDQueryError::Ambiguous { count: 2 })
+        ));
+
+        // Neither a max count of 1 nor a top-k selection of 1 hides the second match.
+        let capped = MDQuery::new("kMDItemFSName == \"*.app\"", scope.clone(), Some(1)).unwrap();
+        let top = query("kMDItemFSName == \"*.app\"")
+            .with_top_k(1, MDItemKey::FSName, SortDirection::Ascending)
+            .unwrap();
+        for query in [capped, top] {
+            assert!(matches!(
+                query
+                    .execute_one()
+                    .err()
+                    .unwrap()
+                    .downcast_ref::<MDQueryError>(),
+                Some(MDQueryError::Ambiguous { count: 2 })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_search_text() {
+        let dir = std::env::temp_dir().join(format!("mdquery-search-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("notes.txt");
+        std::fs::write(&path, "The zarquonflux reactor needs a new quonvelt.").unwrap();
+        let _ = std::process::Command::new("mdimport").arg(&path).status();
+
+        let mut results = Vec::new();
+        for _ in 0..40 {
+            results = MDQuery::search_text(
+                "zarquonflux quonv",
+                [MDQueryScope::from_path(&dir)],
+                Some(5),
+            )
+            .unwrap();
+            if !results.is_empty() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(250));
+        }
+        let missing = MDQuery::search_text(
+            "zarquonflux nosuchword",
+            [MDQueryScope::from_path(&dir)],
+            None,
+        )
+        .unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            results[0]
+                .item
+                .path()
+                .map(|found| found.ends_with("notes.txt")),
+            Some(true)
+        );
+        assert!(results[0].relevance >= 0.0);
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn test_execute_per_scope() {
+        let query = MDQuery::new("kMDItemFSName = \"*.app\"", [], None).unwrap();
+        let items = query
+            .execute_per_scope(&[
+                (MDQueryScope::from_path("/System/Applications"), 3),
+                (
+                    MDQueryScope::from_path("/System/Applications/Utilities"),
+                    20,
+                ),
+            ])
+            .unwrap();
+
+        let from_scope = |index| items.iter().filter(move |item| item.scope_index == index);
+        assert_eq!(from_scope(0).count(), 3);
+        assert!(from_scope(1).count() <= 20);
+        assert!(from_scope(1).count() > 0);
+        assert!(from_scope(1).all(|item| item
+            .item
+            .path()
+            .unwrap()
+            .starts_with("/System/Applications/Utilities")));
+        let paths = items
+            .iter()
+            .map(|item| item.item.path())
+            .collect::<HashSet<_>>();
+        assert_eq!(paths.len(), items.len());
+    }
+
+    #[test]
+    fn test_execute_grouped_by_content_type() {
+        let query = MDQuery::new(
+            "kMDItemFSName = \"*\"",
+            [MDQueryScope::Custom("/Applications".into())],
+            Some(50),
+        )
+        .unwrap();
+
+        let groups = query.execute_grouped(MDItemKey::ContentType).unwrap();
+        assert!(!groups.is_empty());
+        let apps = groups
+            .iter()
+            .find(|(value, _)| {
+                value.as_ref().and_then(MDAttributeValue::as_str)
+                    == Some("com.apple.application-bundle")
+            })
+            .map(|(_, items)| items)
+            .unwrap();
+        assert!(apps
+            .iter()
+            .all(|item| item.content_type().as_deref() == Some("com.apple.application-bundle")));
+        // Only the last group may be the `None` bucket.
+        assert!(groups
+            .iter()
+            .rev()
+            .skip(1)
+            .all(|(value, _)| value.is_some()));
+    }
+
+    #[test]
+    fn test_attribute_values_and_counts() {
+        let query = MDQuery::new(
+            "kMDItemFSName = \"*\"c",
+            [MDQueryScope::Custom("/System/Applications".into())],
+            Some(20),
+        )
+        .unwrap()
+        .with_value_list_attributes([MDItemKey::ContentType])
+        .unwrap();
+        assert!(query.attribute_values(MDItemKey::ContentType).is_err());
+        assert!(query.value_counts(MDItemKey::ContentType).is_empty());
+
+        let items = query.execute().unwrap();
+        let values = query.attribute_values(MDItemKey::ContentType).unwrap();
+        let counts = query.value_counts(MDItemKey::ContentType);
+        assert!(!values.is_empty());
+        for value in &values {
+            assert!(counts.contains_key(value));
+        }
+        let counted = counts.values().sum::<usize>();
+        assert_eq!(
+            counted,
+            items
+                .iter()
+                .filter(|item| item.content_type().is_some())
+                .count()
+        );
+    }
+
+    #[test]
+    fn test_enumerate_while_files_change() {
+        use std::sync::Arc;
+
+        let dir = std::env::temp_dir().join(format!("mdquery-updates-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dir = dir.canonicalize().unwrap();
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let writer = {
+            let dir = dir.clone();
+            let stop = Arc::clone(&stop);
+            std::thread::spawn(move || {
+                let mut i = 0;
+                while !stop.load(Ordering::Relaxed) {
+                    let path = dir.join(format!("file-{}.txt", i % 20));
+                    if path.exists() {
+                        let _ = std::fs::remove_file(&path);
+                    } else {
+                        let _ = std::fs::write(&path, "mdquery");
+                    }
+                    i += 1;
+                }
+            })
+        };
+
+        let query = MDQuery::new(
+            "kMDItemFSName = \"file-*.txt\"",
+            [MDQueryScope::Custom(dir.clone())],
+            None,
+        )
+        .unwrap();
+        for _ in 0..50 {
+            query.execute().unwrap();
+            let lock = query.lock_updates();
+            let count = lock.result_count();
+            let items = lock.results();
+            assert_eq!(items.len(), count);
+            for item in items {
+                if let Some(path) = item.path() {
+                    assert!(path.starts_with(&dir));
+                }
+            }
+        }
+
+        stop.store(true, Ordering::Relaxed);
+        writer.join().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_execute_with_progress() {
+        let query =
+            MDQuery::new("kMDItemFSName = \"*e*\"c", [MDQueryScope::Computer], None).unwrap();
+
+        let thread = std::thread::current().id();
+        let mut updates = Vec::new();
+        let items = query
+            .execute_with_progress(|count| {
+                assert_eq!(std::thread::current().id(), thread);
+                updates.push(count);
+            })
+            .unwrap();
+        assert!(!updates.is_empty());
+        assert!(updates.iter().all(|&count| count <= items.len()));
+    }
+
+    #[test]
+    fn test_execute_with_progress_panic() {
+        let query =
+            MDQuery::new("kMDItemFSName = \"*e*\"c", [MDQueryScope::Computer], None).unwrap();
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            query.execute_with_progress(|_| panic!("progress callback panicked"))
+        }));
+        assert!(result.is_err());
+
+        // The observer was removed, so the query can run again on this thread.
+        let mut updates = 0;
+        query.execute_with_progress(|_| updates += 1).unwrap();
+        assert!(updates > 0);
+    }
+
+    #[test]
+    fn test_execute_with_timeout_completes() {
+        let query = MDQuery::new(
+            "kMDItemFSName = \"Safari.app\"",
+            [MDQueryScope::Custom("/Applications".into())],
+            Some(5),
+        )
+        .unwrap();
+
+        let items = query.execute_with_timeout(Duration::from_secs(30)).unwrap();
+        assert_eq!(items.len(), 1);
+    }
+
+    #[test]
+    fn test_execute_with_timeout_expires() {
+        let query = MDQuery::new(
+            "kMDItemTextContent = \"*a*\"cd",
+            [MDQueryScope::Computer],
+            None,
+        )
+        .unwrap();
+
+        let err = query
+            .execute_with_timeout(Duration::from_millis(1))
+            .err()
+            .unwrap();
+        match err.downcast_ref::<MDQueryError>() {
+            Some(MDQueryError::Timeout { timeout, .. }) => {
+                assert_eq!(*timeout, Duration::from_millis(1))
+            }
+            other => panic!("expected a timeout, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_execute_with_batches() {
+        let query = MDQuery::new(
+            "kMDItemFSName = \"*\"",
+            [MDQueryScope::Custom("/System/Library/CoreServices".into())],
+            None,
+        )
+        .unwrap();
+
+        let mut batches = Vec::new();
+        query
+            .execute_with_batches(|batch| {
+                batches.push(batch);
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+        let items = query.execute().unwrap();
+        assert!(batches.iter().all(|batch| !batch.is_empty()));
+        assert_eq!(
+            batches
+                .concat()
+                .iter()
+                .map(MDItem::path)
+                .collect::<Vec<_>>(),
+            items.iter().map(MDItem::path).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_top_k_with_progress_timeout_and_batches() {
+        let query = MDQuery::new(
+            "kMDItemFSName = \"*\"",
+            [MDQueryScope::Custom("/System/Applications".into())],
+            None,
+        )
+        .unwrap()
+        .with_top_k(3, MDItemKey::FSName, SortDirection::Descending)
+        .unwrap();
+        let paths = |items: Vec<MDItem>| items.iter().map(MDItem::path).collect::<Vec<_>>();
+
+        let expected = paths(query.execute().unwrap());
+        assert_eq!(expected.len(), 3);
+        assert_eq!(
+            paths(query.execute_with_progress(|_| {}).unwrap()),
+            expected
+        );
+        assert_eq!(
+            paths(query.execute_with_timeout(Duration::from_secs(30)).unwrap()),
+            expected
+        );
+        let err = query
+            .execute_with_batches(|_| ControlFlow::Continue(()))
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<MDQueryError>(),
+            Some(MDQueryError::InvalidBuilder(errors)) if errors.len() == 1
+        ));
+    }
+
+    #[test]
+    fn test_execute_with_batches_break() {
+        let query = MDQuery::new("kMDItemFSName = \"*\"", [MDQueryScope::Computer], None).unwrap();
+
+        let mut calls = 0;
+        query
+            .execute_with_batches(|_| {
+                calls += 1;
+                ControlFlow::Break(())
+            })
+            .unwrap();
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    #[cfg(feature = "nsmetadata")]
+    fn test_ns_metadata_backend_matches_core_services() {
+        let builder = MDQueryBuilder::default()
+            .name_like("Safari")
+            .is_app()
+            .with_scopes([MDQueryScope::from_path("/Applications")])
+            .with_max_count(5);
+        let query = builder.to_query_string().unwrap();
+        let scopes = [MDQueryScope::from_path("/Applications")];
+
+        let core_services =
+            MDQuery::new_with_backend(Backend::CoreServices, &query, scopes.clone(), Some(5))
+                .unwrap();
+        let ns_metadata =
+            MDQuery::new_with_backend(Backend::NsMetadata, &query, scopes, Some(5)).unwrap();
+        assert_eq!(ns_metadata.backend(), Backend::NsMetadata);
+
+        let mut expected = core_services
+            .execute()
+            .unwrap()
+            .iter()
+            .filter_map(MDItem::path)
+            .collect::<Vec<_>>();
+        let mut actual = ns_metadata
+            .execute()
+            .unwrap()
+            .iter()
+            .filter_map(MDItem::path)
+            .collect::<Vec<_>>();
+        expected.sort();
+        actual.sort();
+        assert!(!expected.is_empty());
+        assert_eq!(actual, expected);
+        assert!(ns_metadata.execute_with_progress(|_| {}).is_err());
+        assert!(ns_metadata.md_query.lock().unwrap().is_none());
+        assert_eq!(ns_metadata.lock_updates().result_count(), 0);
+
+        // Items read their attributes from the NSMetadataItem results.
+        let items = ns_metadata.execute().unwrap();
+        let safari = items
+            .iter()
+            .find(|item| item.path() == Some(PathBuf::from("/Applications/Safari.app")))
+            .unwrap();
+        assert_eq!(
+            safari.bundle_identifier().as_deref(),
+            Some("com.apple.Safari")
+        );
+        assert!(!safari.get_attribute_names().is_empty());
+    }
+
     #[test]
     fn test_empty_result() {
         let query = MDQuery::new(
             "kMDItemFSName = \"ThisFileDoesNotExist123456789.xyz\"",
-            Some(vec![MDQueryScope::Computer]),
+            [MDQueryScope::Computer],
             None,
         )
         .unwrap();
@@ -156,11 +1904,12 @@ mod tests {
 
     #[test]
     fn test_invalid_query() {
-        let result = MDQuery::new(
-            "invalid query syntax !!!",
-            Some(vec![MDQueryScope::Computer]),
-            None,
+        let result = MDQuery::new("invalid query syntax !!!", [MDQueryScope::Computer], None);
+        let err = result.err().unwrap();
+        assert_eq!(
+            err.downcast_ref::<crate::QuerySyntaxError>()
+                .map(|e| e.offset()),
+            Some(8)
         );
-        assert!(result.is_err());
     }
 }
@@ -1,49 +1,839 @@
-use anyhow::Result;
-use std::future::Future;
+use anyhow::{anyhow, Result};
+use objc2_core_foundation::{
+    kCFRunLoopDefaultMode, CFArray, CFArrayGetCount, CFArrayGetValueAtIndex, CFDictionary,
+    CFDictionaryGetValue, CFNotificationCenter, CFNotificationCenterAddObserver,
+    CFNotificationCenterGetLocalCenter, CFNotificationCenterRemoveObserver, CFNotificationName,
+    CFNotificationSuspensionBehavior, CFRetained, CFRunLoop, CFRunLoopAddSource,
+    CFRunLoopGetCurrent, CFRunLoopRunInMode, CFRunLoopSource, CFRunLoopSourceContext,
+    CFRunLoopSourceCreate, CFRunLoopSourceSignal, CFRunLoopWakeUp, CFString,
+};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::ffi::c_void;
+use std::future::{poll_fn, Future};
+use std::panic::{self, AssertUnwindSafe};
 use std::pin::Pin;
-use std::sync::mpsc;
-use std::task::{Context, Poll};
-use std::thread;
+use std::ptr::{self, NonNull};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex, MutexGuard, OnceLock, PoisonError};
+use std::task::{Context, Poll, Waker};
+use std::thread::{self, JoinHandle};
 
+use super::api::*;
 use super::{MDItem, MDQuery};
+use crate::results::merge_scoped;
+use crate::{MDQueryOptions, MDQueryScope, MDQueryUpdateEvent, ScopedItem};
+
+/// Default number of worker threads used for async `NSMetadataQuery` execution.
+const DEFAULT_ASYNC_WORKERS: usize = 4;
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// A lazily initialized, bounded pool of threads that async queries on the
+/// `NSMetadataQuery` backend are dispatched onto, since that backend only executes
+/// synchronously.
+///
+/// Jobs are executed in FIFO order. Workers are spawned on demand up to `max_workers`
+/// and stay alive waiting for more work until the pool is shut down.
+struct WorkerPool {
+    state: Mutex<PoolState>,
+    available: Condvar,
+}
+
+struct PoolState {
+    queue: VecDeque<Job>,
+    handles: Vec<JoinHandle<()>>,
+    max_workers: usize,
+    /// Spawned workers that have not exited.
+    workers: usize,
+    /// Workers that have started running, see `PoolState::starting`.
+    started: usize,
+    /// Workers waiting for a job.
+    idle: usize,
+    shutting_down: bool,
+}
+
+impl WorkerPool {
+    fn new(max_workers: usize) -> Self {
+        WorkerPool {
+            state: Mutex::new(PoolState {
+                queue: VecDeque::new(),
+                handles: Vec::new(),
+                max_workers,
+                workers: 0,
+                started: 0,
+                idle: 0,
+                shutting_down: false,
+            }),
+            available: Condvar::new(),
+        }
+    }
+
+    fn global() -> &'static WorkerPool {
+        static POOL: OnceLock<WorkerPool> = OnceLock::new();
+        POOL.get_or_init(|| WorkerPool::new(DEFAULT_ASYNC_WORKERS))
+    }
+
+    /// Locks the pool state. Jobs run without the lock, so a poisoned lock still holds
+    /// consistent state.
+    fn lock(&self) -> MutexGuard<'_, PoolState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn submit(&'static self, job: Job) {
+        let mut state = self.lock();
+        state.queue.push_back(job);
+        self.spawn_workers(&mut state);
+        self.available.notify_one();
+    }
+
+    /// Spawns workers for the queued jobs that no idle worker will pick up, up to the
+    /// limit. Idle workers count as idle until they wake up, so each one is only relied
+    /// on for a single job.
+    fn spawn_workers(&'static self, state: &mut PoolState) {
+        state.handles.retain(|handle| !handle.is_finished());
+        while state.queue.len() > state.idle + state.starting() && state.workers < state.max_workers
+        {
+            let spawned = thread::Builder::new()
+                .name("mdquery-async".to_string())
+                .spawn(move || self.run_worker());
+            match spawned {
+                Ok(handle) => {
+                    state.workers += 1;
+                    state.handles.push(handle);
+                }
+                // The jobs stay queued for the running workers or the next submission.
+                Err(_) => break,
+            }
+        }
+    }
+
+    fn run_worker(&'static self) {
+        let mut state = self.lock();
+        state.started += 1;
+        loop {
+            if state.workers > state.max_workers {
+                break;
+            }
+            if let Some(job) = state.queue.pop_front() {
+                drop(state);
+                let guard = WorkerPanicGuard(self);
+                job();
+                std::mem::forget(guard);
+                state = self.lock();
+                continue;
+            }
+            if state.shutting_down {
+                break;
+            }
+            state.idle += 1;
+            state = self
+                .available
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+            state.idle -= 1;
+        }
+        state.workers -= 1;
+        state.started -= 1;
+    }
+
+    fn set_max_workers(&self, n: usize) {
+        let mut state = self.lock();
+        state.max_workers = n.max(1);
+        self.available.notify_all();
+    }
+
+    /// Lets the workers finish the queued jobs and joins all of them, including workers
+    /// spawned for jobs submitted while shutting down.
+    fn shutdown(&self) {
+        self.lock().shutting_down = true;
+        self.available.notify_all();
+        loop {
+            let handles = std::mem::take(&mut self.lock().handles);
+            if handles.is_empty() {
+                break;
+            }
+            for handle in handles {
+                let _ = handle.join();
+            }
+        }
+        self.lock().shutting_down = false;
+    }
+
+    #[cfg(test)]
+    fn worker_count(&self) -> usize {
+        self.lock().workers
+    }
+}
+
+impl PoolState {
+    /// The number of spawned workers that have not picked up the lock yet.
+    fn starting(&self) -> usize {
+        self.workers - self.started
+    }
+}
+
+/// Removes a worker from the count if a job panics and takes the worker thread down, and
+/// spawns a replacement if jobs are waiting.
+struct WorkerPanicGuard(&'static WorkerPool);
+
+impl Drop for WorkerPanicGuard {
+    fn drop(&mut self) {
+        let mut state = self.0.lock();
+        state.workers -= 1;
+        state.started -= 1;
+        self.0.spawn_workers(&mut state);
+    }
+}
+
+/// The thread whose run loop all asynchronously executed Core Services queries run on.
+///
+/// Queries are started without `kMDQuerySynchronous`, so the thread is never blocked by
+/// a gathering query: it only starts queued queries, reports their progress and
+/// completes their futures when `kMDQueryDidFinishNotification` arrives. However many
+/// queries are in flight, they share this one thread. Jobs are handed to the thread
+/// through a custom run loop source, which wakes it without polling.
+struct RunLoopDriver {
+    state: Mutex<DriverState>,
+}
+
+struct DriverState {
+    /// Work for the driver thread, run by the perform callback of its run loop source.
+    jobs: VecDeque<Job>,
+    thread: Option<DriverThread>,
+    /// The number of started queries that have not finished yet.
+    active: usize,
+}
+
+/// A running driver thread.
+struct DriverThread {
+    wakeup: DriverWakeup,
+    /// Set by `shutdown` to make the thread exit once its queries have finished.
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+/// What other threads need to hand work to the driver thread.
+struct DriverWakeup {
+    run_loop: CFRetained<CFRunLoop>,
+    source: CFRetained<CFRunLoopSource>,
+}
+
+// SAFETY: Other threads only signal the source and wake the run loop, which
+// CoreFoundation permits from any thread.
+unsafe impl Send for DriverWakeup {}
+
+impl DriverWakeup {
+    fn wake(&self) {
+        unsafe {
+            CFRunLoopSourceSignal(&self.source);
+            CFRunLoopWakeUp(&self.run_loop);
+        }
+    }
+}
+
+thread_local! {
+    /// Queries that finished on this driver thread, released after their notification has
+    /// been delivered.
+    static FINISHED: RefCell<Vec<*mut InFlightQuery>> = const { RefCell::new(Vec::new()) };
+}
+
+impl RunLoopDriver {
+    fn global() -> &'static RunLoopDriver {
+        static DRIVER: OnceLock<RunLoopDriver> = OnceLock::new();
+        DRIVER.get_or_init(|| RunLoopDriver {
+            state: Mutex::new(DriverState {
+                jobs: VecDeque::new(),
+                thread: None,
+                active: 0,
+            }),
+        })
+    }
+
+    /// Locks the driver state. Jobs and callbacks run without the lock, so a poisoned lock
+    /// still holds consistent state.
+    fn lock(&self) -> MutexGuard<'_, DriverState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Queues `job` to run on the driver thread, starting the thread if it is not running.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, and drops `job`, if the driver thread could not be started.
+    fn submit(&'static self, job: Job) -> Result<()> {
+        let mut state = self.lock();
+        if state
+            .thread
+            .as_ref()
+            .is_none_or(|thread| thread.handle.is_finished())
+        {
+            // The queries of a thread that died went down with it.
+            if state.thread.take().is_some() {
+                state.active = 0;
+            }
+            state.thread = Some(self.spawn()?);
+        }
+        state.jobs.push_back(job);
+        if let Some(thread) = &state.thread {
+            thread.wakeup.wake();
+        }
+        Ok(())
+    }
+
+    /// Starts the driver thread and waits until it can accept jobs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the thread could not be spawned or failed to set up its run loop.
+    fn spawn(&'static self) -> Result<DriverThread> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let (ready, wakeup) = mpsc::channel();
+        let handle = {
+            let stop = Arc::clone(&stop);
+            thread::Builder::new()
+                .name("mdquery-async".to_string())
+                .spawn(move || {
+                    let Some(run_loop) = (unsafe { CFRunLoopGetCurrent() }) else {
+                        return;
+                    };
+                    let mut context = CFRunLoopSourceContext {
+                        version: 0,
+                        info: ptr::null_mut(),
+                        retain: None,
+                        release: None,
+                        copyDescription: None,
+                        equal: None,
+                        hash: None,
+                        schedule: None,
+                        cancel: None,
+                        perform: Some(perform_jobs),
+                    };
+                    let Some(source) = (unsafe { CFRunLoopSourceCreate(None, 0, &mut context) })
+                    else {
+                        return;
+                    };
+                    unsafe { CFRunLoopAddSource(&run_loop, Some(&source), kCFRunLoopDefaultMode) };
+                    let _ = ready.send(DriverWakeup { run_loop, source });
+                    self.run(&stop);
+                })
+                .map_err(|err| anyhow!("Failed to spawn the mdquery async thread: {}", err))?
+        };
+        let wakeup = wakeup
+            .recv()
+            .map_err(|_| anyhow!("The mdquery async thread failed to set up its run loop."))?;
+        Ok(DriverThread {
+            wakeup,
+            stop,
+            handle,
+        })
+    }
+
+    /// Runs the driver thread's run loop until `stop` is set and all queries finished.
+    fn run(&self, stop: &AtomicBool) {
+        loop {
+            unsafe { CFRunLoopRunInMode(kCFRunLoopDefaultMode, 1.0, true) };
+            self.release_finished();
+            let state = self.lock();
+            if stop.load(Ordering::Acquire) && state.active == 0 && state.jobs.is_empty() {
+                break;
+            }
+        }
+    }
+
+    /// Runs the queued jobs, called by the run loop source. A panicking job is dropped so
+    /// the panic neither unwinds into the run loop nor takes the thread down.
+    fn run_jobs(&self) {
+        loop {
+            let job = self.lock().jobs.pop_front();
+            match job {
+                Some(job) => {
+                    let _ = panic::catch_unwind(AssertUnwindSafe(job));
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Unregisters and drops the queries that finished since the last call.
+    fn release_finished(&self) {
+        let finished = FINISHED.with(|finished| std::mem::take(&mut *finished.borrow_mut()));
+        if finished.is_empty() {
+            return;
+        }
+        let center = unsafe { CFNotificationCenterGetLocalCenter() };
+        for in_flight in &finished {
+            // SAFETY: Queries are only queued once, by the callback that finished them.
+            let in_flight = unsafe { Box::from_raw(*in_flight) };
+            if let Some(center) = &center {
+                let object = &*in_flight.md_query as *const CoreMDQuery as *const c_void;
+                let observer = &*in_flight as *const InFlightQuery as *const c_void;
+                unsafe { CFNotificationCenterRemoveObserver(center, observer, None, object) };
+            }
+        }
+        let mut state = self.lock();
+        state.active = state.active.saturating_sub(finished.len());
+    }
+
+    /// Starts `query` on the driver thread, which must be the calling thread.
+    fn start(
+        &self,
+        query: MDQuery,
+        state: Arc<Mutex<AsyncState>>,
+        on_progress: Option<ProgressCallback>,
+    ) {
+        if state.lock().unwrap().cancelled {
+            return;
+        }
+        let md_query = match query.next_md_query() {
+            Ok(md_query) => md_query,
+            Err(err) => return complete(&state, Err(err)),
+        };
+        let Some(center) = (unsafe { CFNotificationCenterGetLocalCenter() }) else {
+            return complete(
+                &state,
+                Err(anyhow!(
+                    "MDQuery execute failed to get the notification center."
+                )),
+            );
+        };
+        let object = &*md_query as *const CoreMDQuery as *const c_void;
+        let in_flight = Box::into_raw(Box::new(InFlightQuery {
+            query,
+            md_query: md_query.clone(),
+            state,
+            on_progress,
+            finished: false,
+        }));
+
+        unsafe {
+            CFNotificationCenterAddObserver(
+                &center,
+                in_flight as *const c_void,
+                Some(in_flight_callback),
+                None,
+                object,
+                CFNotificationSuspensionBehavior::DeliverImmediately,
+            );
+        }
+        if unsafe { MDQueryExecute(&md_query, MDQueryOptions::NONE.bits() as _) } {
+            self.lock().active += 1;
+        } else {
+            unsafe {
+                CFNotificationCenterRemoveObserver(
+                    &center,
+                    in_flight as *const c_void,
+                    None,
+                    object,
+                )
+            };
+            // SAFETY: The query never started, so no notification refers to it.
+            let in_flight = unsafe { Box::from_raw(in_flight) };
+            complete(&in_flight.state, Err(anyhow!("MDQuery execute failed.")));
+        }
+    }
+
+    fn shutdown(&self) {
+        let thread = self.lock().thread.take();
+        if let Some(thread) = thread {
+            thread.stop.store(true, Ordering::Release);
+            thread.wakeup.wake();
+            let _ = thread.handle.join();
+        }
+    }
+}
+
+unsafe extern "C-unwind" fn perform_jobs(_info: *mut c_void) {
+    RunLoopDriver::global().run_jobs();
+}
+
+/// A query gathering results on the driver thread.
+struct InFlightQuery {
+    query: MDQuery,
+    md_query: CFRetained<CoreMDQuery>,
+    state: Arc<Mutex<AsyncState>>,
+    on_progress: Option<ProgressCallback>,
+    /// Whether the result was delivered or the query stopped; later notifications are
+    /// ignored.
+    finished: bool,
+}
+
+unsafe extern "C-unwind" fn in_flight_callback(
+    _center: *mut CFNotificationCenter,
+    observer: *mut c_void,
+    name: *const CFNotificationName,
+    _object: *const c_void,
+    _user_info: *const CFDictionary,
+) {
+    let in_flight_ptr = observer as *mut InFlightQuery;
+    // SAFETY: The observer is registered by `RunLoopDriver::start` and only released by
+    // `release_finished` after unregistering it, both on the thread delivering this.
+    let (Some(in_flight), Some(name)) =
+        (unsafe { in_flight_ptr.as_mut() }, unsafe { name.as_ref() })
+    else {
+        return;
+    };
+    if in_flight.finished {
+        return;
+    }
+    // A panicking progress callback must not unwind into the notification center, so the
+    // query is stopped and its future fails instead.
+    let finished =
+        panic::catch_unwind(AssertUnwindSafe(|| in_flight.notify(name))).unwrap_or_else(|_| {
+            unsafe { MDQueryStop(&in_flight.md_query) };
+            complete(
+                &in_flight.state,
+                Err(anyhow!("MDQuery async callback panicked.")),
+            );
+            true
+        });
+    if finished {
+        in_flight.finished = true;
+        FINISHED.with(|finished| finished.borrow_mut().push(in_flight_ptr));
+    }
+}
+
+impl InFlightQuery {
+    /// Handles the notification `name` of the query, returning whether it finished.
+    fn notify(&mut self, name: &CFNotificationName) -> bool {
+        if name == unsafe { kMDQueryProgressNotification } {
+            if self.state.lock().unwrap().cancelled {
+                unsafe { MDQueryStop(&self.md_query) };
+                return true;
+            }
+            if let Some(on_progress) = &mut self.on_progress {
+                on_progress(unsafe { MDQueryGetResultCount(&self.md_query) } as usize);
+            }
+            false
+        } else if name == unsafe { kMDQueryDidFinishNotification } {
+            let items = self.query.collect_results(&self.md_query);
+            complete(&self.state, Ok(items));
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Stores the result of an async query and wakes the task awaiting it.
+fn complete(state: &Mutex<AsyncState>, result: Result<Vec<MDItem>>) {
+    let waker = {
+        let mut state = state.lock().unwrap();
+        state.result = Some(result);
+        state.waker.take()
+    };
+    if let Some(waker) = waker {
+        waker.wake();
+    }
+}
+
+/// Sets the maximum number of worker threads used by [`MDQuery::execute_async`] for
+/// queries on the `NSMetadataQuery` backend.
+///
+/// Queries on the Core Services backend do not occupy a worker while they gather; they
+/// all run on a single shared run loop thread. The pool is shared by all async
+/// `NSMetadataQuery` queries in the process and defaults to 4 workers. Lowering the limit
+/// lets surplus workers exit once they finish their current query. Values below 1 are
+/// treated as 1.
+pub fn set_async_workers(n: usize) {
+    WorkerPool::global().set_max_workers(n);
+}
+
+/// Gracefully shuts down the async worker pool and the run loop thread of async queries.
+///
+/// Queries that are already queued are executed before the threads exit, and this
+/// function blocks until all of them have stopped. The threads are restarted lazily by
+/// the next call to [`MDQuery::execute_async`].
+///
+/// Must not be called from within a query running on the pool, or from a progress
+/// callback.
+pub fn shutdown_async_workers() {
+    WorkerPool::global().shutdown();
+    RunLoopDriver::global().shutdown();
+}
+
+struct AsyncState {
+    result: Option<Result<Vec<MDItem>>>,
+    waker: Option<Waker>,
+    cancelled: bool,
+}
+
+type ProgressCallback = Box<dyn FnMut(usize) + Send + 'static>;
 
 pub struct MDQueryAsyncResult {
     query: Option<MDQuery>,
-    receiver: Option<mpsc::Receiver<Result<Vec<MDItem>>>>,
+    on_progress: Option<ProgressCallback>,
+    state: Arc<Mutex<AsyncState>>,
 }
 
 impl Future for MDQueryAsyncResult {
     type Output = Result<Vec<MDItem>>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        if let Some(rx) = &self.receiver {
-            if let Ok(result) = rx.try_recv() {
-                return Poll::Ready(result);
-            }
+        self.start();
+
+        let mut state = self.state.lock().unwrap();
+        if let Some(result) = state.result.take() {
+            return Poll::Ready(result);
         }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
 
+impl MDQueryAsyncResult {
+    /// Dispatches the query onto the run loop thread, or the worker pool for the
+    /// `NSMetadataQuery` backend, unless that already happened.
+    fn start(&mut self) {
         if let Some(query) = self.query.take() {
-            let waker = cx.waker().clone();
-            let (tx, rx) = mpsc::channel();
+            let state = Arc::clone(&self.state);
+            let on_progress = self.on_progress.take();
+            if !query.backend().is_ns_metadata() {
+                let driver = RunLoopDriver::global();
+                let job_state = Arc::clone(&state);
+                let submitted = driver.submit(Box::new(move || {
+                    let panicked_state = Arc::clone(&state);
+                    let started = panic::catch_unwind(AssertUnwindSafe(|| {
+                        driver.start(query, state, on_progress)
+                    }));
+                    if started.is_err() {
+                        complete(
+                            &panicked_state,
+                            Err(anyhow!("MDQuery async start panicked.")),
+                        );
+                    }
+                }));
+                if let Err(err) = submitted {
+                    complete(&job_state, Err(err));
+                }
+                return;
+            }
+            WorkerPool::global().submit(Box::new(move || {
+                if state.lock().unwrap().cancelled {
+                    return;
+                }
+                let result = panic::catch_unwind(AssertUnwindSafe(|| match on_progress {
+                    Some(on_progress) => query.execute_with_progress(on_progress),
+                    None => query.execute(),
+                }))
+                .unwrap_or_else(|_| Err(anyhow!("async query panicked")));
+                complete(&state, result);
+            }));
+        }
+    }
+}
 
-            thread::spawn(move || {
-                let result = query.execute();
-                let _ = tx.send(result);
-                waker.wake();
-            });
+impl Drop for MDQueryAsyncResult {
+    fn drop(&mut self) {
+        // A query still waiting in the pool queue is skipped once its future is gone.
+        self.state.lock().unwrap().cancelled = true;
+    }
+}
+
+/// Events of a watched query that have not been polled yet.
+struct WatchState {
+    events: VecDeque<MDQueryUpdateEvent>,
+    waker: Option<Waker>,
+    /// Whether the watching thread has exited, so no more events will arrive.
+    finished: bool,
+}
 
-            self.receiver = Some(rx);
+impl WatchState {
+    /// Queues `event` and wakes the task polling the stream, if any.
+    fn push(state: &Mutex<WatchState>, event: MDQueryUpdateEvent) {
+        let waker = {
+            let mut state = state.lock().unwrap();
+            state.events.push_back(event);
+            state.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
         }
+    }
+}
+
+/// A stream of changes to the results of a live query, created by
+/// [`MDQuery::watch_stream`].
+///
+/// The query runs on a dedicated thread with its own run loop, so the stream can be polled
+/// from any executor. Dropping the stream stops the query, removes its notification
+/// observer and waits for the thread to exit.
+///
+/// The stream has the shape of `futures::Stream`: [`poll_next`](Self::poll_next) has the
+/// same signature as `Stream::poll_next`, so it can be adapted without this crate
+/// depending on `futures`.
+pub struct MDQueryWatchStream {
+    state: Arc<Mutex<WatchState>>,
+    stopped: Arc<AtomicBool>,
+    worker: Option<JoinHandle<()>>,
+}
 
+impl MDQueryWatchStream {
+    /// Polls for the next event.
+    ///
+    /// # Returns
+    /// `Poll::Ready(Some(event))` for the next queued event, `Poll::Ready(None)` once the
+    /// watched query has ended, or `Poll::Pending` after registering the task to be woken
+    /// by the next event.
+    pub fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<MDQueryUpdateEvent>> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(event) = state.events.pop_front() {
+            return Poll::Ready(Some(event));
+        }
+        if state.finished {
+            return Poll::Ready(None);
+        }
+        state.waker = Some(cx.waker().clone());
         Poll::Pending
     }
+
+    /// Waits for the next event.
+    ///
+    /// # Returns
+    /// The next event, or None once the watched query has ended.
+    pub async fn next(&mut self) -> Option<MDQueryUpdateEvent> {
+        poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+}
+
+impl Drop for MDQueryWatchStream {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Release);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// State shared with [`watch_callback`] while a query is watched.
+struct WatchObserver<'a> {
+    md_query: &'a CoreMDQuery,
+    state: &'a Mutex<WatchState>,
+}
+
+unsafe extern "C-unwind" fn watch_callback(
+    _center: *mut CFNotificationCenter,
+    observer: *mut c_void,
+    name: *const CFNotificationName,
+    _object: *const c_void,
+    user_info: *const CFDictionary,
+) {
+    // SAFETY: The observer is registered by `watch`, which keeps it alive and removes it
+    // before returning. Notifications are delivered on the thread running it.
+    let (Some(observer), Some(name)) = (
+        unsafe { (observer as *const WatchObserver).as_ref() },
+        unsafe { name.as_ref() },
+    ) else {
+        return;
+    };
+    if name == unsafe { kMDQueryDidFinishNotification } {
+        // The results gathered before the query went live are reported as one batch.
+        let items = MDQuery::results(observer.md_query);
+        if !items.is_empty() {
+            WatchState::push(observer.state, MDQueryUpdateEvent::Added(items));
+        }
+    } else if name == unsafe { kMDQueryDidUpdateNotification } {
+        let Some(user_info) = (unsafe { user_info.as_ref() }) else {
+            return;
+        };
+        let kinds = unsafe {
+            [
+                (
+                    kMDQueryUpdateAddedItems,
+                    MDQueryUpdateEvent::Added as fn(_) -> _,
+                ),
+                (kMDQueryUpdateChangedItems, MDQueryUpdateEvent::Changed),
+                (kMDQueryUpdateRemovedItems, MDQueryUpdateEvent::Removed),
+            ]
+        };
+        for (key, event) in kinds {
+            let items = unsafe { update_items(user_info, key) };
+            if !items.is_empty() {
+                WatchState::push(observer.state, event(items));
+            }
+        }
+    }
+}
+
+/// Reads the items stored under `key` in the user info of an update notification.
+///
+/// # Safety
+/// `user_info` must be the user info of a `kMDQueryDidUpdateNotification`, whose values
+/// are arrays of `MDItemRef`s.
+unsafe fn update_items(user_info: &CFDictionary, key: &CFString) -> Vec<MDItem> {
+    let array = unsafe { CFDictionaryGetValue(user_info, key as *const CFString as *const c_void) }
+        as *const CFArray;
+    let Some(array) = (unsafe { array.as_ref() }) else {
+        return Vec::new();
+    };
+    (0..unsafe { CFArrayGetCount(array) })
+        .filter_map(|i| {
+            let item = unsafe { CFArrayGetValueAtIndex(array, i) } as *mut CoreMDItem;
+            // The notification owns the items, so take our own reference.
+            NonNull::new(item)
+                .map(|item| MDItem::from_retained(unsafe { CFRetained::retain(item) }))
+        })
+        .collect()
+}
+
+/// Executes `md_query` with live updates and runs the current thread's run loop until
+/// `stopped` is set, queueing every change in `state`.
+///
+/// `started` receives the outcome of starting the query.
+fn watch(
+    md_query: &CoreMDQuery,
+    state: &Mutex<WatchState>,
+    stopped: &AtomicBool,
+    started: mpsc::Sender<Result<()>>,
+) -> Result<()> {
+    let center = unsafe { CFNotificationCenterGetLocalCenter() }.ok_or(anyhow!(
+        "MDQuery execute failed to get the notification center."
+    ))?;
+    let observer = WatchObserver { md_query, state };
+    let observer_ptr: *const WatchObserver = &observer;
+    let object = md_query as *const CoreMDQuery as *const c_void;
+
+    unsafe {
+        CFNotificationCenterAddObserver(
+            &center,
+            observer_ptr as *const c_void,
+            Some(watch_callback),
+            None,
+            object,
+            CFNotificationSuspensionBehavior::DeliverImmediately,
+        );
+    }
+    let success = unsafe { MDQueryExecute(md_query, MDQueryOptions::WANTS_UPDATES.bits() as _) };
+    if success {
+        let _ = started.send(Ok(()));
+        while !stopped.load(Ordering::Acquire) {
+            unsafe { CFRunLoopRunInMode(kCFRunLoopDefaultMode, 0.1, false) };
+        }
+        unsafe { MDQueryStop(md_query) };
+    }
+    unsafe {
+        CFNotificationCenterRemoveObserver(&center, observer_ptr as *const c_void, None, object)
+    };
+
+    if !success {
+        return Err(anyhow!("MDQuery execute failed."));
+    }
+    Ok(())
 }
 
 impl MDQuery {
     /// Executes the MDQuery asynchronously
-    /// 
-    /// This method encapsulates the query operation in a Future and executes it in a separate thread.
-    /// When the Future is awaited, it returns the query results.
+    ///
+    /// This method encapsulates the query operation in a Future. When the Future is first
+    /// polled, the query is started on a run loop thread shared by all async queries and
+    /// gathers its results without blocking any thread; the Future completes when
+    /// Spotlight posts `kMDQueryDidFinishNotification`. Queries on the `NSMetadataQuery`
+    /// backend are executed on a shared, bounded worker pool instead (see
+    /// [`set_async_workers`]). Dropping the Future before its query has started cancels
+    /// it, and a gathering query is stopped at its next progress notification.
     ///
     /// # Returns
     ///
@@ -52,14 +842,152 @@ impl MDQuery {
     ///
     /// # Example
     ///
-    /// ```
-    /// let query = MDQuery::new("kMDItemFSName = \"Safari.app\"", None, None)?;
+    /// ```no_run
+    /// use mdquery_rs::MDQuery;
+    ///
+    /// # async fn run() -> anyhow::Result<()> {
+    /// let query = MDQuery::new("kMDItemFSName = \"Safari.app\"", [], None)?;
     /// let items = query.execute_async().await?;
+    /// # Ok(())
+    /// # }
     /// ```
     pub fn execute_async(self) -> MDQueryAsyncResult {
+        self.into_async_result(None)
+    }
+
+    /// Executes the MDQuery asynchronously, reporting progress while results are gathered.
+    ///
+    /// Works like [`execute_async`](Self::execute_async), calling `on_progress` each time
+    /// Spotlight posts `kMDQueryProgressNotification`. `on_progress` is called on the run
+    /// loop thread executing the query, never on the thread polling the Future, and must
+    /// return quickly since it holds up every other async query.
+    ///
+    /// # Parameters
+    /// * `on_progress` - Called with the current result count during gathering
+    ///
+    /// # Returns
+    ///
+    /// Returns an MDQueryAsyncResult that implements the Future trait,
+    /// with an Output type of Result<Vec<MDItem>>
+    pub fn execute_async_with_progress<F>(self, on_progress: F) -> MDQueryAsyncResult
+    where
+        F: FnMut(usize) + Send + 'static,
+    {
+        self.into_async_result(Some(Box::new(on_progress)))
+    }
+
+    /// Runs the query once per scope asynchronously, like
+    /// [`execute_per_scope`](Self::execute_per_scope).
+    ///
+    /// All sub-queries are started at once and gather concurrently.
+    ///
+    /// # Parameters
+    /// * `limits` - The scopes to search and the maximum number of results from each
+    ///
+    /// # Returns
+    /// A Result containing the merged items with the index of their scope in `limits`, or
+    /// an error if creating or executing a sub-query fails.
+    pub async fn execute_per_scope_async(
+        &self,
+        limits: &[(MDQueryScope, usize)],
+    ) -> Result<Vec<ScopedItem>> {
+        let mut pending = limits
+            .iter()
+            .map(|(scope, limit)| Ok(self.scoped(scope, *limit)?.into_async_result(None)))
+            .collect::<Result<Vec<_>>>()?;
+        for query in &mut pending {
+            query.start();
+        }
+        let mut results = Vec::with_capacity(pending.len());
+        for query in pending {
+            results.push(query.await?);
+        }
+        Ok(merge_scoped(results))
+    }
+
+    /// Runs the query as a live query and streams the changes to its results.
+    ///
+    /// The query runs with `kMDQueryWantsUpdates` on a dedicated thread, which runs its
+    /// own run loop, so the caller needs neither a run loop nor a particular executor. The
+    /// results gathered initially arrive as one [`MDQueryUpdateEvent::Added`] batch, and
+    /// every later update posted by Spotlight as `Added`, `Changed` and `Removed` batches.
+    /// Events are delivered until the stream is dropped, which stops the query.
+    ///
+    /// ```no_run
+    /// use mdquery_rs::{MDQuery, MDQueryScope, MDQueryUpdateEvent};
+    ///
+    /// # async fn watch() -> anyhow::Result<()> {
+    /// let query = MDQuery::new("kMDItemFSName == \"*.pdf\"", [MDQueryScope::Home], None)?;
+    /// let mut stream = query.watch_stream()?;
+    /// while let Some(event) = stream.next().await {
+    ///     if let MDQueryUpdateEvent::Added(items) = event {
+    ///         println!("{} new PDFs", items.len());
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Returns
+    /// A Result containing the stream once the query has started.
+    ///
+    /// # Errors
+    /// Returns an error if the query cannot be started, or if it runs on the
+    /// `NSMetadataQuery` backend.
+    pub fn watch_stream(self) -> Result<MDQueryWatchStream> {
+        let state = Arc::new(Mutex::new(WatchState {
+            events: VecDeque::new(),
+            waker: None,
+            finished: false,
+        }));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let (started, start) = mpsc::channel();
+        let worker = {
+            let state = Arc::clone(&state);
+            let stopped = Arc::clone(&stopped);
+            thread::Builder::new()
+                .name("mdquery-watch".to_string())
+                .spawn(move || {
+                    let result = self.execute_with(|md_query| {
+                        watch(md_query, &state, &stopped, started.clone())
+                    });
+                    if let Err(err) = result {
+                        let _ = started.send(Err(err));
+                    }
+                    let waker = {
+                        let mut state = state.lock().unwrap();
+                        state.finished = true;
+                        state.waker.take()
+                    };
+                    if let Some(waker) = waker {
+                        waker.wake();
+                    }
+                })?
+        };
+
+        let stream = MDQueryWatchStream {
+            state,
+            stopped,
+            worker: Some(worker),
+        };
+        match start.recv() {
+            Ok(Ok(())) => Ok(stream),
+            Ok(Err(err)) => Err(err),
+            Err(_) => Err(anyhow!(
+                "MDQuery watch thread exited before the query started."
+            )),
+        }
+    }
+
+    fn into_async_result(self, on_progress: Option<ProgressCallback>) -> MDQueryAsyncResult {
         MDQueryAsyncResult {
             query: Some(self),
-            receiver: None,
+            on_progress,
+            state: Arc::new(Mutex::new(AsyncState {
+                result: None,
+                waker: None,
+                cancelled: false,
+            })),
         }
     }
 }
@@ -68,6 +996,7 @@ impl MDQuery {
 mod tests {
     use crate::MDQueryScope;
     use std::path::PathBuf;
+    use std::time::Duration;
 
     use super::*;
 
@@ -75,7 +1004,7 @@ mod tests {
     async fn test_md_query_execute_async() {
         let query = MDQuery::new(
             "kMDItemFSName = \"Safari.app\"",
-            Some(vec![MDQueryScope::Custom("/Applications".into())]),
+            [MDQueryScope::Custom("/Applications".into())],
             Some(5),
         )
         .unwrap();
@@ -87,4 +1016,262 @@ mod tests {
             PathBuf::from("/Applications/Safari.app")
         );
     }
+
+    #[tokio::test]
+    async fn test_builder_execute_async() {
+        let items = crate::MDQueryBuilder::default()
+            .name_like("Safari")
+            .is_app()
+            .with_scopes([MDQueryScope::from_path("/Applications")])
+            .with_max_count(1)
+            .execute_async()
+            .await
+            .unwrap();
+        assert_eq!(items.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_execute_async_with_progress() {
+        let query =
+            MDQuery::new("kMDItemFSName = \"*e*\"c", [MDQueryScope::Computer], None).unwrap();
+
+        let updates = Arc::new(Mutex::new(Vec::new()));
+        let items = {
+            let updates = Arc::clone(&updates);
+            query
+                .execute_async_with_progress(move |count| {
+                    assert_eq!(thread::current().name(), Some("mdquery-async"));
+                    updates.lock().unwrap().push(count);
+                })
+                .await
+                .unwrap()
+        };
+        let updates = updates.lock().unwrap();
+        assert!(!updates.is_empty());
+        assert!(updates.iter().all(|&count| count <= items.len()));
+    }
+
+    #[tokio::test]
+    async fn test_panicking_progress_callback_fails_query() {
+        let query =
+            MDQuery::new("kMDItemFSName = \"*e*\"c", [MDQueryScope::Computer], None).unwrap();
+        let result = query
+            .execute_async_with_progress(|_| panic!("progress callback panicked"))
+            .await;
+        assert!(result.is_err());
+
+        // The driver thread keeps serving queries.
+        let query = MDQuery::new(
+            "kMDItemFSName = \"Safari.app\"",
+            [MDQueryScope::Custom("/Applications".into())],
+            Some(1),
+        )
+        .unwrap();
+        assert_eq!(query.execute_async().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_execute_per_scope_async() {
+        let query = MDQuery::new("kMDItemFSName = \"*.app\"", [], None).unwrap();
+        let limits = [
+            (MDQueryScope::from_path("/System/Applications"), 3),
+            (
+                MDQueryScope::from_path("/System/Applications/Utilities"),
+                20,
+            ),
+        ];
+        let items = query.execute_per_scope_async(&limits).await.unwrap();
+        let serial = query.execute_per_scope(&limits).unwrap();
+        assert!(items == serial);
+        assert_eq!(items.iter().filter(|item| item.scope_index == 0).count(), 3);
+    }
+
+    #[cfg(feature = "nsmetadata")]
+    #[tokio::test]
+    async fn test_concurrent_execute_async_bounded_threads() {
+        let handles = (0..50)
+            .map(|_| {
+                let query = MDQuery::new_with_backend(
+                    crate::Backend::NsMetadata,
+                    "kMDItemFSName = \"Safari.app\"",
+                    [MDQueryScope::Custom("/Applications".into())],
+                    Some(5),
+                )
+                .unwrap();
+                tokio::spawn(query.execute_async())
+            })
+            .collect::<Vec<_>>();
+        // Lets the spawned tasks submit their queries to the pool before any is awaited.
+        tokio::task::yield_now().await;
+
+        let mut max_workers = 0;
+        for handle in handles {
+            max_workers = max_workers.max(WorkerPool::global().worker_count());
+            let items = handle.await.unwrap().unwrap();
+            assert_eq!(items.len(), 1);
+        }
+        assert!(
+            (1..=DEFAULT_ASYNC_WORKERS).contains(&max_workers),
+            "{max_workers} workers"
+        );
+    }
+
+    #[test]
+    fn test_worker_pool_concurrency_is_bounded() {
+        let pool: &'static WorkerPool = Box::leak(Box::new(WorkerPool::new(2)));
+        let in_flight = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let max_in_flight = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let (tx, rx) = mpsc::channel();
+        for _ in 0..8 {
+            let (in_flight, max_in_flight, tx) =
+                (in_flight.clone(), max_in_flight.clone(), tx.clone());
+            pool.submit(Box::new(move || {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(now, Ordering::SeqCst);
+                thread::sleep(std::time::Duration::from_millis(50));
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                tx.send(()).unwrap();
+            }));
+        }
+        for _ in 0..8 {
+            rx.recv_timeout(std::time::Duration::from_secs(10)).unwrap();
+        }
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+        pool.shutdown();
+        assert_eq!(pool.worker_count(), 0);
+    }
+
+    #[test]
+    fn test_worker_pool_survives_panicking_job() {
+        let pool: &'static WorkerPool = Box::leak(Box::new(WorkerPool::new(1)));
+        pool.submit(Box::new(|| panic!("job panicked")));
+        let (tx, rx) = mpsc::channel();
+        pool.submit(Box::new(move || tx.send(()).unwrap()));
+        rx.recv_timeout(std::time::Duration::from_secs(10)).unwrap();
+        assert!(pool.worker_count() <= 1);
+        pool.shutdown();
+        assert_eq!(pool.worker_count(), 0);
+    }
+
+    /// Returns the number of threads of the current process.
+    fn thread_count() -> i32 {
+        let mut info = std::mem::MaybeUninit::<libc::proc_taskinfo>::zeroed();
+        let size = std::mem::size_of::<libc::proc_taskinfo>() as i32;
+        let written = unsafe {
+            libc::proc_pidinfo(
+                std::process::id() as i32,
+                libc::PROC_PIDTASKINFO,
+                0,
+                info.as_mut_ptr().cast(),
+                size,
+            )
+        };
+        assert_eq!(written, size);
+        unsafe { info.assume_init() }.pti_threadnum
+    }
+
+    #[tokio::test]
+    async fn test_many_concurrent_queries_share_one_thread() {
+        let query = || {
+            MDQuery::new(
+                "kMDItemFSName = \"*.app\"",
+                [MDQueryScope::from_path("/System/Applications")],
+                None,
+            )
+            .unwrap()
+        };
+        // Start the run loop thread and let Spotlight set up its own threads first.
+        let expected = query().execute_async().await.unwrap().len();
+        let baseline = thread_count();
+
+        // Sample the thread count from another thread while the queries are gathering.
+        let done = Arc::new(AtomicBool::new(false));
+        let sampler = {
+            let done = Arc::clone(&done);
+            thread::spawn(move || {
+                let mut during = None;
+                while !done.load(Ordering::Acquire) {
+                    if RunLoopDriver::global().lock().active > 0 {
+                        during = during.max(Some(thread_count()));
+                    }
+                    thread::sleep(Duration::from_millis(1));
+                }
+                during
+            })
+        };
+        let pending = (0..100)
+            .map(|_| {
+                let mut result = query().execute_async();
+                result.start();
+                result
+            })
+            .collect::<Vec<_>>();
+        for result in pending {
+            assert_eq!(result.await.unwrap().len(), expected);
+        }
+        done.store(true, Ordering::Release);
+        let during = sampler
+            .join()
+            .unwrap()
+            .expect("no sample while queries were in flight");
+        // Spotlight may use a few dispatch threads of its own, but no thread per query.
+        assert!(
+            during <= baseline + 8,
+            "{} threads before, {} with 100 queries in flight",
+            baseline,
+            during
+        );
+        assert!(thread_count() <= baseline + 8);
+    }
+
+    /// Waits for an event of the stream that `matches`, skipping any others.
+    async fn next_matching(
+        stream: &mut MDQueryWatchStream,
+        matches: impl Fn(&MDQueryUpdateEvent) -> bool,
+    ) {
+        loop {
+            let event = tokio::time::timeout(Duration::from_secs(30), stream.next())
+                .await
+                .expect("no matching update within 30 seconds")
+                .expect("stream ended");
+            if matches(&event) {
+                return;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_watch_stream_reports_added_and_removed() {
+        let home = PathBuf::from(std::env::var("HOME").unwrap());
+        let dir = home.join(format!("mdquery-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let query = MDQuery::new(
+            "kMDItemFSName == \"watched-*.txt\"",
+            [MDQueryScope::from_path(&dir)],
+            None,
+        )
+        .unwrap();
+        let mut stream = query.watch_stream().unwrap();
+
+        let path = dir.join("watched-file.txt");
+        std::fs::write(&path, "watch me").unwrap();
+        let _ = std::process::Command::new("mdimport").arg(&path).status();
+        next_matching(&mut stream, |event| match event {
+            MDQueryUpdateEvent::Added(items) => items
+                .iter()
+                .any(|item| item.path().as_deref() == Some(path.as_path())),
+            _ => false,
+        })
+        .await;
+
+        std::fs::remove_file(&path).unwrap();
+        next_matching(
+            &mut stream,
+            |event| matches!(event, MDQueryUpdateEvent::Removed(items) if !items.is_empty()),
+        )
+        .await;
+
+        drop(stream);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -0,0 +1,42 @@
+use super::api::*;
+use super::item::{cf_array_to_strings, cf_dictionary_value_for_key};
+use crate::schema::{AttributeInfo, AttributeType};
+use objc2_core_foundation::{CFBoolean, CFData, CFDate, CFNumber, CFString, ConcreteType};
+
+pub(crate) fn all_attribute_names() -> Vec<String> {
+    unsafe { MDSchemaCopyAllAttributes() }
+        .map(|names| cf_array_to_strings(&names))
+        .unwrap_or_default()
+}
+
+pub(crate) fn display_name(attribute: &str) -> Option<String> {
+    let attribute = CFString::from_str(attribute);
+    unsafe { MDSchemaCopyDisplayNameForAttribute(&attribute) }.map(|name| name.to_string())
+}
+
+pub(crate) fn attribute_info(attribute: &str) -> Option<AttributeInfo> {
+    let name = CFString::from_str(attribute);
+    let meta = unsafe { MDSchemaCopyMetaAttributesForAttribute(&name) }?;
+    // `kMDAttributeType` holds the CFTypeID of the attribute's values.
+    let type_id = cf_dictionary_value_for_key::<CFNumber>(&meta, unsafe { kMDAttributeType })
+        .and_then(|type_id| type_id.as_i64())
+        .and_then(|type_id| usize::try_from(type_id).ok());
+    let type_hint = match type_id {
+        Some(id) if id == CFString::type_id() => AttributeType::String,
+        Some(id) if id == CFNumber::type_id() => AttributeType::Number,
+        Some(id) if id == CFDate::type_id() => AttributeType::Date,
+        Some(id) if id == CFBoolean::type_id() => AttributeType::Bool,
+        Some(id) if id == CFData::type_id() => AttributeType::Data,
+        _ => AttributeType::Unknown,
+    };
+    let multivalued =
+        cf_dictionary_value_for_key::<CFBoolean>(&meta, unsafe { kMDAttributeMultiValued })
+            .is_some_and(|multivalued| multivalued.as_bool());
+    let description = unsafe { MDSchemaCopyDisplayDescriptionForAttribute(&name) }
+        .map(|description| description.to_string());
+    Some(AttributeInfo {
+        type_hint,
+        multivalued,
+        description,
+    })
+}
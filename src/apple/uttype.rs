@@ -0,0 +1,135 @@
+use super::as_object;
+use objc2::rc::Retained;
+use objc2::runtime::AnyObject;
+use objc2::{class, msg_send};
+use objc2_core_foundation::CFString;
+use std::fmt;
+
+#[link(name = "UniformTypeIdentifiers", kind = "framework")]
+extern "C" {}
+
+/// A uniform type identifier, backed by `UTType` from the UniformTypeIdentifiers
+/// framework.
+///
+/// Unlike raw UTI strings, a `UTType` knows the types it conforms to, so
+/// `com.apple.application-bundle` conforms to `com.apple.bundle` without consulting an
+/// item's content type tree.
+///
+/// # Examples
+///
+/// ```no_run
+/// use mdquery_rs::UTType;
+///
+/// let app = UTType::from_identifier("com.apple.application-bundle").unwrap();
+/// let bundle = UTType::from_identifier("com.apple.bundle").unwrap();
+/// assert!(app.conforms_to(&bundle));
+/// ```
+#[derive(Clone)]
+pub struct UTType {
+    ut_type: Retained<AnyObject>,
+}
+
+// SAFETY: `UTType` objects are immutable and documented as sendable.
+unsafe impl Send for UTType {}
+unsafe impl Sync for UTType {}
+
+impl UTType {
+    /// Looks up the type with a UTI string.
+    ///
+    /// # Arguments
+    /// * `identifier` - A UTI such as `"public.image"`
+    ///
+    /// # Returns
+    /// * `Option<Self>` - The type, or None if no type is declared for the identifier
+    pub fn from_identifier(identifier: &str) -> Option<Self> {
+        let identifier = CFString::from_str(identifier);
+        let ut_type: Option<Retained<AnyObject>> =
+            unsafe { msg_send![class!(UTType), typeWithIdentifier: as_object(&*identifier)] };
+        ut_type.map(|ut_type| Self { ut_type })
+    }
+
+    /// Looks up the preferred type for a filename extension.
+    ///
+    /// # Arguments
+    /// * `extension` - A filename extension without the leading dot, such as `"png"`
+    ///
+    /// # Returns
+    /// * `Option<Self>` - The type, or None if the extension cannot be represented
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        let extension = CFString::from_str(extension);
+        let ut_type: Option<Retained<AnyObject>> = unsafe {
+            msg_send![
+                class!(UTType),
+                typeWithFilenameExtension: as_object(&*extension)
+            ]
+        };
+        ut_type.map(|ut_type| Self { ut_type })
+    }
+
+    /// Returns the UTI string of this type.
+    ///
+    /// # Returns
+    /// * `String` - The identifier, such as `"public.image"`
+    pub fn identifier(&self) -> String {
+        let identifier: *mut AnyObject = unsafe { msg_send![&self.ut_type, identifier] };
+        // NSString is toll-free bridged to CFString.
+        unsafe { identifier.cast::<CFString>().as_ref() }
+            .map(|identifier| identifier.to_string())
+            .unwrap_or_default()
+    }
+
+    /// Checks whether this type conforms to another type, directly or transitively.
+    ///
+    /// Every type conforms to itself.
+    ///
+    /// # Arguments
+    /// * `other` - The type to check conformance to
+    ///
+    /// # Returns
+    /// * `bool` - Returns true if this type conforms to `other`
+    pub fn conforms_to(&self, other: &UTType) -> bool {
+        unsafe { msg_send![&self.ut_type, conformsToType: &*other.ut_type] }
+    }
+}
+
+impl PartialEq for UTType {
+    fn eq(&self, other: &Self) -> bool {
+        unsafe { msg_send![&self.ut_type, isEqual: &*other.ut_type] }
+    }
+}
+
+impl Eq for UTType {}
+
+impl fmt::Debug for UTType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("UTType").field(&self.identifier()).finish()
+    }
+}
+
+impl fmt::Display for UTType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.identifier())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conformance() {
+        let app = UTType::from_identifier("com.apple.application-bundle").unwrap();
+        let bundle = UTType::from_identifier("com.apple.bundle").unwrap();
+        assert_eq!(app.identifier(), "com.apple.application-bundle");
+        assert!(app.conforms_to(&bundle));
+        assert!(app.conforms_to(&app));
+        assert!(!bundle.conforms_to(&app));
+    }
+
+    #[test]
+    fn test_from_extension() {
+        let png = UTType::from_extension("png").unwrap();
+        assert_eq!(png, UTType::from_identifier("public.png").unwrap());
+        assert!(png.conforms_to(&UTType::from_identifier("public.image").unwrap()));
+    }
+}
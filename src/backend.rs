@@ -0,0 +1,157 @@
+use crate::{MDItem, MDQuery, MDQueryBuilder, MDQueryError, MDQueryScope};
+use anyhow::Result;
+
+/// Executes query strings produced by [`MDQueryBuilder`].
+///
+/// [`SpotlightBackend`] runs queries against the Spotlight index. Other implementations can
+/// serve results from elsewhere, such as the in-memory `MockBackend` behind the `mock`
+/// feature, so code built around queries can be tested off-device.
+pub trait QueryBackend {
+    /// The type of the items returned by the backend.
+    type Item;
+
+    /// Executes a query.
+    ///
+    /// # Parameters
+    /// * `query` - A Spotlight query string
+    /// * `scopes` - The search scopes to limit the query to
+    /// * `max_count` - Optional maximum number of results to return
+    ///
+    /// # Returns
+    /// A Result containing the matching items, or an error if execution fails.
+    fn execute(
+        &self,
+        query: &str,
+        scopes: &[MDQueryScope],
+        max_count: Option<usize>,
+    ) -> Result<Vec<Self::Item>>;
+}
+
+impl<B: QueryBackend + ?Sized> QueryBackend for &B {
+    type Item = B::Item;
+
+    fn execute(
+        &self,
+        query: &str,
+        scopes: &[MDQueryScope],
+        max_count: Option<usize>,
+    ) -> Result<Vec<Self::Item>> {
+        (**self).execute(query, scopes, max_count)
+    }
+}
+
+/// The Spotlight API an [`MDQuery`] runs on, see [`MDQuery::new_with_backend`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum Backend {
+    /// `NsMetadata` when the process runs in the App Sandbox and the `nsmetadata` feature
+    /// is enabled, `CoreServices` otherwise.
+    #[default]
+    Auto,
+    /// The Core Services `MDQuery` API.
+    CoreServices,
+    /// `NSMetadataQuery`, the query API permitted in the App Sandbox.
+    ///
+    /// Query strings are parsed with `NSPredicate`'s metadata query syntax, which accepts
+    /// everything [`MDQueryBuilder`] emits. `max_count` is applied after gathering, and
+    /// only [`MDQuery::execute`] and the methods built on it are supported.
+    #[cfg(feature = "nsmetadata")]
+    NsMetadata,
+}
+
+impl Backend {
+    /// Resolves [`Backend::Auto`] to the backend it stands for in this process.
+    pub fn resolve(self) -> Self {
+        match self {
+            #[cfg(feature = "nsmetadata")]
+            Self::Auto if is_sandboxed() => Self::NsMetadata,
+            Self::Auto => Self::CoreServices,
+            backend => backend,
+        }
+    }
+
+    /// Whether this is the `NSMetadataQuery` backend.
+    #[cfg_attr(
+        not(all(target_vendor = "apple", not(feature = "fallback"))),
+        allow(dead_code)
+    )]
+    pub(crate) fn is_ns_metadata(self) -> bool {
+        #[cfg(feature = "nsmetadata")]
+        if self == Self::NsMetadata {
+            return true;
+        }
+        false
+    }
+}
+
+/// Whether the process runs in the App Sandbox, which sets up a container for it.
+#[cfg(feature = "nsmetadata")]
+fn is_sandboxed() -> bool {
+    std::env::var_os("APP_SANDBOX_CONTAINER_ID").is_some()
+}
+
+/// The default backend, which runs queries with [`MDQuery`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SpotlightBackend;
+
+impl QueryBackend for SpotlightBackend {
+    type Item = MDItem;
+
+    fn execute(
+        &self,
+        query: &str,
+        scopes: &[MDQueryScope],
+        max_count: Option<usize>,
+    ) -> Result<Vec<MDItem>> {
+        MDQuery::new(query, scopes.iter().cloned(), max_count)?.execute()
+    }
+}
+
+/// A builder bound to a backend, created by [`MDQueryBuilder::with_backend`].
+pub struct BackendQuery<B> {
+    pub(crate) builder: MDQueryBuilder,
+    pub(crate) backend: B,
+}
+
+impl<B: QueryBackend> BackendQuery<B> {
+    /// Builds the query and executes it on the backend.
+    ///
+    /// # Returns
+    /// A Result containing the matching items, or an error if building or execution fails.
+    pub fn execute(self) -> Result<Vec<B::Item>> {
+        let (query, scopes, max_count) = self.builder.into_backend_parts()?;
+        self.backend.execute(&query, &scopes, max_count)
+    }
+
+    /// Builds the query and returns its first result on the backend.
+    ///
+    /// # Returns
+    /// A Result containing the first matching item, or None if nothing matches.
+    pub fn execute_first(self) -> Result<Option<B::Item>> {
+        let (query, scopes, max_count) = self.builder.into_backend_parts()?;
+        let max_count = max_count.map_or(1, |max_count| max_count.min(1));
+        let items = self.backend.execute(&query, &scopes, Some(max_count))?;
+        Ok(items.into_iter().next())
+    }
+
+    /// Builds the query and returns its only result on the backend.
+    ///
+    /// The query runs with a maximum count of 2, whatever the max count of the builder.
+    ///
+    /// # Returns
+    /// A Result containing the only matching item.
+    ///
+    /// # Errors
+    /// Returns [`MDQueryError::NotFound`] if nothing matches, [`MDQueryError::Ambiguous`]
+    /// if several items match, or an error if building or execution fails. The count of an
+    /// ambiguous query is a lower bound on the number of matches.
+    pub fn execute_one(self) -> Result<B::Item> {
+        let (query, scopes, _) = self.builder.into_backend_parts()?;
+        let mut items = self.backend.execute(&query, &scopes, Some(2))?;
+        match items.len() {
+            0 => Err(MDQueryError::NotFound.into()),
+            1 => Ok(items.remove(0)),
+            count => Err(MDQueryError::Ambiguous { count }.into()),
+        }
+    }
+}
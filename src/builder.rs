@@ -0,0 +1,6248 @@
+use crate::json::JsonValue;
+use crate::model::{user_directory, UserDirectory, PACKAGE_CONTENT_TYPES};
+use crate::saved::{self, Operation, Save};
+use crate::{
+    BackendQuery, BuilderError, ContentKind, FileSize, FinderLabel, MDItem, MDItemKey,
+    MDItemKeyKind, MDQuery, MDQueryError, MDQueryScope, QueryBackend, ScopeOptions, SortDirection,
+};
+use anyhow::Result;
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::ops::BitOr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Builder for constructing MDQuery instances with a fluent interface.
+///
+/// This builder allows for creating complex metadata queries on macOS using
+/// the Spotlight search technology. It provides methods to build search expressions
+/// that can be combined with logical AND operations.
+///
+/// # Examples
+///
+/// ```no_run
+/// use mdquery_rs::{MDQueryBuilder, MDQueryScope};
+///
+/// // Find files containing "document" in their name
+/// let results = MDQueryBuilder::default()
+///     .name_like("document")
+///     .with_scopes([MDQueryScope::Home])
+///     .execute()
+///     .unwrap();
+///
+/// for item in results {
+///     println!("{:?}", item.path());
+/// }
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MDQueryBuilder {
+    condition: MDQueryCondition,
+    /// Search scopes; `None` means the default scope (`MDQueryScope::Computer`).
+    scopes: Option<Vec<MDQueryScope>>,
+    max_count: Option<usize>,
+    scope_options: ScopeOptions,
+    /// Whether network scopes are dropped, see `local_volumes_only`.
+    local_volumes_only: bool,
+    /// Whether `match_all` was called, which requires a scope or max count.
+    match_all: bool,
+    /// The number of results to keep, the attribute they are sorted by and the order.
+    top_k: Option<(usize, MDItemKey, SortDirection)>,
+    /// The name required by `name_is`, kept to detect calls that can never all hold.
+    exact_name: Option<(String, MatchModifiers)>,
+    /// The content type required by `content_type`, kept to detect conflicting calls.
+    exact_content_type: Option<String>,
+    /// Problems found in the arguments of builder calls, reported by `build`.
+    errors: Vec<BuilderError>,
+    /// The public method calls that made the builder, saved by `to_json`.
+    operations: Vec<Operation>,
+}
+
+impl MDQueryBuilder {
+    /// Builds the final MDQuery with the current expressions.
+    ///
+    /// The given scopes and max count replace any set via [`with_scopes`](Self::with_scopes)
+    /// and [`with_max_count`](Self::with_max_count).
+    ///
+    /// # Parameters
+    /// * `scopes` - List of search scopes to apply (e.g., Home, Computer)
+    /// * `max_count` - Optional maximum number of results to return
+    ///
+    /// # Returns
+    /// A Result containing the MDQuery if successful, or an error if no expressions were added.
+    ///
+    /// # Errors
+    /// Returns [`MDQueryError::InvalidBuilder`] if a builder call received invalid arguments,
+    /// or an error if no expressions were added to the builder.
+    pub fn build(
+        mut self,
+        scopes: impl IntoIterator<Item = MDQueryScope>,
+        max_count: Option<usize>,
+    ) -> Result<MDQuery> {
+        self.scopes = Some(scopes.into_iter().collect());
+        self.max_count = max_count;
+        self.into_query()
+    }
+
+    /// Builds the final MDQuery using the scopes and max count configured on the builder.
+    ///
+    /// Defaults to `MDQueryScope::Computer` with no max count.
+    ///
+    /// # Returns
+    /// A Result containing the MDQuery if successful, or an error if no expressions were added.
+    ///
+    /// # Errors
+    /// Returns [`MDQueryError::InvalidBuilder`] if a builder call received invalid arguments,
+    /// [`MDQueryError::EmptyConditionGroup`] if an added condition has no expressions, or an
+    /// error if no expressions were added to the builder.
+    pub fn into_query(self) -> Result<MDQuery> {
+        let scope_options = self.scope_options;
+        let top_k = self.top_k;
+        let (query, scopes, max_count) = self.into_parts()?;
+        let query = MDQuery::new_with_options(query, scopes, max_count, scope_options)?;
+        match top_k {
+            Some((k, key, direction)) => query.with_top_k(k, key, direction),
+            None => Ok(query),
+        }
+    }
+
+    /// Binds the builder to a backend other than Spotlight.
+    ///
+    /// ```
+    /// # #[cfg(feature = "mock")]
+    /// # {
+    /// use mdquery_rs::{MDQueryBuilder, MockBackend, MockItem};
+    ///
+    /// let backend = MockBackend::new()
+    ///     .with_item(MockItem::new("/Users/me/report.pdf"))
+    ///     .with_item(MockItem::new("/Users/me/notes.txt"));
+    /// let items = MDQueryBuilder::default()
+    ///     .extension("pdf")
+    ///     .with_backend(&backend)
+    ///     .execute()
+    ///     .unwrap();
+    /// assert_eq!(items.len(), 1);
+    /// # }
+    /// ```
+    ///
+    /// # Parameters
+    /// * `backend` - The backend to execute the query on
+    ///
+    /// # Returns
+    /// A [`BackendQuery`] whose `execute` runs the query on `backend`
+    pub fn with_backend<B: QueryBackend>(self, backend: B) -> BackendQuery<B> {
+        BackendQuery {
+            builder: self,
+            backend,
+        }
+    }
+
+    /// Checks the builder and returns the query string, scopes and max count to run.
+    pub(crate) fn into_parts(mut self) -> Result<(String, Vec<MDQueryScope>, Option<usize>)> {
+        if self.match_all
+            && self.scopes.as_ref().is_none_or(Vec::is_empty)
+            && self.max_count.is_none()
+        {
+            self.errors.push(BuilderError::new(
+                "match_all",
+                "a search scope or max count is required",
+            ));
+        }
+        if self.top_k.is_some() && self.max_count.is_some() {
+            self.errors.push(BuilderError::new(
+                "top_k",
+                "max_count truncates the results before they are sorted, use only top_k",
+            ));
+        }
+        let mut scopes = self
+            .scopes
+            .take()
+            .unwrap_or_else(|| vec![MDQueryScope::Computer]);
+        if self.local_volumes_only {
+            scopes.retain(|scope| {
+                !matches!(scope, MDQueryScope::Network | MDQueryScope::NetworkIndexed)
+            });
+            if scopes.is_empty() {
+                self.errors.push(BuilderError::new(
+                    "local_volumes_only",
+                    "all search scopes are network scopes",
+                ));
+            }
+        }
+        if !self.errors.is_empty() {
+            return Err(MDQueryError::InvalidBuilder(self.errors).into());
+        }
+        if self.condition.is_empty() {
+            anyhow::bail!("No expressions to build");
+        }
+        let query = self.to_query_string()?;
+        Ok((query, scopes, self.max_count))
+    }
+
+    /// Like [`into_parts`](Self::into_parts), for backends bound with `with_backend`, which
+    /// only return items and therefore cannot select the top results by an attribute.
+    pub(crate) fn into_backend_parts(
+        mut self,
+    ) -> Result<(String, Vec<MDQueryScope>, Option<usize>)> {
+        if self.top_k.is_some() {
+            self.errors.push(BuilderError::new(
+                "top_k",
+                "not supported on custom backends",
+            ));
+        }
+        self.into_parts()
+    }
+
+    /// Returns the query string the builder will pass to Spotlight, without building a query.
+    ///
+    /// # Returns
+    /// The combined query expression, identical to what [`into_query`](Self::into_query) uses.
+    ///
+    /// # Errors
+    /// Returns [`MDQueryError::EmptyConditionGroup`] if the builder or a condition added to it
+    /// has no expressions.
+    pub fn to_query_string(&self) -> Result<String> {
+        self.condition.to_expression()
+    }
+
+    /// Returns the top-level expressions added to the builder so far, in call order.
+    ///
+    /// Calls that match several alternatives, such as `keywords_any`, appear as a single
+    /// nested condition.
+    pub fn expressions(&self) -> &[MDQueryConditionExpression] {
+        &self.condition.expressions
+    }
+
+    /// Returns the number of top-level expressions added to the builder.
+    pub fn len(&self) -> usize {
+        self.condition.expressions.len()
+    }
+
+    /// Returns true if no expressions have been added to the builder.
+    pub fn is_empty(&self) -> bool {
+        self.condition.is_empty()
+    }
+
+    /// Builds the query and executes it synchronously.
+    ///
+    /// # Returns
+    /// A Result containing the matching items, or an error if building or execution fails.
+    pub fn execute(self) -> Result<Vec<MDItem>> {
+        self.into_query()?.execute()
+    }
+
+    /// Builds the query and returns its first result, see [`MDQuery::execute_first`].
+    ///
+    /// # Returns
+    /// A Result containing the first matching item, or None if nothing matches.
+    pub fn execute_first(mut self) -> Result<Option<MDItem>> {
+        match &mut self.top_k {
+            Some((k, _, _)) => *k = (*k).min(1),
+            None => self.max_count = Some(self.max_count.map_or(1, |max_count| max_count.min(1))),
+        }
+        Ok(self.into_query()?.execute()?.into_iter().next())
+    }
+
+    /// Builds the query and returns its only result, see [`MDQuery::execute_one`].
+    ///
+    /// # Returns
+    /// A Result containing the only matching item.
+    ///
+    /// # Errors
+    /// Returns [`MDQueryError::NotFound`] if nothing matches, [`MDQueryError::Ambiguous`]
+    /// if several items match, or an error if building or execution fails.
+    pub fn execute_one(self) -> Result<MDItem> {
+        self.into_query()?.execute_one()
+    }
+
+    /// Builds the query and executes it asynchronously.
+    ///
+    /// # Returns
+    /// A Result containing the matching items, or an error if building or execution fails.
+    #[cfg(feature = "async")]
+    pub async fn execute_async(self) -> Result<Vec<MDItem>> {
+        self.into_query()?.execute_async().await
+    }
+
+    /// Sets the search scopes used by [`into_query`](Self::into_query) and [`execute`](Self::execute).
+    ///
+    /// # Parameters
+    /// * `scopes` - List of search scopes to apply (e.g., Home, Computer)
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn with_scopes(mut self, scopes: impl IntoIterator<Item = MDQueryScope>) -> Self {
+        let scopes = scopes.into_iter().collect::<Vec<_>>();
+        self.record("with_scopes", vec![scopes.save()]);
+        self.scopes = Some(scopes);
+        self
+    }
+
+    /// Sets the maximum number of results returned by the query.
+    ///
+    /// # Parameters
+    /// * `max_count` - Maximum number of results to return
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn with_max_count(mut self, max_count: usize) -> Self {
+        self.record("with_max_count", vec![max_count.save()]);
+        self.max_count = Some(max_count);
+        self
+    }
+
+    /// Keeps only the first `k` results in the order of an attribute.
+    ///
+    /// Spotlight applies a max count while gathering, before any ordering, so combining
+    /// [`with_max_count`](Self::with_max_count) with a sort would return an arbitrary
+    /// subset sorted. Instead, the query gathers all matches with `key` as a sorting
+    /// attribute, which makes Spotlight cache its value with every result, and selects the
+    /// first `k` with a heap of `k` entries. Only the selected results are turned into
+    /// items. Results without the attribute sort last in either direction.
+    ///
+    /// ```no_run
+    /// use mdquery_rs::{MDItemKey, MDQueryBuilder, MDQueryScope, SortDirection};
+    ///
+    /// let recent = MDQueryBuilder::default()
+    ///     .extension("pdf")
+    ///     .with_scopes([MDQueryScope::Home])
+    ///     .top_k(10, MDItemKey::ModificationDate, SortDirection::Descending)
+    ///     .execute()?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
+    /// # Parameters
+    /// * `k` - The number of results to keep
+    /// * `key` - The attribute to sort the results by
+    /// * `direction` - Whether the smallest or the largest values come first
+    ///
+    /// # Returns
+    /// Self for method chaining. Building fails with [`MDQueryError::InvalidBuilder`] if
+    /// `k` is 0 or a max count is set as well.
+    pub fn top_k(mut self, k: usize, key: MDItemKey, direction: SortDirection) -> Self {
+        self.record("top_k", vec![k.save(), key.save(), direction.save()]);
+        if k == 0 {
+            self.errors
+                .push(BuilderError::new("top_k", "k must be greater than 0"));
+        }
+        self.top_k = Some((k, key, direction));
+        self
+    }
+
+    /// Sets the option bits passed to Spotlight along with the search scopes.
+    ///
+    /// Defaults to [`ScopeOptions::NONE`].
+    ///
+    /// # Parameters
+    /// * `options` - The scope options to apply
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn with_scope_options(mut self, options: ScopeOptions) -> Self {
+        self.record("with_scope_options", vec![options.save()]);
+        self.scope_options = options;
+        self
+    }
+
+    /// Creates a new builder from a condition.
+    ///
+    /// # Parameters
+    /// * `condition` - The condition to add to the builder
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn from_condition(condition: MDQueryCondition) -> Self {
+        let args = vec![condition.save()];
+        let mut builder = Self {
+            condition,
+            ..Default::default()
+        };
+        builder.record("from_condition", args);
+        builder
+    }
+
+    /// Adds a condition tree as a single expression, combined with the others using AND.
+    ///
+    /// Together with `From<MDQueryBuilder> for MDQueryConditionExpression` this lets builders
+    /// be used as reusable fragments: build each alternative with the builder methods, combine
+    /// them with [`MDQueryCondition::any`] or [`MDQueryCondition::all`], and add the result
+    /// back to a builder that sets scopes and limits.
+    ///
+    /// ```
+    /// use mdquery_rs::{FileSize, MDQueryBuilder, MDQueryCompareOp, MDQueryCondition};
+    ///
+    /// let large_reports = MDQueryBuilder::default()
+    ///     .name_like("report")
+    ///     .size(MDQueryCompareOp::GreaterThan, FileSize::mb(1));
+    /// let pdfs = MDQueryBuilder::default().extension("pdf");
+    ///
+    /// let builder = MDQueryBuilder::default()
+    ///     .condition(MDQueryCondition::any([large_reports, pdfs]))
+    ///     .with_max_count(10);
+    /// assert_eq!(
+    ///     builder.to_query_string().unwrap(),
+    ///     "((((kMDItemDisplayName == \"*report*\"w) && (kMDItemFSSize > 1000000)) || \
+    ///      ((kMDItemFSName == \"*.pdf\"c))))"
+    /// );
+    /// ```
+    ///
+    /// # Parameters
+    /// * `condition` - The condition to add
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn condition(mut self, condition: MDQueryCondition) -> Self {
+        self.record("condition", vec![condition.save()]);
+        self.condition.add(condition.into());
+        self
+    }
+
+    /// Combines the expressions of this builder and another builder with AND or OR.
+    ///
+    /// Each builder's expressions are kept together as one group, so
+    /// `a.merge(b, MDQueryConditionType::Any)` matches what `a` or `b` match on their own.
+    /// The OR group becomes a single expression of the merged builder: expressions added
+    /// afterwards must match as well, and further merges with `Any` add alternatives to the
+    /// group. A builder without expressions is ignored. Scopes, the maximum count and scope
+    /// options are taken from this builder, or from `other` where this builder has none.
+    /// [`local_volumes_only`](Self::local_volumes_only) applies if either builder set it,
+    /// and argument errors of both builders are kept and reported by `build`. Merging with
+    /// AND also reports [`name_is`](Self::name_is) and
+    /// [`content_type`](Self::content_type) calls of the two builders that conflict.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mdquery_rs::{MDQueryBuilder, MDQueryConditionType};
+    ///
+    /// let pdfs = MDQueryBuilder::default().extension("pdf");
+    /// let reports = MDQueryBuilder::default().name_like("report");
+    /// assert_eq!(
+    ///     pdfs.merge(reports, MDQueryConditionType::Any)
+    ///         .to_query_string()
+    ///         .unwrap(),
+    ///     "((((kMDItemFSName == \"*.pdf\"c)) || ((kMDItemDisplayName == \"*report*\"w))))"
+    /// );
+    /// ```
+    ///
+    /// # Parameters
+    /// * `other` - The builder to combine with this one
+    /// * `condition_type` - Whether both groups must match (All) or either (Any)
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn merge(mut self, other: MDQueryBuilder, condition_type: MDQueryConditionType) -> Self {
+        self.record("merge", vec![other.save(), condition_type.save()]);
+        if self.condition.is_empty() {
+            self.condition = other.condition;
+            self.exact_name = other.exact_name;
+            self.exact_content_type = other.exact_content_type;
+        } else if !other.condition.is_empty() {
+            if condition_type == MDQueryConditionType::All {
+                if let Some((name, modifiers)) = other.exact_name {
+                    self.require_name("merge", &name, modifiers);
+                }
+                if let Some(content_type) = other.exact_content_type {
+                    self.require_content_type("merge", &content_type);
+                }
+            } else {
+                self.exact_name = None;
+                self.exact_content_type = None;
+            }
+            match condition_type {
+                MDQueryConditionType::All => self.condition.add(other.condition.into()),
+                MDQueryConditionType::Any => match self.condition.expressions.as_mut_slice() {
+                    [MDQueryConditionExpression::Condition(group)]
+                        if group.condition_type == MDQueryConditionType::Any =>
+                    {
+                        group.add(other.condition.into())
+                    }
+                    _ => {
+                        let alternatives = vec![self.condition.into(), other.condition.into()];
+                        self.condition = MDQueryCondition::all([MDQueryCondition {
+                            condition_type,
+                            expressions: alternatives,
+                            operations: None,
+                        }]);
+                    }
+                },
+            }
+        }
+        self.scopes = self.scopes.or(other.scopes);
+        self.max_count = self.max_count.or(other.max_count);
+        if self.scope_options == ScopeOptions::default() {
+            self.scope_options = other.scope_options;
+        }
+        self.local_volumes_only |= other.local_volumes_only;
+        self.match_all |= other.match_all;
+        self.top_k = self.top_k.or(other.top_k);
+        self.errors.extend(other.errors);
+        self
+    }
+
+    /// Creates a new builder from a raw query string.
+    ///
+    /// # Parameters
+    /// * `query` - The raw query string
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn from_raw(query: &str) -> Self {
+        let mut builder = Self::default();
+        builder.record("from_raw", vec![query.save()]);
+        builder
+            .condition
+            .add(MDQueryConditionExpression::Expression(query.to_string()));
+        builder
+    }
+
+    /// Saves the builder as JSON, for example to let users keep their searches.
+    ///
+    /// The JSON holds the builder method calls and their arguments in call order, rather
+    /// than the expressions they produced, so [`from_json`](Self::from_json) rebuilds the
+    /// builder by calling the same methods. A restored builder therefore applies the
+    /// escaping and checks of the version that restores it. Raw expressions, given to
+    /// [`from_raw`](Self::from_raw) or to conditions as strings, are kept as written, and
+    /// paths that are not valid UTF-8 are saved lossily.
+    ///
+    /// ```
+    /// use mdquery_rs::{MDQueryBuilder, MDQueryScope};
+    ///
+    /// let builder = MDQueryBuilder::default()
+    ///     .name_like("report")
+    ///     .with_scopes([MDQueryScope::Documents]);
+    /// let json = builder.to_json();
+    /// assert_eq!(
+    ///     json,
+    ///     r#"{"version":1,"operations":[{"method":"name_like","args":["report"]},{"method":"with_scopes","args":[["Documents"]]}]}"#
+    /// );
+    /// assert_eq!(MDQueryBuilder::from_json(&json).unwrap(), builder);
+    /// ```
+    ///
+    /// # Returns
+    /// A JSON document that [`from_json`](Self::from_json) accepts
+    pub fn to_json(&self) -> String {
+        saved::to_json(&self.operations)
+    }
+
+    /// Restores a builder saved by [`to_json`](Self::to_json).
+    ///
+    /// # Parameters
+    /// * `json` - The JSON document returned by `to_json`
+    ///
+    /// # Returns
+    /// A builder equal to the saved one, made by the same method calls
+    ///
+    /// # Errors
+    /// Returns [`MDQueryError::InvalidSavedQuery`] if `json` is not valid JSON, has an
+    /// unsupported version, or names an unknown method or invalid arguments.
+    pub fn from_json(json: &str) -> Result<Self> {
+        saved::from_json(json).map_err(|message| MDQueryError::InvalidSavedQuery(message).into())
+    }
+
+    /// Adds an expression matching every item, for queries such as "everything in this
+    /// folder, sorted by date".
+    ///
+    /// Building without any expressions stays an error, so a catch-all query has to be
+    /// asked for explicitly. Such a query makes Spotlight enumerate and return every
+    /// indexed item in the search scopes, which is slow and memory-hungry for large scopes,
+    /// so prefer a narrow custom scope or a max count.
+    ///
+    /// ```
+    /// use mdquery_rs::{MDQueryBuilder, MDQueryScope};
+    ///
+    /// let builder = MDQueryBuilder::default()
+    ///     .match_all()
+    ///     .with_scopes([MDQueryScope::from_path("~/Downloads")]);
+    /// assert_eq!(
+    ///     builder.to_query_string().unwrap(),
+    ///     "((kMDItemFSName == \"*\"))"
+    /// );
+    /// ```
+    ///
+    /// # Returns
+    /// Self for method chaining
+    ///
+    /// # Errors
+    /// Building fails with [`MDQueryError::InvalidBuilder`] unless at least one search scope
+    /// or a max count is set, via [`with_scopes`](Self::with_scopes), [`with_max_count`](Self::with_max_count)
+    /// or the arguments of [`build`](Self::build).
+    pub fn match_all(mut self) -> Self {
+        self.record("match_all", Vec::new());
+        self.match_all = true;
+        self.condition
+            .add(MDQueryConditionExpression::Expression(format!(
+                "{} == \"*\"",
+                MDItemKey::FSName
+            )));
+        self
+    }
+
+    /// Adds an expression to match items whose display name contains the specified string.
+    ///
+    /// This performs a case-insensitive substring search and supports Chinese Pinyin.
+    ///
+    /// # Parameters
+    /// * `name` - The substring to match in display names
+    ///
+    /// # Returns
+    /// Self for method chaining
+    ///
+    /// # Errors
+    /// An empty `name` makes `build` fail with [`MDQueryError::InvalidBuilder`].
+    pub fn name_like(self, name: &str) -> Self {
+        self.record_calls("name_like", vec![name.save()], |builder| {
+            builder.name_like_with(name, MatchModifiers::WORD_BASED)
+        })
+    }
+
+    /// Adds an expression to match items whose display name contains the specified string,
+    /// using explicit match modifiers.
+    ///
+    /// Wildcards in `name` are matched literally.
+    ///
+    /// # Parameters
+    /// * `name` - The substring to match in display names
+    /// * `modifiers` - The string comparison modifiers to apply
+    ///
+    /// # Returns
+    /// Self for method chaining
+    ///
+    /// # Errors
+    /// An empty `name` makes `build` fail with [`MDQueryError::InvalidBuilder`].
+    pub fn name_like_with(mut self, name: &str, modifiers: MatchModifiers) -> Self {
+        self.record("name_like_with", vec![name.save(), modifiers.save()]);
+        if name.is_empty() {
+            self.errors
+                .push(BuilderError::new("name_like", "name is empty"));
+            return self;
+        }
+        let pattern = format!("*{}*", escape_query_value(name));
+        self.string_match(MDItemKey::DisplayName, &pattern, modifiers)
+    }
+
+    /// Adds an expression to match items whose display name exactly matches the specified string.
+    ///
+    /// This performs a case-insensitive exact match.
+    ///
+    /// # Parameters
+    /// * `name` - The exact name to match
+    ///
+    /// # Returns
+    /// Self for method chaining
+    ///
+    /// # Errors
+    /// An empty `name`, or a name that can never equal the name of an earlier `name_is` call,
+    /// makes `build` fail with [`MDQueryError::InvalidBuilder`].
+    pub fn name_is(self, name: &str) -> Self {
+        self.record_calls("name_is", vec![name.save()], |builder| {
+            builder.name_is_with(name, MatchModifiers::CASE_INSENSITIVE)
+        })
+    }
+
+    /// Adds an expression to match items whose display name exactly matches the specified
+    /// string, using explicit match modifiers.
+    ///
+    /// Pass [`MatchModifiers::NONE`] for a case-sensitive exact match.
+    ///
+    /// # Parameters
+    /// * `name` - The exact name to match
+    /// * `modifiers` - The string comparison modifiers to apply
+    ///
+    /// # Returns
+    /// Self for method chaining
+    ///
+    /// # Errors
+    /// The same checks as for [`name_is`](Self::name_is) apply.
+    pub fn name_is_with(mut self, name: &str, modifiers: MatchModifiers) -> Self {
+        self.record("name_is_with", vec![name.save(), modifiers.save()]);
+        if name.is_empty() {
+            self.errors
+                .push(BuilderError::new("name_is", "name is empty"));
+            return self;
+        }
+        self.require_name("name_is", name, modifiers);
+        let pattern = escape_query_value(name);
+        self.string_match(MDItemKey::DisplayName, &pattern, modifiers)
+    }
+
+    /// Adds an expression to match items whose display name starts with the specified string.
+    ///
+    /// This performs a case- and diacritic-insensitive, word-based prefix match, so "saf"
+    /// matches both "Safari" and "Apple Safari". Wildcards in `prefix` are matched literally.
+    ///
+    /// # Parameters
+    /// * `prefix` - The prefix to match in display names
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn name_starts_with(mut self, prefix: &str) -> Self {
+        self.record("name_starts_with", vec![prefix.save()]);
+        let pattern = format!("{}*", escape_query_value(prefix));
+        self.string_match(
+            MDItemKey::DisplayName,
+            &pattern,
+            MatchModifiers::CASE_INSENSITIVE
+                | MatchModifiers::DIACRITIC_INSENSITIVE
+                | MatchModifiers::WORD_BASED,
+        )
+    }
+
+    /// Adds an expression to match items whose display name ends with the specified string.
+    ///
+    /// This performs a case- and diacritic-insensitive suffix match. Wildcards in `suffix`
+    /// are matched literally.
+    ///
+    /// # Parameters
+    /// * `suffix` - The suffix to match in display names
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn name_ends_with(mut self, suffix: &str) -> Self {
+        self.record("name_ends_with", vec![suffix.save()]);
+        let pattern = format!("*{}", escape_query_value(suffix));
+        self.string_match(
+            MDItemKey::DisplayName,
+            &pattern,
+            MatchModifiers::CASE_DIACRITIC_INSENSITIVE,
+        )
+    }
+
+    /// Adds an expression to match items whose file name starts with the specified string.
+    ///
+    /// This performs a case- and diacritic-insensitive prefix match on `kMDItemFSName`.
+    /// Wildcards in `prefix` are matched literally.
+    ///
+    /// # Parameters
+    /// * `prefix` - The prefix to match in file names
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn fs_name_starts_with(mut self, prefix: &str) -> Self {
+        self.record("fs_name_starts_with", vec![prefix.save()]);
+        let pattern = format!("{}*", escape_query_value(prefix));
+        self.string_match(
+            MDItemKey::FSName,
+            &pattern,
+            MatchModifiers::CASE_DIACRITIC_INSENSITIVE,
+        )
+    }
+
+    /// Adds an expression to match items whose file name ends with the specified string.
+    ///
+    /// This performs a case- and diacritic-insensitive suffix match on `kMDItemFSName`.
+    /// Wildcards in `suffix` are matched literally.
+    ///
+    /// # Parameters
+    /// * `suffix` - The suffix to match in file names
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn fs_name_ends_with(mut self, suffix: &str) -> Self {
+        self.record("fs_name_ends_with", vec![suffix.save()]);
+        let pattern = format!("*{}", escape_query_value(suffix));
+        self.string_match(
+            MDItemKey::FSName,
+            &pattern,
+            MatchModifiers::CASE_DIACRITIC_INSENSITIVE,
+        )
+    }
+
+    /// Adds an expression to match items located anywhere below the specified directory.
+    ///
+    /// This compares against `kMDItemPath`, so it can narrow a query to a directory that
+    /// is not itself a search scope. The directory itself is not matched.
+    ///
+    /// # Parameters
+    /// * `dir` - The directory whose descendants should match
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn path_starts_with<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.record("path_starts_with", vec![dir.as_ref().save()]);
+        self.condition
+            .add(MDQueryConditionExpression::Expression(format!(
+                "{} == \"{}\"",
+                MDItemKey::Path,
+                path_prefix_pattern(dir.as_ref())
+            )));
+        self
+    }
+
+    /// Adds an expression to exclude items located anywhere below the specified directory.
+    ///
+    /// Multiple exclusions are combined with AND, so an item must lie outside all of them.
+    /// The directory itself is not excluded.
+    ///
+    /// # Parameters
+    /// * `dir` - The directory whose descendants should be excluded
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn exclude_path_prefix<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.record("exclude_path_prefix", vec![dir.as_ref().save()]);
+        self.condition
+            .add(MDQueryConditionExpression::Expression(format!(
+                "{} != \"{}\"",
+                MDItemKey::Path,
+                path_prefix_pattern(dir.as_ref())
+            )));
+        self
+    }
+
+    /// Excludes the locations whose items end users rarely want to see in computer-wide
+    /// results: the Trash, Time Machine snapshots and backups, and caches.
+    ///
+    /// This is [`exclude_trash`](Self::exclude_trash),
+    /// [`exclude_time_machine`](Self::exclude_time_machine) and
+    /// [`exclude_caches`](Self::exclude_caches) combined, and adds one `kMDItemPath`
+    /// expression per excluded directory. It composes with the other filters and can be
+    /// applied to any builder. `~/Library` as a whole stays included, see
+    /// [`exclude_library`](Self::exclude_library).
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn exclude_system_noise(self) -> Self {
+        self.record_calls("exclude_system_noise", Vec::new(), |builder| {
+            builder
+                .exclude_trash()
+                .exclude_time_machine()
+                .exclude_caches()
+        })
+    }
+
+    /// Excludes items in the current user's Trash, usually `~/.Trash`.
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn exclude_trash(self) -> Self {
+        self.record_calls("exclude_trash", Vec::new(), |builder| {
+            builder.exclude_path_prefix(user_directory(UserDirectory::Trash))
+        })
+    }
+
+    /// Excludes items in local Time Machine snapshots and mounted Time Machine backups.
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn exclude_time_machine(self) -> Self {
+        self.record_calls("exclude_time_machine", Vec::new(), |builder| {
+            builder
+                .exclude_path_prefix("/Volumes/com.apple.TimeMachine.localsnapshots")
+                .exclude_path_prefix("/Volumes/.timemachine")
+                .exclude_path_prefix("/.MobileBackups")
+        })
+    }
+
+    /// Excludes items in the current user's and the system's caches, usually
+    /// `~/Library/Caches`, and `/Library/Caches`.
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn exclude_caches(self) -> Self {
+        self.record_calls("exclude_caches", Vec::new(), |builder| {
+            builder
+                .exclude_path_prefix(user_directory(UserDirectory::Caches))
+                .exclude_path_prefix("/Library/Caches")
+        })
+    }
+
+    /// Excludes items in the current user's Library folder, usually `~/Library`, which holds
+    /// application support files, preferences and caches.
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn exclude_library(self) -> Self {
+        self.record_calls("exclude_library", Vec::new(), |builder| {
+            builder.exclude_path_prefix(user_directory(UserDirectory::Library))
+        })
+    }
+
+    /// Restricts the query to the startup volume, leaving out external and network volumes.
+    ///
+    /// This excludes items below `/Volumes`, where macOS mounts other volumes, and drops
+    /// [`MDQueryScope::Network`] and [`MDQueryScope::NetworkIndexed`] from the search
+    /// scopes, whether they are set before or after this call.
+    ///
+    /// # Returns
+    /// Self for method chaining
+    ///
+    /// # Errors
+    /// `build` fails with [`MDQueryError::InvalidBuilder`] if only network scopes are set.
+    pub fn local_volumes_only(self) -> Self {
+        self.record_calls("local_volumes_only", Vec::new(), |mut builder| {
+            builder.local_volumes_only = true;
+            builder.exclude_path_prefix("/Volumes")
+        })
+    }
+
+    /// Restricts the query to items on the volume mounted at `mount_point`.
+    ///
+    /// `on_volume("/")` matches the startup volume, like
+    /// [`local_volumes_only`](Self::local_volumes_only) without dropping network scopes;
+    /// other mount points match the items below them, see
+    /// [`path_starts_with`](Self::path_starts_with).
+    ///
+    /// # Parameters
+    /// * `mount_point` - The mount point of the volume, such as `/Volumes/Backup`
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn on_volume<P: AsRef<Path>>(self, mount_point: P) -> Self {
+        let mount_point = mount_point.as_ref();
+        self.record_calls("on_volume", vec![mount_point.save()], |builder| {
+            if mount_point == Path::new("/") {
+                builder.exclude_path_prefix("/Volumes")
+            } else {
+                builder.path_starts_with(mount_point)
+            }
+        })
+    }
+
+    /// Records a call of the public method `method` for [`to_json`](Self::to_json).
+    fn record(&mut self, method: &'static str, args: Vec<JsonValue>) {
+        self.operations.push(Operation { method, args });
+    }
+
+    /// Applies `calls`, which calls other public methods, and records it as the single call
+    /// `method` instead of them.
+    fn record_calls(
+        self,
+        method: &'static str,
+        args: Vec<JsonValue>,
+        calls: impl FnOnce(Self) -> Self,
+    ) -> Self {
+        let recorded = self.operations.len();
+        let mut builder = calls(self);
+        builder.operations.truncate(recorded);
+        builder.record(method, args);
+        builder
+    }
+
+    /// Returns the recorded public method calls, in call order.
+    pub(crate) fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    /// Appends the expressions combined with OR, or a plain expression if there is only one.
+    fn any_of(mut self, expressions: Vec<String>) -> Self {
+        let mut expressions = expressions
+            .into_iter()
+            .map(MDQueryConditionExpression::Expression)
+            .collect::<Vec<_>>();
+        if expressions.len() == 1 {
+            self.condition.add(expressions.remove(0));
+        } else {
+            self.condition
+                .add(MDQueryCondition::any(expressions).into());
+        }
+        self
+    }
+
+    /// Appends a `key == "pattern"modifiers` expression.
+    ///
+    /// `pattern` must already be escaped with [`escape_query_value`].
+    fn string_match(mut self, key: MDItemKey, pattern: &str, modifiers: MatchModifiers) -> Self {
+        self.condition
+            .add(MDQueryConditionExpression::Expression(format!(
+                "{} == \"{}\"{}",
+                key,
+                pattern,
+                modifiers.to_query_string()
+            )));
+        self
+    }
+
+    /// Appends a numeric comparison with a float, reporting an error for `method` if the
+    /// value is not finite. Floats are formatted by `Display`, which always uses `.` as the
+    /// decimal separator and never an exponent.
+    fn float(
+        mut self,
+        method: &'static str,
+        key: MDItemKey,
+        op: MDQueryCompareOp,
+        value: f64,
+    ) -> Self {
+        if !value.is_finite() {
+            self.errors.push(BuilderError::new(
+                method,
+                format!("{} is not a finite number", value),
+            ));
+            return self;
+        }
+        self.numeric(key, op, value)
+    }
+
+    /// Records the exact name required by `name_is`, reporting an error if no name can
+    /// equal both it and the name required by an earlier call.
+    ///
+    /// Names are only compared while the top-level expressions are combined with AND, and
+    /// diacritic-insensitive or word-based matches are never treated as conflicting.
+    fn require_name(&mut self, method: &'static str, name: &str, modifiers: MatchModifiers) {
+        if self.condition.condition_type != MDQueryConditionType::All {
+            return;
+        }
+        let Some((earlier, earlier_modifiers)) = &self.exact_name else {
+            self.exact_name = Some((name.to_string(), modifiers));
+            return;
+        };
+        let comparable =
+            |modifiers: &MatchModifiers| !modifiers.diacritic_insensitive && !modifiers.word_based;
+        let conflicts = if !comparable(&modifiers) || !comparable(earlier_modifiers) {
+            false
+        } else if modifiers.case_insensitive || earlier_modifiers.case_insensitive {
+            name.to_lowercase() != earlier.to_lowercase()
+        } else {
+            name != earlier
+        };
+        if conflicts {
+            let message = format!(
+                "name {:?} conflicts with the earlier exact name {:?}",
+                name, earlier
+            );
+            self.errors.push(BuilderError::new(method, message));
+        }
+    }
+
+    /// Records the content type required by `content_type`, reporting an error if it
+    /// differs from the content type required by an earlier call.
+    fn require_content_type(&mut self, method: &'static str, content_type: &str) {
+        if self.condition.condition_type != MDQueryConditionType::All {
+            return;
+        }
+        match &self.exact_content_type {
+            Some(earlier) if earlier != content_type => {
+                let message = format!(
+                    "content type {:?} conflicts with the earlier content type {:?}",
+                    content_type, earlier
+                );
+                self.errors.push(BuilderError::new(method, message));
+            }
+            Some(_) => {}
+            None => self.exact_content_type = Some(content_type.to_string()),
+        }
+    }
+
+    /// Appends an unquoted `key op value` numeric comparison.
+    fn numeric(
+        mut self,
+        key: MDItemKey,
+        op: MDQueryCompareOp,
+        value: impl std::fmt::Display,
+    ) -> Self {
+        self.condition
+            .add(MDQueryConditionExpression::Expression(format!(
+                "{} {} {}",
+                key,
+                op.into_query_string(),
+                value
+            )));
+        self
+    }
+
+    /// Adds an expression comparing any attribute with a value, checking that the value
+    /// type fits the attribute.
+    ///
+    /// Strings are quoted and escaped, so wildcards match literally, numbers and booleans
+    /// are written unquoted, and dates are written as `$time.iso(...)`. Array attributes
+    /// match if any element matches.
+    ///
+    /// ```
+    /// use mdquery_rs::{MDItemKey, MDQueryBuilder, MDQueryCompareOp};
+    ///
+    /// let builder = MDQueryBuilder::default()
+    ///     .compare(MDItemKey::Title, MDQueryCompareOp::Equal, "Q3 \"final\"")
+    ///     .compare(MDItemKey::NumberOfPages, MDQueryCompareOp::GreaterThan, 10);
+    /// assert_eq!(
+    ///     builder.to_query_string().unwrap(),
+    ///     r#"((kMDItemTitle == "Q3 \"final\"") && (kMDItemNumberOfPages > 10))"#
+    /// );
+    /// ```
+    ///
+    /// # Parameters
+    /// * `key` - The attribute to compare
+    /// * `op` - The comparison operator to use
+    /// * `value` - The value to compare against
+    ///
+    /// # Returns
+    /// Self for method chaining
+    ///
+    /// # Errors
+    /// A value whose type does not match [`MDItemKey::kind`], an ordering operator with a
+    /// string or boolean value, or a non-finite float makes `build` fail with
+    /// [`MDQueryError::InvalidBuilder`].
+    pub fn compare(
+        mut self,
+        key: MDItemKey,
+        op: MDQueryCompareOp,
+        value: impl Into<MDQueryValue>,
+    ) -> Self {
+        let value = value.into();
+        self.record("compare", vec![key.save(), op.save(), value.save()]);
+        let fits = match value {
+            MDQueryValue::Str(_) => {
+                matches!(key.kind(), MDItemKeyKind::Str | MDItemKeyKind::StrArray)
+            }
+            MDQueryValue::Int(_) | MDQueryValue::Float(_) => key.kind() == MDItemKeyKind::Number,
+            MDQueryValue::Bool(_) => key.kind() == MDItemKeyKind::Bool,
+            MDQueryValue::Date(_) => key.is_time(),
+        };
+        if !fits {
+            self.errors.push(BuilderError::new(
+                "compare",
+                format!(
+                    "{} value cannot be compared with {}, which holds {:?} values",
+                    value.type_name(),
+                    key,
+                    key.kind()
+                ),
+            ));
+            return self;
+        }
+        let ordering = !matches!(op, MDQueryCompareOp::Equal);
+        if ordering && matches!(value, MDQueryValue::Str(_) | MDQueryValue::Bool(_)) {
+            self.errors.push(BuilderError::new(
+                "compare",
+                format!(
+                    "{} values only support Equal, not {}",
+                    value.type_name(),
+                    op.into_query_string()
+                ),
+            ));
+            return self;
+        }
+        let value = match value {
+            MDQueryValue::Str(value) => format!("\"{}\"", escape_query_value(&value)),
+            MDQueryValue::Int(value) => value.to_string(),
+            MDQueryValue::Float(value) if value.is_finite() => value.to_string(),
+            MDQueryValue::Float(value) => {
+                self.errors.push(BuilderError::new(
+                    "compare",
+                    format!("{} is not a finite number", value),
+                ));
+                return self;
+            }
+            MDQueryValue::Bool(value) => u8::from(value).to_string(),
+            MDQueryValue::Date(value) => format!("$time.iso({})", format_iso(&value)),
+        };
+        self.condition
+            .add(MDQueryConditionExpression::Expression(format!(
+                "{} {} {}",
+                key,
+                op.into_query_string(),
+                value
+            )));
+        self
+    }
+
+    /// Adds a time-based comparison expression.
+    ///
+    /// # Parameters
+    /// * `key` - The time-related metadata key to compare
+    /// * `op` - The comparison operator to use
+    /// * `timestamp` - Unix timestamp to compare against
+    ///
+    /// # Returns
+    /// Self for method chaining
+    ///
+    /// # Errors
+    /// A non-time key or an out-of-range timestamp makes `build` fail with
+    /// [`MDQueryError::InvalidBuilder`].
+    pub fn time(self, key: MDItemKey, op: MDQueryCompareOp, timestamp: i64) -> Self {
+        self.record_calls(
+            "time",
+            vec![key.save(), op.save(), timestamp.save()],
+            |mut builder| {
+                if !key.is_time() {
+                    builder.errors.push(BuilderError::new(
+                        "time",
+                        format!("{} is not a time attribute", key),
+                    ));
+                    return builder;
+                }
+
+                let Some(time) = DateTime::from_timestamp(timestamp, 0) else {
+                    builder.errors.push(BuilderError::new(
+                        "time",
+                        format!("timestamp {} is out of range", timestamp),
+                    ));
+                    return builder;
+                };
+                builder.time_dt(key, op, time)
+            },
+        )
+    }
+
+    /// Adds a time-based comparison expression against a `chrono` date-time.
+    ///
+    /// Any time zone is accepted and normalized to UTC; fractional seconds are preserved.
+    ///
+    /// # Parameters
+    /// * `key` - The time-related metadata key to compare
+    /// * `op` - The comparison operator to use
+    /// * `time` - The date-time to compare against
+    ///
+    /// # Returns
+    /// Self for method chaining
+    ///
+    /// # Errors
+    /// A non-time key makes `build` fail with [`MDQueryError::InvalidBuilder`].
+    pub fn time_dt(
+        mut self,
+        key: MDItemKey,
+        op: MDQueryCompareOp,
+        time: impl Into<DateTime<Utc>>,
+    ) -> Self {
+        let time = time.into();
+        self.record("time_dt", vec![key.save(), op.save(), time.save()]);
+        if !key.is_time() {
+            self.errors.push(BuilderError::new(
+                "time_dt",
+                format!("{} is not a time attribute", key),
+            ));
+            return self;
+        }
+
+        self.condition
+            .add(MDQueryConditionExpression::Expression(format!(
+                "{} {} $time.iso({})",
+                key,
+                op.into_query_string(),
+                format_iso(&time)
+            )));
+        self
+    }
+
+    /// Adds a comparison against a time evaluated by Spotlight when the query runs.
+    ///
+    /// Unlike [`time`](Self::time), relative times use the `$time.*` query variables, so
+    /// they are resolved in the system time zone at execution time.
+    ///
+    /// # Parameters
+    /// * `key` - The time-related metadata key to compare
+    /// * `op` - The comparison operator to use
+    /// * `time` - The relative time to compare against
+    ///
+    /// # Returns
+    /// Self for method chaining
+    ///
+    /// # Errors
+    /// A non-time key makes `build` fail with [`MDQueryError::InvalidBuilder`].
+    pub fn time_relative(
+        mut self,
+        key: MDItemKey,
+        op: MDQueryCompareOp,
+        time: RelativeTime,
+    ) -> Self {
+        self.record("time_relative", vec![key.save(), op.save(), time.save()]);
+        if !key.is_time() {
+            self.errors.push(BuilderError::new(
+                "time_relative",
+                format!("{} is not a time attribute", key),
+            ));
+            return self;
+        }
+
+        self.condition
+            .add(MDQueryConditionExpression::Expression(format!(
+                "{} {} {}",
+                key,
+                op.into_query_string(),
+                time.to_query_string()
+            )));
+        self
+    }
+
+    /// Adds an expression matching items modified since the start of the day `days` days ago.
+    ///
+    /// `modified_within_days(0)` matches items modified today.
+    ///
+    /// # Parameters
+    /// * `days` - The number of days to look back
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn modified_within_days(self, days: u32) -> Self {
+        self.record_calls("modified_within_days", vec![days.save()], |builder| {
+            builder.time_relative(
+                MDItemKey::ModificationDate,
+                MDQueryCompareOp::GreaterThanOrEqual,
+                RelativeTime::Today(-(days as i64)),
+            )
+        })
+    }
+
+    /// Adds an expression matching items created today.
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn created_today(self) -> Self {
+        self.record_calls("created_today", Vec::new(), |builder| {
+            builder.time_relative(
+                MDItemKey::CreationDate,
+                MDQueryCompareOp::GreaterThanOrEqual,
+                RelativeTime::Today(0),
+            )
+        })
+    }
+
+    /// Adds an expression matching items added to their folder after the specified time.
+    ///
+    /// The date added changes when an item is moved or copied into a folder, unlike its
+    /// creation and modification dates.
+    ///
+    /// # Parameters
+    /// * `time` - The date-time the item must have been added after
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn added_after(self, time: impl Into<DateTime<Utc>>) -> Self {
+        let time = time.into();
+        self.record_calls("added_after", vec![time.save()], |builder| {
+            builder.time_dt(MDItemKey::DateAdded, MDQueryCompareOp::GreaterThan, time)
+        })
+    }
+
+    /// Adds an expression matching items added to their folder since the start of the day
+    /// `days` days ago.
+    ///
+    /// `added_within_days(0)` matches items added today.
+    ///
+    /// # Parameters
+    /// * `days` - The number of days to look back
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn added_within_days(self, days: u32) -> Self {
+        self.record_calls("added_within_days", vec![days.save()], |builder| {
+            builder.time_relative(
+                MDItemKey::DateAdded,
+                MDQueryCompareOp::GreaterThanOrEqual,
+                RelativeTime::Today(-(days as i64)),
+            )
+        })
+    }
+
+    /// Adds an expression matching items last used within the given duration before now.
+    ///
+    /// # Parameters
+    /// * `duration` - How far back to look, with second precision
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn used_within(self, duration: Duration) -> Self {
+        self.record_calls("used_within", vec![duration.save()], |mut builder| {
+            let Ok(secs) = i64::try_from(duration.as_secs()) else {
+                builder.errors.push(BuilderError::new(
+                    "used_within",
+                    format!("duration {:?} is too large", duration),
+                ));
+                return builder;
+            };
+            builder.time_relative(
+                MDItemKey::LastUsedDate,
+                MDQueryCompareOp::GreaterThanOrEqual,
+                RelativeTime::SecondsFromNow(-secs),
+            )
+        })
+    }
+
+    /// Adds an expression matching items whose time attribute lies within a range.
+    ///
+    /// This emits Spotlight's `InRange` function, which includes both bounds.
+    ///
+    /// # Parameters
+    /// * `key` - The time-related metadata key to compare
+    /// * `start` - Unix timestamp of the start of the range
+    /// * `end` - Unix timestamp of the end of the range
+    ///
+    /// # Returns
+    /// Self for method chaining
+    ///
+    /// # Errors
+    /// A non-time key, an out-of-range timestamp, or `start > end` makes `build` fail
+    /// with [`MDQueryError::InvalidBuilder`].
+    pub fn time_in_range(mut self, key: MDItemKey, start: i64, end: i64) -> Self {
+        self.record("time_in_range", vec![key.save(), start.save(), end.save()]);
+        if !key.is_time() {
+            self.errors.push(BuilderError::new(
+                "time_in_range",
+                format!("{} is not a time attribute", key),
+            ));
+            return self;
+        }
+        if start > end {
+            self.errors.push(BuilderError::new(
+                "time_in_range",
+                format!("start {} is after end {}", start, end),
+            ));
+            return self;
+        }
+        let (Some(start_str), Some(end_str)) = (iso_time(start), iso_time(end)) else {
+            self.errors.push(BuilderError::new(
+                "time_in_range",
+                format!("range {}..={} is out of range", start, end),
+            ));
+            return self;
+        };
+
+        self.condition
+            .add(MDQueryConditionExpression::Expression(format!(
+                "InRange({}, $time.iso({}), $time.iso({}))",
+                key, start_str, end_str
+            )));
+        self
+    }
+
+    /// Adds a file size comparison expression.
+    ///
+    /// # Parameters
+    /// * `op` - The comparison operator to use
+    /// * `size` - The file size to compare against, in bytes or as a [`FileSize`]
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn size(self, op: MDQueryCompareOp, size: impl Into<FileSize>) -> Self {
+        let size = size.into();
+        self.record_calls("size", vec![op.save(), size.save()], |builder| {
+            builder.size_of(MDItemKey::Size, op, size)
+        })
+    }
+
+    /// Adds a size comparison expression on a specific size attribute.
+    ///
+    /// `kMDItemFSSize`, `kMDItemLogicalSize` and `kMDItemPhysicalSize` differ for sparse
+    /// files and for cloud files that are not downloaded.
+    ///
+    /// # Parameters
+    /// * `key` - The size-related metadata key to compare
+    /// * `op` - The comparison operator to use
+    /// * `size` - The size to compare against, in bytes or as a [`FileSize`]
+    ///
+    /// # Returns
+    /// Self for method chaining
+    ///
+    /// # Errors
+    /// A non-size key makes `build` fail with [`MDQueryError::InvalidBuilder`].
+    pub fn size_of(
+        mut self,
+        key: MDItemKey,
+        op: MDQueryCompareOp,
+        size: impl Into<FileSize>,
+    ) -> Self {
+        let size = size.into();
+        self.record("size_of", vec![key.save(), op.save(), size.save()]);
+        if !key.is_size() {
+            self.errors.push(BuilderError::new(
+                "size_of",
+                format!("{} is not a size attribute", key),
+            ));
+            return self;
+        }
+
+        self.numeric(key, op, size)
+    }
+
+    /// Adds an expression matching items whose file size lies within a range.
+    ///
+    /// This emits Spotlight's `InRange` function, which includes both bounds.
+    ///
+    /// # Parameters
+    /// * `min` - The minimum file size, in bytes or as a [`FileSize`]
+    /// * `max` - The maximum file size, in bytes or as a [`FileSize`]
+    ///
+    /// # Returns
+    /// Self for method chaining
+    ///
+    /// # Errors
+    /// `min > max` makes `build` fail with [`MDQueryError::InvalidBuilder`].
+    pub fn size_in_range(mut self, min: impl Into<FileSize>, max: impl Into<FileSize>) -> Self {
+        let (min, max) = (min.into(), max.into());
+        self.record("size_in_range", vec![min.save(), max.save()]);
+        if min > max {
+            self.errors.push(BuilderError::new(
+                "size_in_range",
+                format!("min {} is greater than max {}", min, max),
+            ));
+            return self;
+        }
+        self.condition
+            .add(MDQueryConditionExpression::Expression(format!(
+                "InRange({}, {}, {})",
+                MDItemKey::Size,
+                min,
+                max
+            )));
+        self
+    }
+
+    /// Adds an expression matching items whose file size is between `min` and `max`, inclusive.
+    ///
+    /// Equivalent to [`size_in_range`](Self::size_in_range).
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn size_between(self, min: impl Into<FileSize>, max: impl Into<FileSize>) -> Self {
+        let (min, max) = (min.into(), max.into());
+        self.record_calls("size_between", vec![min.save(), max.save()], |builder| {
+            builder.size_in_range(min, max)
+        })
+    }
+
+    /// Adds an expression to filter items based on whether they are directories.
+    ///
+    /// # Parameters
+    /// * `value` - If true, matches only directories; if false, matches only non-directories
+    ///
+    /// # Returns
+    /// Self for method chaining
+    ///
+    /// # Note
+    /// Packages such as app bundles are directories presented as files and have their own
+    /// content type, so they are not matched. Use [`is_dir_by_tree`](Self::is_dir_by_tree)
+    /// to match folders by content type tree, including packages, and
+    /// [`is_package`](Self::is_package) to tell packages and plain folders apart.
+    pub fn is_dir(mut self, value: bool) -> Self {
+        self.record("is_dir", vec![value.save()]);
+        self.condition
+            .add(MDQueryConditionExpression::Expression(format!(
+                "{} {} \"{}\"",
+                MDItemKey::ContentType,
+                if value { "==" } else { "!=" },
+                "public.folder"
+            )));
+        self
+    }
+
+    /// Adds an expression to filter items based on whether their content type tree contains
+    /// `public.folder`.
+    ///
+    /// This classifies items the same way as [`MDItem::is_dir`], unlike [`is_dir`](Self::is_dir)
+    /// which compares the exact content type.
+    ///
+    /// # Parameters
+    /// * `value` - If true, matches only directories; if false, matches only non-directories
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn is_dir_by_tree(mut self, value: bool) -> Self {
+        self.record("is_dir_by_tree", vec![value.save()]);
+        self.condition
+            .add(MDQueryConditionExpression::Expression(format!(
+                "{} {} \"{}\"",
+                MDItemKey::ContentTypeTree,
+                if value { "==" } else { "!=" },
+                "public.folder"
+            )));
+        self
+    }
+
+    /// Adds an expression to filter items based on whether they are application bundles.
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn is_app(self) -> Self {
+        self.record_calls("is_app", Vec::new(), |builder| {
+            builder.content_type("com.apple.application-bundle")
+        })
+    }
+
+    /// Adds an expression to filter items based on whether they are packages: directories
+    /// presented as files, such as app bundles, frameworks, photo libraries and document
+    /// packages.
+    ///
+    /// This classifies items the same way as [`MDItem::is_package`], by whether the
+    /// content type tree contains `com.apple.package` or `com.apple.bundle`.
+    ///
+    /// # Parameters
+    /// * `value` - If true, matches only packages; if false, matches only non-packages
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn is_package(mut self, value: bool) -> Self {
+        self.record("is_package", vec![value.save()]);
+        if value {
+            return self.any_of(
+                PACKAGE_CONTENT_TYPES
+                    .iter()
+                    .map(|uti| format!("{} == \"{}\"", MDItemKey::ContentTypeTree, uti))
+                    .collect(),
+            );
+        }
+        for uti in PACKAGE_CONTENT_TYPES {
+            self.condition
+                .add(MDQueryConditionExpression::Expression(format!(
+                    "{} != \"{}\"",
+                    MDItemKey::ContentTypeTree,
+                    uti
+                )));
+        }
+        self
+    }
+
+    /// Adds an expression to filter items based on whether they are frameworks, whose
+    /// content type tree contains `com.apple.framework`.
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn is_framework(self) -> Self {
+        self.record_calls("is_framework", Vec::new(), |builder| {
+            builder.content_type_conforms_to("com.apple.framework")
+        })
+    }
+
+    /// Adds an expression excluding Finder aliases, whose content type tree contains
+    /// `com.apple.alias-file`.
+    ///
+    /// This classifies items the same way as [`MDItem::is_alias`].
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn exclude_aliases(mut self) -> Self {
+        self.record("exclude_aliases", Vec::new());
+        self.condition
+            .add(MDQueryConditionExpression::Expression(format!(
+                "{} != \"com.apple.alias-file\"",
+                MDItemKey::ContentTypeTree
+            )));
+        self
+    }
+
+    /// Adds an expression matching only symbolic links, whose content type tree contains
+    /// `public.symlink`.
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn only_symlinks(self) -> Self {
+        self.record_calls("only_symlinks", Vec::new(), |builder| {
+            builder.content_type_conforms_to("public.symlink")
+        })
+    }
+
+    /// Controls whether hidden (invisible) items are included in the results.
+    ///
+    /// `include_hidden(false)` adds an expression excluding items with `kMDItemFSInvisible`
+    /// set; `include_hidden(true)` leaves the query unchanged. Items without the attribute
+    /// are treated as visible.
+    ///
+    /// # Parameters
+    /// * `value` - If false, hidden items are excluded
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn include_hidden(mut self, value: bool) -> Self {
+        self.record("include_hidden", vec![value.save()]);
+        if !value {
+            self.condition
+                .add(MDQueryConditionExpression::Expression(format!(
+                    "{} != 1",
+                    MDItemKey::FSInvisible
+                )));
+        }
+        self
+    }
+
+    /// Adds an expression to match documents whose title contains the specified text.
+    ///
+    /// The match is case- and diacritic-insensitive.
+    ///
+    /// # Parameters
+    /// * `text` - The text to search for in the title
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn title_contains(mut self, text: &str) -> Self {
+        self.record("title_contains", vec![text.save()]);
+        let pattern = format!("*{}*", escape_query_value(text));
+        self.string_match(
+            MDItemKey::Title,
+            &pattern,
+            MatchModifiers::CASE_DIACRITIC_INSENSITIVE,
+        )
+    }
+
+    /// Adds an expression to match documents written in the specified language.
+    ///
+    /// `kMDItemLanguages` is an array, so this matches items where any language equals
+    /// `language`, compared case-insensitively.
+    ///
+    /// # Parameters
+    /// * `language` - The language code to match (e.g. "en")
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn language(mut self, language: &str) -> Self {
+        self.record("language", vec![language.save()]);
+        let pattern = escape_query_value(language);
+        self.string_match(
+            MDItemKey::Languages,
+            &pattern,
+            MatchModifiers::CASE_INSENSITIVE,
+        )
+    }
+
+    /// Adds an expression to match password-protected documents.
+    ///
+    /// Spotlight sets `kMDItemSecurityMethod` to "Password Encrypted" for encrypted PDFs
+    /// and other protected documents.
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn is_password_protected(mut self) -> Self {
+        self.record("is_password_protected", Vec::new());
+        self.string_match(
+            MDItemKey::SecurityMethod,
+            "Password Encrypted",
+            MatchModifiers::NONE,
+        )
+    }
+
+    /// Adds an expression to match screenshots taken with the system screenshot tool.
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn is_screenshot(mut self) -> Self {
+        self.record("is_screenshot", Vec::new());
+        self.condition
+            .add(MDQueryConditionExpression::Expression(format!(
+                "{} == 1",
+                MDItemKey::IsScreenCapture
+            )));
+        self
+    }
+
+    /// Adds an expression to match screenshots of the specified type.
+    ///
+    /// The type is compared case-insensitively; macOS uses values such as "window",
+    /// "selection" and "display".
+    ///
+    /// # Parameters
+    /// * `capture_type` - The screenshot type to match
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn screenshot_type(mut self, capture_type: &str) -> Self {
+        self.record("screenshot_type", vec![capture_type.save()]);
+        let pattern = escape_query_value(capture_type);
+        self.string_match(
+            MDItemKey::ScreenCaptureType,
+            &pattern,
+            MatchModifiers::CASE_INSENSITIVE,
+        )
+    }
+
+    /// Adds an expression to match items whose Finder kind equals the specified string.
+    ///
+    /// The kind is localized to the user's language (e.g. "Application" or "Programm"), so
+    /// this is best suited to values shown to or entered by the user. Use
+    /// [`of_kind`](Self::of_kind) or [`content_type`](Self::content_type) for
+    /// locale-independent filtering.
+    ///
+    /// # Parameters
+    /// * `kind` - The kind to match, compared case-insensitively
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn kind(mut self, kind: &str) -> Self {
+        self.record("kind", vec![kind.save()]);
+        let pattern = escape_query_value(kind);
+        self.string_match(MDItemKey::Kind, &pattern, MatchModifiers::CASE_INSENSITIVE)
+    }
+
+    /// Adds an expression to match items belonging to a content kind.
+    ///
+    /// The kind expands to a `kMDItemContentTypeTree` comparison, OR-ed together when the
+    /// kind covers several UTIs, followed by a `!=` comparison for every excluded UTI.
+    ///
+    /// # Parameters
+    /// * `kind` - The content kind to match
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn of_kind(self, kind: ContentKind) -> Self {
+        self.record_calls("of_kind", vec![kind.save()], |builder| {
+            let expressions = kind
+                .content_types()
+                .iter()
+                .map(|uti| format!("{} == \"{}\"", MDItemKey::ContentTypeTree, uti))
+                .collect();
+            let excluded = kind.excluded_content_types();
+            if excluded.is_empty() {
+                return builder.any_of(expressions);
+            }
+            // The content match and the exclusions form one group, so they stay combined with
+            // AND under a root condition that combines its expressions with OR.
+            let mut group = MDQueryBuilder::default().any_of(expressions).condition;
+            for uti in excluded {
+                group.add(MDQueryConditionExpression::Expression(format!(
+                    "{} != \"{}\"",
+                    MDItemKey::ContentTypeTree,
+                    uti
+                )));
+            }
+            builder.condition(group)
+        })
+    }
+
+    /// Adds an expression to match bundles with the specified bundle identifier.
+    ///
+    /// The identifier is compared case-insensitively.
+    ///
+    /// # Parameters
+    /// * `bundle_id` - The bundle identifier to match (e.g. "com.apple.Safari")
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn bundle_identifier(mut self, bundle_id: &str) -> Self {
+        self.record("bundle_identifier", vec![bundle_id.save()]);
+        let pattern = escape_query_value(bundle_id);
+        self.string_match(
+            MDItemKey::BundleIdentifier,
+            &pattern,
+            MatchModifiers::CASE_INSENSITIVE,
+        )
+    }
+
+    /// Adds an expression to match applications in an App Store category.
+    ///
+    /// A category identifier such as `public.app-category.productivity` is compared with
+    /// `kMDItemAppStoreCategoryType`; anything else is compared case-insensitively with the
+    /// localized category name in `kMDItemAppStoreCategory`, such as "Productivity".
+    ///
+    /// # Parameters
+    /// * `category` - The category name or identifier to match
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn app_category(mut self, category: &str) -> Self {
+        self.record("app_category", vec![category.save()]);
+        let pattern = escape_query_value(category);
+        if category.starts_with("public.app-category.") {
+            self.string_match(
+                MDItemKey::AppStoreCategoryType,
+                &pattern,
+                MatchModifiers::NONE,
+            )
+        } else {
+            self.string_match(
+                MDItemKey::AppStoreCategory,
+                &pattern,
+                MatchModifiers::CASE_INSENSITIVE,
+            )
+        }
+    }
+
+    /// Adds an expression to filter applications based on whether they declare an App
+    /// Store category. Apps not distributed through the App Store often declare none.
+    ///
+    /// # Parameters
+    /// * `value` - If true, matches only items with a category; if false, only items
+    ///   without one
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn has_app_category(mut self, value: bool) -> Self {
+        self.record("has_app_category", vec![value.save()]);
+        self.condition
+            .add(MDQueryConditionExpression::Expression(format!(
+                "{} {} \"*\"",
+                MDItemKey::AppStoreCategory,
+                if value { "==" } else { "!=" }
+            )));
+        self
+    }
+
+    /// Adds an expression to match applications and executables containing code for the
+    /// CPU architecture `arch`, one of the values of `kMDItemExecutableArchitectures`.
+    ///
+    /// # Parameters
+    /// * `arch` - The architecture to match (e.g. "arm64" or "x86_64")
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn has_architecture(mut self, arch: &str) -> Self {
+        self.record("has_architecture", vec![arch.save()]);
+        let pattern = escape_query_value(arch);
+        self.string_match(
+            MDItemKey::ExecutableArchitectures,
+            &pattern,
+            MatchModifiers::NONE,
+        )
+    }
+
+    /// Adds an expression to match applications and executables lacking code for the CPU
+    /// architecture `arch`, such as Intel-only apps with `missing_architecture("arm64")`.
+    ///
+    /// `kMDItemExecutableArchitectures` is an array, and `!=` on it holds when no element
+    /// equals the value. It also holds for items without the attribute, so an expression
+    /// requiring the attribute to be set is added as well, keeping documents and folders
+    /// out of the results.
+    ///
+    /// # Parameters
+    /// * `arch` - The architecture that must be missing (e.g. "arm64")
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn missing_architecture(mut self, arch: &str) -> Self {
+        self.record("missing_architecture", vec![arch.save()]);
+        self = self.string_match(
+            MDItemKey::ExecutableArchitectures,
+            "*",
+            MatchModifiers::NONE,
+        );
+        self.condition
+            .add(MDQueryConditionExpression::Expression(format!(
+                "{} != \"{}\"",
+                MDItemKey::ExecutableArchitectures,
+                escape_query_value(arch)
+            )));
+        self
+    }
+
+    /// Adds an expression to match items whose version string is exactly `version`.
+    ///
+    /// For applications this is the short version string (e.g. "17.4.1"). Combine it with
+    /// [`is_app`](Self::is_app) and [`bundle_identifier`](Self::bundle_identifier) to find a
+    /// specific release of an app.
+    ///
+    /// # Parameters
+    /// * `version` - The version string to match
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn version_is(mut self, version: &str) -> Self {
+        self.record("version_is", vec![version.save()]);
+        let pattern = escape_query_value(version);
+        self.string_match(MDItemKey::Version, &pattern, MatchModifiers::NONE)
+    }
+
+    /// Adds an expression to match items with the specified author.
+    ///
+    /// `kMDItemAuthors` is an array; Spotlight's equality on array attributes matches if
+    /// any element is equal, so this matches items where any author equals `author`,
+    /// compared case-insensitively.
+    ///
+    /// # Parameters
+    /// * `author` - The author name to match
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn author(mut self, author: &str) -> Self {
+        self.record("author", vec![author.save()]);
+        let pattern = escape_query_value(author);
+        self.string_match(
+            MDItemKey::Authors,
+            &pattern,
+            MatchModifiers::CASE_INSENSITIVE,
+        )
+    }
+
+    /// Adds an expression to match items whose content was created by the specified application.
+    ///
+    /// This performs a case-insensitive substring match on `kMDItemCreator`, which often
+    /// includes a version (e.g. "Microsoft Word 16.0").
+    ///
+    /// # Parameters
+    /// * `app` - The substring to match in the creating application's name
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn created_by_app(mut self, app: &str) -> Self {
+        self.record("created_by_app", vec![app.save()]);
+        let pattern = format!("*{}*", escape_query_value(app));
+        self.string_match(
+            MDItemKey::Creator,
+            &pattern,
+            MatchModifiers::CASE_INSENSITIVE,
+        )
+    }
+
+    /// Adds an expression comparing the pixel width of images and videos.
+    ///
+    /// # Parameters
+    /// * `op` - The comparison operator to use
+    /// * `width` - The width in pixels to compare against
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn pixel_width(mut self, op: MDQueryCompareOp, width: u32) -> Self {
+        self.record("pixel_width", vec![op.save(), width.save()]);
+        self.numeric(MDItemKey::PixelWidth, op, width)
+    }
+
+    /// Adds an expression comparing the pixel height of images and videos.
+    ///
+    /// # Parameters
+    /// * `op` - The comparison operator to use
+    /// * `height` - The height in pixels to compare against
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn pixel_height(mut self, op: MDQueryCompareOp, height: u32) -> Self {
+        self.record("pixel_height", vec![op.save(), height.save()]);
+        self.numeric(MDItemKey::PixelHeight, op, height)
+    }
+
+    /// Adds an expression comparing the number of bits per color channel of images, such
+    /// as `bits_per_sample(MDQueryCompareOp::GreaterThan, 8)` for 16-bit images.
+    ///
+    /// # Parameters
+    /// * `op` - The comparison operator to use
+    /// * `bits` - The bits per sample to compare against
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn bits_per_sample(mut self, op: MDQueryCompareOp, bits: u32) -> Self {
+        self.record("bits_per_sample", vec![op.save(), bits.save()]);
+        self.numeric(MDItemKey::BitsPerSample, op, bits)
+    }
+
+    /// Adds an expression to filter images based on whether they have an alpha channel.
+    ///
+    /// Both `true` and `false` only match items that record the attribute, so
+    /// `has_alpha_channel(false)` finds opaque images rather than every other file.
+    ///
+    /// # Parameters
+    /// * `value` - If true, matches only images with an alpha channel; if false, only
+    ///   images without one
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn has_alpha_channel(mut self, value: bool) -> Self {
+        self.record("has_alpha_channel", vec![value.save()]);
+        self.condition
+            .add(MDQueryConditionExpression::Expression(format!(
+                "{} == {}",
+                MDItemKey::HasAlphaChannel,
+                u8::from(value)
+            )));
+        self
+    }
+
+    /// Adds an expression to match images in a color space, such as "CMYK".
+    ///
+    /// The name is compared case-insensitively.
+    ///
+    /// # Parameters
+    /// * `color_space` - The color space to match (e.g. "RGB", "Gray" or "CMYK")
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn color_space(mut self, color_space: &str) -> Self {
+        self.record("color_space", vec![color_space.save()]);
+        let pattern = escape_query_value(color_space);
+        self.string_match(
+            MDItemKey::ColorSpace,
+            &pattern,
+            MatchModifiers::CASE_INSENSITIVE,
+        )
+    }
+
+    /// Adds expressions matching images and videos at least `width` × `height` pixels large.
+    ///
+    /// # Parameters
+    /// * `width` - The minimum width in pixels
+    /// * `height` - The minimum height in pixels
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn min_resolution(self, width: u32, height: u32) -> Self {
+        self.record_calls(
+            "min_resolution",
+            vec![width.save(), height.save()],
+            |builder| {
+                builder
+                    .pixel_width(MDQueryCompareOp::GreaterThanOrEqual, width)
+                    .pixel_height(MDQueryCompareOp::GreaterThanOrEqual, height)
+            },
+        )
+    }
+
+    /// Adds expressions matching videos at least `width` × `height` pixels large.
+    ///
+    /// This is [`min_resolution`](Self::min_resolution) restricted to
+    /// [`ContentKind::Movie`], so images of that size are left out.
+    ///
+    /// # Parameters
+    /// * `width` - The minimum width in pixels
+    /// * `height` - The minimum height in pixels
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn min_video_resolution(self, width: u32, height: u32) -> Self {
+        self.record_calls(
+            "min_video_resolution",
+            vec![width.save(), height.save()],
+            |builder| {
+                builder
+                    .of_kind(ContentKind::Movie)
+                    .min_resolution(width, height)
+            },
+        )
+    }
+
+    /// Adds an expression comparing the playback duration of audio and video items.
+    ///
+    /// The duration is compared in fractional seconds, so sub-second precision is kept.
+    ///
+    /// # Parameters
+    /// * `op` - The comparison operator to use
+    /// * `duration` - The duration to compare against
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn duration(mut self, op: MDQueryCompareOp, duration: Duration) -> Self {
+        self.record("duration", vec![op.save(), duration.save()]);
+        self.numeric(MDItemKey::DurationSeconds, op, duration.as_secs_f64())
+    }
+
+    /// Adds an expression to match items owned by the specified user.
+    ///
+    /// # Parameters
+    /// * `uid` - The owner's user ID
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn owned_by_uid(mut self, uid: u32) -> Self {
+        self.record("owned_by_uid", vec![uid.save()]);
+        self.numeric(MDItemKey::FSOwnerUserID, MDQueryCompareOp::Equal, uid)
+    }
+
+    /// Adds an expression comparing the number of items directly contained in folders.
+    ///
+    /// Only folders have a node count, so this also restricts results to folders.
+    ///
+    /// # Parameters
+    /// * `op` - The comparison operator to use
+    /// * `count` - The item count to compare against
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn folder_item_count(mut self, op: MDQueryCompareOp, count: u64) -> Self {
+        self.record("folder_item_count", vec![op.save(), count.save()]);
+        self.numeric(MDItemKey::FSNodeCount, op, count)
+    }
+
+    /// Adds an expression to match items with the specified Finder label color.
+    ///
+    /// `FinderLabel::None` matches items without a label.
+    ///
+    /// # Parameters
+    /// * `label` - The label color to match
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn with_label(mut self, label: FinderLabel) -> Self {
+        self.record("with_label", vec![label.save()]);
+        self.numeric(MDItemKey::FSLabel, MDQueryCompareOp::Equal, label.value())
+    }
+
+    /// Adds an expression comparing the number of times items have been opened.
+    ///
+    /// # Parameters
+    /// * `op` - The comparison operator to use
+    /// * `count` - The use count to compare against
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn use_count(mut self, op: MDQueryCompareOp, count: u64) -> Self {
+        self.record("use_count", vec![op.save(), count.save()]);
+        self.numeric(MDItemKey::UseCount, op, count)
+    }
+
+    /// Adds an expression comparing the number of pages in documents such as PDFs.
+    ///
+    /// Items without a page count never match.
+    ///
+    /// # Parameters
+    /// * `op` - The comparison operator to use
+    /// * `pages` - The page count to compare against
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn page_count(mut self, op: MDQueryCompareOp, pages: u32) -> Self {
+        self.record("page_count", vec![op.save(), pages.save()]);
+        self.numeric(MDItemKey::NumberOfPages, op, pages)
+    }
+
+    /// Adds an expression to match documents using the font `font`, or font files
+    /// containing it when combined with [`of_kind`](Self::of_kind) and
+    /// [`ContentKind::Font`].
+    ///
+    /// The name is compared case-insensitively with every element of `kMDItemFonts`.
+    /// Documents usually record PostScript names such as "HelveticaNeue-Bold", so each
+    /// style of a family has its own name.
+    ///
+    /// # Parameters
+    /// * `font` - The font name to match
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn uses_font(mut self, font: &str) -> Self {
+        self.record("uses_font", vec![font.save()]);
+        let pattern = escape_query_value(font);
+        self.string_match(MDItemKey::Fonts, &pattern, MatchModifiers::CASE_INSENSITIVE)
+    }
+
+    /// Adds an expression comparing the audio sample rate of audio and video items.
+    ///
+    /// # Parameters
+    /// * `op` - The comparison operator to use
+    /// * `hertz` - The sample rate in hertz to compare against
+    ///
+    /// # Returns
+    /// Self for method chaining
+    ///
+    /// # Errors
+    /// A non-finite sample rate makes `build` fail with [`MDQueryError::InvalidBuilder`].
+    pub fn audio_sample_rate(mut self, op: MDQueryCompareOp, hertz: f64) -> Self {
+        self.record("audio_sample_rate", vec![op.save(), hertz.save()]);
+        self.float("audio_sample_rate", MDItemKey::AudioSampleRate, op, hertz)
+    }
+
+    /// Adds an expression comparing the number of audio channels.
+    ///
+    /// # Parameters
+    /// * `op` - The comparison operator to use
+    /// * `channels` - The channel count to compare against
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn audio_channel_count(mut self, op: MDQueryCompareOp, channels: u32) -> Self {
+        self.record("audio_channel_count", vec![op.save(), channels.save()]);
+        self.numeric(MDItemKey::AudioChannelCount, op, channels)
+    }
+
+    /// Adds an expression comparing the combined bit rate of all media streams.
+    ///
+    /// # Parameters
+    /// * `op` - The comparison operator to use
+    /// * `kbps` - The bit rate in kilobits per second to compare against
+    ///
+    /// # Returns
+    /// Self for method chaining
+    ///
+    /// # Errors
+    /// A non-finite bit rate makes `build` fail with [`MDQueryError::InvalidBuilder`].
+    pub fn total_bit_rate(mut self, op: MDQueryCompareOp, kbps: f64) -> Self {
+        self.record("total_bit_rate", vec![op.save(), kbps.save()]);
+        self.float("total_bit_rate", MDItemKey::TotalBitRate, op, kbps)
+    }
+
+    /// Adds an expression comparing the bit rate of the video stream.
+    ///
+    /// # Parameters
+    /// * `op` - The comparison operator to use
+    /// * `kbps` - The bit rate in kilobits per second to compare against
+    ///
+    /// # Returns
+    /// Self for method chaining
+    ///
+    /// # Errors
+    /// A non-finite bit rate makes `build` fail with [`MDQueryError::InvalidBuilder`].
+    pub fn video_bit_rate(mut self, op: MDQueryCompareOp, kbps: f64) -> Self {
+        self.record("video_bit_rate", vec![op.save(), kbps.save()]);
+        self.float("video_bit_rate", MDItemKey::VideoBitRate, op, kbps)
+    }
+
+    /// Adds an expression to match media items encoded with the specified codec.
+    ///
+    /// `kMDItemCodecs` is an array, so this matches items where any codec equals `codec`,
+    /// compared case-insensitively.
+    ///
+    /// # Parameters
+    /// * `codec` - The codec name to match (e.g. "HEVC" or "AAC")
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn codec(mut self, codec: &str) -> Self {
+        self.record("codec", vec![codec.save()]);
+        let pattern = escape_query_value(codec);
+        self.string_match(
+            MDItemKey::Codecs,
+            &pattern,
+            MatchModifiers::CASE_INSENSITIVE,
+        )
+    }
+
+    /// Adds an expression to match music tracks from the specified album.
+    ///
+    /// The match is case-insensitive.
+    ///
+    /// # Parameters
+    /// * `album` - The album title to match
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn album(mut self, album: &str) -> Self {
+        self.record("album", vec![album.save()]);
+        let pattern = escape_query_value(album);
+        self.string_match(MDItemKey::Album, &pattern, MatchModifiers::CASE_INSENSITIVE)
+    }
+
+    /// Adds an expression to match music tracks by the specified artist.
+    ///
+    /// Spotlight stores track artists in the `kMDItemAuthors` array, so this matches items
+    /// where any artist equals `artist`, compared case-insensitively. It is equivalent to
+    /// [`author`](Self::author).
+    ///
+    /// # Parameters
+    /// * `artist` - The artist name to match
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn artist(self, artist: &str) -> Self {
+        self.record_calls("artist", vec![artist.save()], |builder| {
+            builder.author(artist)
+        })
+    }
+
+    /// Adds an expression to match music tracks of the specified genre.
+    ///
+    /// The match is case-insensitive.
+    ///
+    /// # Parameters
+    /// * `genre` - The genre to match
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn genre(mut self, genre: &str) -> Self {
+        self.record("genre", vec![genre.save()]);
+        let pattern = escape_query_value(genre);
+        self.string_match(
+            MDItemKey::MusicalGenre,
+            &pattern,
+            MatchModifiers::CASE_INSENSITIVE,
+        )
+    }
+
+    /// Adds an expression comparing the recording year of music tracks.
+    ///
+    /// # Parameters
+    /// * `op` - The comparison operator to use
+    /// * `year` - The year to compare against
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn recording_year(mut self, op: MDQueryCompareOp, year: u16) -> Self {
+        self.record("recording_year", vec![op.save(), year.save()]);
+        self.numeric(MDItemKey::RecordingYear, op, year)
+    }
+
+    /// Adds an expression to match items captured with a camera from the specified manufacturer.
+    ///
+    /// The match is case-insensitive. Combine it with
+    /// [`of_kind`](Self::of_kind)`(ContentKind::Image)` to restrict results to photos:
+    ///
+    /// ```no_run
+    /// use mdquery_rs::{ContentKind, MDQueryBuilder};
+    ///
+    /// let photos = MDQueryBuilder::default()
+    ///     .of_kind(ContentKind::Image)
+    ///     .camera_make("Fujifilm")
+    ///     .camera_model("X-T4")
+    ///     .execute()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// # Parameters
+    /// * `make` - The camera manufacturer to match
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn camera_make(mut self, make: &str) -> Self {
+        self.record("camera_make", vec![make.save()]);
+        let pattern = escape_query_value(make);
+        self.string_match(
+            MDItemKey::AcquisitionMake,
+            &pattern,
+            MatchModifiers::CASE_INSENSITIVE,
+        )
+    }
+
+    /// Adds an expression to match items captured with the specified camera model.
+    ///
+    /// The match is case-insensitive. See [`camera_make`](Self::camera_make) for an example.
+    ///
+    /// # Parameters
+    /// * `model` - The camera model to match
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn camera_model(mut self, model: &str) -> Self {
+        self.record("camera_model", vec![model.save()]);
+        let pattern = escape_query_value(model);
+        self.string_match(
+            MDItemKey::AcquisitionModel,
+            &pattern,
+            MatchModifiers::CASE_INSENSITIVE,
+        )
+    }
+
+    /// Adds an expression comparing the ISO speed photos were captured with.
+    ///
+    /// # Parameters
+    /// * `op` - The comparison operator to use
+    /// * `iso` - The ISO speed to compare against
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn iso(mut self, op: MDQueryCompareOp, iso: u32) -> Self {
+        self.record("iso", vec![op.save(), iso.save()]);
+        self.numeric(MDItemKey::ISOSpeed, op, iso)
+    }
+
+    /// Adds an expression comparing the focal length photos were captured with.
+    ///
+    /// # Parameters
+    /// * `op` - The comparison operator to use
+    /// * `millimeters` - The focal length in millimeters to compare against
+    ///
+    /// # Returns
+    /// Self for method chaining
+    ///
+    /// # Errors
+    /// A non-finite focal length makes `build` fail with [`MDQueryError::InvalidBuilder`].
+    pub fn focal_length(mut self, op: MDQueryCompareOp, millimeters: f64) -> Self {
+        self.record("focal_length", vec![op.save(), millimeters.save()]);
+        self.float("focal_length", MDItemKey::FocalLength, op, millimeters)
+    }
+
+    /// Adds an expression comparing the exposure time photos were captured with, such as
+    /// `exposure_time(MDQueryCompareOp::GreaterThan, 1.0 / 30.0)` for slow shutter speeds.
+    ///
+    /// # Parameters
+    /// * `op` - The comparison operator to use
+    /// * `seconds` - The exposure time in seconds to compare against
+    ///
+    /// # Returns
+    /// Self for method chaining
+    ///
+    /// # Errors
+    /// A non-finite exposure time makes `build` fail with [`MDQueryError::InvalidBuilder`].
+    pub fn exposure_time(mut self, op: MDQueryCompareOp, seconds: f64) -> Self {
+        self.record("exposure_time", vec![op.save(), seconds.save()]);
+        self.float("exposure_time", MDItemKey::ExposureTimeSeconds, op, seconds)
+    }
+
+    /// Adds an expression comparing the f-number (aperture) photos were captured with.
+    ///
+    /// # Parameters
+    /// * `op` - The comparison operator to use
+    /// * `f_number` - The f-number to compare against (e.g. 2.8)
+    ///
+    /// # Returns
+    /// Self for method chaining
+    ///
+    /// # Errors
+    /// A non-finite f-number makes `build` fail with [`MDQueryError::InvalidBuilder`].
+    pub fn f_number(mut self, op: MDQueryCompareOp, f_number: f64) -> Self {
+        self.record("f_number", vec![op.save(), f_number.save()]);
+        self.float("f_number", MDItemKey::FNumber, op, f_number)
+    }
+
+    /// Adds an expression to match items downloaded from a URL containing `domain`.
+    ///
+    /// This performs a case-insensitive wildcard match against any of the URLs in
+    /// `kMDItemWhereFroms`.
+    ///
+    /// # Parameters
+    /// * `domain` - The domain (or any other URL fragment) to match
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn downloaded_from_domain(mut self, domain: &str) -> Self {
+        self.record("downloaded_from_domain", vec![domain.save()]);
+        let pattern = format!("*{}*", escape_query_value(domain));
+        self.string_match(
+            MDItemKey::WhereFroms,
+            &pattern,
+            MatchModifiers::CASE_INSENSITIVE,
+        )
+    }
+
+    /// Adds an expression to match items downloaded after the specified time.
+    ///
+    /// # Parameters
+    /// * `time` - The date-time the download must be later than
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn downloaded_after(self, time: impl Into<DateTime<Utc>>) -> Self {
+        let time = time.into();
+        self.record_calls("downloaded_after", vec![time.save()], |builder| {
+            builder.time_dt(
+                MDItemKey::DownloadedDate,
+                MDQueryCompareOp::GreaterThan,
+                time,
+            )
+        })
+    }
+
+    /// Adds an expression to match items whose Finder comment contains the specified text.
+    ///
+    /// The match is case- and diacritic-insensitive and word-based.
+    ///
+    /// # Parameters
+    /// * `text` - The text to search for in the Finder comment
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn finder_comment_contains(mut self, text: &str) -> Self {
+        self.record("finder_comment_contains", vec![text.save()]);
+        let pattern = format!("*{}*", escape_query_value(text));
+        self.string_match(
+            MDItemKey::FinderComment,
+            &pattern,
+            MatchModifiers::CASE_DIACRITIC_INSENSITIVE | MatchModifiers::WORD_BASED,
+        )
+    }
+
+    /// Adds an expression to match email messages whose subject contains the specified text.
+    ///
+    /// The match is case- and diacritic-insensitive. Mail messages usually have no
+    /// [`MDItem::path`], but are still returned by `execute`.
+    ///
+    /// # Parameters
+    /// * `text` - The text to search for in the subject
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn subject_contains(mut self, text: &str) -> Self {
+        self.record("subject_contains", vec![text.save()]);
+        let pattern = format!("*{}*", escape_query_value(text));
+        self.string_match(
+            MDItemKey::Subject,
+            &pattern,
+            MatchModifiers::CASE_DIACRITIC_INSENSITIVE,
+        )
+    }
+
+    /// Adds an expression to match items whose URL contains the specified text.
+    ///
+    /// This performs a case-insensitive substring match on `kMDItemURL`, which is set on
+    /// Safari bookmarks and history items and on web archives. Combine it with
+    /// [`content_type`](Self::content_type) to search bookmarks only:
+    ///
+    /// ```no_run
+    /// use mdquery_rs::MDQueryBuilder;
+    ///
+    /// let bookmarks = MDQueryBuilder::default()
+    ///     .content_type("com.apple.safari.bookmark")
+    ///     .url_contains("rust-lang.org")
+    ///     .execute()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// # Parameters
+    /// * `text` - The text to search for in the URL
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn url_contains(mut self, text: &str) -> Self {
+        self.record("url_contains", vec![text.save()]);
+        let pattern = format!("*{}*", escape_query_value(text));
+        self.string_match(MDItemKey::Url, &pattern, MatchModifiers::CASE_INSENSITIVE)
+    }
+
+    /// Adds an expression to match items sent by the specified email address.
+    ///
+    /// This matches any element of `kMDItemAuthorEmailAddresses`, compared
+    /// case-insensitively.
+    ///
+    /// # Parameters
+    /// * `address` - The sender's email address
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn from_email(mut self, address: &str) -> Self {
+        self.record("from_email", vec![address.save()]);
+        let pattern = escape_query_value(address);
+        self.string_match(
+            MDItemKey::AuthorEmailAddresses,
+            &pattern,
+            MatchModifiers::CASE_INSENSITIVE,
+        )
+    }
+
+    /// Adds an expression to match items sent to the specified email address.
+    ///
+    /// This matches any element of `kMDItemRecipientEmailAddresses`, compared
+    /// case-insensitively.
+    ///
+    /// # Parameters
+    /// * `address` - The recipient's email address
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn to_email(mut self, address: &str) -> Self {
+        self.record("to_email", vec![address.save()]);
+        let pattern = escape_query_value(address);
+        self.string_match(
+            MDItemKey::RecipientEmailAddresses,
+            &pattern,
+            MatchModifiers::CASE_INSENSITIVE,
+        )
+    }
+
+    /// Adds an expression to match items tagged with the specified keyword.
+    ///
+    /// This performs a case-insensitive, word-based match against any element of
+    /// `kMDItemKeywords`.
+    ///
+    /// # Parameters
+    /// * `keyword` - The keyword to match
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn keyword(self, keyword: &str) -> Self {
+        self.record_calls("keyword", vec![keyword.save()], |builder| {
+            builder.keyword_with(
+                keyword,
+                MatchModifiers::CASE_INSENSITIVE | MatchModifiers::WORD_BASED,
+            )
+        })
+    }
+
+    /// Adds an expression to match items tagged with the specified keyword, using explicit
+    /// match modifiers.
+    ///
+    /// # Parameters
+    /// * `keyword` - The keyword to match
+    /// * `modifiers` - The string comparison modifiers to apply
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn keyword_with(mut self, keyword: &str, modifiers: MatchModifiers) -> Self {
+        self.record("keyword_with", vec![keyword.save(), modifiers.save()]);
+        let pattern = escape_query_value(keyword);
+        self.string_match(MDItemKey::Keywords, &pattern, modifiers)
+    }
+
+    /// Adds an expression to match items tagged with any of the specified keywords.
+    ///
+    /// Each keyword is matched like [`keyword`](Self::keyword) and the matches are combined
+    /// with OR.
+    ///
+    /// # Parameters
+    /// * `keywords` - The keywords to match
+    ///
+    /// # Returns
+    /// Self for method chaining
+    ///
+    /// # Errors
+    /// An empty keyword list makes `build` fail with [`MDQueryError::InvalidBuilder`].
+    pub fn keywords_any(mut self, keywords: &[&str]) -> Self {
+        self.record("keywords_any", vec![keywords.save()]);
+        if keywords.is_empty() {
+            self.errors
+                .push(BuilderError::new("keywords_any", "no keywords given"));
+            return self;
+        }
+        let modifiers = MatchModifiers::CASE_INSENSITIVE | MatchModifiers::WORD_BASED;
+        let expressions = keywords
+            .iter()
+            .map(|keyword| {
+                format!(
+                    "{} == \"{}\"{}",
+                    MDItemKey::Keywords,
+                    escape_query_value(keyword),
+                    modifiers.to_query_string()
+                )
+            })
+            .collect();
+        self.any_of(expressions)
+    }
+
+    /// Adds an expression to match items with the specified file extension.
+    ///
+    /// Wildcards in `ext` are matched literally.
+    ///
+    /// # Parameters
+    /// * `ext` - The file extension to match (without the leading dot)
+    ///
+    /// # Returns
+    /// Self for method chaining
+    ///
+    /// # Errors
+    /// An empty `ext` makes `build` fail with [`MDQueryError::InvalidBuilder`].
+    pub fn extension(mut self, ext: &str) -> Self {
+        self.record("extension", vec![ext.save()]);
+        if ext.is_empty() {
+            self.errors
+                .push(BuilderError::new("extension", "extension is empty"));
+            return self;
+        }
+        self.condition
+            .add(MDQueryConditionExpression::Expression(format!(
+                "{} == \"*.{}\"c",
+                MDItemKey::FSName,
+                escape_query_value(ext)
+            )));
+        self
+    }
+
+    /// Adds an expression to match items with the specified content type.
+    ///
+    /// # Parameters
+    /// * `content_type` - The content type to match, as a UTI string or a `UTType`
+    ///
+    /// # Returns
+    /// Self for method chaining
+    ///
+    /// # Errors
+    /// An empty content type, or one that differs from the content type of an earlier
+    /// `content_type` call, makes `build` fail with [`MDQueryError::InvalidBuilder`].
+    pub fn content_type(mut self, content_type: impl Into<ContentTypeArg>) -> Self {
+        let content_type = content_type.into();
+        self.record("content_type", vec![content_type.as_str().save()]);
+        if content_type.as_str().is_empty() {
+            self.errors
+                .push(BuilderError::new("content_type", "content type is empty"));
+            return self;
+        }
+        self.require_content_type("content_type", content_type.as_str());
+        self.condition
+            .add(MDQueryConditionExpression::Expression(format!(
+                "{} == \"{}\"",
+                MDItemKey::ContentType,
+                escape_query_value(content_type.as_str())
+            )));
+        self
+    }
+
+    /// Adds an expression to match items whose content type conforms to the specified UTI.
+    ///
+    /// Unlike [`content_type`](Self::content_type), this matches any UTI in the content
+    /// type tree, so `"public.image"` matches JPEG and PNG files.
+    ///
+    /// # Parameters
+    /// * `uti` - The type the content type must conform to, as a UTI string or a `UTType`
+    ///
+    /// # Returns
+    /// Self for method chaining
+    ///
+    /// # Errors
+    /// An empty UTI makes `build` fail with [`MDQueryError::InvalidBuilder`].
+    pub fn content_type_conforms_to(mut self, uti: impl Into<ContentTypeArg>) -> Self {
+        let uti = uti.into();
+        self.record("content_type_conforms_to", vec![uti.as_str().save()]);
+        if uti.as_str().is_empty() {
+            self.errors.push(BuilderError::new(
+                "content_type_conforms_to",
+                "content type is empty",
+            ));
+            return self;
+        }
+        self.condition
+            .add(MDQueryConditionExpression::Expression(format!(
+                "{} == \"{}\"",
+                MDItemKey::ContentTypeTree,
+                escape_query_value(uti.as_str())
+            )));
+        self
+    }
+}
+
+/// A content type argument for [`MDQueryBuilder::content_type`] and
+/// [`MDQueryBuilder::content_type_conforms_to`].
+///
+/// Converts from UTI strings and, with the `uttype` feature, from `UTType`s.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ContentTypeArg(String);
+
+impl ContentTypeArg {
+    /// Returns the UTI string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for ContentTypeArg {
+    fn from(uti: &str) -> Self {
+        Self(uti.to_string())
+    }
+}
+
+impl From<String> for ContentTypeArg {
+    fn from(uti: String) -> Self {
+        Self(uti)
+    }
+}
+
+#[cfg(feature = "uttype")]
+impl From<crate::UTType> for ContentTypeArg {
+    fn from(ut_type: crate::UTType) -> Self {
+        Self(ut_type.identifier())
+    }
+}
+
+#[cfg(feature = "uttype")]
+impl From<&crate::UTType> for ContentTypeArg {
+    fn from(ut_type: &crate::UTType) -> Self {
+        Self(ut_type.identifier())
+    }
+}
+
+/// Builds the query run by [`MDQuery::search_text`]: every word of `term` must prefix a
+/// word of the text content, display name or keywords of an item.
+#[cfg_attr(
+    any(feature = "fallback", not(target_vendor = "apple")),
+    allow(dead_code)
+)]
+pub(crate) fn text_search_query(term: &str) -> Result<String> {
+    let condition = MDQueryCondition::all(term.split_whitespace().map(|word| {
+        let pattern = format!("{}*", escape_query_value(word));
+        MDQueryCondition::any(
+            [
+                MDItemKey::TextContent,
+                MDItemKey::DisplayName,
+                MDItemKey::Keywords,
+            ]
+            .map(|key| format!("{} == \"{}\"cdw", key, pattern)),
+        )
+    }));
+    if condition.is_empty() {
+        return Err(MDQueryError::InvalidBuilder(vec![BuilderError::new(
+            "search_text",
+            "search term is empty",
+        )])
+        .into());
+    }
+    condition.into_expression()
+}
+
+/// Escapes a user-provided string for use inside a quoted query value.
+///
+/// Backslashes and quotes are escaped so the value cannot terminate the string, and `*` is
+/// escaped so it is matched literally instead of as a wildcard.
+pub(crate) fn escape_query_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | '"' | '*') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Builds an escaped `kMDItemPath` pattern matching everything below `dir`.
+fn path_prefix_pattern(dir: &Path) -> String {
+    let dir = dir.to_string_lossy();
+    format!("{}/*", escape_query_value(dir.trim_end_matches('/')))
+}
+
+/// Formats a date-time as an RFC 3339 string for use in `$time.iso(...)`.
+///
+/// Fractional seconds are included only when present.
+fn format_iso(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::AutoSi, false)
+}
+
+/// Formats a Unix timestamp as an RFC 3339 string for use in `$time.iso(...)`.
+///
+/// Returns `None` if the timestamp is out of range.
+fn iso_time(timestamp: i64) -> Option<String> {
+    DateTime::from_timestamp(timestamp, 0).map(|time| format_iso(&time))
+}
+
+/// Finds an application by its bundle identifier.
+///
+/// Searches the standard application directories (`/Applications`, `/System/Applications`
+/// and `~/Applications`) and returns the first match.
+///
+/// # Parameters
+/// * `bundle_id` - The bundle identifier to look up (e.g. "com.apple.Safari")
+///
+/// # Returns
+/// A Result containing the application if found, or None if no application matches.
+pub fn find_app_by_bundle_id(bundle_id: &str) -> Result<Option<MDItem>> {
+    let mut items = MDQueryBuilder::default()
+        .bundle_identifier(bundle_id)
+        .is_app()
+        .build(application_scopes(), Some(1))?
+        .execute()?;
+    Ok(if items.is_empty() {
+        None
+    } else {
+        Some(items.swap_remove(0))
+    })
+}
+
+/// Finds the applications in an App Store category.
+///
+/// Searches the same directories as [`find_app_by_bundle_id`]. The category is matched as
+/// described for [`MDQueryBuilder::app_category`], so apps without a category are never
+/// returned.
+///
+/// # Parameters
+/// * `category` - The category name or identifier (e.g. "Productivity" or
+///   "public.app-category.productivity")
+///
+/// # Returns
+/// A Result containing the applications in the category.
+pub fn applications_in_category(category: &str) -> Result<Vec<MDItem>> {
+    MDQueryBuilder::default()
+        .is_app()
+        .app_category(category)
+        .build(application_scopes(), None)?
+        .execute()
+}
+
+/// Returns the standard application directories that exist.
+fn application_scopes() -> Vec<MDQueryScope> {
+    let mut dirs = vec![
+        PathBuf::from("/Applications"),
+        PathBuf::from("/System/Applications"),
+    ];
+    if let Some(home) = std::env::var_os("HOME") {
+        dirs.push(PathBuf::from(home).join("Applications"));
+    }
+    dirs.into_iter()
+        .filter(|dir| dir.is_dir())
+        .map(MDQueryScope::Custom)
+        .collect()
+}
+
+/// A structure for building complex, nested query conditions with logical operators.
+///
+/// `MDQueryCondition` allows for creating sophisticated search expressions by combining
+/// multiple conditions with either logical AND (All) or logical OR (Any) operators.
+/// It can be used to build complex queries that are not easily expressible with the
+/// simple chained methods of `MDQueryBuilder`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MDQueryCondition {
+    /// Specifies whether the expressions should be combined with logical AND (All) or OR (Any).
+    pub(crate) condition_type: MDQueryConditionType,
+    /// The list of expressions to be combined according to the condition_type.
+    pub(crate) expressions: Vec<MDQueryConditionExpression>,
+    /// The calls of the builder the condition was taken from, saved by `to_json` in place
+    /// of the expressions until more are added.
+    pub(crate) operations: Option<Vec<Operation>>,
+}
+
+impl Default for MDQueryCondition {
+    fn default() -> Self {
+        Self {
+            condition_type: MDQueryConditionType::All,
+            expressions: Vec::new(),
+            operations: None,
+        }
+    }
+}
+
+impl MDQueryCondition {
+    /// Creates a condition that matches when all of the expressions match.
+    ///
+    /// # Parameters
+    /// * `expressions` - Raw query expressions or nested conditions to combine with AND
+    ///
+    /// # Returns
+    /// A new `MDQueryCondition` of type `All`.
+    pub fn all<I, E>(expressions: I) -> Self
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<MDQueryConditionExpression>,
+    {
+        Self {
+            condition_type: MDQueryConditionType::All,
+            expressions: expressions.into_iter().map(Into::into).collect(),
+            operations: None,
+        }
+    }
+
+    /// Creates a condition that matches when any of the expressions match.
+    ///
+    /// # Parameters
+    /// * `expressions` - Raw query expressions or nested conditions to combine with OR
+    ///
+    /// # Returns
+    /// A new `MDQueryCondition` of type `Any`.
+    pub fn any<I, E>(expressions: I) -> Self
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<MDQueryConditionExpression>,
+    {
+        Self {
+            condition_type: MDQueryConditionType::Any,
+            expressions: expressions.into_iter().map(Into::into).collect(),
+            operations: None,
+        }
+    }
+
+    /// Appends an expression to the condition.
+    ///
+    /// # Parameters
+    /// * `expr` - A raw query expression or nested condition
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn push(mut self, expr: impl Into<MDQueryConditionExpression>) -> Self {
+        self.add(expr.into());
+        self
+    }
+
+    /// Builds an MDQuery from this condition.
+    ///
+    /// # Parameters
+    /// * `scopes` - Search scopes to limit the query
+    /// * `max_count` - Optional maximum number of results to return
+    ///
+    /// # Returns
+    /// A Result containing the MDQuery if successful, or an error if the condition is empty.
+    pub fn build(
+        self,
+        scopes: impl IntoIterator<Item = MDQueryScope>,
+        max_count: Option<usize>,
+    ) -> Result<MDQuery> {
+        MDQueryBuilder::from_condition(self).build(scopes, max_count)
+    }
+
+    /// Converts the condition structure into a query expression string.
+    ///
+    /// This method recursively processes the condition structure, combining all expressions
+    /// according to the condition_type (All = AND, Any = OR) and returning a properly
+    /// formatted query string that can be used with MDQuery.
+    ///
+    /// # Returns
+    /// A string representation of the combined query expression, properly parenthesized.
+    ///
+    /// # Errors
+    /// Returns [`MDQueryError::EmptyConditionGroup`] if this condition or any nested condition
+    /// has no expressions.
+    pub fn into_expression(self) -> Result<String> {
+        self.to_expression()
+    }
+
+    /// Converts the condition into a query expression string without consuming it.
+    ///
+    /// # Returns
+    /// The same result as [`into_expression`](Self::into_expression).
+    pub fn to_expression(&self) -> Result<String> {
+        self.expression_at("root")
+    }
+
+    /// Converts the condition, located at `path` in the tree, into a query expression string.
+    fn expression_at(&self, path: &str) -> Result<String> {
+        if self.expressions.is_empty() {
+            return Err(MDQueryError::EmptyConditionGroup(path.to_string()).into());
+        }
+        let separator = match self.condition_type {
+            MDQueryConditionType::All => " && ",
+            MDQueryConditionType::Any => " || ",
+        };
+        let expr = self
+            .expressions
+            .iter()
+            .enumerate()
+            .map(|(i, e)| match e {
+                MDQueryConditionExpression::Condition(c) => {
+                    c.expression_at(&format!("{path}[{i}]"))
+                }
+                MDQueryConditionExpression::Expression(e) => Ok(format!("({})", e)),
+            })
+            .collect::<Result<Vec<_>>>()?
+            .join(separator);
+        Ok(format!("({})", expr))
+    }
+
+    /// Add a new expression to the condition.
+    ///
+    /// # Parameters
+    /// * `expr` - The expression to add to the condition
+    pub fn add(&mut self, expr: MDQueryConditionExpression) {
+        self.operations = None;
+        self.expressions.push(expr);
+    }
+
+    /// Checks if the condition is empty.
+    ///
+    /// # Returns
+    /// True if the condition is empty, false otherwise.
+    pub fn is_empty(&self) -> bool {
+        self.expressions.is_empty()
+    }
+}
+
+/// Defines the logical operation to apply when combining multiple expressions.
+///
+/// This enum determines how the expressions within an `MDQueryCondition` are combined, and
+/// how [`MDQueryBuilder::merge`] combines two builders:
+/// - `All`: Combines expressions with logical AND (&&)
+/// - `Any`: Combines expressions with logical OR (||)
+///
+/// Conditions themselves are created with [`MDQueryCondition::all`] and
+/// [`MDQueryCondition::any`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MDQueryConditionType {
+    /// Combines all expressions with logical AND (&&)
+    All,
+    /// Combines all expressions with logical OR (||)
+    Any,
+}
+
+/// Represents either a nested condition or a raw query expression string.
+///
+/// This enum allows for building complex, nested query structures by combining
+/// both raw expression strings and other condition structures.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MDQueryConditionExpression {
+    /// A nested condition structure
+    Condition(MDQueryCondition),
+    /// A raw query expression string
+    Expression(String),
+}
+
+impl MDQueryConditionExpression {
+    /// Converts the expression into a query string.
+    ///
+    /// For nested conditions, this recursively processes the condition structure.
+    /// For raw expressions, it wraps the expression in parentheses.
+    ///
+    /// # Returns
+    /// A properly formatted query string representation of this expression.
+    ///
+    /// # Errors
+    /// Returns [`MDQueryError::EmptyConditionGroup`] if a nested condition has no expressions.
+    pub fn into_expression(self) -> Result<String> {
+        self.to_expression()
+    }
+
+    /// Converts the expression into a query string without consuming it.
+    ///
+    /// # Returns
+    /// The same result as [`into_expression`](Self::into_expression).
+    pub fn to_expression(&self) -> Result<String> {
+        match self {
+            Self::Condition(c) => c.to_expression(),
+            Self::Expression(e) => Ok(format!("({})", e)),
+        }
+    }
+}
+
+impl From<String> for MDQueryConditionExpression {
+    fn from(expr: String) -> Self {
+        Self::Expression(expr)
+    }
+}
+
+impl From<&str> for MDQueryConditionExpression {
+    fn from(expr: &str) -> Self {
+        Self::Expression(expr.to_string())
+    }
+}
+
+/// Takes the builder's expressions as an `All` condition.
+///
+/// Scopes, limits and argument errors recorded by the builder are dropped, as for
+/// `From<MDQueryBuilder> for MDQueryConditionExpression`.
+impl From<MDQueryBuilder> for MDQueryCondition {
+    fn from(builder: MDQueryBuilder) -> Self {
+        let mut condition = builder.condition;
+        condition.operations = Some(builder.operations);
+        condition
+    }
+}
+
+/// Nests the builder's expressions as a single condition.
+///
+/// Only the expressions are kept. Scopes, limits and argument errors recorded by the builder
+/// are dropped, so check fragments that take fallible arguments with
+/// [`MDQueryBuilder::into_query`] or build them from known-good values.
+impl From<MDQueryBuilder> for MDQueryConditionExpression {
+    fn from(builder: MDQueryBuilder) -> Self {
+        Self::Condition(builder.into())
+    }
+}
+
+impl From<MDQueryCondition> for MDQueryConditionExpression {
+    fn from(condition: MDQueryCondition) -> Self {
+        Self::Condition(condition)
+    }
+}
+
+/// Comparison operators for metadata query expressions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MDQueryCompareOp {
+    /// Greater than (>)
+    GreaterThan,
+    /// Less than (<)
+    LessThan,
+    /// Equal to (==)
+    Equal,
+    /// Greater than or equal to (>=)
+    GreaterThanOrEqual,
+    /// Less than or equal to (<=)
+    LessThanOrEqual,
+}
+
+impl MDQueryCompareOp {
+    /// Converts the operator to its string representation in a query.
+    ///
+    /// # Returns
+    /// The string representation of the operator.
+    fn into_query_string(self) -> &'static str {
+        match self {
+            Self::GreaterThan => ">",
+            Self::LessThan => "<",
+            Self::Equal => "==",
+            Self::GreaterThanOrEqual => ">=",
+            Self::LessThanOrEqual => "<=",
+        }
+    }
+}
+
+/// A typed value for [`MDQueryBuilder::compare`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum MDQueryValue {
+    /// A string, compared literally
+    Str(String),
+    /// An integer
+    Int(i64),
+    /// A floating-point number
+    Float(f64),
+    /// A boolean
+    Bool(bool),
+    /// A point in time
+    Date(DateTime<Utc>),
+}
+
+impl MDQueryValue {
+    /// Returns the name of the value's type for error messages.
+    fn type_name(&self) -> &'static str {
+        match self {
+            Self::Str(_) => "Str",
+            Self::Int(_) => "Int",
+            Self::Float(_) => "Float",
+            Self::Bool(_) => "Bool",
+            Self::Date(_) => "Date",
+        }
+    }
+}
+
+impl From<&str> for MDQueryValue {
+    fn from(value: &str) -> Self {
+        Self::Str(value.to_string())
+    }
+}
+
+impl From<String> for MDQueryValue {
+    fn from(value: String) -> Self {
+        Self::Str(value)
+    }
+}
+
+impl From<i64> for MDQueryValue {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<i32> for MDQueryValue {
+    fn from(value: i32) -> Self {
+        Self::Int(value.into())
+    }
+}
+
+impl From<u32> for MDQueryValue {
+    fn from(value: u32) -> Self {
+        Self::Int(value.into())
+    }
+}
+
+impl From<f64> for MDQueryValue {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<bool> for MDQueryValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<DateTime<Utc>> for MDQueryValue {
+    fn from(value: DateTime<Utc>) -> Self {
+        Self::Date(value)
+    }
+}
+
+impl From<FileSize> for MDQueryValue {
+    fn from(value: FileSize) -> Self {
+        Self::Int(i64::try_from(value.as_bytes()).unwrap_or(i64::MAX))
+    }
+}
+
+/// String comparison modifiers appended to a quoted query value.
+///
+/// Modifiers can be combined with `|`, e.g.
+/// `MatchModifiers::CASE_INSENSITIVE | MatchModifiers::DIACRITIC_INSENSITIVE` emits `cd`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MatchModifiers {
+    /// Ignore case (`c`)
+    pub case_insensitive: bool,
+    /// Ignore diacritics, so "cafe" matches "café" (`d`)
+    pub diacritic_insensitive: bool,
+    /// Match on word boundaries, including CJK transliterations (`w`)
+    pub word_based: bool,
+}
+
+impl MatchModifiers {
+    /// No modifiers: a case- and diacritic-sensitive match.
+    pub const NONE: Self = Self {
+        case_insensitive: false,
+        diacritic_insensitive: false,
+        word_based: false,
+    };
+    /// Case-insensitive matching (`c`).
+    pub const CASE_INSENSITIVE: Self = Self {
+        case_insensitive: true,
+        ..Self::NONE
+    };
+    /// Diacritic-insensitive matching (`d`).
+    pub const DIACRITIC_INSENSITIVE: Self = Self {
+        diacritic_insensitive: true,
+        ..Self::NONE
+    };
+    /// Case- and diacritic-insensitive matching (`cd`).
+    pub const CASE_DIACRITIC_INSENSITIVE: Self = Self {
+        case_insensitive: true,
+        diacritic_insensitive: true,
+        ..Self::NONE
+    };
+    /// Word-based matching (`w`).
+    pub const WORD_BASED: Self = Self {
+        word_based: true,
+        ..Self::NONE
+    };
+
+    /// Converts the modifiers to their query suffix, always in `cdw` order.
+    ///
+    /// # Returns
+    /// The modifier suffix, empty if no modifier is set.
+    fn to_query_string(self) -> String {
+        let mut suffix = String::new();
+        if self.case_insensitive {
+            suffix.push('c');
+        }
+        if self.diacritic_insensitive {
+            suffix.push('d');
+        }
+        if self.word_based {
+            suffix.push('w');
+        }
+        suffix
+    }
+}
+
+impl BitOr for MatchModifiers {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self {
+            case_insensitive: self.case_insensitive || rhs.case_insensitive,
+            diacritic_insensitive: self.diacritic_insensitive || rhs.diacritic_insensitive,
+            word_based: self.word_based || rhs.word_based,
+        }
+    }
+}
+
+/// A point in time that Spotlight evaluates when the query is executed.
+///
+/// Offsets may be negative to refer to the past, e.g. `Today(-7)` is the start of the day
+/// one week ago and `ThisMonth(-1)` is the start of last month.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RelativeTime {
+    /// The current time (`$time.now`)
+    Now,
+    /// The current time shifted by a number of seconds (`$time.now(n)`)
+    SecondsFromNow(i64),
+    /// The start of the day shifted by a number of days (`$time.today(n)`)
+    Today(i64),
+    /// The start of the week shifted by a number of weeks (`$time.this_week(n)`)
+    ThisWeek(i64),
+    /// The start of the month shifted by a number of months (`$time.this_month(n)`)
+    ThisMonth(i64),
+    /// The start of the year shifted by a number of years (`$time.this_year(n)`)
+    ThisYear(i64),
+    /// A fixed point in time (`$time.iso(...)`)
+    Iso(DateTime<Utc>),
+}
+
+impl RelativeTime {
+    /// Converts the time to its `$time` expression in a query.
+    ///
+    /// # Returns
+    /// The query string representation of the time.
+    fn to_query_string(&self) -> String {
+        match self {
+            Self::Now => "$time.now".to_string(),
+            Self::SecondsFromNow(n) => format!("$time.now({})", n),
+            Self::Today(n) => format!("$time.today({})", n),
+            Self::ThisWeek(n) => format!("$time.this_week({})", n),
+            Self::ThisMonth(n) => format!("$time.this_month({})", n),
+            Self::ThisYear(n) => format!("$time.this_year({})", n),
+            Self::Iso(time) => format!("$time.iso({})", format_iso(time)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ImageOrientation, MDQueryOptions};
+    use chrono::TimeZone;
+
+    /// Returns the messages of an `InvalidBuilder` error, formatted as `method: message`.
+    fn builder_errors(err: &anyhow::Error) -> Vec<String> {
+        match err.downcast_ref::<MDQueryError>() {
+            Some(MDQueryError::InvalidBuilder(errors)) => {
+                errors.iter().map(ToString::to_string).collect()
+            }
+            _ => panic!("expected InvalidBuilder, got {err}"),
+        }
+    }
+
+    #[test]
+    fn test_builder_execute_empty() {
+        assert!(MDQueryBuilder::default().execute().is_err());
+    }
+
+    #[test]
+    fn test_time_non_time_key() {
+        let err = MDQueryBuilder::default()
+            .time(MDItemKey::Size, MDQueryCompareOp::LessThan, 0)
+            .execute()
+            .err()
+            .unwrap();
+        assert_eq!(
+            builder_errors(&err),
+            ["time: kMDItemFSSize is not a time attribute"]
+        );
+    }
+
+    #[test]
+    fn test_date_added_expressions() {
+        let builder = MDQueryBuilder::default()
+            .added_within_days(7)
+            .added_after(DateTime::from_timestamp(1_700_000_000, 0).unwrap())
+            .time(
+                MDItemKey::DateAdded,
+                MDQueryCompareOp::LessThan,
+                1_800_000_000,
+            )
+            .time_in_range(MDItemKey::DateAdded, 1_700_000_000, 1_800_000_000);
+        assert_eq!(
+            builder.to_query_string().unwrap(),
+            "((kMDItemDateAdded >= $time.today(-7)) && \
+             (kMDItemDateAdded > $time.iso(2023-11-14T22:13:20+00:00)) && \
+             (kMDItemDateAdded < $time.iso(2027-01-15T08:00:00+00:00)) && \
+             (InRange(kMDItemDateAdded, $time.iso(2023-11-14T22:13:20+00:00), \
+             $time.iso(2027-01-15T08:00:00+00:00))))"
+        );
+    }
+
+    #[test]
+    fn test_time_invalid_timestamp() {
+        let err = MDQueryBuilder::default()
+            .name_like("Safari")
+            .time(
+                MDItemKey::ModificationDate,
+                MDQueryCompareOp::GreaterThan,
+                i64::MAX,
+            )
+            .build([MDQueryScope::Computer], None)
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Invalid query builder arguments: time: timestamp {} is out of range",
+                i64::MAX
+            )
+        );
+    }
+
+    #[test]
+    fn test_time_in_range_expression() {
+        let builder = MDQueryBuilder::default().time_in_range(
+            MDItemKey::ModificationDate,
+            1_709_251_200,
+            1_711_843_200,
+        );
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            "((InRange(kMDItemContentModificationDate, $time.iso(2024-03-01T00:00:00+00:00), $time.iso(2024-03-31T00:00:00+00:00))))"
+        );
+    }
+
+    #[test]
+    fn test_time_in_range_invalid() {
+        let err = MDQueryBuilder::default()
+            .time_in_range(MDItemKey::Size, 0, 1)
+            .time_in_range(MDItemKey::CreationDate, 10, 0)
+            .execute()
+            .err()
+            .unwrap();
+        assert_eq!(
+            builder_errors(&err),
+            [
+                "time_in_range: kMDItemFSSize is not a time attribute",
+                "time_in_range: start 10 is after end 0",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_size_in_range() {
+        let builder = MDQueryBuilder::default().size_in_range(1024, 2048);
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            "((InRange(kMDItemFSSize, 1024, 2048)))"
+        );
+
+        let err = MDQueryBuilder::default()
+            .size_in_range(2048, 1024)
+            .execute()
+            .err()
+            .unwrap();
+        assert!(matches!(
+            err.downcast_ref::<MDQueryError>(),
+            Some(MDQueryError::InvalidBuilder(_))
+        ));
+    }
+
+    #[test]
+    fn test_time_dt_fixed_offset() {
+        let time = DateTime::parse_from_rfc3339("2024-03-01T08:30:15.250+08:00").unwrap();
+        let builder = MDQueryBuilder::default().time_dt(
+            MDItemKey::CreationDate,
+            MDQueryCompareOp::GreaterThan,
+            time,
+        );
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            "((kMDItemContentCreationDate > $time.iso(2024-03-01T00:30:15.250+00:00)))"
+        );
+    }
+
+    #[test]
+    fn test_time_matches_time_dt() {
+        let with_timestamp = MDQueryBuilder::default().time(
+            MDItemKey::ModificationDate,
+            MDQueryCompareOp::LessThan,
+            1_700_000_000,
+        );
+        let with_datetime = MDQueryBuilder::default().time_dt(
+            MDItemKey::ModificationDate,
+            MDQueryCompareOp::LessThan,
+            DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+        );
+        assert_eq!(
+            with_timestamp.condition.into_expression().unwrap(),
+            with_datetime.condition.into_expression().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_finder_label() {
+        let labels = [
+            FinderLabel::None,
+            FinderLabel::Gray,
+            FinderLabel::Green,
+            FinderLabel::Purple,
+            FinderLabel::Blue,
+            FinderLabel::Yellow,
+            FinderLabel::Red,
+            FinderLabel::Orange,
+        ];
+        for (value, label) in labels.into_iter().enumerate() {
+            assert_eq!(label.value() as usize, value);
+            assert_eq!(FinderLabel::from_value(value as u8), Some(label));
+        }
+        assert_eq!(FinderLabel::from_value(8), None);
+
+        let builder = MDQueryBuilder::default().with_label(FinderLabel::Red);
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            "((kMDItemFSLabel == 6))"
+        );
+    }
+
+    #[test]
+    fn test_absolutize() {
+        assert_eq!(
+            crate::model::absolutize(Path::new("/a/./b/../c/")).unwrap(),
+            PathBuf::from("/a/c")
+        );
+        assert_eq!(
+            crate::model::absolutize(Path::new("x/../y")).unwrap(),
+            std::env::current_dir().unwrap().join("y")
+        );
+    }
+
+    #[test]
+    fn test_scope_path_expansion() {
+        let custom_path = |scope: MDQueryScope| match scope {
+            MDQueryScope::Custom(path) => path,
+            _ => panic!("expected a custom scope"),
+        };
+
+        if let Some(home) = std::env::var_os("HOME").filter(|home| !home.is_empty()) {
+            assert_eq!(
+                custom_path(MDQueryScope::from_path("~/Documents/")),
+                PathBuf::from(&home).join("Documents")
+            );
+            assert_eq!(
+                custom_path(MDQueryScope::from_path("~")),
+                PathBuf::from(&home)
+            );
+        }
+        let cwd = std::env::current_dir().unwrap();
+        assert_eq!(custom_path(MDQueryScope::from_path(".")), cwd);
+        assert_eq!(custom_path(MDQueryScope::try_from_path(".").unwrap()), cwd);
+
+        let missing = cwd.join("no-such-directory-for-mdquery");
+        assert_eq!(
+            MDQueryScope::try_from_path("./no-such-directory-for-mdquery").err(),
+            Some(MDQueryError::InvalidScope(missing))
+        );
+    }
+
+    #[test]
+    fn test_scope_dedup() {
+        let scopes = MDQueryScope::into_scope_strings(vec![
+            MDQueryScope::from_path("/a/b/c"),
+            MDQueryScope::Home,
+            MDQueryScope::from_path("/a/b"),
+            MDQueryScope::from_path("/a/bc"),
+            MDQueryScope::from_path("/a/b/"),
+            MDQueryScope::Home,
+            MDQueryScope::from_path("/x"),
+        ])
+        .unwrap();
+        assert_eq!(scopes, vec!["kMDQueryScopeHome", "/a/b", "/a/bc", "/x"]);
+    }
+
+    #[test]
+    fn test_scope_from_paths() {
+        let cwd = std::env::current_dir().unwrap();
+        let src = cwd.join("src");
+        let scopes = MDQueryScope::from_paths([&cwd, &src, &cwd]).unwrap();
+        assert_eq!(scopes.len(), 1);
+        assert!(matches!(&scopes[0], MDQueryScope::Custom(path) if *path == cwd));
+
+        let missing = cwd.join("no-such-directory-for-mdquery");
+        let err = MDQueryScope::from_paths([Path::new("relative"), &missing, &src])
+            .err()
+            .unwrap();
+        assert_eq!(
+            err,
+            MDQueryError::InvalidBuilder(vec![
+                BuilderError::new("from_paths", "relative is not absolute"),
+                BuilderError::new(
+                    "from_paths",
+                    format!("{} is not an existing directory", missing.display()),
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_standard_directory_scopes() {
+        assert_eq!(
+            MDQueryScope::applications().into_scope_string().ok(),
+            std::path::Path::new("/Applications")
+                .is_dir()
+                .then(|| "/Applications".to_string())
+        );
+        for scope in [
+            MDQueryScope::documents(),
+            MDQueryScope::downloads(),
+            MDQueryScope::desktop(),
+            MDQueryScope::pictures(),
+            MDQueryScope::movies(),
+            MDQueryScope::music(),
+            MDQueryScope::icloud_drive(),
+            MDQueryScope::icloud(),
+        ] {
+            match scope.into_scope_string() {
+                Ok(path) => {
+                    let path = PathBuf::from(path);
+                    assert!(path.is_absolute() && path.is_dir());
+                }
+                Err(MDQueryError::InvalidScope(path)) => assert!(!path.is_dir()),
+                Err(err) => panic!("unexpected error: {err}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_query_options() {
+        assert_eq!(MDQueryOptions::default(), MDQueryOptions::SYNCHRONOUS);
+        let options = MDQueryOptions::SYNCHRONOUS | MDQueryOptions::ALLOW_FS_TRANSLATIONS;
+        assert_eq!(options.bits(), 9);
+        assert!(options.contains(MDQueryOptions::SYNCHRONOUS));
+        assert!(!options.contains(MDQueryOptions::WANTS_UPDATES));
+        assert!(options.contains(MDQueryOptions::NONE));
+        assert_eq!(MDQueryOptions::from_bits(4), MDQueryOptions::WANTS_UPDATES);
+    }
+
+    #[test]
+    fn test_scope_options() {
+        assert_eq!(ScopeOptions::default(), ScopeOptions::NONE);
+        assert_eq!(ScopeOptions::NONE.bits(), 0);
+        assert_eq!(
+            (ScopeOptions::from_bits(1) | ScopeOptions::from_bits(4)).bits(),
+            5
+        );
+    }
+
+    #[test]
+    fn test_builder_output_passes_validation() {
+        let builder = MDQueryBuilder::default()
+            .name_like("my \"file")
+            .path_starts_with("/Users/me")
+            .modified_within_days(7)
+            .time_in_range(MDItemKey::CreationDate, 1_700_000_000, 1_710_000_000)
+            .size_between(FileSize::mb(1), FileSize::gb(1))
+            .audio_sample_rate(MDQueryCompareOp::GreaterThanOrEqual, 44_100.0)
+            .keywords_any(&["a", "b"])
+            .of_kind(ContentKind::Image)
+            .include_hidden(false);
+        assert_eq!(
+            crate::validate_query(&builder.condition.into_expression().unwrap()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_to_query_string() {
+        let builder = MDQueryBuilder::default();
+        assert!(builder.is_empty());
+        assert_eq!(builder.len(), 0);
+
+        let builder = builder.name_is("Safari.app").keywords_any(&["a", "b"]);
+        assert!(!builder.is_empty());
+        assert_eq!(builder.len(), 2);
+        assert!(matches!(
+            builder.expressions(),
+            [
+                MDQueryConditionExpression::Expression(_),
+                MDQueryConditionExpression::Condition(_)
+            ]
+        ));
+        let expected = "((kMDItemDisplayName == \"Safari.app\"c) && \
+                        ((kMDItemKeywords == \"a\"cw) || (kMDItemKeywords == \"b\"cw)))";
+        assert_eq!(builder.to_query_string().unwrap(), expected);
+        assert_eq!(builder.condition.into_expression().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_builder_condition_composition() {
+        let large = MDQueryBuilder::default()
+            .name_like("a")
+            .size(MDQueryCompareOp::GreaterThan, FileSize::mib(1));
+        let pdfs = MDQueryBuilder::default().extension("pdf");
+        let builder = MDQueryBuilder::default()
+            .condition(MDQueryCondition::any([large, pdfs]))
+            .include_hidden(false);
+        assert_eq!(
+            builder.to_query_string().unwrap(),
+            "((((kMDItemDisplayName == \"*a*\"w) && (kMDItemFSSize > 1048576)) || \
+             ((kMDItemFSName == \"*.pdf\"c))) && (kMDItemFSInvisible != 1))"
+        );
+    }
+
+    #[test]
+    fn test_content_type_arg() {
+        let from_str = MDQueryBuilder::default()
+            .content_type("public.png")
+            .content_type_conforms_to("public.image")
+            .to_query_string()
+            .unwrap();
+        let from_string = MDQueryBuilder::default()
+            .content_type(String::from("public.png"))
+            .content_type_conforms_to(ContentTypeArg::from("public.image"))
+            .to_query_string()
+            .unwrap();
+        assert_eq!(from_str, from_string);
+        assert!(from_str.contains("kMDItemContentTypeTree == \"public.image\""));
+    }
+
+    #[test]
+    fn test_extension_and_content_type_escaped() {
+        let query = MDQueryBuilder::default()
+            .extension("p\"df*")
+            .content_type("com.example.\"x\"")
+            .to_query_string()
+            .unwrap();
+        assert_eq!(
+            query,
+            r#"((kMDItemFSName == "*.p\"df\*"c) && (kMDItemContentType == "com.example.\"x\""))"#
+        );
+        assert!(crate::validate_query(&query).is_ok());
+    }
+
+    #[test]
+    fn test_clone_and_eq() {
+        let base = MDQueryBuilder::default()
+            .name_like("report")
+            .with_scopes([MDQueryScope::Home]);
+        let copy = base.clone();
+        assert!(copy == base);
+        assert_eq!(
+            copy.to_query_string().unwrap(),
+            base.to_query_string().unwrap()
+        );
+
+        let extended = copy.extension("pdf");
+        assert!(extended != base);
+        assert!(MDQueryBuilder::default().name_like("report") != base);
+    }
+
+    #[test]
+    fn test_clone_base_builder() {
+        let base = MDQueryBuilder::default()
+            .path_starts_with("/Users/me/Projects")
+            .include_hidden(false);
+        let base_query = base.to_query_string().unwrap();
+
+        let first = base.clone().name_like("main");
+        let second = base.clone().extension("rs");
+        let first_query = first.to_query_string().unwrap();
+        let second_query = second.to_query_string().unwrap();
+        assert_ne!(first_query, second_query);
+        assert!(first_query.contains("*main*"));
+        assert!(!first_query.contains("*.rs"));
+        assert!(second_query.contains("*.rs"));
+        assert!(!second_query.contains("*main*"));
+        assert_eq!(base.to_query_string().unwrap(), base_query);
+        assert!(format!("{:?}", first).contains("*main*"));
+    }
+
+    #[test]
+    fn test_merge() {
+        let pdfs = MDQueryBuilder::default()
+            .extension("pdf")
+            .with_scopes([MDQueryScope::Home]);
+        let reports = MDQueryBuilder::default()
+            .name_like("report")
+            .with_max_count(5);
+
+        let any = pdfs
+            .clone()
+            .merge(reports.clone(), MDQueryConditionType::Any);
+        assert_eq!(
+            any.to_query_string().unwrap(),
+            "((((kMDItemFSName == \"*.pdf\"c)) || ((kMDItemDisplayName == \"*report*\"w))))"
+        );
+        assert_eq!(any.scopes, Some(vec![MDQueryScope::Home]));
+        assert_eq!(any.max_count, Some(5));
+
+        let all = pdfs
+            .clone()
+            .merge(reports.clone(), MDQueryConditionType::All);
+        assert!(all.to_query_string().unwrap().contains(") && ("));
+
+        let narrowed = any
+            .clone()
+            .merge(
+                MDQueryBuilder::default().extension("docx"),
+                MDQueryConditionType::Any,
+            )
+            .include_hidden(false);
+        assert_eq!(
+            narrowed.to_query_string().unwrap(),
+            "((((kMDItemFSName == \"*.pdf\"c)) || ((kMDItemDisplayName == \"*report*\"w)) || \
+             ((kMDItemFSName == \"*.docx\"c))) && (kMDItemFSInvisible != 1))"
+        );
+
+        let unchanged = pdfs
+            .clone()
+            .merge(MDQueryBuilder::default(), MDQueryConditionType::Any);
+        assert_eq!(
+            unchanged.to_query_string().unwrap(),
+            pdfs.to_query_string().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_alias_and_symlink_filters() {
+        assert_eq!(
+            MDQueryBuilder::default()
+                .exclude_aliases()
+                .to_query_string()
+                .unwrap(),
+            "((kMDItemContentTypeTree != \"com.apple.alias-file\"))"
+        );
+        assert_eq!(
+            MDQueryBuilder::default()
+                .only_symlinks()
+                .to_query_string()
+                .unwrap(),
+            "((kMDItemContentTypeTree == \"public.symlink\"))"
+        );
+    }
+
+    #[test]
+    fn test_volume_filters() {
+        let (query, scopes, _) = MDQueryBuilder::default()
+            .name_like("report")
+            .local_volumes_only()
+            .with_scopes([
+                MDQueryScope::Home,
+                MDQueryScope::Network,
+                MDQueryScope::NetworkIndexed,
+            ])
+            .into_parts()
+            .unwrap();
+        assert_eq!(
+            query,
+            "((kMDItemDisplayName == \"*report*\"w) && (kMDItemPath != \"/Volumes/*\"))"
+        );
+        assert_eq!(scopes, vec![MDQueryScope::Home]);
+
+        let err = MDQueryBuilder::default()
+            .name_like("report")
+            .with_scopes([MDQueryScope::Network])
+            .merge(
+                MDQueryBuilder::default().local_volumes_only(),
+                MDQueryConditionType::All,
+            )
+            .into_parts()
+            .err()
+            .unwrap();
+        assert!(matches!(
+            err.downcast_ref::<MDQueryError>(),
+            Some(MDQueryError::InvalidBuilder(_))
+        ));
+
+        assert_eq!(
+            MDQueryBuilder::default()
+                .on_volume("/Volumes/Backup/")
+                .to_query_string()
+                .unwrap(),
+            "((kMDItemPath == \"/Volumes/Backup/*\"))"
+        );
+        assert_eq!(
+            MDQueryBuilder::default()
+                .on_volume("/")
+                .to_query_string()
+                .unwrap(),
+            "((kMDItemPath != \"/Volumes/*\"))"
+        );
+    }
+
+    #[test]
+    fn test_collects_all_argument_errors() {
+        let err = MDQueryBuilder::default()
+            .name_like("")
+            .extension("")
+            .content_type("")
+            .time(MDItemKey::Size, MDQueryCompareOp::LessThan, 0)
+            .name_is("Safari")
+            .name_is("Finder")
+            .content_type("public.png")
+            .content_type("public.jpeg")
+            .execute()
+            .err()
+            .unwrap();
+        assert_eq!(
+            builder_errors(&err),
+            [
+                "name_like: name is empty",
+                "extension: extension is empty",
+                "content_type: content type is empty",
+                "time: kMDItemFSSize is not a time attribute",
+                "name_is: name \"Finder\" conflicts with the earlier exact name \"Safari\"",
+                "content_type: content type \"public.jpeg\" conflicts with the earlier content \
+                 type \"public.png\"",
+            ]
+        );
+        assert!(err
+            .to_string()
+            .starts_with("Invalid query builder arguments: name_like: name is empty; extension:"));
+    }
+
+    #[test]
+    fn test_compatible_exact_constraints() {
+        let builder = MDQueryBuilder::default()
+            .name_is("Safari")
+            .name_is("safari")
+            .name_is_with("Safari", MatchModifiers::NONE)
+            .content_type("public.png")
+            .content_type("public.png");
+        assert!(builder.to_query_string().is_ok());
+        assert!(builder.into_parts().is_ok());
+
+        let either = MDQueryBuilder::default()
+            .name_is("Safari")
+            .merge(
+                MDQueryBuilder::default().name_is("Finder"),
+                MDQueryConditionType::Any,
+            )
+            .name_is("Finder");
+        assert!(either.into_parts().is_ok());
+
+        let err = MDQueryBuilder::default()
+            .content_type("public.png")
+            .merge(
+                MDQueryBuilder::default().content_type("public.jpeg"),
+                MDQueryConditionType::All,
+            )
+            .into_parts()
+            .err()
+            .unwrap();
+        assert_eq!(
+            builder_errors(&err),
+            [
+                "merge: content type \"public.jpeg\" conflicts with the earlier content type \
+              \"public.png\""
+            ]
+        );
+    }
+
+    #[test]
+    fn test_match_all_requires_scope_or_limit() {
+        let err = MDQueryBuilder::default()
+            .match_all()
+            .into_parts()
+            .err()
+            .unwrap();
+        assert_eq!(
+            builder_errors(&err),
+            ["match_all: a search scope or max count is required"]
+        );
+
+        let (query, _, max_count) = MDQueryBuilder::default()
+            .match_all()
+            .with_max_count(10)
+            .into_parts()
+            .unwrap();
+        assert_eq!(query, "((kMDItemFSName == \"*\"))");
+        assert_eq!(max_count, Some(10));
+        assert!(MDQueryBuilder::default()
+            .match_all()
+            .with_scopes([MDQueryScope::Home])
+            .into_parts()
+            .is_ok());
+        assert!(MDQueryBuilder::default().into_parts().is_err());
+
+        // An empty scope list searches everywhere, so it does not count as a scope.
+        let err = MDQueryBuilder::default()
+            .match_all()
+            .with_scopes([])
+            .into_parts()
+            .err()
+            .unwrap();
+        assert_eq!(
+            builder_errors(&err),
+            ["match_all: a search scope or max count is required"]
+        );
+    }
+
+    #[test]
+    fn test_into_parts_reports_all_errors() {
+        let err = MDQueryBuilder::default()
+            .match_all()
+            .top_k(0, MDItemKey::FSName, SortDirection::Ascending)
+            .into_parts()
+            .err()
+            .unwrap();
+        assert_eq!(
+            builder_errors(&err),
+            [
+                "top_k: k must be greater than 0",
+                "match_all: a search scope or max count is required",
+            ]
+        );
+
+        let err = MDQueryBuilder::default()
+            .extension("app")
+            .top_k(5, MDItemKey::FSName, SortDirection::Ascending)
+            .with_max_count(5)
+            .with_scopes([MDQueryScope::Network])
+            .local_volumes_only()
+            .into_parts()
+            .err()
+            .unwrap();
+        assert_eq!(
+            builder_errors(&err),
+            [
+                "top_k: max_count truncates the results before they are sorted, use only top_k",
+                "local_volumes_only: all search scopes are network scopes",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_top_k_rejects_max_count_and_zero() {
+        let top_k = || {
+            MDQueryBuilder::default().extension("app").top_k(
+                5,
+                MDItemKey::ModificationDate,
+                SortDirection::Descending,
+            )
+        };
+        assert!(top_k().into_parts().is_ok());
+
+        let err = top_k().with_max_count(5).into_parts().err().unwrap();
+        assert_eq!(
+            builder_errors(&err),
+            ["top_k: max_count truncates the results before they are sorted, use only top_k"]
+        );
+
+        let err = MDQueryBuilder::default()
+            .extension("app")
+            .top_k(0, MDItemKey::FSName, SortDirection::Ascending)
+            .into_parts()
+            .err()
+            .unwrap();
+        assert_eq!(builder_errors(&err), ["top_k: k must be greater than 0"]);
+
+        let merged = MDQueryBuilder::default()
+            .extension("pdf")
+            .merge(top_k(), MDQueryConditionType::All);
+        assert_eq!(
+            merged.top_k,
+            Some((5, MDItemKey::ModificationDate, SortDirection::Descending))
+        );
+    }
+
+    #[test]
+    fn test_item_key_from_str() {
+        let mut seen = std::collections::HashSet::new();
+        for &key in MDItemKey::ALL {
+            assert!(seen.insert(key), "{key} is listed twice");
+            assert_eq!(key.as_str().parse::<MDItemKey>(), Ok(key));
+        }
+        assert_eq!("name".parse(), Ok(MDItemKey::DisplayName));
+        assert_eq!("Size".parse(), Ok(MDItemKey::Size));
+        assert_eq!("modified".parse(), Ok(MDItemKey::ModificationDate));
+        assert_eq!("added".parse(), Ok(MDItemKey::DateAdded));
+        assert_eq!(
+            "kmditemfssize".parse::<MDItemKey>(),
+            Err(MDQueryError::UnknownAttribute("kmditemfssize".to_string()))
+        );
+        assert!("".parse::<MDItemKey>().is_err());
+    }
+
+    #[test]
+    fn test_item_key_kinds() {
+        let mut names = std::collections::HashSet::new();
+        for key in MDItemKey::ALL {
+            assert!(key.as_str().starts_with("kMDItem"), "{key}");
+            assert!(names.insert(key.as_str()), "{key} is used twice");
+            assert_eq!(key.to_string(), key.as_str());
+            assert_eq!(
+                key.is_time(),
+                matches!(key.kind(), MDItemKeyKind::Date | MDItemKeyKind::DateArray)
+            );
+        }
+        assert_eq!(MDItemKey::DisplayName.kind(), MDItemKeyKind::Str);
+        assert_eq!(MDItemKey::Size.kind(), MDItemKeyKind::Number);
+        assert_eq!(MDItemKey::Latitude.kind(), MDItemKeyKind::Number);
+        assert_eq!(MDItemKey::DateAdded.kind(), MDItemKeyKind::Date);
+        assert_eq!(MDItemKey::FSInvisible.kind(), MDItemKeyKind::Bool);
+        assert_eq!(MDItemKey::UserTags.kind(), MDItemKeyKind::StrArray);
+        assert_eq!(MDItemKey::UsedDates.kind(), MDItemKeyKind::DateArray);
+        assert_eq!(MDItemKey::DownloadedDate.kind(), MDItemKeyKind::DateArray);
+        assert_eq!(MDItemKey::UserTags.as_str(), "kMDItemUserTags");
+        assert_eq!("tags".parse(), Ok(MDItemKey::UserTags));
+    }
+
+    #[test]
+    fn test_compare_expressions() {
+        let date = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+        let cases: Vec<(MDItemKey, MDQueryCompareOp, MDQueryValue, &str)> = vec![
+            (
+                MDItemKey::Title,
+                MDQueryCompareOp::Equal,
+                "a \"b\" *".into(),
+                r#"kMDItemTitle == "a \"b\" \*""#,
+            ),
+            (
+                MDItemKey::Keywords,
+                MDQueryCompareOp::Equal,
+                String::from("rust").into(),
+                r#"kMDItemKeywords == "rust""#,
+            ),
+            (
+                MDItemKey::NumberOfPages,
+                MDQueryCompareOp::GreaterThan,
+                10.into(),
+                "kMDItemNumberOfPages > 10",
+            ),
+            (
+                MDItemKey::FSOwnerUserID,
+                MDQueryCompareOp::Equal,
+                501u32.into(),
+                "kMDItemFSOwnerUserID == 501",
+            ),
+            (
+                MDItemKey::Size,
+                MDQueryCompareOp::LessThanOrEqual,
+                FileSize::kb(4).into(),
+                "kMDItemFSSize <= 4000",
+            ),
+            (
+                MDItemKey::Latitude,
+                MDQueryCompareOp::GreaterThanOrEqual,
+                (-12.5).into(),
+                "kMDItemLatitude >= -12.5",
+            ),
+            (
+                MDItemKey::DurationSeconds,
+                MDQueryCompareOp::LessThan,
+                60i64.into(),
+                "kMDItemDurationSeconds < 60",
+            ),
+            (
+                MDItemKey::FSInvisible,
+                MDQueryCompareOp::Equal,
+                true.into(),
+                "kMDItemFSInvisible == 1",
+            ),
+            (
+                MDItemKey::IsScreenCapture,
+                MDQueryCompareOp::Equal,
+                false.into(),
+                "kMDItemIsScreenCapture == 0",
+            ),
+            (
+                MDItemKey::DateAdded,
+                MDQueryCompareOp::GreaterThan,
+                date.into(),
+                "kMDItemDateAdded > $time.iso(2024-01-02T03:04:05+00:00)",
+            ),
+            (
+                MDItemKey::UsedDates,
+                MDQueryCompareOp::LessThanOrEqual,
+                date.into(),
+                "kMDItemUsedDates <= $time.iso(2024-01-02T03:04:05+00:00)",
+            ),
+        ];
+        for (key, op, value, expected) in cases {
+            let (query, _, _) = MDQueryBuilder::default()
+                .compare(key, op, value)
+                .into_parts()
+                .unwrap();
+            assert_eq!(query, format!("(({}))", expected));
+        }
+    }
+
+    #[test]
+    fn test_compare_rejects_mismatches() {
+        let date = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+        let err = MDQueryBuilder::default()
+            .compare(MDItemKey::Size, MDQueryCompareOp::GreaterThan, "large")
+            .compare(MDItemKey::Title, MDQueryCompareOp::Equal, date)
+            .compare(MDItemKey::Title, MDQueryCompareOp::GreaterThan, "a")
+            .compare(MDItemKey::FSInvisible, MDQueryCompareOp::LessThan, true)
+            .compare(MDItemKey::DateAdded, MDQueryCompareOp::Equal, 5)
+            .compare(MDItemKey::Authors, MDQueryCompareOp::Equal, false)
+            .compare(MDItemKey::Latitude, MDQueryCompareOp::Equal, f64::NAN)
+            .into_parts()
+            .err()
+            .unwrap();
+        assert_eq!(
+            builder_errors(&err),
+            [
+                "compare: Str value cannot be compared with kMDItemFSSize, which holds Number \
+                 values",
+                "compare: Date value cannot be compared with kMDItemTitle, which holds Str values",
+                "compare: Str values only support Equal, not >",
+                "compare: Bool values only support Equal, not <",
+                "compare: Int value cannot be compared with kMDItemDateAdded, which holds Date \
+                 values",
+                "compare: Bool value cannot be compared with kMDItemAuthors, which holds \
+                 StrArray values",
+                "compare: NaN is not a finite number",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_text_search_query() {
+        assert_eq!(
+            text_search_query("  quarterly re*port ").unwrap(),
+            "(((kMDItemTextContent == \"quarterly*\"cdw) || \
+             (kMDItemDisplayName == \"quarterly*\"cdw) || (kMDItemKeywords == \"quarterly*\"cdw)) \
+             && ((kMDItemTextContent == \"re\\*port*\"cdw) || \
+             (kMDItemDisplayName == \"re\\*port*\"cdw) || (kMDItemKeywords == \"re\\*port*\"cdw)))"
+        );
+        let err = text_search_query(" \t").err().unwrap();
+        assert_eq!(builder_errors(&err), ["search_text: search term is empty"]);
+    }
+
+    #[test]
+    fn test_file_size_units() {
+        assert_eq!(FileSize::bytes(10).as_bytes(), 10);
+        assert_eq!(FileSize::kb(2).as_bytes(), 2_000);
+        assert_eq!(FileSize::mb(3).as_bytes(), 3_000_000);
+        assert_eq!(FileSize::gb(1).as_bytes(), 1_000_000_000);
+        assert_eq!(FileSize::kib(2).as_bytes(), 2_048);
+        assert_eq!(FileSize::mib(100).as_bytes(), 104_857_600);
+        assert_eq!(FileSize::gib(1).as_bytes(), 1_073_741_824);
+        assert_eq!(FileSize::from(42), FileSize::bytes(42));
+        assert_eq!(FileSize::kb(u64::MAX).as_bytes(), u64::MAX);
+        assert_eq!(FileSize::gb(u64::MAX / 1000).as_bytes(), u64::MAX);
+        assert_eq!(FileSize::gib(u64::MAX).as_bytes(), u64::MAX);
+    }
+
+    #[test]
+    fn test_size_expressions() {
+        let builder = MDQueryBuilder::default()
+            .size(MDQueryCompareOp::GreaterThan, FileSize::mib(100))
+            .size(MDQueryCompareOp::LessThan, 5_000u64)
+            .size_of(
+                MDItemKey::PhysicalSize,
+                MDQueryCompareOp::GreaterThanOrEqual,
+                FileSize::kb(4),
+            )
+            .size_between(FileSize::mb(1), FileSize::gb(1));
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            "((kMDItemFSSize > 104857600) && (kMDItemFSSize < 5000) && \
+             (kMDItemPhysicalSize >= 4000) && (InRange(kMDItemFSSize, 1000000, 1000000000)))"
+        );
+    }
+
+    #[test]
+    fn test_size_of_non_size_key() {
+        let err = MDQueryBuilder::default()
+            .size_of(MDItemKey::DisplayName, MDQueryCompareOp::Equal, 1u64)
+            .execute()
+            .err()
+            .unwrap();
+        assert!(matches!(
+            err.downcast_ref::<MDQueryError>(),
+            Some(MDQueryError::InvalidBuilder(_))
+        ));
+    }
+
+    #[test]
+    fn test_escape_query_value() {
+        assert_eq!(escape_query_value("Safari"), "Safari");
+        assert_eq!(
+            escape_query_value(r#"a "quoted" *star* \path"#),
+            r#"a \"quoted\" \*star\* \\path"#
+        );
+    }
+
+    #[test]
+    fn test_name_prefix_suffix_expressions() {
+        let builder = MDQueryBuilder::default()
+            .name_starts_with("Saf")
+            .name_ends_with("ari")
+            .fs_name_starts_with("Safari")
+            .fs_name_ends_with(".app");
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            "((kMDItemDisplayName == \"Saf*\"cdw) && (kMDItemDisplayName == \"*ari\"cd) && \
+             (kMDItemFSName == \"Safari*\"cd) && (kMDItemFSName == \"*.app\"cd))"
+        );
+    }
+
+    #[test]
+    fn test_name_starts_with_escaping() {
+        let builder = MDQueryBuilder::default().name_starts_with("my \"*file");
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            r#"((kMDItemDisplayName == "my \"\*file*"cdw))"#
+        );
+    }
+
+    #[test]
+    fn test_match_modifiers_suffix() {
+        assert_eq!(MatchModifiers::NONE.to_query_string(), "");
+        assert_eq!(MatchModifiers::default(), MatchModifiers::NONE);
+        assert_eq!(MatchModifiers::CASE_INSENSITIVE.to_query_string(), "c");
+        assert_eq!(MatchModifiers::DIACRITIC_INSENSITIVE.to_query_string(), "d");
+        assert_eq!(MatchModifiers::WORD_BASED.to_query_string(), "w");
+        assert_eq!(
+            MatchModifiers::CASE_DIACRITIC_INSENSITIVE.to_query_string(),
+            "cd"
+        );
+        assert_eq!(
+            (MatchModifiers::WORD_BASED
+                | MatchModifiers::DIACRITIC_INSENSITIVE
+                | MatchModifiers::CASE_INSENSITIVE)
+                .to_query_string(),
+            "cdw"
+        );
+        assert_eq!(
+            (MatchModifiers::WORD_BASED | MatchModifiers::CASE_INSENSITIVE).to_query_string(),
+            "cw"
+        );
+    }
+
+    #[test]
+    fn test_name_with_modifiers() {
+        let builder = MDQueryBuilder::default()
+            .name_like("report")
+            .name_is("Report")
+            .name_like_with("cafe", MatchModifiers::DIACRITIC_INSENSITIVE)
+            .name_is_with("Café", MatchModifiers::NONE);
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            "((kMDItemDisplayName == \"*report*\"w) && (kMDItemDisplayName == \"Report\"c) && \
+             (kMDItemDisplayName == \"*cafe*\"d) && (kMDItemDisplayName == \"Café\"))"
+        );
+    }
+
+    #[test]
+    fn test_path_prefix_expressions() {
+        let builder = MDQueryBuilder::default()
+            .path_starts_with("/Users/me/My \"Projects\"/")
+            .exclude_path_prefix(Path::new("/Users/me/My \"Projects\"/node_modules"))
+            .exclude_path_prefix("/Users/me/Library");
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            r#"((kMDItemPath == "/Users/me/My \"Projects\"/*") && (kMDItemPath != "/Users/me/My \"Projects\"/node_modules/*") && (kMDItemPath != "/Users/me/Library/*"))"#
+        );
+    }
+
+    #[test]
+    fn test_exclude_system_noise() {
+        let excluded = |path: PathBuf| format!("(kMDItemPath != \"{}/*\")", path.display());
+        assert_eq!(
+            MDQueryBuilder::default()
+                .name_like("report")
+                .exclude_system_noise()
+                .condition
+                .into_expression()
+                .unwrap(),
+            format!(
+                "((kMDItemDisplayName == \"*report*\"w) && {} && \
+                 (kMDItemPath != \"/Volumes/com.apple.TimeMachine.localsnapshots/*\") && \
+                 (kMDItemPath != \"/Volumes/.timemachine/*\") && \
+                 (kMDItemPath != \"/.MobileBackups/*\") && {} && \
+                 (kMDItemPath != \"/Library/Caches/*\"))",
+                excluded(user_directory(UserDirectory::Trash)),
+                excluded(user_directory(UserDirectory::Caches)),
+            )
+        );
+        assert_eq!(
+            MDQueryBuilder::default()
+                .exclude_library()
+                .condition
+                .into_expression()
+                .unwrap(),
+            format!("({})", excluded(user_directory(UserDirectory::Library)))
+        );
+    }
+
+    #[test]
+    fn test_include_hidden() {
+        let builder = MDQueryBuilder::default()
+            .name_like("config")
+            .include_hidden(true);
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            "((kMDItemDisplayName == \"*config*\"w))"
+        );
+
+        let builder = MDQueryBuilder::default()
+            .name_like("config")
+            .include_hidden(false);
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            "((kMDItemDisplayName == \"*config*\"w) && (kMDItemFSInvisible != 1))"
+        );
+    }
+
+    #[test]
+    fn test_kind_expression() {
+        let builder = MDQueryBuilder::default().kind("PDF \"Document\"");
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            r#"((kMDItemKind == "PDF \"Document\""c))"#
+        );
+    }
+
+    #[test]
+    fn test_of_kind_expressions() {
+        let cases = [
+            (
+                ContentKind::Image,
+                r#"(kMDItemContentTypeTree == "public.image")"#,
+            ),
+            (
+                ContentKind::Movie,
+                r#"(kMDItemContentTypeTree == "public.movie")"#,
+            ),
+            (
+                ContentKind::Audio,
+                r#"(kMDItemContentTypeTree == "public.audio")"#,
+            ),
+            (
+                ContentKind::Pdf,
+                r#"(kMDItemContentTypeTree == "com.adobe.pdf")"#,
+            ),
+            (
+                ContentKind::Text,
+                r#"(kMDItemContentTypeTree == "public.text")"#,
+            ),
+            (
+                ContentKind::Archive,
+                r#"((kMDItemContentTypeTree == "public.archive") || (kMDItemContentTypeTree == "public.zip-archive") || (kMDItemContentTypeTree == "org.gnu.gnu-zip-archive") || (kMDItemContentTypeTree == "public.tar-archive"))"#,
+            ),
+            (
+                ContentKind::Application,
+                r#"(kMDItemContentTypeTree == "com.apple.application")"#,
+            ),
+            (
+                ContentKind::Folder,
+                r#"(kMDItemContentTypeTree == "public.folder")"#,
+            ),
+            (
+                ContentKind::Presentation,
+                r#"(kMDItemContentTypeTree == "public.presentation")"#,
+            ),
+            (
+                ContentKind::Spreadsheet,
+                r#"(kMDItemContentTypeTree == "public.spreadsheet")"#,
+            ),
+            (
+                ContentKind::SourceCode,
+                r#"(kMDItemContentTypeTree == "public.source-code")"#,
+            ),
+            (
+                ContentKind::Font,
+                r#"(kMDItemContentTypeTree == "public.font")"#,
+            ),
+            (
+                ContentKind::Email,
+                r#"((kMDItemContentTypeTree == "public.email-message") || (kMDItemContentTypeTree == "com.apple.mail.emlx"))"#,
+            ),
+            (
+                ContentKind::Bookmark,
+                r#"((kMDItemContentTypeTree == "com.apple.safari.bookmark") || (kMDItemContentTypeTree == "com.apple.web-internet-location"))"#,
+            ),
+            (
+                ContentKind::Document,
+                r#"((kMDItemContentTypeTree == "public.content") && (kMDItemContentTypeTree != "public.image") && (kMDItemContentTypeTree != "public.movie") && (kMDItemContentTypeTree != "public.audio"))"#,
+            ),
+        ];
+        for (kind, expected) in cases {
+            let builder = MDQueryBuilder::default().of_kind(kind);
+            assert_eq!(
+                builder.condition.into_expression().unwrap(),
+                format!("({})", expected)
+            );
+        }
+    }
+
+    #[test]
+    fn test_document_kind_under_any_root() {
+        let builder = MDQueryBuilder::from_condition(MDQueryCondition::any([
+            MDQueryConditionExpression::Expression("kMDItemFSName == \"notes.txt\"".into()),
+        ]))
+        .of_kind(ContentKind::Document);
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            r#"((kMDItemFSName == "notes.txt") || ((kMDItemContentTypeTree == "public.content") && (kMDItemContentTypeTree != "public.image") && (kMDItemContentTypeTree != "public.movie") && (kMDItemContentTypeTree != "public.audio")))"#
+        );
+    }
+
+    #[test]
+    fn test_min_video_resolution_expression() {
+        assert_eq!(
+            MDQueryBuilder::default()
+                .min_video_resolution(1920, 1080)
+                .condition
+                .into_expression()
+                .unwrap(),
+            r#"((kMDItemContentTypeTree == "public.movie") && (kMDItemPixelWidth >= 1920) && (kMDItemPixelHeight >= 1080))"#
+        );
+    }
+
+    #[test]
+    fn test_exposure_expressions() {
+        assert_eq!(
+            MDQueryBuilder::default()
+                .iso(MDQueryCompareOp::GreaterThanOrEqual, 3200)
+                .focal_length(MDQueryCompareOp::LessThan, 35.0)
+                .exposure_time(MDQueryCompareOp::GreaterThan, 1.0 / 8.0)
+                .f_number(MDQueryCompareOp::LessThanOrEqual, 1.8)
+                .exposure_time(MDQueryCompareOp::LessThan, 0.00001)
+                .condition
+                .into_expression()
+                .unwrap(),
+            "((kMDItemISOSpeed >= 3200) && (kMDItemFocalLength < 35) && \
+             (kMDItemExposureTimeSeconds > 0.125) && (kMDItemFNumber <= 1.8) && \
+             (kMDItemExposureTimeSeconds < 0.00001))"
+        );
+
+        let err = MDQueryBuilder::default()
+            .f_number(MDQueryCompareOp::Equal, f64::NAN)
+            .build([MDQueryScope::Home], None)
+            .err()
+            .unwrap();
+        assert_eq!(
+            builder_errors(&err),
+            ["f_number: NaN is not a finite number"]
+        );
+    }
+
+    #[test]
+    fn test_image_technical_expressions() {
+        assert_eq!(
+            MDQueryBuilder::default()
+                .bits_per_sample(MDQueryCompareOp::GreaterThan, 8)
+                .has_alpha_channel(true)
+                .color_space("CMYK")
+                .has_alpha_channel(false)
+                .condition
+                .into_expression()
+                .unwrap(),
+            r#"((kMDItemBitsPerSample > 8) && (kMDItemHasAlphaChannel == 1) && (kMDItemColorSpace == "CMYK"c) && (kMDItemHasAlphaChannel == 0))"#
+        );
+    }
+
+    #[test]
+    fn test_image_orientation() {
+        assert_eq!(ImageOrientation::from_value(0), ImageOrientation::Landscape);
+        assert_eq!(ImageOrientation::from_value(1), ImageOrientation::Portrait);
+        assert_eq!(ImageOrientation::from_value(7), ImageOrientation::Other(7));
+        for value in [0, 1, 7] {
+            assert_eq!(ImageOrientation::from_value(value).value(), value);
+        }
+    }
+
+    #[test]
+    fn test_uses_font_expression() {
+        assert_eq!(
+            MDQueryBuilder::default()
+                .of_kind(ContentKind::Font)
+                .uses_font("Helvetica \"Neue\"")
+                .condition
+                .into_expression()
+                .unwrap(),
+            r#"((kMDItemContentTypeTree == "public.font") && (kMDItemFonts == "Helvetica \"Neue\""c))"#
+        );
+    }
+
+    #[test]
+    fn test_app_category_expressions() {
+        assert_eq!(
+            MDQueryBuilder::default()
+                .is_app()
+                .app_category("Productivity")
+                .condition
+                .into_expression()
+                .unwrap(),
+            r#"((kMDItemContentType == "com.apple.application-bundle") && (kMDItemAppStoreCategory == "Productivity"c))"#
+        );
+        assert_eq!(
+            MDQueryBuilder::default()
+                .app_category("public.app-category.developer-tools")
+                .has_app_category(true)
+                .condition
+                .into_expression()
+                .unwrap(),
+            r#"((kMDItemAppStoreCategoryType == "public.app-category.developer-tools") && (kMDItemAppStoreCategory == "*"))"#
+        );
+        assert_eq!(
+            MDQueryBuilder::default()
+                .has_app_category(false)
+                .condition
+                .into_expression()
+                .unwrap(),
+            r#"((kMDItemAppStoreCategory != "*"))"#
+        );
+    }
+
+    #[test]
+    fn test_architecture_expressions() {
+        assert_eq!(
+            MDQueryBuilder::default()
+                .has_architecture("arm64")
+                .missing_architecture("x86_64")
+                .condition
+                .into_expression()
+                .unwrap(),
+            r#"((kMDItemExecutableArchitectures == "arm64") && (kMDItemExecutableArchitectures == "*") && (kMDItemExecutableArchitectures != "x86_64"))"#
+        );
+    }
+
+    #[test]
+    fn test_package_expressions() {
+        assert_eq!(
+            MDQueryBuilder::default()
+                .is_package(true)
+                .condition
+                .into_expression()
+                .unwrap(),
+            r#"(((kMDItemContentTypeTree == "com.apple.package") || (kMDItemContentTypeTree == "com.apple.bundle")))"#
+        );
+        assert_eq!(
+            MDQueryBuilder::default()
+                .is_package(false)
+                .condition
+                .into_expression()
+                .unwrap(),
+            r#"((kMDItemContentTypeTree != "com.apple.package") && (kMDItemContentTypeTree != "com.apple.bundle"))"#
+        );
+        assert_eq!(
+            MDQueryBuilder::default()
+                .is_framework()
+                .condition
+                .into_expression()
+                .unwrap(),
+            r#"((kMDItemContentTypeTree == "com.apple.framework"))"#
+        );
+    }
+
+    #[test]
+    fn test_content_type_tree_expressions() {
+        let builder = MDQueryBuilder::default()
+            .content_type_conforms_to("public.image")
+            .is_dir_by_tree(false);
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            r#"((kMDItemContentTypeTree == "public.image") && (kMDItemContentTypeTree != "public.folder"))"#
+        );
+    }
+
+    #[test]
+    fn test_bundle_identifier_expression() {
+        let builder = MDQueryBuilder::default().bundle_identifier("com.apple.Safari");
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            r#"((kMDItemCFBundleIdentifier == "com.apple.Safari"c))"#
+        );
+    }
+
+    #[test]
+    fn test_author_creator_expressions() {
+        let builder = MDQueryBuilder::default()
+            .author("Jane Doe")
+            .created_by_app("Word");
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            r#"((kMDItemAuthors == "Jane Doe"c) && (kMDItemCreator == "*Word*"c))"#
+        );
+    }
+
+    #[test]
+    fn test_keyword_expressions() {
+        let builder = MDQueryBuilder::default()
+            .keyword("holiday")
+            .keyword_with("Paris", MatchModifiers::NONE)
+            .keywords_any(&["beach", "sea \"side\""]);
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            r#"((kMDItemKeywords == "holiday"cw) && (kMDItemKeywords == "Paris") && ((kMDItemKeywords == "beach"cw) || (kMDItemKeywords == "sea \"side\""cw)))"#
+        );
+
+        let err = MDQueryBuilder::default()
+            .keywords_any(&[])
+            .execute()
+            .err()
+            .unwrap();
+        assert!(matches!(
+            err.downcast_ref::<MDQueryError>(),
+            Some(MDQueryError::InvalidBuilder(_))
+        ));
+    }
+
+    #[test]
+    fn test_finder_comment_contains() {
+        let builder = MDQueryBuilder::default().finder_comment_contains(r#"ticket "ABC-12""#);
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            r#"((kMDItemFinderComment == "*ticket \"ABC-12\"*"cdw))"#
+        );
+    }
+
+    #[test]
+    fn test_download_expressions() {
+        let time = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let builder = MDQueryBuilder::default()
+            .downloaded_from_domain("github.com")
+            .downloaded_after(time);
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            r#"((kMDItemWhereFroms == "*github.com*"c) && (kMDItemDownloadedDate > $time.iso(2023-11-14T22:13:20+00:00)))"#
+        );
+    }
+
+    #[test]
+    fn test_pixel_dimension_expressions() {
+        let builder = MDQueryBuilder::default()
+            .min_resolution(3840, 2160)
+            .pixel_width(MDQueryCompareOp::LessThan, 8000);
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            "((kMDItemPixelWidth >= 3840) && (kMDItemPixelHeight >= 2160) && (kMDItemPixelWidth < 8000))"
+        );
+    }
+
+    #[test]
+    fn test_camera_expressions() {
+        let builder = MDQueryBuilder::default()
+            .camera_make("Fujifilm")
+            .camera_model("X-T4");
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            r#"((kMDItemAcquisitionMake == "Fujifilm"c) && (kMDItemAcquisitionModel == "X-T4"c))"#
+        );
+    }
+
+    #[test]
+    fn test_duration_expressions() {
+        let builder = MDQueryBuilder::default()
+            .duration(MDQueryCompareOp::GreaterThan, Duration::from_secs(30 * 60))
+            .duration(MDQueryCompareOp::LessThan, Duration::from_millis(9_250));
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            "((kMDItemDurationSeconds > 1800) && (kMDItemDurationSeconds < 9.25))"
+        );
+    }
+
+    #[test]
+    fn test_music_expressions() {
+        let builder = MDQueryBuilder::default()
+            .album("Abbey Road")
+            .artist("The Beatles")
+            .genre("Rock")
+            .recording_year(MDQueryCompareOp::LessThan, 1970);
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            r#"((kMDItemAlbum == "Abbey Road"c) && (kMDItemAuthors == "The Beatles"c) && (kMDItemMusicalGenre == "Rock"c) && (kMDItemRecordingYear < 1970))"#
+        );
+    }
+
+    #[test]
+    fn test_av_technical_expressions() {
+        let builder = MDQueryBuilder::default()
+            .audio_sample_rate(MDQueryCompareOp::GreaterThanOrEqual, 44_100.0)
+            .audio_channel_count(MDQueryCompareOp::Equal, 1)
+            .total_bit_rate(MDQueryCompareOp::LessThan, 320.5)
+            .video_bit_rate(MDQueryCompareOp::GreaterThan, 8000.0)
+            .codec("HEVC");
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            r#"((kMDItemAudioSampleRate >= 44100) && (kMDItemAudioChannelCount == 1) && (kMDItemTotalBitRate < 320.5) && (kMDItemVideoBitRate > 8000) && (kMDItemCodecs == "HEVC"c))"#
+        );
+
+        let err = MDQueryBuilder::default()
+            .audio_sample_rate(MDQueryCompareOp::Equal, f64::NAN)
+            .total_bit_rate(MDQueryCompareOp::LessThan, f64::INFINITY)
+            .video_bit_rate(MDQueryCompareOp::GreaterThan, f64::NEG_INFINITY)
+            .build([MDQueryScope::Home], None)
+            .err()
+            .unwrap();
+        assert_eq!(
+            builder_errors(&err),
+            [
+                "audio_sample_rate: NaN is not a finite number",
+                "total_bit_rate: inf is not a finite number",
+                "video_bit_rate: -inf is not a finite number",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_page_count_expressions() {
+        let builder = MDQueryBuilder::default()
+            .extension("pdf")
+            .page_count(MDQueryCompareOp::GreaterThan, 100);
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            r#"((kMDItemFSName == "*.pdf"c) && (kMDItemNumberOfPages > 100))"#
+        );
+    }
+
+    #[test]
+    fn test_email_expressions() {
+        let builder = MDQueryBuilder::default()
+            .subject_contains("Invoice")
+            .from_email("billing@example.com")
+            .to_email("me@example.com");
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            r#"((kMDItemSubject == "*Invoice*"cd) && (kMDItemAuthorEmailAddresses == "billing@example.com"c) && (kMDItemRecipientEmailAddresses == "me@example.com"c))"#
+        );
+    }
+
+    #[test]
+    fn test_url_contains() {
+        let builder = MDQueryBuilder::default()
+            .content_type("com.apple.safari.bookmark")
+            .url_contains("example.com/?q=\"*\"");
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            r#"((kMDItemContentType == "com.apple.safari.bookmark") && (kMDItemURL == "*example.com/?q=\"\*\"*"c))"#
+        );
+    }
+
+    #[test]
+    fn test_use_count_expressions() {
+        let time = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let builder = MDQueryBuilder::default()
+            .is_app()
+            .use_count(MDQueryCompareOp::GreaterThan, 50)
+            .time_dt(MDItemKey::UsedDates, MDQueryCompareOp::GreaterThan, time);
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            r#"((kMDItemContentType == "com.apple.application-bundle") && (kMDItemUseCount > 50) && (kMDItemUsedDates > $time.iso(2023-11-14T22:13:20+00:00)))"#
+        );
+    }
+
+    #[test]
+    fn test_owner_and_node_count_expressions() {
+        let builder = MDQueryBuilder::default()
+            .owned_by_uid(501)
+            .folder_item_count(MDQueryCompareOp::GreaterThan, 10_000);
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            "((kMDItemFSOwnerUserID == 501) && (kMDItemFSNodeCount > 10000))"
+        );
+    }
+
+    #[test]
+    fn test_screenshot_expressions() {
+        let builder = MDQueryBuilder::default()
+            .is_screenshot()
+            .screenshot_type("window")
+            .time_relative(
+                MDItemKey::CreationDate,
+                MDQueryCompareOp::LessThan,
+                RelativeTime::Today(-30),
+            )
+            .size(MDQueryCompareOp::GreaterThan, FileSize::mb(5));
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            r#"((kMDItemIsScreenCapture == 1) && (kMDItemScreenCaptureType == "window"c) && (kMDItemContentCreationDate < $time.today(-30)) && (kMDItemFSSize > 5000000))"#
+        );
+    }
+
+    #[test]
+    fn test_is_password_protected() {
+        let builder = MDQueryBuilder::default()
+            .of_kind(ContentKind::Pdf)
+            .is_password_protected();
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            r#"((kMDItemContentTypeTree == "com.adobe.pdf") && (kMDItemSecurityMethod == "Password Encrypted"))"#
+        );
+    }
+
+    #[test]
+    fn test_document_text_expressions() {
+        let builder = MDQueryBuilder::default()
+            .title_contains("Annual Report")
+            .language("en");
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            r#"((kMDItemTitle == "*Annual Report*"cd) && (kMDItemLanguages == "en"c))"#
+        );
+    }
+
+    #[test]
+    fn test_version_is() {
+        let builder = MDQueryBuilder::default()
+            .is_app()
+            .bundle_identifier("com.apple.Safari")
+            .version_is("17.4.1");
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            r#"((kMDItemContentType == "com.apple.application-bundle") && (kMDItemCFBundleIdentifier == "com.apple.Safari"c) && (kMDItemVersion == "17.4.1"))"#
+        );
+    }
+
+    #[test]
+    fn test_relative_time_expressions() {
+        let cases = [
+            (RelativeTime::Now, "$time.now"),
+            (RelativeTime::SecondsFromNow(-60), "$time.now(-60)"),
+            (RelativeTime::Today(-7), "$time.today(-7)"),
+            (RelativeTime::ThisWeek(0), "$time.this_week(0)"),
+            (RelativeTime::ThisMonth(-1), "$time.this_month(-1)"),
+            (RelativeTime::ThisYear(1), "$time.this_year(1)"),
+            (
+                RelativeTime::Iso(DateTime::from_timestamp(0, 0).unwrap()),
+                "$time.iso(1970-01-01T00:00:00+00:00)",
+            ),
+        ];
+        for (time, expected) in cases {
+            assert_eq!(time.to_query_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_relative_time_helpers() {
+        let builder = MDQueryBuilder::default()
+            .modified_within_days(7)
+            .created_today()
+            .used_within(Duration::from_secs(3600));
+        assert_eq!(
+            builder.condition.into_expression().unwrap(),
+            "((kMDItemContentModificationDate >= $time.today(-7)) && \
+             (kMDItemContentCreationDate >= $time.today(0)) && \
+             (kMDItemLastUsedDate >= $time.now(-3600)))"
+        );
+    }
+
+    #[test]
+    fn test_time_relative_non_time_key() {
+        let err = MDQueryBuilder::default()
+            .time_relative(
+                MDItemKey::DisplayName,
+                MDQueryCompareOp::LessThan,
+                RelativeTime::Now,
+            )
+            .execute()
+            .err()
+            .unwrap();
+        assert!(matches!(
+            err.downcast_ref::<MDQueryError>(),
+            Some(MDQueryError::InvalidBuilder(_))
+        ));
+    }
+
+    #[test]
+    fn test_condition_all() {
+        let condition = MDQueryCondition::all([
+            "kMDItemFSName == \"test.txt\"",
+            "kMDItemTextContent == \"hello\"",
+        ]);
+        assert_eq!(
+            condition.into_expression().unwrap(),
+            "((kMDItemFSName == \"test.txt\") && (kMDItemTextContent == \"hello\"))"
+        );
+    }
+
+    #[test]
+    fn test_condition_any() {
+        let condition = MDQueryCondition::any([
+            "kMDItemFSName == \"doc.pdf\"".to_string(),
+            "kMDItemFSName == \"doc.txt\"".to_string(),
+        ]);
+        assert_eq!(
+            condition.into_expression().unwrap(),
+            "((kMDItemFSName == \"doc.pdf\") || (kMDItemFSName == \"doc.txt\"))"
+        );
+    }
+
+    #[test]
+    fn test_nested_condition() {
+        let inner_condition =
+            MDQueryCondition::any(["kMDItemFSName == \"*.txt\"", "kMDItemFSName == \"*.pdf\""]);
+
+        let outer_condition = MDQueryCondition::default()
+            .push(inner_condition)
+            .push("kMDItemTextContent == \"test\"");
+
+        assert_eq!(
+            outer_condition.into_expression().unwrap(),
+            "(((kMDItemFSName == \"*.txt\") || (kMDItemFSName == \"*.pdf\")) && (kMDItemTextContent == \"test\"))"
+        );
+    }
+
+    #[test]
+    fn test_empty_condition() {
+        let condition = MDQueryCondition::default();
+        let err = condition.to_expression().unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<MDQueryError>(),
+            Some(&MDQueryError::EmptyConditionGroup("root".to_string()))
+        );
+        assert!(condition.build([MDQueryScope::Computer], None).is_err());
+    }
+
+    #[test]
+    fn test_nested_empty_condition() {
+        let condition = MDQueryCondition::all([
+            "kMDItemFSName == \"a\"".into(),
+            MDQueryConditionExpression::from(MDQueryCondition::any([
+                MDQueryConditionExpression::from("kMDItemFSName == \"b\""),
+                MDQueryCondition::default().into(),
+            ])),
+        ]);
+        let err = MDQueryBuilder::default()
+            .name_is("c")
+            .condition(condition)
+            .build([MDQueryScope::Computer], None)
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.downcast_ref::<MDQueryError>(),
+            Some(&MDQueryError::EmptyConditionGroup(
+                "root[1][1][1]".to_string()
+            ))
+        );
+        assert_eq!(
+            err.to_string(),
+            "Condition group root[1][1][1] has no expressions"
+        );
+    }
+
+    fn assert_json_round_trip(builder: MDQueryBuilder) {
+        let json = builder.to_json();
+        let restored = MDQueryBuilder::from_json(&json).unwrap();
+        assert_eq!(
+            restored.to_query_string().ok(),
+            builder.to_query_string().ok(),
+            "{json}"
+        );
+        assert_eq!(restored, builder, "{json}");
+        assert_eq!(restored.to_json(), json);
+    }
+
+    #[test]
+    fn test_json_round_trip_every_method() {
+        let time = Utc.with_ymd_and_hms(2024, 5, 6, 7, 8, 9).unwrap() + Duration::from_millis(250);
+        let op = MDQueryCompareOp::GreaterThanOrEqual;
+        let modifiers = MatchModifiers::CASE_INSENSITIVE | MatchModifiers::WORD_BASED;
+        let report = MDQueryBuilder::default()
+            .name_like("report")
+            .size(MDQueryCompareOp::GreaterThan, FileSize::mb(1));
+        let builders = [
+            MDQueryBuilder::default().extension("pdf").with_scopes([
+                MDQueryScope::Home,
+                MDQueryScope::Computer,
+                MDQueryScope::Network,
+                MDQueryScope::AllIndexed,
+                MDQueryScope::ComputerIndexed,
+                MDQueryScope::NetworkIndexed,
+                MDQueryScope::Custom(PathBuf::from("/tmp/a \"b\"")),
+                MDQueryScope::Documents,
+                MDQueryScope::Downloads,
+                MDQueryScope::Desktop,
+                MDQueryScope::Pictures,
+                MDQueryScope::Movies,
+                MDQueryScope::Music,
+                MDQueryScope::Applications,
+                MDQueryScope::ICloudDrive,
+                MDQueryScope::ICloud,
+            ]),
+            MDQueryBuilder::default().extension("pdf").with_max_count(5),
+            MDQueryBuilder::default()
+                .extension("pdf")
+                .top_k(3, MDItemKey::ModificationDate, SortDirection::Descending)
+                .top_k(0, MDItemKey::Size, SortDirection::Ascending),
+            MDQueryBuilder::default()
+                .extension("pdf")
+                .with_scope_options(ScopeOptions::from_bits(4)),
+            MDQueryBuilder::from_condition(MDQueryCondition::any([
+                MDQueryConditionExpression::from("kMDItemFSSize > 10"),
+                MDQueryCondition::all(["kMDItemFSName == \"a\"", "kMDItemFSName == \"b\""]).into(),
+                report.clone().into(),
+            ])),
+            MDQueryBuilder::default()
+                .extension("pdf")
+                .condition(MDQueryCondition::any([
+                    report.clone(),
+                    MDQueryBuilder::default(),
+                ]))
+                .condition(report.clone().into()),
+            MDQueryBuilder::default()
+                .extension("pdf")
+                .merge(report.clone(), MDQueryConditionType::Any)
+                .merge(
+                    MDQueryBuilder::default().name_is("x"),
+                    MDQueryConditionType::All,
+                ),
+            MDQueryBuilder::from_raw("kMDItemFSName == \"*.p\\\"df\""),
+            MDQueryBuilder::default().match_all().with_max_count(1),
+            MDQueryBuilder::default().name_like("a*b").name_like(""),
+            MDQueryBuilder::default().name_like_with("ü", MatchModifiers::DIACRITIC_INSENSITIVE),
+            MDQueryBuilder::default()
+                .name_is("a")
+                .name_is("b")
+                .name_is(""),
+            MDQueryBuilder::default().name_is_with("a", MatchModifiers::NONE),
+            MDQueryBuilder::default().name_starts_with("saf"),
+            MDQueryBuilder::default().name_ends_with("ari"),
+            MDQueryBuilder::default().fs_name_starts_with("IMG_"),
+            MDQueryBuilder::default().fs_name_ends_with(".tar.gz"),
+            MDQueryBuilder::default().path_starts_with("/tmp/docs"),
+            MDQueryBuilder::default().exclude_path_prefix("/tmp/docs"),
+            MDQueryBuilder::default().exclude_system_noise(),
+            MDQueryBuilder::default().exclude_trash(),
+            MDQueryBuilder::default().exclude_time_machine(),
+            MDQueryBuilder::default().exclude_caches(),
+            MDQueryBuilder::default().exclude_library(),
+            MDQueryBuilder::default()
+                .local_volumes_only()
+                .with_scopes([MDQueryScope::Network]),
+            MDQueryBuilder::default()
+                .on_volume("/")
+                .on_volume("/Volumes/Backup"),
+            MDQueryBuilder::default()
+                .compare(MDItemKey::Title, MDQueryCompareOp::Equal, "Q3 \"final\"")
+                .compare(MDItemKey::NumberOfPages, MDQueryCompareOp::LessThan, 10)
+                .compare(MDItemKey::FocalLength, op, 1.5)
+                .compare(MDItemKey::FSInvisible, MDQueryCompareOp::Equal, true)
+                .compare(
+                    MDItemKey::CreationDate,
+                    MDQueryCompareOp::LessThanOrEqual,
+                    time,
+                )
+                .compare(MDItemKey::Title, MDQueryCompareOp::GreaterThan, "x")
+                .compare(MDItemKey::FocalLength, op, f64::NAN),
+            MDQueryBuilder::default()
+                .time(MDItemKey::ModificationDate, op, 1_700_000_000)
+                .time(MDItemKey::Title, op, 0)
+                .time(MDItemKey::ModificationDate, op, i64::MAX),
+            MDQueryBuilder::default()
+                .time_dt(MDItemKey::CreationDate, op, time)
+                .time_dt(MDItemKey::Size, op, time),
+            MDQueryBuilder::default()
+                .time_relative(MDItemKey::LastUsedDate, op, RelativeTime::Now)
+                .time_relative(
+                    MDItemKey::LastUsedDate,
+                    op,
+                    RelativeTime::SecondsFromNow(-60),
+                )
+                .time_relative(MDItemKey::LastUsedDate, op, RelativeTime::Today(-1))
+                .time_relative(MDItemKey::LastUsedDate, op, RelativeTime::ThisWeek(-2))
+                .time_relative(MDItemKey::LastUsedDate, op, RelativeTime::ThisMonth(-3))
+                .time_relative(MDItemKey::LastUsedDate, op, RelativeTime::ThisYear(4))
+                .time_relative(MDItemKey::LastUsedDate, op, RelativeTime::Iso(time)),
+            MDQueryBuilder::default().modified_within_days(7),
+            MDQueryBuilder::default().created_today(),
+            MDQueryBuilder::default().added_after(time),
+            MDQueryBuilder::default().added_within_days(3),
+            MDQueryBuilder::default()
+                .used_within(Duration::new(3600, 5))
+                .used_within(Duration::MAX),
+            MDQueryBuilder::default()
+                .time_in_range(MDItemKey::CreationDate, 0, 100)
+                .time_in_range(MDItemKey::CreationDate, 100, 0),
+            MDQueryBuilder::default().size(MDQueryCompareOp::LessThan, FileSize::gib(2)),
+            MDQueryBuilder::default()
+                .size_of(MDItemKey::PhysicalSize, op, 4096u64)
+                .size_of(MDItemKey::Title, op, 1u64),
+            MDQueryBuilder::default()
+                .size_in_range(FileSize::kib(4), FileSize::mib(1))
+                .size_in_range(2u64, 1u64),
+            MDQueryBuilder::default().size_between(1u64, 2u64),
+            MDQueryBuilder::default().is_dir(true).is_dir(false),
+            MDQueryBuilder::default().is_dir_by_tree(true),
+            MDQueryBuilder::default().is_app(),
+            MDQueryBuilder::default().is_package(true).is_package(false),
+            MDQueryBuilder::default().is_framework(),
+            MDQueryBuilder::default().exclude_aliases(),
+            MDQueryBuilder::default().only_symlinks(),
+            MDQueryBuilder::default()
+                .include_hidden(false)
+                .include_hidden(true),
+            MDQueryBuilder::default().title_contains("budget"),
+            MDQueryBuilder::default().language("en"),
+            MDQueryBuilder::default().is_password_protected(),
+            MDQueryBuilder::default().is_screenshot(),
+            MDQueryBuilder::default().screenshot_type("window"),
+            MDQueryBuilder::default().kind("PDF document"),
+            MDQueryBuilder::default()
+                .of_kind(ContentKind::Image)
+                .of_kind(ContentKind::Movie)
+                .of_kind(ContentKind::Audio)
+                .of_kind(ContentKind::Pdf)
+                .of_kind(ContentKind::Text),
+            MDQueryBuilder::default()
+                .of_kind(ContentKind::Archive)
+                .of_kind(ContentKind::Application)
+                .of_kind(ContentKind::Folder)
+                .of_kind(ContentKind::Presentation)
+                .of_kind(ContentKind::Spreadsheet),
+            MDQueryBuilder::default()
+                .of_kind(ContentKind::SourceCode)
+                .of_kind(ContentKind::Font)
+                .of_kind(ContentKind::Email)
+                .of_kind(ContentKind::Bookmark)
+                .of_kind(ContentKind::Document),
+            MDQueryBuilder::default().bundle_identifier("com.apple.Safari"),
+            MDQueryBuilder::default().app_category("public.app-category.games"),
+            MDQueryBuilder::default().has_app_category(false),
+            MDQueryBuilder::default().has_architecture("arm64"),
+            MDQueryBuilder::default().missing_architecture("x86_64"),
+            MDQueryBuilder::default().version_is("1.2"),
+            MDQueryBuilder::default().author("Ada"),
+            MDQueryBuilder::default().created_by_app("Pages"),
+            MDQueryBuilder::default().pixel_width(op, 1920),
+            MDQueryBuilder::default().pixel_height(op, 1080),
+            MDQueryBuilder::default().bits_per_sample(op, 16),
+            MDQueryBuilder::default().has_alpha_channel(true),
+            MDQueryBuilder::default().color_space("RGB"),
+            MDQueryBuilder::default().min_resolution(640, 480),
+            MDQueryBuilder::default().min_video_resolution(1280, 720),
+            MDQueryBuilder::default().duration(op, Duration::from_millis(1500)),
+            MDQueryBuilder::default().owned_by_uid(501),
+            MDQueryBuilder::default().folder_item_count(op, 10),
+            MDQueryBuilder::default()
+                .with_label(FinderLabel::None)
+                .with_label(FinderLabel::Red),
+            MDQueryBuilder::default().use_count(op, 3),
+            MDQueryBuilder::default().page_count(op, 20),
+            MDQueryBuilder::default().uses_font("Helvetica"),
+            MDQueryBuilder::default()
+                .audio_sample_rate(op, 44100.0)
+                .audio_sample_rate(op, f64::NAN),
+            MDQueryBuilder::default().audio_channel_count(op, 2),
+            MDQueryBuilder::default()
+                .total_bit_rate(op, 320.5)
+                .total_bit_rate(op, f64::INFINITY),
+            MDQueryBuilder::default()
+                .video_bit_rate(op, 1e-7)
+                .video_bit_rate(op, f64::NEG_INFINITY),
+            MDQueryBuilder::default().codec("H.264"),
+            MDQueryBuilder::default().album("Abbey Road"),
+            MDQueryBuilder::default().artist("The Beatles"),
+            MDQueryBuilder::default().genre("Rock"),
+            MDQueryBuilder::default().recording_year(op, 1969),
+            MDQueryBuilder::default().camera_make("Canon"),
+            MDQueryBuilder::default().camera_model("EOS R5"),
+            MDQueryBuilder::default().iso(op, 800),
+            MDQueryBuilder::default().focal_length(op, 35.0),
+            MDQueryBuilder::default().exposure_time(op, 1.0 / 250.0),
+            MDQueryBuilder::default().f_number(op, 2.8),
+            MDQueryBuilder::default().downloaded_from_domain("example.com"),
+            MDQueryBuilder::default().downloaded_after(time),
+            MDQueryBuilder::default().finder_comment_contains("todo"),
+            MDQueryBuilder::default().subject_contains("invoice"),
+            MDQueryBuilder::default().url_contains("example.com/a?b"),
+            MDQueryBuilder::default().from_email("a@example.com"),
+            MDQueryBuilder::default().to_email("b@example.com"),
+            MDQueryBuilder::default().keyword("tax"),
+            MDQueryBuilder::default().keyword_with("tax", modifiers),
+            MDQueryBuilder::default()
+                .keywords_any(&["tax", "receipt"])
+                .keywords_any(&[]),
+            MDQueryBuilder::default().extension("").extension("p*f"),
+            MDQueryBuilder::default()
+                .content_type("com.adobe.pdf")
+                .content_type("public.png"),
+            MDQueryBuilder::default().content_type_conforms_to("public.image"),
+        ];
+        for builder in builders {
+            assert_json_round_trip(builder);
+        }
+    }
+
+    #[test]
+    fn test_json_records_calls() {
+        let builder = MDQueryBuilder::default()
+            .exclude_system_noise()
+            .size_between(1u64, FileSize::kb(2))
+            .name_like_with("a\tb", MatchModifiers::CASE_DIACRITIC_INSENSITIVE);
+        assert_eq!(
+            builder.to_json(),
+            r#"{"version":1,"operations":[{"method":"exclude_system_noise","args":[]},{"method":"size_between","args":[1,2000]},{"method":"name_like_with","args":["a\tb","cd"]}]}"#
+        );
+    }
+
+    #[test]
+    fn test_json_applies_current_escaping() {
+        let json = r#"{"version":1,"operations":[
+            {"method": "name_like", "args": ["a*b\"c"]},
+            {"method": "compare", "args": ["kMDItemTitle", "==", {"Str": "\\"}]}
+        ]}"#;
+        let builder = MDQueryBuilder::from_json(json).unwrap();
+        assert_eq!(
+            builder.to_query_string().unwrap(),
+            r#"((kMDItemDisplayName == "*a\*b\"c*"w) && (kMDItemTitle == "\\"))"#
+        );
+        assert_eq!(
+            builder,
+            MDQueryBuilder::default().name_like("a*b\"c").compare(
+                MDItemKey::Title,
+                MDQueryCompareOp::Equal,
+                "\\"
+            )
+        );
+    }
+
+    #[test]
+    fn test_json_errors() {
+        let cases = [
+            ("{", "invalid JSON: expected a member name at offset 1"),
+            (r#"{"operations":[]}"#, "missing version"),
+            (r#"{"version":2,"operations":[]}"#, "unsupported version 2"),
+            (r#"{"version":1}"#, "missing operations"),
+            (
+                r#"{"version":1,"operations":[{"method":"rm","args":[]}]}"#,
+                "unknown method \"rm\"",
+            ),
+            (
+                r#"{"version":1,"operations":[{"method":"name_like","args":[]}]}"#,
+                "name_like takes 1 arguments, found 0",
+            ),
+            (
+                r#"{"version":1,"operations":[{"method":"size","args":[">",-1]}]}"#,
+                "invalid argument 1 of size",
+            ),
+            (
+                r#"{"version":1,"operations":[{"method":"time","args":["kMDItemNope",">",0]}]}"#,
+                "invalid argument 0 of time",
+            ),
+            (
+                r#"{"version":1,"operations":[{"method":"match_all"}]}"#,
+                "match_all without arguments",
+            ),
+        ];
+        for (json, message) in cases {
+            let err = MDQueryBuilder::from_json(json).unwrap_err();
+            assert_eq!(
+                err.downcast_ref::<MDQueryError>(),
+                Some(&MDQueryError::InvalidSavedQuery(message.to_string())),
+                "{json}"
+            );
+        }
+    }
+}
+
+#[cfg(all(test, target_vendor = "apple", not(feature = "fallback")))]
+mod spotlight_tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::MDAttributeValue;
+
+    /// Returns the `kMDItemFSSize` of an item in bytes.
+    fn item_size(item: &MDItem) -> Option<u64> {
+        match item.attribute_value(MDItemKey::Size)? {
+            MDAttributeValue::Integer(size) => u64::try_from(size).ok(),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_name_like() {
+        let builder = MDQueryBuilder::default().name_like("Safari");
+        let query = builder.build([MDQueryScope::Computer], Some(1)).unwrap();
+        let results = query.execute().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            results[0].path(),
+            Some(PathBuf::from("/Applications/Safari.app"))
+        );
+    }
+
+    #[test]
+    fn test_is_app() {
+        let builder = MDQueryBuilder::default().name_like("Safari").is_app();
+        let query = builder.build([MDQueryScope::Computer], Some(1)).unwrap();
+        let results = query.execute().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            results[0].path(),
+            Some(PathBuf::from("/Applications/Safari.app"))
+        );
+    }
+
+    #[test]
+    fn test_extension() {
+        let builder = MDQueryBuilder::default().extension("txt");
+        let query = builder.build([MDQueryScope::Computer], Some(1)).unwrap();
+        let results = query.execute().unwrap();
+        assert!(!results.is_empty());
+        assert!(results[0]
+            .path()
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .ends_with(".txt"));
+    }
+
+    #[test]
+    fn test_time_search() {
+        let now = chrono::Utc::now().timestamp();
+        let builder = MDQueryBuilder::default().time(
+            MDItemKey::ModificationDate,
+            MDQueryCompareOp::LessThan,
+            now,
+        );
+        let query = builder
+            .build([MDQueryScope::from_path("/Applications")], Some(1))
+            .unwrap();
+        let results = query.execute().unwrap();
+        assert!(!results.is_empty());
+    }
+
+    #[test]
+    fn test_size_filter() {
+        let builder = MDQueryBuilder::default().size(MDQueryCompareOp::GreaterThan, 1024 * 1024); // > 1MB
+        let query = builder.build([MDQueryScope::Computer], Some(1)).unwrap();
+        let results = query.execute().unwrap();
+        assert!(!results.is_empty());
+    }
+
+    #[test]
+    fn test_min_video_resolution() {
+        let Some(home) = std::env::var_os("HOME") else {
+            return;
+        };
+        let movies = PathBuf::from(home).join("Movies");
+        if !movies.is_dir() {
+            return;
+        }
+        let results = MDQueryBuilder::default()
+            .min_video_resolution(640, 480)
+            .with_scopes([MDQueryScope::from_path(movies)])
+            .with_max_count(5)
+            .execute()
+            .unwrap();
+        for item in results {
+            let info = item.video_info().unwrap();
+            assert!(info.width >= 640 && info.height >= 480);
+        }
+    }
+
+    #[test]
+    fn test_uses_font() {
+        let scopes = [MDQueryScope::from_path("/System/Library/Fonts")];
+        let Some(font) = MDQueryBuilder::default()
+            .of_kind(ContentKind::Font)
+            .with_scopes(scopes.clone())
+            .with_max_count(1)
+            .execute()
+            .unwrap()
+            .pop()
+            .and_then(|item| item.fonts())
+            .and_then(|mut fonts| fonts.pop())
+        else {
+            return;
+        };
+        let files = MDQueryBuilder::default()
+            .of_kind(ContentKind::Font)
+            .uses_font(&font.to_uppercase())
+            .with_scopes(scopes)
+            .execute()
+            .unwrap();
+        assert!(!files.is_empty());
+        for file in files {
+            assert!(file
+                .fonts()
+                .unwrap()
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case(&font)));
+        }
+
+        let documents = MDQueryBuilder::from_raw("kMDItemFonts == \"*\"")
+            .of_kind(ContentKind::Pdf)
+            .with_scopes([MDQueryScope::Home])
+            .with_max_count(5)
+            .execute()
+            .unwrap();
+        for document in documents {
+            assert!(document.fonts().is_some_and(|fonts| !fonts.is_empty()));
+        }
+    }
+
+    #[test]
+    fn test_applications_in_category() {
+        let Some(category) = MDQueryBuilder::default()
+            .is_app()
+            .has_app_category(true)
+            .with_scopes([MDQueryScope::from_path("/System/Applications")])
+            .with_max_count(1)
+            .execute()
+            .unwrap()
+            .pop()
+            .and_then(|app| app.app_store_category())
+        else {
+            return;
+        };
+        let apps = applications_in_category(&category).unwrap();
+        assert!(!apps.is_empty());
+        for app in apps {
+            assert!(app
+                .app_store_category()
+                .is_some_and(|found| found.eq_ignore_ascii_case(&category)));
+        }
+
+        let uncategorized = MDQueryBuilder::default()
+            .is_app()
+            .has_app_category(false)
+            .with_scopes([MDQueryScope::from_path("/Applications")])
+            .with_max_count(10)
+            .execute()
+            .unwrap();
+        for app in uncategorized {
+            assert_eq!(app.app_store_category(), None);
+        }
+    }
+
+    #[test]
+    fn test_architectures() {
+        let scopes = [MDQueryScope::from_path("/System/Applications")];
+        let native = MDQueryBuilder::default()
+            .name_is("Calculator")
+            .is_app()
+            .has_architecture("arm64")
+            .with_scopes(scopes.clone())
+            .execute()
+            .unwrap();
+        assert_eq!(native.len(), 1);
+        let intel_only = MDQueryBuilder::default()
+            .is_app()
+            .missing_architecture("arm64")
+            .with_scopes(scopes.clone())
+            .with_max_count(20)
+            .execute()
+            .unwrap();
+        for item in intel_only {
+            let architectures = item.executable_architectures().unwrap();
+            assert!(!architectures.iter().any(|arch| arch == "arm64"));
+        }
+        // Calculator.app is native, so it never lacks arm64.
+        let calculator = MDQueryBuilder::default()
+            .name_is("Calculator")
+            .is_app()
+            .missing_architecture("arm64")
+            .with_scopes(scopes)
+            .execute()
+            .unwrap();
+        assert!(calculator.is_empty());
+    }
+
+    #[test]
+    fn test_is_package() {
+        let scopes = [MDQueryScope::from_path("/")];
+        let packages = MDQueryBuilder::default()
+            .name_like("Safari")
+            .is_package(true)
+            .with_scopes([MDQueryScope::from_path("/Applications")])
+            .execute()
+            .unwrap();
+        assert!(packages
+            .iter()
+            .any(|item| item.path() == Some(PathBuf::from("/Applications/Safari.app"))));
+        let folders = MDQueryBuilder::default()
+            .name_is("Applications")
+            .is_dir_by_tree(true)
+            .is_package(false)
+            .with_scopes(scopes)
+            .execute()
+            .unwrap();
+        assert!(folders
+            .iter()
+            .any(|item| item.path() == Some(PathBuf::from("/Applications"))));
+        let not_packages = MDQueryBuilder::default()
+            .name_like("Safari")
+            .is_package(false)
+            .with_scopes([MDQueryScope::from_path("/Applications")])
+            .execute()
+            .unwrap();
+        assert!(!not_packages
+            .iter()
+            .any(|item| item.path() == Some(PathBuf::from("/Applications/Safari.app"))));
+    }
+
+    #[test]
+    fn test_is_framework() {
+        let results = MDQueryBuilder::default()
+            .is_framework()
+            .with_scopes([MDQueryScope::from_path("/System/Library/Frameworks")])
+            .with_max_count(5)
+            .execute()
+            .unwrap();
+        assert!(!results.is_empty());
+        assert!(results.iter().all(MDItem::is_package));
+    }
+
+    #[test]
+    fn test_exclude_trash() {
+        let home = PathBuf::from(std::env::var("HOME").unwrap());
+        let name = format!("mdquery-noise-{}.txt", std::process::id());
+        let dir = home.join(format!("mdquery-noise-{}", std::process::id()));
+        let trashed = user_directory(UserDirectory::Trash).join(&name);
+        std::fs::create_dir_all(&dir).unwrap();
+        // Writing to the Trash needs Full Disk Access. The mock backend tests cover the
+        // exclusion without it.
+        if std::fs::write(&trashed, "trashed").is_err() {
+            let _ = std::fs::remove_dir_all(&dir);
+            return;
+        }
+        std::fs::write(dir.join(&name), "kept").unwrap();
+        for path in [dir.join(&name), trashed.clone()] {
+            let _ = std::process::Command::new("mdimport").arg(path).status();
+        }
+
+        let find = |builder: MDQueryBuilder| {
+            builder
+                .fs_name_starts_with(&name)
+                .with_scopes([MDQueryScope::Home])
+                .execute()
+                .unwrap()
+                .into_iter()
+                .filter_map(|item| item.path())
+                .collect::<Vec<_>>()
+        };
+        let mut all = Vec::new();
+        for _ in 0..40 {
+            all = find(MDQueryBuilder::default());
+            if all.len() == 2 {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(250));
+        }
+        let without_trash = find(MDQueryBuilder::default().exclude_system_noise());
+        let _ = std::fs::remove_dir_all(&dir);
+        let _ = std::fs::remove_file(&trashed);
+
+        assert!(all.contains(&trashed));
+        assert_eq!(without_trash, [dir.join(&name)]);
+    }
+
+    #[test]
+    fn test_is_dir() {
+        let builder = MDQueryBuilder::default().is_dir(true);
+        let query = builder.build([MDQueryScope::Computer], Some(1)).unwrap();
+        let results = query.execute().unwrap();
+        assert!(!results.is_empty());
+    }
+
+    #[test]
+    fn test_builder_execute() {
+        let results = MDQueryBuilder::default()
+            .name_like("Safari")
+            .is_app()
+            .with_scopes([MDQueryScope::from_path("/Applications")])
+            .with_max_count(1)
+            .execute()
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            results[0].path(),
+            Some(PathBuf::from("/Applications/Safari.app"))
+        );
+    }
+
+    #[test]
+    fn test_builder_execute_default_scope() {
+        let results = MDQueryBuilder::default()
+            .name_is("Safari")
+            .is_app()
+            .execute()
+            .unwrap();
+        assert!(!results.is_empty());
+    }
+
+    #[test]
+    fn test_time_in_range_search() {
+        let now = chrono::Utc::now().timestamp();
+        let results = MDQueryBuilder::default()
+            .time_in_range(MDItemKey::ModificationDate, 0, now)
+            .build([MDQueryScope::from_path("/Applications")], Some(1))
+            .unwrap()
+            .execute()
+            .unwrap();
+        assert!(!results.is_empty());
+    }
+
+    #[test]
+    fn test_size_in_range_search() {
+        let (min, max) = (FileSize::kib(4), FileSize::mib(1));
+        let results = MDQueryBuilder::default()
+            .size_in_range(min, max)
+            .build(
+                [MDQueryScope::from_path("/System/Library/CoreServices")],
+                Some(20),
+            )
+            .unwrap()
+            .execute()
+            .unwrap();
+        assert!(!results.is_empty());
+        for item in &results {
+            let size = item_size(item).unwrap();
+            assert!(
+                (min.as_bytes()..=max.as_bytes()).contains(&size),
+                "{:?} has size {}",
+                item.path(),
+                size
+            );
+        }
+    }
+
+    #[test]
+    fn test_modified_within_last_year() {
+        let results = MDQueryBuilder::default()
+            .modified_within_days(365)
+            .build([MDQueryScope::from_path("/Applications")], Some(1))
+            .unwrap()
+            .execute()
+            .unwrap();
+        assert!(!results.is_empty());
+    }
+
+    #[test]
+    fn test_size_over_one_gib() {
+        // Sparse files, so the test needs no disk space. Spotlight records their length.
+        let dir = std::env::temp_dir().join(format!("mdquery-gib-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let gib = FileSize::gib(1).as_bytes();
+        for (name, len) in [("exact.bin", gib), ("over.bin", gib + 1), ("small.bin", 1)] {
+            let path = dir.join(name);
+            std::fs::File::create(&path).unwrap().set_len(len).unwrap();
+            let _ = std::process::Command::new("mdimport").arg(&path).status();
+        }
+
+        let find = |builder: MDQueryBuilder| {
+            builder
+                .with_scopes([MDQueryScope::from_path(&dir)])
+                .execute()
+                .unwrap()
+        };
+        // Wait until all three files are indexed.
+        for _ in 0..40 {
+            if find(MDQueryBuilder::default().match_all()).len() >= 3 {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(250));
+        }
+        let results = find(MDQueryBuilder::default().size(MDQueryCompareOp::GreaterThan, gib));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let sizes = results
+            .iter()
+            .map(|item| (item.path().unwrap(), item_size(item).unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(sizes, [(dir.join("over.bin"), gib + 1)]);
+    }
+
+    #[test]
+    fn test_name_starts_with() {
+        let results = MDQueryBuilder::default()
+            .name_starts_with("Safar")
+            .is_app()
+            .build([MDQueryScope::from_path("/Applications")], None)
+            .unwrap()
+            .execute()
+            .unwrap();
+        assert!(results
+            .iter()
+            .any(|item| item.path() == Some(PathBuf::from("/Applications/Safari.app"))));
+    }
+
+    #[test]
+    fn test_exclude_path_prefix() {
+        let results = MDQueryBuilder::default()
+            .is_app()
+            .exclude_path_prefix("/Applications/Utilities")
+            .build([MDQueryScope::from_path("/Applications")], None)
+            .unwrap()
+            .execute()
+            .unwrap();
+        assert!(!results.is_empty());
+        assert!(results
+            .iter()
+            .all(|item| !item.path().unwrap().starts_with("/Applications/Utilities")));
+    }
+
+    #[test]
+    fn test_path_predicates_include_and_exclude() {
+        let find = |builder: MDQueryBuilder| {
+            builder
+                .is_app()
+                .build([MDQueryScope::from_path("/System/Applications")], None)
+                .unwrap()
+                .execute()
+                .unwrap()
+                .into_iter()
+                .filter_map(|item| item.path())
+                .collect::<Vec<_>>()
+        };
+        let calculator = PathBuf::from("/System/Applications/Calculator.app");
+        let terminal = PathBuf::from("/System/Applications/Utilities/Terminal.app");
+
+        let utilities =
+            find(MDQueryBuilder::default().path_starts_with("/System/Applications/Utilities"));
+        assert!(utilities.contains(&terminal));
+        assert!(!utilities.contains(&calculator));
+        let others =
+            find(MDQueryBuilder::default().exclude_path_prefix("/System/Applications/Utilities"));
+        assert!(others.contains(&calculator));
+        assert!(!others.contains(&terminal));
+
+        for builder in [
+            MDQueryBuilder::default().local_volumes_only(),
+            MDQueryBuilder::default().on_volume("/"),
+            MDQueryBuilder::default().exclude_system_noise(),
+        ] {
+            let apps = find(builder);
+            assert!(apps.contains(&calculator));
+            assert!(apps.contains(&terminal));
+        }
+    }
+
+    #[test]
+    fn test_kind_filter() {
+        let safari = crate::MDItem::from_path("/Applications/Safari.app").unwrap();
+        let kind = safari.kind().unwrap();
+        let results = MDQueryBuilder::default()
+            .name_is("Safari")
+            .kind(&kind)
+            .build([MDQueryScope::from_path("/Applications")], None)
+            .unwrap()
+            .execute()
+            .unwrap();
+        assert!(results
+            .iter()
+            .any(|item| item.path() == Some(PathBuf::from("/Applications/Safari.app"))));
+    }
+
+    #[test]
+    fn test_of_kind_search() {
+        let cases = [
+            (ContentKind::Application, "/Applications"),
+            (ContentKind::Folder, "/Applications"),
+            (ContentKind::Image, "/System/Library"),
+        ];
+        for (kind, scope) in cases {
+            let results = MDQueryBuilder::default()
+                .of_kind(kind)
+                .build([MDQueryScope::from_path(scope)], Some(1))
+                .unwrap()
+                .execute()
+                .unwrap();
+            assert_eq!(results.len(), 1);
+            assert!(results[0].matches_kind(kind));
+        }
+    }
+
+    #[test]
+    fn test_document_kind_excludes_images() {
+        let results = MDQueryBuilder::from_condition(MDQueryCondition::any([
+            MDQueryConditionExpression::from("kMDItemFSName == \"mdquery-no-such-file\""),
+        ]))
+        .of_kind(ContentKind::Document)
+        .build(
+            [MDQueryScope::from_path("/System/Library/CoreServices")],
+            Some(200),
+        )
+        .unwrap()
+        .execute()
+        .unwrap();
+        assert!(!results.is_empty());
+        assert!(results
+            .iter()
+            .all(|item| !item.matches_kind(ContentKind::Image)));
+    }
+
+    #[test]
+    fn test_content_type_conforms_to() {
+        let results = MDQueryBuilder::default()
+            .content_type_conforms_to("public.image")
+            .extension("png")
+            .build([MDQueryScope::from_path("/System/Library")], Some(1))
+            .unwrap()
+            .execute()
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_image());
+        assert_eq!(
+            results[0]
+                .path()
+                .unwrap()
+                .extension()
+                .unwrap()
+                .to_ascii_lowercase(),
+            "png"
+        );
+    }
+
+    #[test]
+    fn test_find_app_by_bundle_id() {
+        let safari = find_app_by_bundle_id("com.apple.Safari").unwrap().unwrap();
+        assert_eq!(
+            safari.path(),
+            Some(PathBuf::from("/Applications/Safari.app"))
+        );
+        assert_eq!(
+            safari.bundle_identifier(),
+            Some("com.apple.Safari".to_string())
+        );
+
+        let missing = find_app_by_bundle_id("com.example.does-not-exist-123456").unwrap();
+        assert!(missing.is_none());
+    }
+
+    #[test]
+    fn test_downloaded_from_domain() {
+        let Some(home) = std::env::var_os("HOME") else {
+            return;
+        };
+        let downloads = PathBuf::from(home).join("Downloads");
+        if !downloads.is_dir() {
+            return;
+        }
+        let results = MDQueryBuilder::default()
+            .downloaded_from_domain("http")
+            .with_scopes([MDQueryScope::from_path(downloads)])
+            .with_max_count(5)
+            .execute()
+            .unwrap();
+        for item in results {
+            assert!(item
+                .where_froms()
+                .unwrap()
+                .iter()
+                .any(|url| url.to_lowercase().contains("http")));
+        }
+    }
+
+    #[test]
+    fn test_camera_make() {
+        let Some(home) = std::env::var_os("HOME") else {
+            return;
+        };
+        let pictures = PathBuf::from(home).join("Pictures");
+        if !pictures.is_dir() {
+            return;
+        }
+        let results = MDQueryBuilder::default()
+            .of_kind(ContentKind::Image)
+            .camera_make("Apple")
+            .with_scopes([MDQueryScope::from_path(pictures)])
+            .with_max_count(5)
+            .execute()
+            .unwrap();
+        for item in results {
+            assert!(item
+                .camera_make()
+                .is_some_and(|make| make.eq_ignore_ascii_case("apple")));
+        }
+    }
+
+    #[test]
+    fn test_email_messages() {
+        let results = MDQueryBuilder::default()
+            .content_type("com.apple.mail.emlx")
+            .with_max_count(5)
+            .execute()
+            .unwrap();
+        for item in results {
+            assert!(item.subject().is_some() || item.author_email_addresses().is_some());
+        }
+    }
+
+    #[test]
+    fn test_url_contains_bookmarks() {
+        let results = MDQueryBuilder::default()
+            .content_type("com.apple.safari.bookmark")
+            .url_contains("http")
+            .with_max_count(5)
+            .execute()
+            .unwrap();
+        for item in results {
+            assert!(item
+                .url()
+                .is_some_and(|url| url.to_lowercase().contains("http")));
+        }
+    }
+
+    #[test]
+    fn test_folder_item_count() {
+        let results = MDQueryBuilder::default()
+            .folder_item_count(MDQueryCompareOp::GreaterThan, 100)
+            .with_scopes([MDQueryScope::Home])
+            .with_max_count(5)
+            .execute()
+            .unwrap();
+        for item in results {
+            assert!(item.is_dir());
+            assert!(item.node_count().is_some_and(|count| count > 100));
+        }
+    }
+
+    #[test]
+    fn test_is_screenshot() {
+        let Some(home) = std::env::var_os("HOME") else {
+            return;
+        };
+        let desktop = PathBuf::from(home).join("Desktop");
+        if !desktop.is_dir() {
+            return;
+        }
+        let results = MDQueryBuilder::default()
+            .is_screenshot()
+            .with_scopes([MDQueryScope::from_path(desktop)])
+            .with_max_count(5)
+            .execute()
+            .unwrap();
+        for item in results {
+            assert!(item.is_screenshot());
+        }
+    }
+
+    #[test]
+    fn test_standard_directory_scopes_build() {
+        for scope in [
+            MDQueryScope::documents(),
+            MDQueryScope::downloads(),
+            MDQueryScope::desktop(),
+            MDQueryScope::pictures(),
+            MDQueryScope::movies(),
+            MDQueryScope::music(),
+            MDQueryScope::applications(),
+        ] {
+            MDQueryBuilder::default()
+                .name_like("a")
+                .build([scope], Some(1))
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_icloud_drive_scope() {
+        let query = match MDQueryBuilder::default()
+            .name_like("a")
+            .build([MDQueryScope::icloud_drive()], Some(5))
+        {
+            Ok(query) => query,
+            Err(err) => {
+                // Machines without iCloud Drive have no such folder.
+                assert!(matches!(
+                    err.downcast_ref::<MDQueryError>(),
+                    Some(MDQueryError::InvalidScope(_))
+                ));
+                return;
+            }
+        };
+        for item in query.execute().unwrap() {
+            assert!(item.is_in_icloud());
+        }
+    }
+
+    #[test]
+    fn test_with_scope_options() {
+        let results = MDQueryBuilder::default()
+            .name_like("Safari")
+            .is_app()
+            .with_scopes([MDQueryScope::from_path("/Applications")])
+            .with_scope_options(ScopeOptions::from_bits(1))
+            .with_max_count(1)
+            .execute()
+            .unwrap();
+        assert_eq!(results.len(), 1);
+    }
+
+    #[test]
+    fn test_to_query_string_matches_build() {
+        let builder = MDQueryBuilder::default().name_like("Safari").is_app();
+        let preview = builder.to_query_string().unwrap();
+        let query = builder
+            .build([MDQueryScope::from_path("/Applications")], Some(1))
+            .unwrap();
+        assert_eq!(query.query_string(), preview);
+    }
+
+    #[test]
+    fn test_condition_build() {
+        let results = MDQueryCondition::all(["kMDItemFSName == \"Safari.app\""])
+            .build([MDQueryScope::from_path("/Applications")], Some(1))
+            .unwrap()
+            .execute()
+            .unwrap();
+        assert_eq!(results.len(), 1);
+    }
+
+    #[test]
+    fn test_builder_condition_composition_builds() {
+        let large = MDQueryBuilder::default()
+            .name_like("a")
+            .size(MDQueryCompareOp::GreaterThan, FileSize::mib(1));
+        let pdfs = MDQueryBuilder::default().extension("pdf");
+        let query = MDQueryBuilder::default()
+            .condition(MDQueryCondition::any([large, pdfs]))
+            .build([MDQueryScope::Home], Some(1));
+        assert!(query.is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "uttype")]
+    fn test_content_type_uttype() {
+        let image = crate::UTType::from_identifier("public.image").unwrap();
+        let query = MDQueryBuilder::default()
+            .content_type_conforms_to(&image)
+            .to_query_string()
+            .unwrap();
+        let expected = MDQueryBuilder::default()
+            .content_type_conforms_to("public.image")
+            .to_query_string()
+            .unwrap();
+        assert_eq!(query, expected);
+    }
+
+    #[test]
+    fn test_local_volumes_only() {
+        let items = MDQueryBuilder::default()
+            .name_like("a")
+            .local_volumes_only()
+            .with_scopes([MDQueryScope::Applications, MDQueryScope::Network])
+            .with_max_count(50)
+            .execute()
+            .unwrap();
+        assert!(!items.is_empty());
+        for item in &items {
+            let path = item.path().unwrap();
+            assert!(path.starts_with("/Applications"));
+            assert!(!path.starts_with("/Volumes"));
+            assert_eq!(item.volume_path(), Some(PathBuf::from("/")));
+        }
+    }
+
+    #[test]
+    fn test_match_all_lists_scope() {
+        let dir = PathBuf::from("/System/Applications/Utilities");
+        let items = MDQueryBuilder::default()
+            .match_all()
+            .with_scopes([MDQueryScope::from_path(&dir)])
+            .execute()
+            .unwrap();
+        assert!(!items.is_empty());
+        assert!(items
+            .iter()
+            .all(|item| item.path().is_some_and(|path| path.starts_with(&dir))));
+    }
+
+    #[test]
+    fn test_top_k_matches_full_sort() {
+        let scope = [MDQueryScope::from_path("/System/Applications")];
+        let query = || {
+            MDQueryBuilder::default()
+                .extension("app")
+                .with_scopes(scope.clone())
+        };
+        let all = query().execute().unwrap();
+        assert!(all.len() > 5);
+
+        let mut paths = all.iter().filter_map(MDItem::path).collect::<Vec<_>>();
+        paths.sort();
+        let top = query()
+            .top_k(5, MDItemKey::Path, SortDirection::Ascending)
+            .execute()
+            .unwrap();
+        assert_eq!(
+            top.iter().filter_map(MDItem::path).collect::<Vec<_>>(),
+            paths[..5]
+        );
+
+        // Modification dates can repeat, so compare the selected dates, not the items.
+        let modified =
+            |item: &MDItem| item.get_attribute_value(MDItemKey::ModificationDate.as_str());
+        let mut dates = all.iter().filter_map(modified).collect::<Vec<_>>();
+        dates.sort_by(|a, b| crate::sort::compare_values(b, a));
+        let top = query()
+            .top_k(3, MDItemKey::ModificationDate, SortDirection::Descending)
+            .execute()
+            .unwrap();
+        assert_eq!(
+            top.iter().filter_map(modified).collect::<Vec<_>>(),
+            dates[..3]
+        );
+
+        let first = query()
+            .top_k(5, MDItemKey::Path, SortDirection::Ascending)
+            .execute_first()
+            .unwrap();
+        assert_eq!(first.and_then(|item| item.path()), paths.first().cloned());
+    }
+}
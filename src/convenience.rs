@@ -0,0 +1,148 @@
+//! One-line equivalents of the `mdfind` and `mdls` command-line tools.
+//!
+//! ```no_run
+//! let apps = mdquery_rs::mdfind_name("Safari", Some("/Applications"))?;
+//! let attributes = mdquery_rs::mdls("/Applications/Safari.app")?;
+//! println!("{:?} {:?}", apps, attributes.get("kMDItemCFBundleIdentifier"));
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+use crate::builder::escape_query_value;
+use crate::{MDAttributeValue, MDItem, MDQuery, MDQueryScope};
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Runs a raw query string everywhere Spotlight searches, like `mdfind query`.
+///
+/// # Parameters
+/// * `query` - A Spotlight query string
+///
+/// # Returns
+/// A Result containing the paths of the matching files, or an error if the query fails.
+/// Results without a path are skipped.
+pub fn mdfind(query: &str) -> Result<Vec<PathBuf>> {
+    find(query, None)
+}
+
+/// Runs a raw query string within a directory, like `mdfind -onlyin dir query`.
+///
+/// # Parameters
+/// * `query` - A Spotlight query string
+/// * `dir` - The directory to search in
+///
+/// # Returns
+/// A Result containing the paths of the matching files, or an error if the query fails.
+pub fn mdfind_in<P: AsRef<Path>>(query: &str, dir: P) -> Result<Vec<PathBuf>> {
+    find(query, Some(dir.as_ref()))
+}
+
+/// Finds files whose name contains `name`, like `mdfind -name name`.
+///
+/// The name is matched case- and diacritic-insensitively against `kMDItemFSName`, and
+/// wildcards in it are matched literally.
+///
+/// # Parameters
+/// * `name` - The substring to match in file names
+/// * `dir` - Optional directory to search in, like `-onlyin`
+///
+/// # Returns
+/// A Result containing the paths of the matching files, or an error if the query fails.
+pub fn mdfind_name<P: AsRef<Path>>(name: &str, dir: Option<P>) -> Result<Vec<PathBuf>> {
+    let query = format!("kMDItemFSName == \"*{}*\"cd", escape_query_value(name));
+    find(&query, dir.as_ref().map(AsRef::as_ref))
+}
+
+fn find(query: &str, dir: Option<&Path>) -> Result<Vec<PathBuf>> {
+    let scopes = dir.map(MDQueryScope::from_path);
+    let items = MDQuery::new(query, scopes, None)?.execute()?;
+    Ok(items.iter().filter_map(MDItem::path).collect())
+}
+
+/// Reads all metadata attributes of a file, like `mdls path`.
+///
+/// # Parameters
+/// * `path` - A path to a file or directory
+///
+/// # Returns
+/// A Result containing the attribute values keyed and sorted by attribute name, or an
+/// error if the item cannot be created. Attributes of unsupported types are skipped.
+pub fn mdls<P: AsRef<Path>>(path: P) -> Result<BTreeMap<String, MDAttributeValue>> {
+    let item = MDItem::from_path(path)?;
+    Ok(item
+        .get_attribute_names()
+        .into_iter()
+        .filter_map(|name| {
+            let value = item.get_attribute_value(&name)?;
+            Some((name, value))
+        })
+        .collect())
+}
+
+#[cfg(all(test, target_vendor = "apple", not(feature = "fallback")))]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+    use std::process::Command;
+
+    const SAFARI: &str = "/Applications/Safari.app";
+
+    fn run(program: &str, args: &[&str]) -> String {
+        let output = Command::new(program).args(args).output().unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    #[test]
+    fn test_mdfind_name_matches_cli() {
+        let expected = run(
+            "mdfind",
+            &["-onlyin", "/Applications", "-name", "Safari.app"],
+        )
+        .lines()
+        .map(PathBuf::from)
+        .collect::<BTreeSet<_>>();
+        let actual = mdfind_name("Safari.app", Some("/Applications"))
+            .unwrap()
+            .into_iter()
+            .collect::<BTreeSet<_>>();
+        assert!(actual.contains(Path::new(SAFARI)));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_mdfind_in_matches_cli() {
+        let query = "kMDItemCFBundleIdentifier == \"com.apple.Safari\"";
+        let expected = run("mdfind", &["-onlyin", "/Applications", query])
+            .lines()
+            .map(PathBuf::from)
+            .collect::<Vec<_>>();
+        assert_eq!(mdfind_in(query, "/Applications").unwrap(), expected);
+    }
+
+    #[test]
+    fn test_mdls_matches_cli() {
+        let attributes = mdls(SAFARI).unwrap();
+        assert_eq!(
+            attributes
+                .get("kMDItemCFBundleIdentifier")
+                .and_then(MDAttributeValue::as_str),
+            Some("com.apple.Safari")
+        );
+
+        // `mdls` prints one `name = value` line per attribute, sorted by name, with array
+        // values continuing on further indented lines.
+        let expected = run("mdls", &[SAFARI])
+            .lines()
+            .filter(|line| line.starts_with("kMDItem"))
+            .filter_map(|line| line.split_whitespace().next())
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        let names = attributes
+            .keys()
+            .filter(|name| name.starts_with("kMDItem"))
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(names, expected);
+    }
+}
@@ -0,0 +1,137 @@
+use std::fmt::{self, Display};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Errors with a specific meaning reported by this crate.
+///
+/// Fallible functions return [`anyhow::Result`]; errors listed here can be recovered
+/// from it with `downcast_ref::<MDQueryError>()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MDQueryError {
+    /// Spotlight metadata queries are not available on the current platform.
+    UnsupportedPlatform,
+    /// One or more `MDQueryBuilder` calls received invalid arguments.
+    ///
+    /// Contains one error per offending call, in call order.
+    InvalidBuilder(Vec<BuilderError>),
+    /// A search scope refers to a standard directory that does not exist.
+    ///
+    /// Contains the path the scope resolved to.
+    InvalidScope(PathBuf),
+    /// A condition group in a query tree has no expressions.
+    ///
+    /// Contains the location of the group: `root` is the top-level group, and `[i]` selects
+    /// the `i`-th expression of a group, so `root[1][0]` is the first expression of the
+    /// group that is the second expression of the top-level group.
+    EmptyConditionGroup(String),
+    /// A string names no known [`MDItemKey`](crate::MDItemKey).
+    ///
+    /// Contains the string that failed to parse.
+    UnknownAttribute(String),
+    /// A query expected to match exactly one item matched none.
+    NotFound,
+    /// A query expected to match exactly one item matched several.
+    Ambiguous {
+        /// The number of matching items found, a lower bound: `execute_one` stops looking
+        /// after the second match.
+        count: usize,
+    },
+    /// A builder saved with [`MDQueryBuilder::to_json`](crate::MDQueryBuilder::to_json)
+    /// could not be restored.
+    ///
+    /// Contains a description of the problem.
+    InvalidSavedQuery(String),
+    /// A query did not finish gathering results within the allotted time and was stopped.
+    Timeout {
+        /// The time the query was allowed to run for.
+        timeout: Duration,
+        /// The number of results gathered before the query was stopped.
+        partial_count: usize,
+    },
+}
+
+impl Display for MDQueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedPlatform => {
+                write!(f, "Spotlight metadata queries are only supported on macOS")
+            }
+            Self::InvalidBuilder(errors) => {
+                write!(f, "Invalid query builder arguments: ")?;
+                for (i, error) in errors.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{}", error)?;
+                }
+                Ok(())
+            }
+            Self::InvalidScope(path) => {
+                write!(
+                    f,
+                    "Search scope directory {} does not exist",
+                    path.display()
+                )
+            }
+            Self::EmptyConditionGroup(path) => {
+                write!(f, "Condition group {} has no expressions", path)
+            }
+            Self::UnknownAttribute(name) => {
+                write!(f, "Unknown metadata attribute {:?}", name)
+            }
+            Self::NotFound => write!(f, "No item matches the query"),
+            Self::Ambiguous { count } => {
+                write!(f, "{} items match the query, expected exactly one", count)
+            }
+            Self::InvalidSavedQuery(message) => {
+                write!(f, "Invalid saved query: {}", message)
+            }
+            Self::Timeout {
+                timeout,
+                partial_count,
+            } => {
+                write!(
+                    f,
+                    "Query timed out after {:?} with {} partial results",
+                    timeout, partial_count
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for MDQueryError {}
+
+/// An invalid argument passed to a builder call, reported by [`MDQueryError::InvalidBuilder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuilderError {
+    method: &'static str,
+    message: String,
+}
+
+impl BuilderError {
+    pub(crate) fn new(method: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            method,
+            message: message.into(),
+        }
+    }
+
+    /// Returns the name of the builder method that received the invalid argument, such as
+    /// `"time_in_range"`.
+    pub fn method(&self) -> &'static str {
+        self.method
+    }
+
+    /// Returns a description of the problem with the argument.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for BuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.method, self.message)
+    }
+}
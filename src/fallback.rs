@@ -0,0 +1,692 @@
+//! Stand-ins for the Spotlight-backed types on platforms without Spotlight.
+//!
+//! The public surface mirrors the Apple implementation so downstream code compiles
+//! everywhere, but every constructor and `execute` call fails with
+//! [`MDQueryError::UnsupportedPlatform`]. Because `MDQuery` and `MDItem` can never be
+//! constructed, their remaining methods are unreachable. Raw CoreFoundation accessors such
+//! as `MDItem::get_attribute` are not available.
+
+use crate::{
+    Backend, ContentKind, DocumentInfo, FinderLabel, ImageOrientation, MDAttributeValue, MDItemKey,
+    MDQueryBuilder, MDQueryError, MDQueryOptions, MDQueryScope, ScopeOptions, ScopedItem,
+    ScoredItem, SortDirection, VideoInfo,
+};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// A Spotlight query. Cannot be created on this platform.
+pub struct MDQuery {
+    never: Infallible,
+}
+
+impl MDQuery {
+    /// Creates a new query builder with default settings.
+    ///
+    /// # Returns
+    /// A new `MDQueryBuilder` instance for configuring the query.
+    pub fn builder() -> MDQueryBuilder {
+        MDQueryBuilder::default()
+    }
+
+    /// Always fails with [`MDQueryError::UnsupportedPlatform`].
+    pub fn new(
+        _query: impl AsRef<str>,
+        _scopes: impl IntoIterator<Item = MDQueryScope>,
+        _max_count: Option<usize>,
+    ) -> Result<Self> {
+        Err(MDQueryError::UnsupportedPlatform.into())
+    }
+
+    /// Always fails with [`MDQueryError::UnsupportedPlatform`].
+    pub fn new_with_options(
+        _query: impl AsRef<str>,
+        _scopes: impl IntoIterator<Item = MDQueryScope>,
+        _max_count: Option<usize>,
+        _scope_options: ScopeOptions,
+    ) -> Result<Self> {
+        Err(MDQueryError::UnsupportedPlatform.into())
+    }
+
+    /// Always fails with [`MDQueryError::UnsupportedPlatform`].
+    pub fn search_text(
+        _term: &str,
+        _scopes: impl IntoIterator<Item = MDQueryScope>,
+        _limit: Option<usize>,
+    ) -> Result<Vec<ScoredItem>> {
+        Err(MDQueryError::UnsupportedPlatform.into())
+    }
+
+    /// Always fails with [`MDQueryError::UnsupportedPlatform`].
+    pub fn new_with_backend(
+        _backend: Backend,
+        _query: impl AsRef<str>,
+        _scopes: impl IntoIterator<Item = MDQueryScope>,
+        _max_count: Option<usize>,
+    ) -> Result<Self> {
+        Err(MDQueryError::UnsupportedPlatform.into())
+    }
+
+    pub fn backend(&self) -> Backend {
+        match self.never {}
+    }
+
+    pub fn with_value_list_attributes<I>(self, _keys: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = MDItemKey>,
+    {
+        match self.never {}
+    }
+
+    pub fn with_top_k(self, _k: usize, _key: MDItemKey, _direction: SortDirection) -> Result<Self> {
+        match self.never {}
+    }
+
+    pub fn query_string(&self) -> &str {
+        match self.never {}
+    }
+
+    pub fn scopes(&self) -> &[MDQueryScope] {
+        match self.never {}
+    }
+
+    /// Always fails with [`MDQueryError::UnsupportedPlatform`].
+    pub fn execute(&self) -> Result<Vec<MDItem>> {
+        match self.never {}
+    }
+
+    /// Always fails with [`MDQueryError::UnsupportedPlatform`].
+    pub fn execute_with_options(&self, _options: MDQueryOptions) -> Result<Vec<MDItem>> {
+        match self.never {}
+    }
+
+    /// Always fails with [`MDQueryError::UnsupportedPlatform`].
+    pub fn execute_with_progress<F: FnMut(usize)>(&self, _on_progress: F) -> Result<Vec<MDItem>> {
+        match self.never {}
+    }
+
+    /// Always fails with [`MDQueryError::UnsupportedPlatform`].
+    pub fn execute_with_timeout(&self, _timeout: Duration) -> Result<Vec<MDItem>> {
+        match self.never {}
+    }
+
+    /// Always fails with [`MDQueryError::UnsupportedPlatform`].
+    pub fn execute_with_batches<F>(&self, _batch: F) -> Result<()>
+    where
+        F: FnMut(Vec<MDItem>) -> ControlFlow<()>,
+    {
+        match self.never {}
+    }
+
+    /// Always fails with [`MDQueryError::UnsupportedPlatform`].
+    pub fn execute_grouped(
+        &self,
+        _group_by: MDItemKey,
+    ) -> Result<Vec<(Option<MDAttributeValue>, Vec<MDItem>)>> {
+        match self.never {}
+    }
+
+    pub fn execute_first(&self) -> Result<Option<MDItem>> {
+        match self.never {}
+    }
+
+    pub fn execute_one(&self) -> Result<MDItem> {
+        match self.never {}
+    }
+
+    /// Always fails with [`MDQueryError::UnsupportedPlatform`].
+    pub fn execute_per_scope(&self, _limits: &[(MDQueryScope, usize)]) -> Result<Vec<ScopedItem>> {
+        match self.never {}
+    }
+
+    pub fn attribute_values(&self, _key: MDItemKey) -> Result<Vec<MDAttributeValue>> {
+        match self.never {}
+    }
+
+    pub fn value_counts(&self, _key: MDItemKey) -> HashMap<MDAttributeValue, usize> {
+        match self.never {}
+    }
+
+    pub fn lock_updates(&self) -> MDQueryUpdateLock {
+        match self.never {}
+    }
+
+    /// Always fails with [`MDQueryError::UnsupportedPlatform`].
+    #[cfg(feature = "async")]
+    pub fn execute_async(self) -> MDQueryAsyncResult {
+        match self.never {}
+    }
+
+    /// Always fails with [`MDQueryError::UnsupportedPlatform`].
+    #[cfg(feature = "async")]
+    pub fn execute_async_with_progress<F>(self, _on_progress: F) -> MDQueryAsyncResult
+    where
+        F: FnMut(usize) + Send + 'static,
+    {
+        match self.never {}
+    }
+
+    #[cfg(feature = "async")]
+    pub fn watch_stream(self) -> Result<MDQueryWatchStream> {
+        match self.never {}
+    }
+
+    /// Always fails with [`MDQueryError::UnsupportedPlatform`].
+    #[cfg(feature = "async")]
+    pub async fn execute_per_scope_async(
+        &self,
+        _limits: &[(MDQueryScope, usize)],
+    ) -> Result<Vec<ScopedItem>> {
+        match self.never {}
+    }
+}
+
+impl std::fmt::Debug for MDQuery {
+    fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.never {}
+    }
+}
+
+/// Suspends result updates of a query. Cannot be created on this platform.
+pub struct MDQueryUpdateLock {
+    never: Infallible,
+}
+
+impl MDQueryUpdateLock {
+    pub fn result_count(&self) -> usize {
+        match self.never {}
+    }
+
+    pub fn results(&self) -> Vec<MDItem> {
+        match self.never {}
+    }
+
+    pub fn results_from(&self, _start: usize) -> Vec<MDItem> {
+        match self.never {}
+    }
+}
+
+/// A uniform type identifier. Cannot be created on this platform.
+#[cfg(feature = "uttype")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UTType {
+    never: Infallible,
+}
+
+#[cfg(feature = "uttype")]
+impl UTType {
+    /// Always returns None on this platform.
+    pub fn from_identifier(_identifier: &str) -> Option<Self> {
+        None
+    }
+
+    /// Always returns None on this platform.
+    pub fn from_extension(_extension: &str) -> Option<Self> {
+        None
+    }
+
+    pub fn identifier(&self) -> String {
+        match self.never {}
+    }
+
+    pub fn conforms_to(&self, _other: &UTType) -> bool {
+        match self.never {}
+    }
+}
+
+/// Future returned by `MDQuery::execute_async`. Cannot be created on this platform.
+#[cfg(feature = "async")]
+pub struct MDQueryAsyncResult {
+    never: Infallible,
+}
+
+#[cfg(feature = "async")]
+impl std::future::Future for MDQueryAsyncResult {
+    type Output = Result<Vec<MDItem>>;
+
+    fn poll(
+        self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        match self.never {}
+    }
+}
+
+/// Stream returned by `MDQuery::watch_stream`. Cannot be created on this platform.
+#[cfg(feature = "async")]
+pub struct MDQueryWatchStream {
+    never: Infallible,
+}
+
+#[cfg(feature = "async")]
+impl MDQueryWatchStream {
+    pub fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<crate::MDQueryUpdateEvent>> {
+        match self.never {}
+    }
+
+    pub async fn next(&mut self) -> Option<crate::MDQueryUpdateEvent> {
+        match self.never {}
+    }
+}
+
+/// Has no effect on this platform.
+#[cfg(feature = "async")]
+pub fn set_async_workers(_n: usize) {}
+
+/// Has no effect on this platform.
+#[cfg(feature = "async")]
+pub fn shutdown_async_workers() {}
+
+/// A Spotlight metadata item. Cannot be created on this platform.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MDItem {
+    never: Infallible,
+}
+
+impl MDItem {
+    /// Always fails with [`MDQueryError::UnsupportedPlatform`].
+    pub fn from_path<P: AsRef<Path>>(_path: P) -> Result<Self> {
+        Err(MDQueryError::UnsupportedPlatform.into())
+    }
+
+    /// Always fails with [`MDQueryError::UnsupportedPlatform`].
+    pub fn from_path_unresolved<P: AsRef<Path>>(_path: P) -> Result<Self> {
+        Err(MDQueryError::UnsupportedPlatform.into())
+    }
+
+    pub fn refresh(&mut self) {
+        match self.never {}
+    }
+
+    pub fn get_attribute_names(&self) -> Vec<String> {
+        match self.never {}
+    }
+
+    pub fn attribute_value(&self, _key: MDItemKey) -> Option<MDAttributeValue> {
+        match self.never {}
+    }
+
+    pub fn get_attribute_value(&self, _name: &str) -> Option<MDAttributeValue> {
+        match self.never {}
+    }
+
+    pub fn path(&self) -> Option<PathBuf> {
+        match self.never {}
+    }
+
+    pub fn display_name(&self) -> Option<String> {
+        match self.never {}
+    }
+
+    pub fn kind(&self) -> Option<String> {
+        match self.never {}
+    }
+
+    pub fn bundle_identifier(&self) -> Option<String> {
+        match self.never {}
+    }
+
+    pub fn app_store_category(&self) -> Option<String> {
+        match self.never {}
+    }
+
+    pub fn executable_architectures(&self) -> Option<Vec<String>> {
+        match self.never {}
+    }
+
+    pub fn version(&self) -> Option<String> {
+        match self.never {}
+    }
+
+    pub fn content_type(&self) -> Option<String> {
+        match self.never {}
+    }
+
+    #[cfg(feature = "uttype")]
+    pub fn content_type_ut(&self) -> Option<UTType> {
+        match self.never {}
+    }
+
+    pub fn content_type_tree(&self) -> Option<Vec<String>> {
+        match self.never {}
+    }
+
+    pub fn authors(&self) -> Option<Vec<String>> {
+        match self.never {}
+    }
+
+    pub fn text_content(&self) -> Option<String> {
+        match self.never {}
+    }
+
+    pub fn text_content_truncated(&self, _max_len: usize) -> Option<String> {
+        match self.never {}
+    }
+
+    pub fn document_info(&self) -> DocumentInfo {
+        match self.never {}
+    }
+
+    pub fn video_info(&self) -> Option<VideoInfo> {
+        match self.never {}
+    }
+
+    pub fn security_method(&self) -> Option<String> {
+        match self.never {}
+    }
+
+    pub fn finder_comment(&self) -> Option<String> {
+        match self.never {}
+    }
+
+    pub fn pixel_dimensions(&self) -> Option<(u32, u32)> {
+        match self.never {}
+    }
+
+    pub fn color_space(&self) -> Option<String> {
+        match self.never {}
+    }
+
+    pub fn bits_per_sample(&self) -> Option<u32> {
+        match self.never {}
+    }
+
+    pub fn has_alpha_channel(&self) -> Option<bool> {
+        match self.never {}
+    }
+
+    pub fn orientation(&self) -> Option<ImageOrientation> {
+        match self.never {}
+    }
+
+    pub fn duration(&self) -> Option<Duration> {
+        match self.never {}
+    }
+
+    pub fn page_count(&self) -> Option<u32> {
+        match self.never {}
+    }
+
+    pub fn fonts(&self) -> Option<Vec<String>> {
+        match self.never {}
+    }
+
+    pub fn audio_sample_rate(&self) -> Option<f64> {
+        match self.never {}
+    }
+
+    pub fn audio_channel_count(&self) -> Option<u32> {
+        match self.never {}
+    }
+
+    pub fn codecs(&self) -> Option<Vec<String>> {
+        match self.never {}
+    }
+
+    pub fn total_bit_rate(&self) -> Option<f64> {
+        match self.never {}
+    }
+
+    pub fn video_bit_rate(&self) -> Option<f64> {
+        match self.never {}
+    }
+
+    pub fn album(&self) -> Option<String> {
+        match self.never {}
+    }
+
+    pub fn artists(&self) -> Option<Vec<String>> {
+        match self.never {}
+    }
+
+    pub fn genre(&self) -> Option<String> {
+        match self.never {}
+    }
+
+    pub fn recording_year(&self) -> Option<u16> {
+        match self.never {}
+    }
+
+    pub fn camera_make(&self) -> Option<String> {
+        match self.never {}
+    }
+
+    pub fn camera_model(&self) -> Option<String> {
+        match self.never {}
+    }
+
+    pub fn iso_speed(&self) -> Option<f64> {
+        match self.never {}
+    }
+
+    pub fn focal_length(&self) -> Option<f64> {
+        match self.never {}
+    }
+
+    pub fn exposure_time_seconds(&self) -> Option<f64> {
+        match self.never {}
+    }
+
+    pub fn f_number(&self) -> Option<f64> {
+        match self.never {}
+    }
+
+    pub fn where_froms(&self) -> Option<Vec<String>> {
+        match self.never {}
+    }
+
+    pub fn downloaded_date(&self) -> Option<DateTime<Utc>> {
+        match self.never {}
+    }
+
+    pub fn date_added(&self) -> Option<DateTime<Utc>> {
+        match self.never {}
+    }
+
+    pub fn owner_uid(&self) -> Option<u32> {
+        match self.never {}
+    }
+
+    pub fn owner_gid(&self) -> Option<u32> {
+        match self.never {}
+    }
+
+    pub fn node_count(&self) -> Option<u64> {
+        match self.never {}
+    }
+
+    pub fn label(&self) -> Option<FinderLabel> {
+        match self.never {}
+    }
+
+    pub fn use_count(&self) -> Option<i64> {
+        match self.never {}
+    }
+
+    pub fn used_dates(&self) -> Option<Vec<DateTime<Utc>>> {
+        match self.never {}
+    }
+
+    pub fn url(&self) -> Option<String> {
+        match self.never {}
+    }
+
+    pub fn subject(&self) -> Option<String> {
+        match self.never {}
+    }
+
+    pub fn recipients(&self) -> Option<Vec<String>> {
+        match self.never {}
+    }
+
+    pub fn author_email_addresses(&self) -> Option<Vec<String>> {
+        match self.never {}
+    }
+
+    pub fn recipient_email_addresses(&self) -> Option<Vec<String>> {
+        match self.never {}
+    }
+
+    pub fn keywords(&self) -> Option<Vec<String>> {
+        match self.never {}
+    }
+
+    pub fn creator_app(&self) -> Option<String> {
+        match self.never {}
+    }
+
+    pub fn user_tags(&self) -> Option<Vec<String>> {
+        match self.never {}
+    }
+
+    pub fn location(&self) -> Option<(f64, f64)> {
+        match self.never {}
+    }
+
+    pub fn altitude(&self) -> Option<f64> {
+        match self.never {}
+    }
+
+    pub fn matches_kind(&self, _kind: ContentKind) -> bool {
+        match self.never {}
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        match self.never {}
+    }
+
+    pub fn is_screenshot(&self) -> bool {
+        match self.never {}
+    }
+
+    pub fn screenshot_type(&self) -> Option<String> {
+        match self.never {}
+    }
+
+    pub fn is_in_icloud(&self) -> bool {
+        match self.never {}
+    }
+
+    pub fn is_dir(&self) -> bool {
+        match self.never {}
+    }
+
+    pub fn is_package(&self) -> bool {
+        match self.never {}
+    }
+
+    pub fn is_image(&self) -> bool {
+        match self.never {}
+    }
+
+    pub fn is_app(&self) -> bool {
+        match self.never {}
+    }
+
+    pub fn is_video(&self) -> bool {
+        match self.never {}
+    }
+
+    pub fn is_audio(&self) -> bool {
+        match self.never {}
+    }
+
+    pub fn volume_path(&self) -> Option<PathBuf> {
+        match self.never {}
+    }
+
+    pub fn is_document(&self) -> bool {
+        match self.never {}
+    }
+
+    pub fn is_archive(&self) -> bool {
+        match self.never {}
+    }
+
+    pub fn is_presentation(&self) -> bool {
+        match self.never {}
+    }
+
+    pub fn is_spreadsheet(&self) -> bool {
+        match self.never {}
+    }
+
+    pub fn is_source_code(&self) -> bool {
+        match self.never {}
+    }
+
+    pub fn is_alias(&self) -> bool {
+        match self.never {}
+    }
+
+    pub fn is_symlink(&self) -> bool {
+        match self.never {}
+    }
+
+    pub fn resolved_path(&self) -> Option<PathBuf> {
+        match self.never {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_unsupported<T>(result: Result<T>) {
+        let err = result.err().expect("expected an error");
+        assert_eq!(
+            err.downcast_ref::<MDQueryError>(),
+            Some(&MDQueryError::UnsupportedPlatform)
+        );
+    }
+
+    #[test]
+    fn test_query_new_unsupported() {
+        assert_unsupported(MDQuery::new(
+            "kMDItemFSName = \"Safari.app\"",
+            [MDQueryScope::Computer],
+            None,
+        ));
+        assert_unsupported(MDQuery::search_text("report", [], None));
+    }
+
+    #[test]
+    fn test_builder_unsupported() {
+        assert_unsupported(MDQuery::builder().name_like("Safari").execute());
+        assert_unsupported(
+            MDQueryBuilder::default()
+                .name_like("Safari")
+                .build([MDQueryScope::Home], Some(1)),
+        );
+    }
+
+    #[test]
+    fn test_item_from_path_unsupported() {
+        assert_unsupported(MDItem::from_path("/Applications/Safari.app"));
+        assert_unsupported(MDItem::from_path_unresolved("/Applications/Safari.app"));
+    }
+}
+
+/// The attribute schema, which is empty on this platform.
+pub(crate) mod schema {
+    use crate::schema::AttributeInfo;
+
+    pub(crate) fn all_attribute_names() -> Vec<String> {
+        Vec::new()
+    }
+
+    pub(crate) fn display_name(_attribute: &str) -> Option<String> {
+        None
+    }
+
+    pub(crate) fn attribute_info(_attribute: &str) -> Option<AttributeInfo> {
+        None
+    }
+}
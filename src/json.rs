@@ -0,0 +1,472 @@
+use crate::MDAttributeValue;
+use chrono::SecondsFormat;
+use std::io::{self, Write};
+
+/// Options for the JSON export of query results, see
+/// [`MDQueryResults::write_json_with`](crate::MDQueryResults::write_json_with).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct JsonOptions {
+    max_string_len: Option<usize>,
+}
+
+impl JsonOptions {
+    /// Truncates string values, such as `kMDItemTextContent`, to at most `max_len`
+    /// characters. Strings inside arrays are truncated as well.
+    ///
+    /// # Parameters
+    /// * `max_len` - The maximum number of characters per string
+    ///
+    /// # Returns
+    /// Self for method chaining
+    pub fn max_string_len(mut self, max_len: usize) -> Self {
+        self.max_string_len = Some(max_len);
+        self
+    }
+}
+
+/// Writes an attribute value as JSON.
+///
+/// Dates are written as RFC 3339 strings in UTC, and non-finite numbers as `null`.
+pub(crate) fn write_value<W: Write>(
+    writer: &mut W,
+    value: &MDAttributeValue,
+    options: &JsonOptions,
+) -> io::Result<()> {
+    match value {
+        MDAttributeValue::String(value) => {
+            let value = match options.max_string_len {
+                Some(max_len) => value
+                    .char_indices()
+                    .nth(max_len)
+                    .map_or(value.as_str(), |(end, _)| &value[..end]),
+                None => value,
+            };
+            write_string(writer, value)
+        }
+        MDAttributeValue::Integer(value) => write!(writer, "{}", value),
+        MDAttributeValue::Float(value) if value.is_finite() => write!(writer, "{}", value),
+        MDAttributeValue::Float(_) => write!(writer, "null"),
+        MDAttributeValue::Bool(value) => write!(writer, "{}", value),
+        MDAttributeValue::Date(value) => {
+            write_string(writer, &value.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+        }
+        MDAttributeValue::Array(values) => {
+            write!(writer, "[")?;
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    write!(writer, ",")?;
+                }
+                write_value(writer, value, options)?;
+            }
+            write!(writer, "]")
+        }
+    }
+}
+
+/// Writes a string as a quoted and escaped JSON string.
+pub(crate) fn write_string<W: Write>(writer: &mut W, value: &str) -> io::Result<()> {
+    write!(writer, "\"")?;
+    for c in value.chars() {
+        match c {
+            '"' => write!(writer, "\\\"")?,
+            '\\' => write!(writer, "\\\\")?,
+            '\n' => write!(writer, "\\n")?,
+            '\r' => write!(writer, "\\r")?,
+            '\t' => write!(writer, "\\t")?,
+            c if (c as u32) < 0x20 => write!(writer, "\\u{:04x}", c as u32)?,
+            c => write!(writer, "{}", c)?,
+        }
+    }
+    write!(writer, "\"")
+}
+
+/// A JSON document, as read by [`JsonValue::parse`].
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum JsonValue {
+    Null,
+    Bool(bool),
+    /// A number as written, so integers keep their full range until they are read.
+    Number(String),
+    String(String),
+    Array(Vec<JsonValue>),
+    /// Members in document order.
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    /// A number of any type whose `Display` output is valid JSON, such as an integer.
+    pub(crate) fn number(value: impl std::fmt::Display) -> Self {
+        Self::Number(value.to_string())
+    }
+
+    /// An object with a single member.
+    pub(crate) fn tagged(tag: &str, value: JsonValue) -> Self {
+        Self::Object(vec![(tag.to_string(), value)])
+    }
+
+    /// Returns the value of the member `key` of an object.
+    pub(crate) fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            Self::Object(members) => members
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Returns the only member of an object, see [`tagged`](Self::tagged).
+    pub(crate) fn as_tagged(&self) -> Option<(&str, &JsonValue)> {
+        match self {
+            Self::Object(members) if members.len() == 1 => {
+                Some((members[0].0.as_str(), &members[0].1))
+            }
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_array(&self) -> Option<&[JsonValue]> {
+        match self {
+            Self::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Converts the value to compact JSON text.
+    pub(crate) fn to_json_string(&self) -> String {
+        let mut json = Vec::new();
+        self.write(&mut json).expect("writing to a Vec never fails");
+        String::from_utf8(json).expect("JSON output is valid UTF-8")
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        match self {
+            Self::Null => write!(writer, "null"),
+            Self::Bool(value) => write!(writer, "{}", value),
+            Self::Number(value) => write!(writer, "{}", value),
+            Self::String(value) => write_string(writer, value),
+            Self::Array(values) => {
+                write!(writer, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(writer, ",")?;
+                    }
+                    value.write(writer)?;
+                }
+                write!(writer, "]")
+            }
+            Self::Object(members) => {
+                write!(writer, "{{")?;
+                for (i, (name, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        write!(writer, ",")?;
+                    }
+                    write_string(writer, name)?;
+                    write!(writer, ":")?;
+                    value.write(writer)?;
+                }
+                write!(writer, "}}")
+            }
+        }
+    }
+
+    /// Parses a JSON document.
+    ///
+    /// # Errors
+    /// Returns a description of the first syntax error and its byte offset.
+    pub(crate) fn parse(text: &str) -> Result<Self, String> {
+        let mut parser = Parser { text, pos: 0 };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.pos < text.len() {
+            return Err(parser.error("trailing characters"));
+        }
+        Ok(value)
+    }
+}
+
+/// A recursive descent parser for [`JsonValue::parse`].
+struct Parser<'a> {
+    text: &'a str,
+    /// The byte offset of the next character.
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> String {
+        format!("{} at offset {}", message, self.pos)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .peek()
+            .is_some_and(|c| matches!(c, ' ' | '\t' | '\n' | '\r'))
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.next() == Some(expected) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected {:?}", expected)))
+        }
+    }
+
+    fn value(&mut self) -> Result<JsonValue, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('n') => self.literal("null", JsonValue::Null),
+            Some('t') => self.literal("true", JsonValue::Bool(true)),
+            Some('f') => self.literal("false", JsonValue::Bool(false)),
+            Some('"') => self.string().map(JsonValue::String),
+            Some('[') => {
+                self.pos += 1;
+                let mut values = Vec::new();
+                self.skip_whitespace();
+                if self.peek() == Some(']') {
+                    self.pos += 1;
+                    return Ok(JsonValue::Array(values));
+                }
+                loop {
+                    values.push(self.value()?);
+                    self.skip_whitespace();
+                    match self.next() {
+                        Some(',') => {}
+                        Some(']') => return Ok(JsonValue::Array(values)),
+                        _ => return Err(self.error("expected ',' or ']'")),
+                    }
+                }
+            }
+            Some('{') => {
+                self.pos += 1;
+                let mut members = Vec::new();
+                self.skip_whitespace();
+                if self.peek() == Some('}') {
+                    self.pos += 1;
+                    return Ok(JsonValue::Object(members));
+                }
+                loop {
+                    self.skip_whitespace();
+                    if self.peek() != Some('"') {
+                        return Err(self.error("expected a member name"));
+                    }
+                    let name = self.string()?;
+                    self.expect(':')?;
+                    members.push((name, self.value()?));
+                    self.skip_whitespace();
+                    match self.next() {
+                        Some(',') => {}
+                        Some('}') => return Ok(JsonValue::Object(members)),
+                        _ => return Err(self.error("expected ',' or '}'")),
+                    }
+                }
+            }
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    fn literal(&mut self, literal: &str, value: JsonValue) -> Result<JsonValue, String> {
+        if self.text[self.pos..].starts_with(literal) {
+            self.pos += literal.len();
+            Ok(value)
+        } else {
+            Err(self.error("expected a value"))
+        }
+    }
+
+    fn number(&mut self) -> Result<JsonValue, String> {
+        let start = self.pos;
+        if self.peek() == Some('-') {
+            self.pos += 1;
+        }
+        let digits = |parser: &mut Self| {
+            let start = parser.pos;
+            while parser.peek().is_some_and(|c| c.is_ascii_digit()) {
+                parser.pos += 1;
+            }
+            parser.pos > start
+        };
+        if !digits(self) {
+            return Err(self.error("expected a digit"));
+        }
+        if self.peek() == Some('.') {
+            self.pos += 1;
+            if !digits(self) {
+                return Err(self.error("expected a digit"));
+            }
+        }
+        if matches!(self.peek(), Some('e' | 'E')) {
+            self.pos += 1;
+            if matches!(self.peek(), Some('+' | '-')) {
+                self.pos += 1;
+            }
+            if !digits(self) {
+                return Err(self.error("expected a digit"));
+            }
+        }
+        Ok(JsonValue::Number(self.text[start..self.pos].to_string()))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut value = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(value),
+                Some('\\') => {
+                    let c = match self.next() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.unicode_escape()?,
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    value.push(c);
+                }
+                Some(c) if (c as u32) < 0x20 => {
+                    return Err(self.error("unescaped control character"))
+                }
+                Some(c) => value.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    /// Reads the digits of a `\u` escape, combining surrogate pairs.
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !self.text[self.pos..].starts_with("\\u") {
+                return Err(self.error("unpaired surrogate"));
+            }
+            self.pos += 2;
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("unpaired surrogate"));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| self.error("invalid code point"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let digits = self
+            .text
+            .get(self.pos..self.pos + 4)
+            .filter(|digits| digits.chars().all(|c| c.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error("expected four hex digits"))?;
+        self.pos += 4;
+        Ok(u32::from_str_radix(digits, 16).expect("checked hex digits"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn to_json(value: &MDAttributeValue, options: &JsonOptions) -> String {
+        let mut json = Vec::new();
+        write_value(&mut json, value, options).unwrap();
+        String::from_utf8(json).unwrap()
+    }
+
+    #[test]
+    fn test_write_values() {
+        let options = JsonOptions::default();
+        let date = Utc.with_ymd_and_hms(2024, 5, 6, 7, 8, 9).unwrap();
+        assert_eq!(
+            to_json(&MDAttributeValue::from("a \"quoted\"\n\\ \u{1}"), &options),
+            r#""a \"quoted\"\n\\ \u0001""#
+        );
+        assert_eq!(to_json(&MDAttributeValue::Integer(-3), &options), "-3");
+        assert_eq!(to_json(&MDAttributeValue::Float(1.5), &options), "1.5");
+        assert_eq!(
+            to_json(&MDAttributeValue::Float(f64::NAN), &options),
+            "null"
+        );
+        assert_eq!(to_json(&MDAttributeValue::Bool(true), &options), "true");
+        assert_eq!(
+            to_json(&MDAttributeValue::Date(date), &options),
+            r#""2024-05-06T07:08:09Z""#
+        );
+        assert_eq!(
+            to_json(
+                &MDAttributeValue::Array(vec!["x".into(), MDAttributeValue::Integer(1)]),
+                &options
+            ),
+            r#"["x",1]"#
+        );
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        let text = r#"{"a":[1,-2.5e3,true,false,null],"b":"x \"y\" \u00e9 \ud83d\ude00","c":{}}"#;
+        let value = JsonValue::parse(text).unwrap();
+        assert_eq!(value.get("a").unwrap().as_array().unwrap().len(), 5);
+        assert_eq!(value.get("b").unwrap().as_str(), Some("x \"y\" é 😀"));
+        assert_eq!(JsonValue::parse(&value.to_json_string()).unwrap(), value);
+        assert_eq!(
+            JsonValue::parse(" [ 1 , { \"k\" : \"v\" } ] ").unwrap(),
+            JsonValue::Array(vec![
+                JsonValue::number(1),
+                JsonValue::tagged("k", JsonValue::String("v".to_string())),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for text in [
+            "",
+            "[1,]",
+            "{\"a\" 1}",
+            "\"open",
+            "01x",
+            "[1] 2",
+            "\"\\ud800\"",
+            "nul",
+        ] {
+            assert!(JsonValue::parse(text).is_err(), "{text:?} parsed");
+        }
+    }
+
+    #[test]
+    fn test_truncate_strings() {
+        let options = JsonOptions::default().max_string_len(3);
+        assert_eq!(to_json(&"héllo".into(), &options), r#""hél""#);
+        assert_eq!(to_json(&"hé".into(), &options), r#""hé""#);
+        assert_eq!(
+            to_json(&MDAttributeValue::Array(vec!["abcd".into()]), &options),
+            r#"["abc"]"#
+        );
+    }
+}
@@ -1,5 +1,55 @@
-#[cfg(any(target_vendor = "apple", docsrs))]
+mod backend;
+mod builder;
+mod error;
+mod json;
+mod macros;
+#[cfg(feature = "mock")]
+mod mock;
+mod model;
+mod results;
+mod saved;
+mod sort;
+mod validate;
+
+#[cfg(all(any(target_vendor = "apple", docsrs), not(feature = "fallback")))]
 mod apple;
+#[cfg(all(any(target_vendor = "apple", docsrs), not(feature = "fallback")))]
+use apple as platform;
+
+#[cfg(not(all(any(target_vendor = "apple", docsrs), not(feature = "fallback"))))]
+mod fallback;
+#[cfg(not(all(any(target_vendor = "apple", docsrs), not(feature = "fallback"))))]
+use fallback as platform;
+
+pub mod convenience;
+pub mod prelude;
+pub mod schema;
+
+pub use platform::{MDItem, MDQuery, MDQueryUpdateLock};
+#[cfg(feature = "uttype")]
+pub use platform::UTType;
+#[cfg(feature = "async")]
+pub use platform::{
+    set_async_workers, shutdown_async_workers, MDQueryAsyncResult, MDQueryWatchStream,
+};
+#[cfg(feature = "async")]
+pub use results::MDQueryUpdateEvent;
 
-#[cfg(any(target_vendor = "apple", docsrs))]
-pub use apple::*;
+pub use backend::{Backend, BackendQuery, QueryBackend, SpotlightBackend};
+pub use builder::{
+    applications_in_category, find_app_by_bundle_id, ContentTypeArg, MDQueryBuilder,
+    MDQueryCompareOp, MDQueryCondition, MDQueryConditionExpression, MDQueryConditionType,
+    MDQueryValue, MatchModifiers, RelativeTime,
+};
+pub use convenience::{mdfind, mdfind_in, mdfind_name, mdls};
+pub use error::{BuilderError, MDQueryError};
+pub use json::JsonOptions;
+#[cfg(feature = "mock")]
+pub use mock::{MockBackend, MockItem, MockValue};
+pub use model::{
+    ContentKind, DocumentInfo, FileSize, FinderLabel, ImageOrientation, MDAttributeValue,
+    MDItemKey, MDItemKeyKind, MDQueryOptions, MDQueryScope, ScopeOptions, VideoInfo,
+};
+pub use results::{HydratedItem, MDQueryResults, ScopedItem, ScoredItem};
+pub use sort::SortDirection;
+pub use validate::{validate_query, QuerySyntaxError};
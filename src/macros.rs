@@ -0,0 +1,383 @@
+/// Builds an [`MDQueryBuilder`](crate::MDQueryBuilder) from a small query language.
+///
+/// Terms are combined with `&&` and `||`, where `&&` binds tighter, and can be grouped with
+/// parentheses. Each term expands to the matching builder method, so values are escaped the
+/// same way, and alternatives and groups are combined with
+/// [`merge`](crate::MDQueryBuilder::merge), so invalid arguments anywhere make `build` fail
+/// with [`MDQueryError::InvalidBuilder`](crate::MDQueryError::InvalidBuilder). Values must be
+/// a single token: a literal, a variable, or an expression in parentheses.
+///
+/// | Term | Builder call |
+/// |------|--------------|
+/// | `name like v` | [`name_like`](crate::MDQueryBuilder::name_like) |
+/// | `name is v` | [`name_is`](crate::MDQueryBuilder::name_is) |
+/// | `name starts_with v` | [`name_starts_with`](crate::MDQueryBuilder::name_starts_with) |
+/// | `name ends_with v` | [`name_ends_with`](crate::MDQueryBuilder::name_ends_with) |
+/// | `ext v` | [`extension`](crate::MDQueryBuilder::extension) |
+/// | `kind Image` | [`of_kind`](crate::MDQueryBuilder::of_kind)`(ContentKind::Image)` |
+/// | `content_type v` | [`content_type`](crate::MDQueryBuilder::content_type) |
+/// | `conforms_to v` | [`content_type_conforms_to`](crate::MDQueryBuilder::content_type_conforms_to) |
+/// | `author v` | [`author`](crate::MDQueryBuilder::author) |
+/// | `keyword v` | [`keyword`](crate::MDQueryBuilder::keyword) |
+/// | `path under v` | [`path_starts_with`](crate::MDQueryBuilder::path_starts_with) |
+/// | `path not_under v` | [`exclude_path_prefix`](crate::MDQueryBuilder::exclude_path_prefix) |
+/// | `is app` | [`is_app`](crate::MDQueryBuilder::is_app) |
+/// | `is dir` | [`is_dir`](crate::MDQueryBuilder::is_dir)`(true)` |
+/// | `size OP v` | [`size`](crate::MDQueryBuilder::size) |
+/// | `modified OP days_ago(n)` | [`time_relative`](crate::MDQueryBuilder::time_relative) with `RelativeTime::Today(-n)` |
+/// | `modified OP v` | [`time_dt`](crate::MDQueryBuilder::time_dt) |
+/// | `raw v` | a raw query expression |
+///
+/// `created`, `used` and `added` work like `modified` for the creation, last-used and
+/// date-added dates. `OP` is one of `>`, `<`, `>=`, `<=` and `==`.
+///
+/// ```
+/// use mdquery_rs::md_query;
+///
+/// let builder = md_query!(name like "report" && (ext "pdf" || ext "docx") && modified > days_ago(7));
+/// assert_eq!(
+///     builder.to_query_string().unwrap(),
+///     "((kMDItemDisplayName == \"*report*\"w) && \
+///      ((((kMDItemFSName == \"*.pdf\"c)) || ((kMDItemFSName == \"*.docx\"c)))) && \
+///      (kMDItemContentModificationDate > $time.today(-7)))"
+/// );
+/// ```
+///
+/// Unknown terms and operators are rejected at compile time:
+///
+/// ```compile_fail
+/// mdquery_rs::md_query!(name ~ "report");
+/// ```
+///
+/// ```compile_fail
+/// mdquery_rs::md_query!(size => 1024);
+/// ```
+///
+/// ```compile_fail
+/// mdquery_rs::md_query!(ext "pdf" ext "docx");
+/// ```
+#[macro_export]
+macro_rules! md_query {
+    ($($query:tt)+) => {
+        $crate::__md_query!(@term [] [$crate::MDQueryBuilder::default()] $($query)+)
+    };
+}
+
+/// Implementation of [`md_query!`].
+///
+/// The first bracket holds the finished `&&` groups seen before each `||`, the second one
+/// the builder for the group currently being parsed.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __md_query {
+    // After a term: finish, or continue with the next term.
+    (@op [] [$and:expr]) => {
+        $and
+    };
+    (@op [$first:expr, $($alt:expr,)*] [$and:expr]) => {
+        $first
+            $(.merge($alt, $crate::MDQueryConditionType::Any))*
+            .merge($and, $crate::MDQueryConditionType::Any)
+    };
+    (@op $alts:tt [$and:expr] && $($rest:tt)+) => {
+        $crate::__md_query!(@term $alts [$and] $($rest)+)
+    };
+    (@op [$($alt:expr,)*] [$and:expr] || $($rest:tt)+) => {
+        $crate::__md_query!(
+            @term [$($alt,)* $and,] [$crate::MDQueryBuilder::default()] $($rest)+
+        )
+    };
+    (@op $alts:tt $and:tt $($rest:tt)+) => {
+        compile_error!(concat!(
+            "md_query!: expected `&&` or `||`, found `",
+            stringify!($($rest)+),
+            "`"
+        ))
+    };
+
+    // Terms.
+    (@term $alts:tt [$and:expr] ($($group:tt)+) $($rest:tt)*) => {
+        $crate::__md_query!(@op $alts [$and.merge(
+            $crate::md_query!($($group)+),
+            $crate::MDQueryConditionType::All,
+        )] $($rest)*)
+    };
+    (@term $alts:tt [$and:expr] name like $v:tt $($rest:tt)*) => {
+        $crate::__md_query!(@op $alts [$and.name_like($v)] $($rest)*)
+    };
+    (@term $alts:tt [$and:expr] name is $v:tt $($rest:tt)*) => {
+        $crate::__md_query!(@op $alts [$and.name_is($v)] $($rest)*)
+    };
+    (@term $alts:tt [$and:expr] name starts_with $v:tt $($rest:tt)*) => {
+        $crate::__md_query!(@op $alts [$and.name_starts_with($v)] $($rest)*)
+    };
+    (@term $alts:tt [$and:expr] name ends_with $v:tt $($rest:tt)*) => {
+        $crate::__md_query!(@op $alts [$and.name_ends_with($v)] $($rest)*)
+    };
+    (@term $alts:tt [$and:expr] ext $v:tt $($rest:tt)*) => {
+        $crate::__md_query!(@op $alts [$and.extension($v)] $($rest)*)
+    };
+    (@term $alts:tt [$and:expr] kind $kind:ident $($rest:tt)*) => {
+        $crate::__md_query!(@op $alts [$and.of_kind($crate::ContentKind::$kind)] $($rest)*)
+    };
+    (@term $alts:tt [$and:expr] content_type $v:tt $($rest:tt)*) => {
+        $crate::__md_query!(@op $alts [$and.content_type($v)] $($rest)*)
+    };
+    (@term $alts:tt [$and:expr] conforms_to $v:tt $($rest:tt)*) => {
+        $crate::__md_query!(@op $alts [$and.content_type_conforms_to($v)] $($rest)*)
+    };
+    (@term $alts:tt [$and:expr] author $v:tt $($rest:tt)*) => {
+        $crate::__md_query!(@op $alts [$and.author($v)] $($rest)*)
+    };
+    (@term $alts:tt [$and:expr] keyword $v:tt $($rest:tt)*) => {
+        $crate::__md_query!(@op $alts [$and.keyword($v)] $($rest)*)
+    };
+    (@term $alts:tt [$and:expr] path under $v:tt $($rest:tt)*) => {
+        $crate::__md_query!(@op $alts [$and.path_starts_with($v)] $($rest)*)
+    };
+    (@term $alts:tt [$and:expr] path not_under $v:tt $($rest:tt)*) => {
+        $crate::__md_query!(@op $alts [$and.exclude_path_prefix($v)] $($rest)*)
+    };
+    (@term $alts:tt [$and:expr] is app $($rest:tt)*) => {
+        $crate::__md_query!(@op $alts [$and.is_app()] $($rest)*)
+    };
+    (@term $alts:tt [$and:expr] is dir $($rest:tt)*) => {
+        $crate::__md_query!(@op $alts [$and.is_dir(true)] $($rest)*)
+    };
+    (@term $alts:tt [$and:expr] size $op:tt $v:tt $($rest:tt)*) => {
+        $crate::__md_query!(@op $alts [$and.size($crate::__md_query!(@cmp $op), $v)] $($rest)*)
+    };
+    (@term $alts:tt [$and:expr] modified $($rest:tt)+) => {
+        $crate::__md_query!(@time $alts [$and] ModificationDate $($rest)+)
+    };
+    (@term $alts:tt [$and:expr] created $($rest:tt)+) => {
+        $crate::__md_query!(@time $alts [$and] CreationDate $($rest)+)
+    };
+    (@term $alts:tt [$and:expr] used $($rest:tt)+) => {
+        $crate::__md_query!(@time $alts [$and] LastUsedDate $($rest)+)
+    };
+    (@term $alts:tt [$and:expr] added $($rest:tt)+) => {
+        $crate::__md_query!(@time $alts [$and] DateAdded $($rest)+)
+    };
+    (@term $alts:tt [$and:expr] raw $v:tt $($rest:tt)*) => {
+        $crate::__md_query!(
+            @op $alts [$and.condition($crate::MDQueryCondition::all([$v]))] $($rest)*
+        )
+    };
+    (@term $alts:tt $and:tt $($rest:tt)*) => {
+        compile_error!(concat!("md_query!: unknown term `", stringify!($($rest)*), "`"))
+    };
+
+    // Time comparisons.
+    (@time $alts:tt [$and:expr] $key:ident $op:tt days_ago($days:expr) $($rest:tt)*) => {
+        $crate::__md_query!(@op $alts [$and.time_relative(
+            $crate::MDItemKey::$key,
+            $crate::__md_query!(@cmp $op),
+            $crate::RelativeTime::Today(-(($days) as i64)),
+        )] $($rest)*)
+    };
+    (@time $alts:tt [$and:expr] $key:ident $op:tt $v:tt $($rest:tt)*) => {
+        $crate::__md_query!(@op $alts [$and.time_dt(
+            $crate::MDItemKey::$key,
+            $crate::__md_query!(@cmp $op),
+            $v,
+        )] $($rest)*)
+    };
+
+    // Comparison operators.
+    (@cmp >) => {
+        $crate::MDQueryCompareOp::GreaterThan
+    };
+    (@cmp <) => {
+        $crate::MDQueryCompareOp::LessThan
+    };
+    (@cmp >=) => {
+        $crate::MDQueryCompareOp::GreaterThanOrEqual
+    };
+    (@cmp <=) => {
+        $crate::MDQueryCompareOp::LessThanOrEqual
+    };
+    (@cmp ==) => {
+        $crate::MDQueryCompareOp::Equal
+    };
+    (@cmp $op:tt) => {
+        compile_error!(concat!(
+            "md_query!: unknown comparison operator `",
+            stringify!($op),
+            "`"
+        ))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ContentKind, FileSize, MDItemKey, MDQueryBuilder, MDQueryCompareOp, MDQueryCondition,
+        MDQueryError, MDQueryScope, RelativeTime,
+    };
+    use chrono::{TimeZone, Utc};
+
+    fn query_string(builder: MDQueryBuilder) -> String {
+        builder.to_query_string().unwrap()
+    }
+
+    #[test]
+    fn test_single_terms() {
+        assert_eq!(
+            query_string(md_query!(name like "report")),
+            query_string(MDQueryBuilder::default().name_like("report"))
+        );
+        assert_eq!(
+            query_string(md_query!(name is "Safari.app")),
+            query_string(MDQueryBuilder::default().name_is("Safari.app"))
+        );
+        assert_eq!(
+            query_string(md_query!(kind Image)),
+            query_string(MDQueryBuilder::default().of_kind(ContentKind::Image))
+        );
+        assert_eq!(
+            query_string(md_query!(is app)),
+            query_string(MDQueryBuilder::default().is_app())
+        );
+        assert_eq!(
+            query_string(md_query!(raw "kMDItemFSSize > 1")),
+            "(((kMDItemFSSize > 1)))"
+        );
+    }
+
+    #[test]
+    fn test_conjunction() {
+        let dir = "/Users/me/Projects";
+        assert_eq!(
+            query_string(md_query!(
+                name starts_with "IMG_" && ext "jpg" && path under dir && path not_under "/tmp"
+            )),
+            query_string(
+                MDQueryBuilder::default()
+                    .name_starts_with("IMG_")
+                    .extension("jpg")
+                    .path_starts_with(dir)
+                    .exclude_path_prefix("/tmp")
+            )
+        );
+    }
+
+    #[test]
+    fn test_disjunction() {
+        assert_eq!(
+            query_string(md_query!(ext "pdf" || ext "docx" || ext "pages")),
+            "((((kMDItemFSName == \"*.pdf\"c)) || ((kMDItemFSName == \"*.docx\"c)) || \
+             ((kMDItemFSName == \"*.pages\"c))))"
+        );
+    }
+
+    #[test]
+    fn test_and_binds_tighter_than_or() {
+        assert_eq!(
+            query_string(md_query!(
+                name like "a" && ext "pdf" || name like "b" && is dir
+            )),
+            query_string(MDQueryBuilder::default().condition(MDQueryCondition::any([
+                MDQueryBuilder::default().name_like("a").extension("pdf"),
+                MDQueryBuilder::default().name_like("b").is_dir(true),
+            ])))
+        );
+    }
+
+    #[test]
+    fn test_groups() {
+        assert_eq!(
+            query_string(md_query!((name like "a" || name like "b") && (kind Pdf))),
+            query_string(
+                MDQueryBuilder::default()
+                    .condition(MDQueryCondition::any([
+                        MDQueryBuilder::default().name_like("a"),
+                        MDQueryBuilder::default().name_like("b"),
+                    ]))
+                    .condition(MDQueryBuilder::default().of_kind(ContentKind::Pdf).into())
+            )
+        );
+    }
+
+    #[test]
+    fn test_invalid_terms_in_groups_fail() {
+        let invalid = [
+            md_query!((name like "" && ext "pdf") || ext "doc"),
+            md_query!(ext "doc" || name like ""),
+            md_query!(ext "pdf" && (name like "" || ext "doc")),
+        ];
+        for builder in invalid {
+            let err = builder.build([MDQueryScope::Home], None).err().unwrap();
+            assert!(matches!(
+                err.downcast_ref::<MDQueryError>(),
+                Some(MDQueryError::InvalidBuilder(errors)) if errors.len() == 1
+            ));
+        }
+    }
+
+    #[test]
+    fn test_escaping() {
+        assert_eq!(
+            query_string(md_query!(name like "my \"file*")),
+            r#"((kMDItemDisplayName == "*my \"file\**"w))"#
+        );
+    }
+
+    #[test]
+    fn test_size_comparisons() {
+        assert_eq!(
+            query_string(md_query!(size > (FileSize::mb(1)) && size <= 5_000_000u64)),
+            query_string(
+                MDQueryBuilder::default()
+                    .size(MDQueryCompareOp::GreaterThan, FileSize::mb(1))
+                    .size(MDQueryCompareOp::LessThanOrEqual, 5_000_000u64)
+            )
+        );
+    }
+
+    #[test]
+    fn test_time_comparisons() {
+        let since = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            query_string(md_query!(
+                modified >= days_ago(7)
+                    && created < since
+                    && used > days_ago(1)
+                    && added >= days_ago(2)
+            )),
+            query_string(
+                MDQueryBuilder::default()
+                    .time_relative(
+                        MDItemKey::ModificationDate,
+                        MDQueryCompareOp::GreaterThanOrEqual,
+                        RelativeTime::Today(-7)
+                    )
+                    .time_dt(MDItemKey::CreationDate, MDQueryCompareOp::LessThan, since)
+                    .time_relative(
+                        MDItemKey::LastUsedDate,
+                        MDQueryCompareOp::GreaterThan,
+                        RelativeTime::Today(-1)
+                    )
+                    .added_within_days(2)
+            )
+        );
+    }
+
+    #[test]
+    fn test_metadata_terms() {
+        assert_eq!(
+            query_string(md_query!(
+                author "Jane" && keyword "tax" && content_type "com.adobe.pdf"
+                    && conforms_to "public.image" && name ends_with ".txt"
+            )),
+            query_string(
+                MDQueryBuilder::default()
+                    .author("Jane")
+                    .keyword("tax")
+                    .content_type("com.adobe.pdf")
+                    .content_type_conforms_to("public.image")
+                    .name_ends_with(".txt")
+            )
+        );
+    }
+}
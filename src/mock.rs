@@ -0,0 +1,720 @@
+//! An in-memory [`QueryBackend`] for testing code that runs queries without Spotlight.
+
+use crate::validate::{parse_query, QueryNode, QueryOp, QueryValue};
+use crate::{MDItemKey, MDQueryScope, QueryBackend};
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// An attribute value of a [`MockItem`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockValue {
+    String(String),
+    Strings(Vec<String>),
+    Number(f64),
+    Date(DateTime<Utc>),
+}
+
+impl From<&str> for MockValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<String> for MockValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<Vec<&str>> for MockValue {
+    fn from(values: Vec<&str>) -> Self {
+        Self::Strings(values.into_iter().map(str::to_string).collect())
+    }
+}
+
+impl From<Vec<String>> for MockValue {
+    fn from(values: Vec<String>) -> Self {
+        Self::Strings(values)
+    }
+}
+
+impl From<f64> for MockValue {
+    fn from(value: f64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<i64> for MockValue {
+    fn from(value: i64) -> Self {
+        Self::Number(value as f64)
+    }
+}
+
+impl From<u64> for MockValue {
+    fn from(value: u64) -> Self {
+        Self::Number(value as f64)
+    }
+}
+
+/// Booleans are stored as `1` and `0`, like Spotlight does.
+impl From<bool> for MockValue {
+    fn from(value: bool) -> Self {
+        Self::Number(if value { 1.0 } else { 0.0 })
+    }
+}
+
+impl From<DateTime<Utc>> for MockValue {
+    fn from(value: DateTime<Utc>) -> Self {
+        Self::Date(value)
+    }
+}
+
+/// A file in the table of a [`MockBackend`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockItem {
+    path: PathBuf,
+    attributes: HashMap<String, MockValue>,
+}
+
+impl MockItem {
+    /// Creates an item for `path`.
+    ///
+    /// The path, file name and display name attributes are filled in from the path.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file
+    ///
+    /// # Returns
+    ///
+    /// * `MockItem` - The new item
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        let path = path.into();
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        Self {
+            attributes: HashMap::from([
+                (
+                    MDItemKey::Path.to_string(),
+                    path.to_string_lossy().to_string().into(),
+                ),
+                (MDItemKey::FSName.to_string(), name.clone().into()),
+                (MDItemKey::DisplayName.to_string(), name.into()),
+            ]),
+            path,
+        }
+    }
+
+    /// Sets an attribute of the item.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The attribute to set
+    /// * `value` - The value of the attribute
+    ///
+    /// # Returns
+    ///
+    /// * `MockItem` - Self for method chaining
+    pub fn with_attribute(mut self, key: MDItemKey, value: impl Into<MockValue>) -> Self {
+        self.attributes.insert(key.to_string(), value.into());
+        self
+    }
+
+    /// Returns the path of the item.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the value of an attribute, if the item has it.
+    pub fn attribute(&self, key: MDItemKey) -> Option<&MockValue> {
+        self.attributes.get(&key.to_string())
+    }
+}
+
+/// A [`QueryBackend`] that evaluates queries against an in-memory table of [`MockItem`]s.
+///
+/// Queries are parsed with the same grammar as [`validate_query`](crate::validate_query)
+/// and evaluated like Spotlight would, with a few simplifications:
+///
+/// * The `w` (word-based) string modifier is ignored, and `d` only folds common Latin
+///   accents.
+/// * `$time` functions are evaluated in UTC, and weeks start on Monday.
+/// * Only [`MDQueryScope::Custom`] scopes restrict results; every other scope matches all
+///   items.
+/// * Comparisons against an attribute an item doesn't have are false, except `!=`.
+///
+/// Items are returned in the order they were added.
+#[derive(Debug, Clone, Default)]
+pub struct MockBackend {
+    items: Vec<MockItem>,
+}
+
+impl MockBackend {
+    /// Creates a backend with no items.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an item to the table.
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - The item to add
+    ///
+    /// # Returns
+    ///
+    /// * `MockBackend` - Self for method chaining
+    pub fn with_item(mut self, item: MockItem) -> Self {
+        self.items.push(item);
+        self
+    }
+
+    /// Returns the items in the table.
+    pub fn items(&self) -> &[MockItem] {
+        &self.items
+    }
+}
+
+impl QueryBackend for MockBackend {
+    type Item = MockItem;
+
+    fn execute(
+        &self,
+        query: &str,
+        scopes: &[MDQueryScope],
+        max_count: Option<usize>,
+    ) -> Result<Vec<MockItem>> {
+        let node = parse_query(query)?;
+        let now = Utc::now();
+        Ok(self
+            .items
+            .iter()
+            .filter(|item| in_scopes(item, scopes) && matches(&node, item, now))
+            .take(max_count.unwrap_or(usize::MAX))
+            .cloned()
+            .collect())
+    }
+}
+
+fn in_scopes(item: &MockItem, scopes: &[MDQueryScope]) -> bool {
+    scopes.is_empty()
+        || scopes.iter().any(|scope| match scope {
+            MDQueryScope::Custom(dir) => item.path.starts_with(dir),
+            _ => true,
+        })
+}
+
+fn matches(node: &QueryNode, item: &MockItem, now: DateTime<Utc>) -> bool {
+    match node {
+        QueryNode::And(nodes) => nodes.iter().all(|node| matches(node, item, now)),
+        QueryNode::Or(nodes) => nodes.iter().any(|node| matches(node, item, now)),
+        QueryNode::Compare {
+            attribute,
+            op,
+            value,
+        } => {
+            let values = attribute_values(item, attribute);
+            if values.is_empty() {
+                return *op == QueryOp::NotEqual;
+            }
+            // `!=` holds when no value is equal; other operators when any value satisfies them.
+            if *op == QueryOp::NotEqual {
+                !values
+                    .iter()
+                    .any(|v| compare(v, QueryOp::Equal, value, now))
+            } else {
+                values.iter().any(|v| compare(v, *op, value, now))
+            }
+        }
+        QueryNode::InRange {
+            attribute,
+            low,
+            high,
+        } => attribute_values(item, attribute).iter().any(|v| {
+            compare(v, QueryOp::GreaterThanOrEqual, low, now)
+                && compare(v, QueryOp::LessThanOrEqual, high, now)
+        }),
+    }
+}
+
+/// A single attribute value; string arrays are flattened into their elements.
+enum Scalar<'a> {
+    String(&'a str),
+    Number(f64),
+    Date(DateTime<Utc>),
+}
+
+fn attribute_values<'a>(item: &'a MockItem, attribute: &str) -> Vec<Scalar<'a>> {
+    let mut values = Vec::new();
+    let mut push = |value: &'a MockValue, strings_only: bool| match value {
+        MockValue::String(s) => values.push(Scalar::String(s)),
+        MockValue::Strings(list) => values.extend(list.iter().map(|s| Scalar::String(s))),
+        MockValue::Number(n) if !strings_only => values.push(Scalar::Number(*n)),
+        MockValue::Date(d) if !strings_only => values.push(Scalar::Date(*d)),
+        _ => {}
+    };
+    if attribute == "*" {
+        item.attributes.values().for_each(|value| push(value, true));
+    } else if let Some(value) = item.attributes.get(attribute) {
+        push(value, false);
+    }
+    values
+}
+
+fn compare(actual: &Scalar, op: QueryOp, expected: &QueryValue, now: DateTime<Utc>) -> bool {
+    match (actual, expected) {
+        (Scalar::String(actual), QueryValue::String { raw, modifiers }) => {
+            let fold = |s: &str| fold_string(s, modifiers);
+            if op == QueryOp::Equal {
+                wildcard_match(&fold(raw), &fold(actual))
+            } else {
+                ordered(fold(actual).as_str(), op, fold(&unescape(raw)).as_str())
+            }
+        }
+        (Scalar::Number(actual), QueryValue::Number(expected)) => ordered(*actual, op, *expected),
+        (Scalar::Date(actual), QueryValue::Time { function, argument }) => {
+            resolve_time(function, argument.as_deref(), now)
+                .is_some_and(|expected| ordered(*actual, op, expected))
+        }
+        _ => false,
+    }
+}
+
+fn ordered<T: PartialOrd>(actual: T, op: QueryOp, expected: T) -> bool {
+    match op {
+        QueryOp::Equal => actual == expected,
+        QueryOp::NotEqual => actual != expected,
+        QueryOp::LessThan => actual < expected,
+        QueryOp::GreaterThan => actual > expected,
+        QueryOp::LessThanOrEqual => actual <= expected,
+        QueryOp::GreaterThanOrEqual => actual >= expected,
+    }
+}
+
+/// Applies the `c` and `d` modifiers. Escapes are preserved.
+fn fold_string(s: &str, modifiers: &str) -> String {
+    s.chars()
+        .map(|c| {
+            let c = if modifiers.contains('d') {
+                fold_diacritic(c)
+            } else {
+                c
+            };
+            if modifiers.contains('c') {
+                c.to_lowercase().next().unwrap_or(c)
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+fn fold_diacritic(c: char) -> char {
+    match c {
+        'à'..='å' => 'a',
+        'À'..='Å' => 'A',
+        'ç' => 'c',
+        'Ç' => 'C',
+        'è'..='ë' => 'e',
+        'È'..='Ë' => 'E',
+        'ì'..='ï' => 'i',
+        'Ì'..='Ï' => 'I',
+        'ñ' => 'n',
+        'Ñ' => 'N',
+        'ò'..='ö' | 'ø' => 'o',
+        'Ò'..='Ö' | 'Ø' => 'O',
+        'ù'..='ü' => 'u',
+        'Ù'..='Ü' => 'U',
+        'ý' | 'ÿ' => 'y',
+        'Ý' => 'Y',
+        _ => c,
+    }
+}
+
+fn unescape(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        out.push(if c == '\\' {
+            chars.next().unwrap_or(c)
+        } else {
+            c
+        });
+    }
+    out
+}
+
+/// Matches `text` against a query pattern where `*` matches any run of characters and
+/// `\x` matches `x` literally.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    enum Token {
+        Literal(char),
+        Wildcard,
+    }
+    let mut tokens = Vec::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        tokens.push(match c {
+            '*' => Token::Wildcard,
+            '\\' => Token::Literal(chars.next().unwrap_or('\\')),
+            c => Token::Literal(c),
+        });
+    }
+    let text = text.chars().collect::<Vec<_>>();
+
+    // Greedy matching with backtracking to the most recent wildcard.
+    let (mut t, mut p) = (0, 0);
+    let mut backtrack = None;
+    while t < text.len() {
+        match tokens.get(p) {
+            Some(Token::Literal(c)) if *c == text[t] => {
+                t += 1;
+                p += 1;
+            }
+            Some(Token::Wildcard) => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            _ => match backtrack {
+                Some((wildcard, start)) => {
+                    p = wildcard + 1;
+                    t = start + 1;
+                    backtrack = Some((wildcard, start + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    tokens[p..]
+        .iter()
+        .all(|token| matches!(token, Token::Wildcard))
+}
+
+fn resolve_time(
+    function: &str,
+    argument: Option<&str>,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    if function == "iso" {
+        return DateTime::parse_from_rfc3339(argument?)
+            .ok()
+            .map(|time| time.with_timezone(&Utc));
+    }
+    let offset = argument.map_or(Some(0), |argument| argument.parse::<i64>().ok())?;
+    let today = now.date_naive();
+    let start_of = |date: NaiveDate| date.and_hms_opt(0, 0, 0).map(|time| time.and_utc());
+    let add_months = |date: NaiveDate, months: i64| {
+        let delta = Months::new(u32::try_from(months.unsigned_abs()).ok()?);
+        if months < 0 {
+            date.checked_sub_months(delta)
+        } else {
+            date.checked_add_months(delta)
+        }
+    };
+    match function {
+        "now" => now.checked_add_signed(chrono::Duration::try_seconds(offset)?),
+        "today" => start_of(today.checked_add_signed(chrono::Duration::try_days(offset)?)?),
+        "yesterday" => start_of(today.pred_opt()?),
+        "this_week" => {
+            let monday = today.checked_sub_signed(chrono::Duration::days(
+                today.weekday().num_days_from_monday() as i64,
+            ))?;
+            start_of(monday.checked_add_signed(chrono::Duration::try_weeks(offset)?)?)
+        }
+        "this_month" => start_of(add_months(today.with_day(1)?, offset)?),
+        "this_year" => start_of(NaiveDate::from_ymd_opt(
+            today.year().checked_add(i32::try_from(offset).ok()?)?,
+            1,
+            1,
+        )?),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{user_directory, UserDirectory};
+    use crate::{ContentKind, FileSize, MDQueryBuilder, MDQueryCompareOp, MatchModifiers};
+
+    use super::*;
+
+    fn backend() -> MockBackend {
+        let now = Utc::now();
+        MockBackend::new()
+            .with_item(
+                MockItem::new("/Users/me/Documents/Report 2024.pdf")
+                    .with_attribute(MDItemKey::Size, 2_000_000u64)
+                    .with_attribute(MDItemKey::ContentType, "com.adobe.pdf")
+                    .with_attribute(
+                        MDItemKey::ContentTypeTree,
+                        vec!["com.adobe.pdf", "public.data", "public.content"],
+                    )
+                    .with_attribute(MDItemKey::Keywords, vec!["tax", "2024"])
+                    .with_attribute(MDItemKey::ModificationDate, now),
+            )
+            .with_item(
+                MockItem::new("/Users/me/Pictures/Café.jpg")
+                    .with_attribute(MDItemKey::Size, 500_000u64)
+                    .with_attribute(
+                        MDItemKey::ContentTypeTree,
+                        vec!["public.jpeg", "public.image", "public.content"],
+                    )
+                    .with_attribute(
+                        MDItemKey::ModificationDate,
+                        now - chrono::Duration::days(30),
+                    ),
+            )
+            .with_item(
+                MockItem::new("/Users/me/.hidden.txt").with_attribute(MDItemKey::FSInvisible, true),
+            )
+    }
+
+    fn names(items: Vec<MockItem>) -> Vec<String> {
+        items
+            .iter()
+            .map(|item| {
+                item.path()
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    fn run(builder: MDQueryBuilder) -> Vec<String> {
+        names(builder.with_backend(backend()).execute().unwrap())
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*ari*", "Safari"));
+        assert!(wildcard_match("Saf*", "Safari"));
+        assert!(wildcard_match("*", ""));
+        assert!(wildcard_match("a*b*c", "aXbYbc"));
+        assert!(!wildcard_match("*.app", "Safari.apps"));
+        assert!(wildcard_match("my \\*file", "my *file"));
+        assert!(!wildcard_match("my \\*file", "my big file"));
+    }
+
+    #[test]
+    fn test_builder_round_trip() {
+        assert_eq!(
+            run(MDQueryBuilder::default().name_like("report")),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            run(MDQueryBuilder::default().name_like("Report")),
+            ["Report 2024.pdf"]
+        );
+        assert_eq!(
+            run(MDQueryBuilder::default().name_is("café.JPG")),
+            ["Café.jpg"]
+        );
+        assert_eq!(
+            run(MDQueryBuilder::default()
+                .name_is_with("cafe.jpg", MatchModifiers::CASE_DIACRITIC_INSENSITIVE)),
+            ["Café.jpg"]
+        );
+        assert_eq!(
+            run(MDQueryBuilder::default().extension("PDF")),
+            ["Report 2024.pdf"]
+        );
+        assert_eq!(
+            run(MDQueryBuilder::default().of_kind(ContentKind::Image)),
+            ["Café.jpg"]
+        );
+        assert_eq!(
+            run(MDQueryBuilder::default().of_kind(ContentKind::Document)),
+            ["Report 2024.pdf"]
+        );
+        assert_eq!(
+            run(MDQueryBuilder::default().size(MDQueryCompareOp::GreaterThan, FileSize::mb(1))),
+            ["Report 2024.pdf"]
+        );
+        assert_eq!(
+            run(MDQueryBuilder::default().size_between(FileSize::kb(100), FileSize::mb(1))),
+            ["Café.jpg"]
+        );
+        assert_eq!(
+            run(MDQueryBuilder::default().modified_within_days(7)),
+            ["Report 2024.pdf"]
+        );
+        assert_eq!(
+            run(MDQueryBuilder::default().keywords_any(&["TAX", "receipts"])),
+            ["Report 2024.pdf"]
+        );
+        assert_eq!(
+            run(MDQueryBuilder::default()
+                .name_like(".")
+                .include_hidden(false)),
+            ["Report 2024.pdf", "Café.jpg"]
+        );
+        assert_eq!(
+            run(MDQueryBuilder::default().path_starts_with("/Users/me/Pictures")),
+            ["Café.jpg"]
+        );
+    }
+
+    #[test]
+    fn test_scopes_and_max_count() {
+        let items = MDQueryBuilder::default()
+            .name_like("e")
+            .with_scopes([MDQueryScope::Custom("/Users/me/Documents".into())])
+            .with_backend(backend())
+            .execute()
+            .unwrap();
+        assert_eq!(names(items), ["Report 2024.pdf"]);
+
+        let items = MDQueryBuilder::default()
+            .name_like("e")
+            .with_max_count(1)
+            .with_backend(backend())
+            .execute()
+            .unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(
+            items[0].attribute(MDItemKey::Size),
+            Some(&MockValue::Number(2_000_000.0))
+        );
+    }
+
+    #[test]
+    fn test_execute_first_and_one() {
+        let backend = backend();
+        let query = |name: &str| {
+            MDQueryBuilder::default()
+                .name_like(name)
+                .with_backend(&backend)
+        };
+
+        assert!(query("zzz").execute_first().unwrap().is_none());
+        let first = query("e").execute_first().unwrap().unwrap();
+        assert_eq!(names(vec![first]), ["Report 2024.pdf"]);
+
+        let one = query("Café").execute_one().unwrap();
+        assert_eq!(names(vec![one]), ["Café.jpg"]);
+        let err = query("zzz").execute_one().err().unwrap();
+        assert_eq!(
+            err.downcast_ref::<crate::MDQueryError>(),
+            Some(&crate::MDQueryError::NotFound)
+        );
+        let err = query(".").execute_one().err().unwrap();
+        assert_eq!(
+            err.downcast_ref::<crate::MDQueryError>(),
+            Some(&crate::MDQueryError::Ambiguous { count: 2 })
+        );
+    }
+
+    #[test]
+    fn test_exclude_system_noise() {
+        let library = user_directory(UserDirectory::Library);
+        let backend = MockBackend::new()
+            .with_item(MockItem::new(
+                user_directory(UserDirectory::Documents).join("kept.txt"),
+            ))
+            .with_item(MockItem::new(
+                user_directory(UserDirectory::Trash).join("trashed.txt"),
+            ))
+            .with_item(MockItem::new(
+                user_directory(UserDirectory::Caches).join("com.example/cached.txt"),
+            ))
+            .with_item(MockItem::new("/Library/Caches/shared.txt"))
+            .with_item(MockItem::new(
+                "/Volumes/com.apple.TimeMachine.localsnapshots/Backups/snapshot.txt",
+            ))
+            .with_item(MockItem::new(library.join("Preferences/settings.plist")));
+        let find =
+            |builder: MDQueryBuilder| names(builder.with_backend(&backend).execute().unwrap());
+        assert_eq!(
+            find(MDQueryBuilder::default().exclude_system_noise()),
+            ["kept.txt", "settings.plist"]
+        );
+        assert_eq!(
+            find(MDQueryBuilder::default().exclude_trash()),
+            [
+                "kept.txt",
+                "cached.txt",
+                "shared.txt",
+                "snapshot.txt",
+                "settings.plist"
+            ]
+        );
+        assert_eq!(
+            find(MDQueryBuilder::default().exclude_library()),
+            ["kept.txt", "trashed.txt", "shared.txt", "snapshot.txt"]
+        );
+    }
+
+    #[test]
+    fn test_missing_architecture() {
+        let backend = MockBackend::new()
+            .with_item(
+                MockItem::new("/Applications/Universal.app")
+                    .with_attribute(MDItemKey::ExecutableArchitectures, vec!["arm64", "x86_64"]),
+            )
+            .with_item(
+                MockItem::new("/Applications/Intel.app")
+                    .with_attribute(MDItemKey::ExecutableArchitectures, vec!["x86_64"]),
+            )
+            .with_item(MockItem::new("/Users/me/notes.txt"));
+        let find =
+            |builder: MDQueryBuilder| names(builder.with_backend(&backend).execute().unwrap());
+        assert_eq!(
+            find(MDQueryBuilder::default().missing_architecture("arm64")),
+            ["Intel.app"]
+        );
+        assert_eq!(
+            find(MDQueryBuilder::default().has_architecture("x86_64")),
+            ["Universal.app", "Intel.app"]
+        );
+    }
+
+    #[test]
+    fn test_top_k_unsupported() {
+        let backend = backend();
+        let err = MDQueryBuilder::default()
+            .name_like("e")
+            .top_k(1, MDItemKey::FSName, crate::SortDirection::Ascending)
+            .with_backend(&backend)
+            .execute()
+            .err()
+            .unwrap();
+        assert!(matches!(
+            err.downcast_ref::<crate::MDQueryError>(),
+            Some(crate::MDQueryError::InvalidBuilder(errors)) if errors[0].method() == "top_k"
+        ));
+    }
+
+    #[test]
+    fn test_macro_round_trip() {
+        let backend = backend();
+        let items = crate::md_query!(
+            (ext "pdf" || kind Image) && modified > days_ago(60) && size < 1_000_000u64
+        )
+        .with_backend(&backend)
+        .execute()
+        .unwrap();
+        assert_eq!(names(items), ["Café.jpg"]);
+    }
+
+    #[test]
+    fn test_raw_queries() {
+        let backend = backend();
+        let run_raw = |query: &str| names(backend.execute(query, &[], None).unwrap());
+        assert_eq!(run_raw("* == \"tax\""), ["Report 2024.pdf"]);
+        assert_eq!(
+            run_raw("kMDItemContentModificationDate >= $time.this_year(-1)"),
+            ["Report 2024.pdf", "Café.jpg"]
+        );
+        assert_eq!(
+            run_raw("kMDItemFSSize != 500000 && kMDItemFSInvisible != 1"),
+            ["Report 2024.pdf"]
+        );
+        assert!(backend.execute("kMDItemFSSize >", &[], None).is_err());
+    }
+}
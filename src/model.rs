@@ -0,0 +1,1301 @@
+use crate::{BuilderError, MDQueryError};
+use chrono::{DateTime, Utc};
+use std::{fmt::{self, Display}, path::{Component, Path, PathBuf}, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MDQueryScope {
+    Home,
+    Computer,
+    Network,
+    AllIndexed,
+    ComputerIndexed,
+    NetworkIndexed,
+    Custom(PathBuf),
+    /// The user's Documents folder
+    Documents,
+    /// The user's Downloads folder
+    Downloads,
+    /// The user's Desktop folder
+    Desktop,
+    /// The user's Pictures folder
+    Pictures,
+    /// The user's Movies folder
+    Movies,
+    /// The user's Music folder
+    Music,
+    /// The `/Applications` folder
+    Applications,
+    /// The iCloud Drive folder (`~/Library/Mobile Documents/com~apple~CloudDocs`)
+    ICloudDrive,
+    /// All iCloud containers, including app-specific ones (`~/Library/Mobile Documents`)
+    ICloud,
+}
+
+impl MDQueryScope {
+    /// Creates a scope limited to the directory at `path`.
+    ///
+    /// A leading `~` is expanded to the home directory, relative paths are resolved
+    /// against the current directory, and `.`/`..` components and trailing slashes are
+    /// removed. The directory is not required to exist; see
+    /// [`try_from_path`](Self::try_from_path).
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        Self::Custom(expand_path(path.as_ref()))
+    }
+
+    /// Creates a scope limited to the directory at `path`, which must exist.
+    ///
+    /// The path is expanded like in [`from_path`](Self::from_path).
+    ///
+    /// # Errors
+    /// Returns [`MDQueryError::InvalidScope`] if the expanded path is not a directory.
+    pub fn try_from_path<P: AsRef<Path>>(path: P) -> Result<Self, MDQueryError> {
+        let path = expand_path(path.as_ref());
+        if !path.is_dir() {
+            return Err(MDQueryError::InvalidScope(path));
+        }
+        Ok(Self::Custom(path))
+    }
+
+    /// Scope limited to the user's Documents folder.
+    ///
+    /// Standard folder scopes are resolved when the query is built, which fails with
+    /// [`MDQueryError::InvalidScope`] if the folder does not exist.
+    pub fn documents() -> Self {
+        Self::Documents
+    }
+
+    /// Scope limited to the user's Downloads folder.
+    pub fn downloads() -> Self {
+        Self::Downloads
+    }
+
+    /// Scope limited to the user's Desktop folder.
+    pub fn desktop() -> Self {
+        Self::Desktop
+    }
+
+    /// Scope limited to the user's Pictures folder.
+    pub fn pictures() -> Self {
+        Self::Pictures
+    }
+
+    /// Scope limited to the user's Movies folder.
+    pub fn movies() -> Self {
+        Self::Movies
+    }
+
+    /// Scope limited to the user's Music folder.
+    pub fn music() -> Self {
+        Self::Music
+    }
+
+    /// Scope limited to the `/Applications` folder.
+    pub fn applications() -> Self {
+        Self::Applications
+    }
+
+    /// Scope limited to the iCloud Drive folder.
+    ///
+    /// Files that are stored in iCloud but not downloaded are represented by placeholders,
+    /// which may lack most content metadata (e.g. pixel dimensions or text content).
+    pub fn icloud_drive() -> Self {
+        Self::ICloudDrive
+    }
+
+    /// Scope covering every iCloud container, including the data of iCloud-enabled apps.
+    ///
+    /// The same placeholder limitation as for [`icloud_drive`](Self::icloud_drive) applies.
+    pub fn icloud() -> Self {
+        Self::ICloud
+    }
+
+    /// Creates scopes for several directories, validating all of them.
+    ///
+    /// Paths are expanded like in [`from_path`](Self::from_path), except that relative
+    /// paths are rejected. Paths that are equal to or nested inside another path are
+    /// dropped, since searching them again is redundant.
+    ///
+    /// # Errors
+    /// Returns [`MDQueryError::InvalidBuilder`] with one message per path that is not
+    /// absolute or not an existing directory.
+    pub fn from_paths<I, P>(paths: I) -> Result<Vec<Self>, MDQueryError>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut errors = Vec::new();
+        let mut directories = Vec::new();
+        for path in paths {
+            let path = path.as_ref();
+            let expanded = expand_path(path);
+            if !path.is_absolute() && !path.starts_with("~") {
+                errors.push(BuilderError::new(
+                    "from_paths",
+                    format!("{} is not absolute", path.display()),
+                ));
+            } else if !expanded.is_dir() {
+                errors.push(BuilderError::new(
+                    "from_paths",
+                    format!("{} is not an existing directory", expanded.display()),
+                ));
+            } else {
+                directories.push(expanded);
+            }
+        }
+        if !errors.is_empty() {
+            return Err(MDQueryError::InvalidBuilder(errors));
+        }
+        Ok(dedup_nested(directories)
+            .into_iter()
+            .map(Self::Custom)
+            .collect())
+    }
+
+    /// Resolves scopes into the strings passed to Spotlight, dropping redundant directories.
+    #[cfg_attr(any(feature = "fallback", not(target_vendor = "apple")), allow(dead_code))]
+    pub(crate) fn into_scope_strings(scopes: Vec<Self>) -> Result<Vec<String>, MDQueryError> {
+        let mut constants = Vec::new();
+        let mut directories = Vec::new();
+        for scope in scopes {
+            let scope = scope.into_scope_string()?;
+            if scope.starts_with('/') {
+                directories.push(PathBuf::from(scope));
+            } else if !constants.contains(&scope) {
+                constants.push(scope);
+            }
+        }
+        constants.extend(
+            dedup_nested(directories)
+                .into_iter()
+                .map(|path| path.to_string_lossy().to_string()),
+        );
+        Ok(constants)
+    }
+
+    #[cfg_attr(any(feature = "fallback", not(target_vendor = "apple")), allow(dead_code))]
+    pub(crate) fn into_scope_string(self) -> Result<String, MDQueryError> {
+        let directory = match self {
+            Self::Home => return Ok("kMDQueryScopeHome".to_string()),
+            Self::Computer => return Ok("kMDQueryScopeComputer".to_string()),
+            Self::Network => return Ok("kMDQueryScopeNetwork".to_string()),
+            Self::AllIndexed => return Ok("kMDQueryScopeAllIndexed".to_string()),
+            Self::ComputerIndexed => return Ok("kMDQueryScopeComputerIndexed".to_string()),
+            Self::NetworkIndexed => return Ok("kMDQueryScopeNetworkIndexed".to_string()),
+            Self::Custom(path) => return Ok(expand_path(&path).to_string_lossy().to_string()),
+            Self::Documents => user_directory(UserDirectory::Documents),
+            Self::Downloads => user_directory(UserDirectory::Downloads),
+            Self::Desktop => user_directory(UserDirectory::Desktop),
+            Self::Pictures => user_directory(UserDirectory::Pictures),
+            Self::Movies => user_directory(UserDirectory::Movies),
+            Self::Music => user_directory(UserDirectory::Music),
+            Self::Applications => PathBuf::from("/Applications"),
+            Self::ICloudDrive => icloud_directory().join("com~apple~CloudDocs"),
+            Self::ICloud => icloud_directory(),
+        };
+        if !directory.is_dir() {
+            return Err(MDQueryError::InvalidScope(directory));
+        }
+        Ok(directory.to_string_lossy().to_string())
+    }
+}
+
+/// Expands a leading `~` and makes `path` absolute, see [`MDQueryScope::from_path`].
+///
+/// If the home or current directory is unavailable, the path is returned as far as it
+/// could be expanded.
+fn expand_path(path: &Path) -> PathBuf {
+    let path = match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) if !home.is_empty() => PathBuf::from(home).join(rest),
+        _ => path.to_path_buf(),
+    };
+    absolutize(&path).unwrap_or(path)
+}
+
+/// Makes `path` absolute and removes `.` and `..` components without touching the filesystem.
+pub(crate) fn absolutize(path: &Path) -> std::io::Result<PathBuf> {
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+    let mut absolute = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                absolute.pop();
+            }
+            component => absolute.push(component),
+        }
+    }
+    Ok(absolute)
+}
+
+/// Removes directories that are equal to or nested inside another directory, keeping order.
+fn dedup_nested(directories: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut kept: Vec<PathBuf> = Vec::with_capacity(directories.len());
+    for (i, directory) in directories.iter().enumerate() {
+        let redundant = directories.iter().enumerate().any(|(j, other)| {
+            directory.starts_with(other) && (directory != other || j < i)
+        });
+        if !redundant {
+            kept.push(directory.clone());
+        }
+    }
+    kept
+}
+
+/// Returns the directory holding the user's iCloud containers.
+pub(crate) fn icloud_directory() -> PathBuf {
+    user_directory(UserDirectory::Library).join("Mobile Documents")
+}
+
+/// A standard folder of the current user, see [`user_directory`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum UserDirectory {
+    Documents,
+    Downloads,
+    Desktop,
+    Pictures,
+    Movies,
+    Music,
+    Library,
+    Caches,
+    Trash,
+}
+
+impl UserDirectory {
+    /// The folder's usual path relative to the home directory.
+    fn name(self) -> &'static str {
+        match self {
+            Self::Documents => "Documents",
+            Self::Downloads => "Downloads",
+            Self::Desktop => "Desktop",
+            Self::Pictures => "Pictures",
+            Self::Movies => "Movies",
+            Self::Music => "Music",
+            Self::Library => "Library",
+            Self::Caches => "Library/Caches",
+            Self::Trash => ".Trash",
+        }
+    }
+
+    /// The `NSSearchPathDirectory` value Foundation knows the folder by.
+    #[cfg(target_vendor = "apple")]
+    fn search_path_directory(self) -> usize {
+        match self {
+            Self::Documents => 9,
+            Self::Downloads => 15,
+            Self::Desktop => 12,
+            Self::Pictures => 19,
+            Self::Movies => 17,
+            Self::Music => 18,
+            Self::Library => 5,
+            Self::Caches => 13,
+            Self::Trash => 102,
+        }
+    }
+}
+
+/// Returns the path of a standard folder of the current user.
+///
+/// On Apple platforms the path comes from `NSFileManager`, which follows the folder into an
+/// app's sandbox container and wherever the user relocated it. Elsewhere, or if Foundation
+/// has no answer, the folder's usual name is joined to the home directory, see
+/// [`home_directory`].
+pub(crate) fn user_directory(directory: UserDirectory) -> PathBuf {
+    #[cfg(target_vendor = "apple")]
+    if let Some(path) = foundation_directory(directory.search_path_directory()) {
+        return path;
+    }
+    home_directory(directory.name())
+}
+
+/// Asks `NSFileManager` for the first URL of `directory` in the user domain.
+#[cfg(target_vendor = "apple")]
+fn foundation_directory(directory: usize) -> Option<PathBuf> {
+    use objc2::{class, msg_send, rc::autoreleasepool, rc::Retained, runtime::AnyObject};
+    use std::{ffi::{c_char, CStr, OsStr}, os::unix::ffi::OsStrExt};
+
+    // `NSFileManager` lives in Foundation, which objc2 does not link.
+    #[link(name = "Foundation", kind = "framework")]
+    extern "C" {}
+
+    const NS_USER_DOMAIN_MASK: usize = 1;
+    autoreleasepool(|_| {
+        let manager: Retained<AnyObject> =
+            unsafe { msg_send![class!(NSFileManager), defaultManager] };
+        let urls: Option<Retained<AnyObject>> = unsafe {
+            msg_send![&manager, URLsForDirectory: directory, inDomains: NS_USER_DOMAIN_MASK]
+        };
+        let url: Option<Retained<AnyObject>> = unsafe { msg_send![&urls?, firstObject] };
+        // The C string belongs to the URL and lives until the pool drains, so it is copied.
+        let path: *const c_char = unsafe { msg_send![&url?, fileSystemRepresentation] };
+        if path.is_null() {
+            return None;
+        }
+        let path = unsafe { CStr::from_ptr(path) };
+        Some(PathBuf::from(OsStr::from_bytes(path.to_bytes())))
+    })
+}
+
+/// Returns `name` inside the user's home directory, or `~/name` if `HOME` is unset.
+///
+/// This is the fallback of [`user_directory`]. It assumes the folder sits at its usual
+/// place, which holds for the English on-disk names macOS keeps for localized folders but
+/// not for a relocated folder or a sandbox container.
+pub(crate) fn home_directory(name: &str) -> PathBuf {
+    match std::env::var_os("HOME") {
+        Some(home) if !home.is_empty() => PathBuf::from(home).join(name),
+        _ => PathBuf::from("~").join(name),
+    }
+}
+
+/// Option bits passed to `MDQuerySetSearchScope` along with the search scopes.
+///
+/// Spotlight currently defines no public scope options, so the default of no bits is right
+/// for almost every query. Bits can be combined with `|`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ScopeOptions(u32);
+
+impl ScopeOptions {
+    /// No scope options.
+    pub const NONE: Self = Self(0);
+
+    /// Creates scope options from raw option bits.
+    pub const fn from_bits(bits: u32) -> Self {
+        Self(bits)
+    }
+
+    /// Returns the raw option bits.
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+}
+
+impl std::ops::BitOr for ScopeOptions {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// Content types whose presence in an item's content type tree marks it as a package, a
+/// directory presented as a file.
+pub(crate) const PACKAGE_CONTENT_TYPES: [&str; 2] = ["com.apple.package", "com.apple.bundle"];
+
+/// Option flags passed to `MDQueryExecute`, see
+/// [`MDQuery::execute_with_options`](crate::MDQuery::execute_with_options).
+///
+/// Flags can be combined with `|`. The default is [`SYNCHRONOUS`](Self::SYNCHRONOUS),
+/// which is what [`MDQuery::execute`](crate::MDQuery::execute) uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MDQueryOptions(u32);
+
+impl MDQueryOptions {
+    /// No options: the query gathers its results asynchronously.
+    pub const NONE: Self = Self(0);
+    /// Blocks until the initial gathering of results has finished.
+    pub const SYNCHRONOUS: Self = Self(1);
+    /// Keeps the query live after gathering, so Spotlight updates its results as files
+    /// change.
+    pub const WANTS_UPDATES: Self = Self(4);
+    /// Allows Spotlight to translate the query for file systems without a Spotlight
+    /// index, such as network volumes.
+    pub const ALLOW_FS_TRANSLATIONS: Self = Self(8);
+
+    /// Creates options from raw option bits.
+    pub const fn from_bits(bits: u32) -> Self {
+        Self(bits)
+    }
+
+    /// Returns the raw option bits.
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Returns whether all bits of `other` are set.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl Default for MDQueryOptions {
+    fn default() -> Self {
+        Self::SYNCHRONOUS
+    }
+}
+
+impl std::ops::BitOr for MDQueryOptions {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// Metadata attribute keys that can be used in queries.
+///
+/// These keys correspond to macOS Spotlight metadata attributes. Keys can be parsed from
+/// their constant names or from short aliases, see the [`FromStr`] implementation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MDItemKey {
+    /// The user-visible display name of the item
+    DisplayName,
+    /// The filename of the item
+    FSName,
+    /// The date the item's content was last modified
+    ModificationDate,
+    /// The date the item's content was created
+    CreationDate,
+    /// The date the item was last used/opened
+    LastUsedDate,
+    /// The date the item was added to its current folder (Finder's "Date Added")
+    DateAdded,
+    /// The size of the item in bytes
+    Size,
+    /// The logical size of the item's data in bytes
+    LogicalSize,
+    /// The number of bytes the item occupies on disk
+    PhysicalSize,
+    /// The UTI (Uniform Type Identifier) of the item
+    ContentType,
+    /// The content type tree of the item
+    ContentTypeTree,
+    /// The path of the item
+    Path,
+    /// Whether the item is invisible in the Finder
+    FSInvisible,
+    /// The localized kind of the item as shown by the Finder (e.g. "Application")
+    Kind,
+    /// The bundle identifier of an application or bundle (e.g. "com.apple.Safari")
+    BundleIdentifier,
+    /// The CPU architectures an application or executable contains code for (e.g. "arm64"
+    /// and "x86_64")
+    ExecutableArchitectures,
+    /// The localized name of the App Store category of an application (e.g. "Productivity")
+    AppStoreCategory,
+    /// The identifier of the App Store category of an application (e.g.
+    /// "public.app-category.productivity")
+    AppStoreCategoryType,
+    /// The authors of the item's content
+    Authors,
+    /// The application that created the item's content (e.g. "Pages")
+    Creator,
+    /// Keywords associated with the item
+    Keywords,
+    /// The Finder comment attached to the item
+    FinderComment,
+    /// The URLs the item was downloaded from
+    WhereFroms,
+    /// The date the item was downloaded
+    DownloadedDate,
+    /// The width of an image or video in pixels
+    PixelWidth,
+    /// The height of an image or video in pixels
+    PixelHeight,
+    /// The total number of pixels in an image or video
+    PixelCount,
+    /// The color space of an image (e.g. "RGB", "Gray" or "CMYK")
+    ColorSpace,
+    /// The number of bits per color channel of an image
+    BitsPerSample,
+    /// Whether an image has an alpha channel
+    HasAlphaChannel,
+    /// The orientation of an image or video, see [`ImageOrientation`]
+    Orientation,
+    /// The manufacturer of the camera or scanner that captured the item
+    AcquisitionMake,
+    /// The model of the camera or scanner that captured the item
+    AcquisitionModel,
+    /// The ISO speed used to capture a photo
+    ISOSpeed,
+    /// The actual focal length of the lens used to capture a photo, in millimeters
+    FocalLength,
+    /// The exposure time used to capture a photo, in seconds
+    ExposureTimeSeconds,
+    /// The f-number (aperture) used to capture a photo
+    FNumber,
+    /// The duration of an audio or video item in seconds
+    DurationSeconds,
+    /// The album a music track belongs to
+    Album,
+    /// The musical genre of a track
+    MusicalGenre,
+    /// The year a track was recorded
+    RecordingYear,
+    /// The sample rate of the audio data, in hertz
+    AudioSampleRate,
+    /// The number of audio channels
+    AudioChannelCount,
+    /// The codecs used to encode the item's media streams
+    Codecs,
+    /// The combined bit rate of all media streams, in kilobits per second
+    TotalBitRate,
+    /// The bit rate of the video stream, in kilobits per second
+    VideoBitRate,
+    /// The number of pages in a document
+    NumberOfPages,
+    /// The names of the fonts used by a document, or contained in a font file
+    Fonts,
+    /// The subject of an email message
+    Subject,
+    /// The names of the recipients of an email message
+    Recipients,
+    /// The email addresses of the authors of an item, such as the sender of a message
+    AuthorEmailAddresses,
+    /// The email addresses of the recipients of an email message
+    RecipientEmailAddresses,
+    /// The URL of a bookmark, history item or web archive
+    Url,
+    /// The number of times the item has been opened
+    UseCount,
+    /// The dates on which the item was opened
+    UsedDates,
+    /// The user ID of the item's owner
+    FSOwnerUserID,
+    /// The group ID of the item's owner
+    FSOwnerGroupID,
+    /// The number of items directly contained in a folder
+    FSNodeCount,
+    /// The index of the Finder label color assigned to the item
+    FSLabel,
+    /// Whether the item is a screenshot
+    IsScreenCapture,
+    /// The kind of screenshot, such as "window" or "selection"
+    ScreenCaptureType,
+    /// The encryption method protecting a document, such as "Password Encrypted"
+    SecurityMethod,
+    /// The title of a document
+    Title,
+    /// A free-form comment stored in the document's metadata
+    Comment,
+    /// The copyright notice of a document
+    Copyright,
+    /// The languages of a document's content, as language codes
+    Languages,
+    /// The text content of a document
+    TextContent,
+    /// The version string of an application or document
+    Version,
+    /// The Finder tags assigned to the item
+    UserTags,
+    /// The latitude where an image or video was captured, in degrees
+    Latitude,
+    /// The longitude where an image or video was captured, in degrees
+    Longitude,
+    /// The altitude where an image or video was captured, in meters above sea level
+    Altitude,
+}
+
+impl MDItemKey {
+    /// Every key, in declaration order.
+    pub const ALL: &'static [MDItemKey] = &[
+        Self::DisplayName,
+        Self::FSName,
+        Self::ModificationDate,
+        Self::CreationDate,
+        Self::LastUsedDate,
+        Self::DateAdded,
+        Self::Size,
+        Self::LogicalSize,
+        Self::PhysicalSize,
+        Self::ContentType,
+        Self::ContentTypeTree,
+        Self::Path,
+        Self::FSInvisible,
+        Self::Kind,
+        Self::BundleIdentifier,
+        Self::ExecutableArchitectures,
+        Self::AppStoreCategory,
+        Self::AppStoreCategoryType,
+        Self::Authors,
+        Self::Creator,
+        Self::Keywords,
+        Self::FinderComment,
+        Self::WhereFroms,
+        Self::DownloadedDate,
+        Self::PixelWidth,
+        Self::PixelHeight,
+        Self::PixelCount,
+        Self::ColorSpace,
+        Self::BitsPerSample,
+        Self::HasAlphaChannel,
+        Self::Orientation,
+        Self::AcquisitionMake,
+        Self::AcquisitionModel,
+        Self::ISOSpeed,
+        Self::FocalLength,
+        Self::ExposureTimeSeconds,
+        Self::FNumber,
+        Self::DurationSeconds,
+        Self::Album,
+        Self::MusicalGenre,
+        Self::RecordingYear,
+        Self::AudioSampleRate,
+        Self::AudioChannelCount,
+        Self::Codecs,
+        Self::TotalBitRate,
+        Self::VideoBitRate,
+        Self::NumberOfPages,
+        Self::Fonts,
+        Self::Subject,
+        Self::Recipients,
+        Self::AuthorEmailAddresses,
+        Self::RecipientEmailAddresses,
+        Self::Url,
+        Self::UseCount,
+        Self::UsedDates,
+        Self::FSOwnerUserID,
+        Self::FSOwnerGroupID,
+        Self::FSNodeCount,
+        Self::FSLabel,
+        Self::IsScreenCapture,
+        Self::ScreenCaptureType,
+        Self::SecurityMethod,
+        Self::Title,
+        Self::Comment,
+        Self::Copyright,
+        Self::Languages,
+        Self::TextContent,
+        Self::Version,
+        Self::UserTags,
+        Self::Latitude,
+        Self::Longitude,
+        Self::Altitude,
+    ];
+
+    /// Returns the Spotlight API string representation of the key.
+    ///
+    /// # Returns
+    /// The string constant used by the Spotlight API for this key.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::DisplayName => "kMDItemDisplayName",
+            Self::FSName => "kMDItemFSName",
+            Self::ModificationDate => "kMDItemContentModificationDate",
+            Self::CreationDate => "kMDItemContentCreationDate",
+            Self::LastUsedDate => "kMDItemLastUsedDate",
+            Self::DateAdded => "kMDItemDateAdded",
+            Self::Size => "kMDItemFSSize",
+            Self::LogicalSize => "kMDItemLogicalSize",
+            Self::PhysicalSize => "kMDItemPhysicalSize",
+            Self::ContentType => "kMDItemContentType",
+            Self::ContentTypeTree => "kMDItemContentTypeTree",
+            Self::Path => "kMDItemPath",
+            Self::FSInvisible => "kMDItemFSInvisible",
+            Self::Kind => "kMDItemKind",
+            Self::BundleIdentifier => "kMDItemCFBundleIdentifier",
+            Self::ExecutableArchitectures => "kMDItemExecutableArchitectures",
+            Self::AppStoreCategory => "kMDItemAppStoreCategory",
+            Self::AppStoreCategoryType => "kMDItemAppStoreCategoryType",
+            Self::Authors => "kMDItemAuthors",
+            Self::Creator => "kMDItemCreator",
+            Self::Keywords => "kMDItemKeywords",
+            Self::FinderComment => "kMDItemFinderComment",
+            Self::WhereFroms => "kMDItemWhereFroms",
+            Self::DownloadedDate => "kMDItemDownloadedDate",
+            Self::PixelWidth => "kMDItemPixelWidth",
+            Self::PixelHeight => "kMDItemPixelHeight",
+            Self::PixelCount => "kMDItemPixelCount",
+            Self::ColorSpace => "kMDItemColorSpace",
+            Self::BitsPerSample => "kMDItemBitsPerSample",
+            Self::HasAlphaChannel => "kMDItemHasAlphaChannel",
+            Self::Orientation => "kMDItemOrientation",
+            Self::AcquisitionMake => "kMDItemAcquisitionMake",
+            Self::AcquisitionModel => "kMDItemAcquisitionModel",
+            Self::ISOSpeed => "kMDItemISOSpeed",
+            Self::FocalLength => "kMDItemFocalLength",
+            Self::ExposureTimeSeconds => "kMDItemExposureTimeSeconds",
+            Self::FNumber => "kMDItemFNumber",
+            Self::DurationSeconds => "kMDItemDurationSeconds",
+            Self::Album => "kMDItemAlbum",
+            Self::MusicalGenre => "kMDItemMusicalGenre",
+            Self::RecordingYear => "kMDItemRecordingYear",
+            Self::AudioSampleRate => "kMDItemAudioSampleRate",
+            Self::AudioChannelCount => "kMDItemAudioChannelCount",
+            Self::Codecs => "kMDItemCodecs",
+            Self::TotalBitRate => "kMDItemTotalBitRate",
+            Self::VideoBitRate => "kMDItemVideoBitRate",
+            Self::NumberOfPages => "kMDItemNumberOfPages",
+            Self::Fonts => "kMDItemFonts",
+            Self::Subject => "kMDItemSubject",
+            Self::Recipients => "kMDItemRecipients",
+            Self::AuthorEmailAddresses => "kMDItemAuthorEmailAddresses",
+            Self::RecipientEmailAddresses => "kMDItemRecipientEmailAddresses",
+            Self::Url => "kMDItemURL",
+            Self::UseCount => "kMDItemUseCount",
+            Self::UsedDates => "kMDItemUsedDates",
+            Self::FSOwnerUserID => "kMDItemFSOwnerUserID",
+            Self::FSOwnerGroupID => "kMDItemFSOwnerGroupID",
+            Self::FSNodeCount => "kMDItemFSNodeCount",
+            Self::FSLabel => "kMDItemFSLabel",
+            Self::IsScreenCapture => "kMDItemIsScreenCapture",
+            Self::ScreenCaptureType => "kMDItemScreenCaptureType",
+            Self::SecurityMethod => "kMDItemSecurityMethod",
+            Self::Title => "kMDItemTitle",
+            Self::Comment => "kMDItemComment",
+            Self::Copyright => "kMDItemCopyright",
+            Self::Languages => "kMDItemLanguages",
+            Self::TextContent => "kMDItemTextContent",
+            Self::Version => "kMDItemVersion",
+            Self::UserTags => "kMDItemUserTags",
+            Self::Latitude => "kMDItemLatitude",
+            Self::Longitude => "kMDItemLongitude",
+            Self::Altitude => "kMDItemAltitude",
+        }
+    }
+
+    /// Returns the type of the attribute's values.
+    ///
+    /// # Returns
+    /// The value type Spotlight stores for this key.
+    pub fn kind(&self) -> MDItemKeyKind {
+        match self {
+            Self::DisplayName
+            | Self::FSName
+            | Self::ContentType
+            | Self::Path
+            | Self::Kind
+            | Self::BundleIdentifier
+            | Self::AppStoreCategory
+            | Self::AppStoreCategoryType
+            | Self::Creator
+            | Self::FinderComment
+            | Self::ColorSpace
+            | Self::AcquisitionMake
+            | Self::AcquisitionModel
+            | Self::Album
+            | Self::MusicalGenre
+            | Self::Subject
+            | Self::Url
+            | Self::ScreenCaptureType
+            | Self::SecurityMethod
+            | Self::Title
+            | Self::Comment
+            | Self::Copyright
+            | Self::TextContent
+            | Self::Version => MDItemKeyKind::Str,
+            Self::Size
+            | Self::LogicalSize
+            | Self::PhysicalSize
+            | Self::PixelWidth
+            | Self::PixelHeight
+            | Self::PixelCount
+            | Self::BitsPerSample
+            | Self::Orientation
+            | Self::ISOSpeed
+            | Self::FocalLength
+            | Self::ExposureTimeSeconds
+            | Self::FNumber
+            | Self::DurationSeconds
+            | Self::RecordingYear
+            | Self::AudioSampleRate
+            | Self::AudioChannelCount
+            | Self::TotalBitRate
+            | Self::VideoBitRate
+            | Self::NumberOfPages
+            | Self::UseCount
+            | Self::FSOwnerUserID
+            | Self::FSOwnerGroupID
+            | Self::FSNodeCount
+            | Self::FSLabel
+            | Self::Latitude
+            | Self::Longitude
+            | Self::Altitude => MDItemKeyKind::Number,
+            Self::ModificationDate
+            | Self::CreationDate
+            | Self::LastUsedDate
+            | Self::DateAdded => MDItemKeyKind::Date,
+            Self::FSInvisible | Self::IsScreenCapture | Self::HasAlphaChannel => {
+                MDItemKeyKind::Bool
+            }
+            Self::ContentTypeTree
+            | Self::ExecutableArchitectures
+            | Self::Authors
+            | Self::Keywords
+            | Self::WhereFroms
+            | Self::Codecs
+            | Self::Fonts
+            | Self::Recipients
+            | Self::AuthorEmailAddresses
+            | Self::RecipientEmailAddresses
+            | Self::Languages
+            | Self::UserTags => MDItemKeyKind::StrArray,
+            Self::UsedDates | Self::DownloadedDate => MDItemKeyKind::DateArray,
+        }
+    }
+
+    /// Checks if this key represents a date/time attribute.
+    ///
+    /// # Returns
+    /// `true` if the key's [`kind`](Self::kind) is `Date` or `DateArray`, `false` otherwise.
+    pub fn is_time(&self) -> bool {
+        matches!(self.kind(), MDItemKeyKind::Date | MDItemKeyKind::DateArray)
+    }
+
+    /// Checks if this key represents a size in bytes.
+    ///
+    /// # Returns
+    /// `true` if this key is a size attribute, `false` otherwise.
+    pub fn is_size(&self) -> bool {
+        matches!(self, Self::Size | Self::LogicalSize | Self::PhysicalSize)
+    }
+}
+
+impl Display for MDItemKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The type of the values of an [`MDItemKey`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MDItemKeyKind {
+    /// A string
+    Str,
+    /// An integer or floating-point number
+    Number,
+    /// A date
+    Date,
+    /// A boolean
+    Bool,
+    /// An array of strings
+    StrArray,
+    /// An array of dates
+    DateArray,
+}
+
+/// Short names accepted by `MDItemKey::from_str`, matched case-insensitively.
+const KEY_ALIASES: &[(&str, MDItemKey)] = &[
+    ("name", MDItemKey::DisplayName),
+    ("filename", MDItemKey::FSName),
+    ("path", MDItemKey::Path),
+    ("size", MDItemKey::Size),
+    ("modified", MDItemKey::ModificationDate),
+    ("created", MDItemKey::CreationDate),
+    ("used", MDItemKey::LastUsedDate),
+    ("added", MDItemKey::DateAdded),
+    ("downloaded", MDItemKey::DownloadedDate),
+    ("kind", MDItemKey::Kind),
+    ("content_type", MDItemKey::ContentType),
+    ("bundle_id", MDItemKey::BundleIdentifier),
+    ("title", MDItemKey::Title),
+    ("authors", MDItemKey::Authors),
+    ("version", MDItemKey::Version),
+    ("tags", MDItemKey::UserTags),
+];
+
+impl FromStr for MDItemKey {
+    type Err = MDQueryError;
+
+    /// Parses a key from its constant name, such as `"kMDItemFSSize"`, or from one of the
+    /// aliases `name`, `filename`, `path`, `size`, `modified`, `created`, `used`, `added`,
+    /// `downloaded`, `kind`, `content_type`, `bundle_id`, `title`, `authors`, `version` and
+    /// `tags`.
+    ///
+    /// The time aliases match the terms of [`md_query!`](crate::md_query), so `"modified"`
+    /// is the content modification date.
+    ///
+    /// # Errors
+    /// Returns [`MDQueryError::UnknownAttribute`] if the string names no key.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|key| key.as_str() == s)
+            .or_else(|| {
+                KEY_ALIASES
+                    .iter()
+                    .find(|(alias, _)| alias.eq_ignore_ascii_case(s))
+                    .map(|(_, key)| key)
+            })
+            .copied()
+            .ok_or_else(|| MDQueryError::UnknownAttribute(s.to_string()))
+    }
+}
+
+/// Common categories of content, each mapping to one or more UTIs.
+///
+/// Kinds are matched against the content type tree, so `ContentKind::Image` matches JPEG,
+/// PNG and any other type conforming to `public.image`. A kind may also exclude UTIs, see
+/// [`excluded_content_types`](Self::excluded_content_types).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContentKind {
+    /// Images (`public.image`)
+    Image,
+    /// Movies and videos (`public.movie`)
+    Movie,
+    /// Audio files (`public.audio`)
+    Audio,
+    /// PDF documents (`com.adobe.pdf`)
+    Pdf,
+    /// Text files, including source code and markup (`public.text`)
+    Text,
+    /// Archives such as zip, gzip and tar files
+    Archive,
+    /// Applications (`com.apple.application`)
+    Application,
+    /// Plain folders (`public.folder`)
+    Folder,
+    /// Presentations (`public.presentation`)
+    Presentation,
+    /// Spreadsheets (`public.spreadsheet`)
+    Spreadsheet,
+    /// Source code (`public.source-code`)
+    SourceCode,
+    /// Fonts (`public.font`)
+    Font,
+    /// Email messages
+    Email,
+    /// Bookmarks and web locations
+    Bookmark,
+    /// Documents: content (`public.content`) other than images, movies and audio
+    Document,
+}
+
+impl ContentKind {
+    /// Returns the UTIs that make up this kind.
+    ///
+    /// An item belongs to the kind if its content type tree contains any of them.
+    ///
+    /// # Returns
+    /// The UTI strings for this kind.
+    pub fn content_types(&self) -> &'static [&'static str] {
+        match self {
+            Self::Image => &["public.image"],
+            Self::Movie => &["public.movie"],
+            Self::Audio => &["public.audio"],
+            Self::Pdf => &["com.adobe.pdf"],
+            Self::Text => &["public.text"],
+            Self::Archive => &[
+                "public.archive",
+                "public.zip-archive",
+                "org.gnu.gnu-zip-archive",
+                "public.tar-archive",
+            ],
+            Self::Application => &["com.apple.application"],
+            Self::Folder => &["public.folder"],
+            Self::Presentation => &["public.presentation"],
+            Self::Spreadsheet => &["public.spreadsheet"],
+            Self::SourceCode => &["public.source-code"],
+            Self::Font => &["public.font"],
+            Self::Email => &["public.email-message", "com.apple.mail.emlx"],
+            Self::Bookmark => &[
+                "com.apple.safari.bookmark",
+                "com.apple.web-internet-location",
+            ],
+            Self::Document => &["public.content"],
+        }
+    }
+
+    /// Returns the UTIs that items of this kind must not conform to.
+    ///
+    /// An item whose content type tree contains any of them does not belong to the kind,
+    /// even if it matches [`content_types`](Self::content_types).
+    ///
+    /// # Returns
+    /// The excluded UTI strings, empty for most kinds.
+    pub fn excluded_content_types(&self) -> &'static [&'static str] {
+        match self {
+            Self::Document => &["public.image", "public.movie", "public.audio"],
+            _ => &[],
+        }
+    }
+}
+
+/// The orientation of an image or video, as stored in `kMDItemOrientation`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImageOrientation {
+    /// Wider than tall
+    Landscape,
+    /// Taller than wide
+    Portrait,
+    /// A value Spotlight does not document, kept as stored
+    Other(i64),
+}
+
+impl ImageOrientation {
+    /// Returns the numeric orientation value used by Spotlight.
+    ///
+    /// # Returns
+    /// 0 for landscape, 1 for portrait, or the stored value of [`Other`](Self::Other).
+    pub const fn value(self) -> i64 {
+        match self {
+            Self::Landscape => 0,
+            Self::Portrait => 1,
+            Self::Other(value) => value,
+        }
+    }
+
+    /// Converts a numeric orientation value into an `ImageOrientation`.
+    ///
+    /// # Parameters
+    /// * `value` - The orientation value
+    ///
+    /// # Returns
+    /// The matching orientation, or [`Other`](Self::Other) with the value.
+    pub const fn from_value(value: i64) -> Self {
+        match value {
+            0 => Self::Landscape,
+            1 => Self::Portrait,
+            value => Self::Other(value),
+        }
+    }
+}
+
+/// A Finder label color, as stored in `kMDItemFSLabel`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FinderLabel {
+    /// No label
+    None,
+    /// Gray label
+    Gray,
+    /// Green label
+    Green,
+    /// Purple label
+    Purple,
+    /// Blue label
+    Blue,
+    /// Yellow label
+    Yellow,
+    /// Red label
+    Red,
+    /// Orange label
+    Orange,
+}
+
+impl FinderLabel {
+    /// Returns the numeric label index used by Spotlight.
+    ///
+    /// # Returns
+    /// The label index, from 0 (no label) to 7 (orange).
+    pub const fn value(self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Gray => 1,
+            Self::Green => 2,
+            Self::Purple => 3,
+            Self::Blue => 4,
+            Self::Yellow => 5,
+            Self::Red => 6,
+            Self::Orange => 7,
+        }
+    }
+
+    /// Converts a numeric label index into a `FinderLabel`.
+    ///
+    /// # Parameters
+    /// * `value` - The label index
+    ///
+    /// # Returns
+    /// The matching label, or `None` if the index is out of range.
+    pub const fn from_value(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::None),
+            1 => Some(Self::Gray),
+            2 => Some(Self::Green),
+            3 => Some(Self::Purple),
+            4 => Some(Self::Blue),
+            5 => Some(Self::Yellow),
+            6 => Some(Self::Red),
+            7 => Some(Self::Orange),
+            _ => None,
+        }
+    }
+}
+
+/// Descriptive metadata of a document, fetched in one call by `MDItem::document_info`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DocumentInfo {
+    /// The document title (`kMDItemTitle`)
+    pub title: Option<String>,
+    /// The metadata comment (`kMDItemComment`)
+    pub comment: Option<String>,
+    /// The copyright notice (`kMDItemCopyright`)
+    pub copyright: Option<String>,
+    /// The content languages (`kMDItemLanguages`)
+    pub languages: Option<Vec<String>>,
+}
+
+/// A summary of a video's dimensions and streams, fetched in one call by
+/// `MDItem::video_info`.
+///
+/// Spotlight records no frame rate, so none is included.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VideoInfo {
+    /// The width in pixels (`kMDItemPixelWidth`)
+    pub width: u32,
+    /// The height in pixels (`kMDItemPixelHeight`)
+    pub height: u32,
+    /// The playback duration (`kMDItemDurationSeconds`)
+    pub duration: Option<std::time::Duration>,
+    /// The codecs of the media streams (`kMDItemCodecs`)
+    pub codecs: Option<Vec<String>>,
+    /// The bit rate of the video stream in kilobits per second (`kMDItemVideoBitRate`)
+    pub bit_rate: Option<f64>,
+}
+
+/// A file size in bytes, with constructors for decimal and binary units.
+///
+/// Decimal units (`kb`, `mb`, `gb`) are powers of 1000, binary units (`kib`, `mib`, `gib`)
+/// are powers of 1024. Plain `u64` values convert into a size in bytes. Sizes that do
+/// not fit in a `u64` saturate at `u64::MAX` bytes.
+///
+/// # Examples
+///
+/// ```
+/// use mdquery_rs::FileSize;
+///
+/// assert_eq!(FileSize::mb(1).as_bytes(), 1_000_000);
+/// assert_eq!(FileSize::mib(1).as_bytes(), 1_048_576);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileSize(u64);
+
+impl FileSize {
+    /// A size of `n` bytes.
+    pub const fn bytes(n: u64) -> Self {
+        Self(n)
+    }
+
+    /// A size of `n` kilobytes (1000 bytes).
+    pub const fn kb(n: u64) -> Self {
+        Self(n.saturating_mul(1000))
+    }
+
+    /// A size of `n` megabytes (1000² bytes).
+    pub const fn mb(n: u64) -> Self {
+        Self(n.saturating_mul(1000 * 1000))
+    }
+
+    /// A size of `n` gigabytes (1000³ bytes).
+    pub const fn gb(n: u64) -> Self {
+        Self(n.saturating_mul(1000 * 1000 * 1000))
+    }
+
+    /// A size of `n` kibibytes (1024 bytes).
+    pub const fn kib(n: u64) -> Self {
+        Self(n.saturating_mul(1024))
+    }
+
+    /// A size of `n` mebibytes (1024² bytes).
+    pub const fn mib(n: u64) -> Self {
+        Self(n.saturating_mul(1024 * 1024))
+    }
+
+    /// A size of `n` gibibytes (1024³ bytes).
+    pub const fn gib(n: u64) -> Self {
+        Self(n.saturating_mul(1024 * 1024 * 1024))
+    }
+
+    /// Returns the size in bytes.
+    pub const fn as_bytes(&self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for FileSize {
+    fn from(bytes: u64) -> Self {
+        Self(bytes)
+    }
+}
+
+impl Display for FileSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A metadata attribute value read from Spotlight, such as a grouping key or a facet.
+///
+/// Floating-point values compare and hash by their bit pattern, so values can be used as
+/// `HashMap` keys.
+#[derive(Debug, Clone)]
+pub enum MDAttributeValue {
+    /// A string, e.g. a content type
+    String(String),
+    /// An integral number
+    Integer(i64),
+    /// A floating-point number
+    Float(f64),
+    /// A boolean
+    Bool(bool),
+    /// A date
+    Date(DateTime<Utc>),
+    /// An array of values, e.g. the authors of a document
+    Array(Vec<MDAttributeValue>),
+}
+
+impl MDAttributeValue {
+    /// Returns the value as a string slice if it is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl PartialEq for MDAttributeValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::String(a), Self::String(b)) => a == b,
+            (Self::Integer(a), Self::Integer(b)) => a == b,
+            (Self::Float(a), Self::Float(b)) => a.to_bits() == b.to_bits(),
+            (Self::Bool(a), Self::Bool(b)) => a == b,
+            (Self::Date(a), Self::Date(b)) => a == b,
+            (Self::Array(a), Self::Array(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for MDAttributeValue {}
+
+impl std::hash::Hash for MDAttributeValue {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Self::String(value) => value.hash(state),
+            Self::Integer(value) => value.hash(state),
+            Self::Float(value) => value.to_bits().hash(state),
+            Self::Bool(value) => value.hash(state),
+            Self::Date(value) => value.hash(state),
+            Self::Array(values) => values.hash(state),
+        }
+    }
+}
+
+impl Display for MDAttributeValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String(value) => write!(f, "{}", value),
+            Self::Integer(value) => write!(f, "{}", value),
+            Self::Float(value) => write!(f, "{}", value),
+            Self::Bool(value) => write!(f, "{}", value),
+            Self::Date(value) => write!(f, "{}", value.to_rfc3339()),
+            Self::Array(values) => {
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl From<&str> for MDAttributeValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<String> for MDAttributeValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
@@ -0,0 +1,16 @@
+//! The types most queries need, for glob importing.
+//!
+//! ```
+//! use mdquery_rs::prelude::*;
+//!
+//! let builder = MDQueryBuilder::default()
+//!     .name_like("report")
+//!     .size(MDQueryCompareOp::GreaterThan, 1_000_000u64);
+//! # let _ = builder;
+//! ```
+
+pub use crate::md_query;
+pub use crate::{
+    MDItem, MDItemKey, MDQuery, MDQueryBuilder, MDQueryCompareOp, MDQueryCondition,
+    MDQueryConditionExpression, MDQueryError, MDQueryResults, MDQueryScope,
+};
@@ -0,0 +1,539 @@
+use crate::json::{self, JsonOptions};
+use crate::{ContentKind, MDAttributeValue, MDItem, MDItemKey};
+use anyhow::Result;
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::PathBuf;
+use std::thread;
+
+/// Below this many items, [`MDQueryResults::hydrate_parallel`] reads attributes on the
+/// calling thread, since spawning threads would cost more than it saves.
+const MIN_PARALLEL_HYDRATION: usize = 256;
+
+/// Helpers for removing duplicate items from query results, capping them per content kind,
+/// reading their attributes and exporting them.
+///
+/// Spotlight can return the same file more than once when search scopes overlap, e.g.
+/// [`MDQueryScope::Home`](crate::MDQueryScope::Home) together with a custom directory
+/// inside the home directory. Both dedup helpers keep the first occurrence of every item and
+/// preserve the order of the results. Items without a path, such as Mail messages, are
+/// always kept.
+///
+/// # Examples
+///
+/// ```no_run
+/// use mdquery_rs::{MDQuery, MDQueryResults, MDQueryScope};
+///
+/// let items = MDQuery::new(
+///     "kMDItemFSName = \"*.pdf\"",
+///     [MDQueryScope::Home, MDQueryScope::from_path("~/Documents")],
+///     None,
+/// )?
+/// .execute()?
+/// .dedup_by_path();
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub trait MDQueryResults {
+    /// Removes items whose path was already seen.
+    fn dedup_by_path(self) -> Self;
+
+    /// Removes items that refer to a file already seen, comparing device and inode.
+    ///
+    /// This also catches the same file reached through different paths, such as hard
+    /// links or paths through a firmlinked directory. Items whose file cannot be
+    /// inspected, and all items on non-Unix platforms, are compared by path instead.
+    fn dedup_by_inode(self) -> Self;
+
+    /// Sorts the items into one bucket per content kind and truncates every bucket.
+    ///
+    /// Each item goes into the bucket of the first kind in `caps` it matches, see
+    /// [`MDItem::matches_kind`], or into a trailing `None` bucket if it matches none of
+    /// them. Items keep their order within a bucket, so a query sorted by relevance yields
+    /// the most relevant items of each kind.
+    ///
+    /// # Parameters
+    /// * `caps` - The kinds to sort items into and the maximum number of items of each
+    /// * `other_cap` - The maximum number of items matching none of the kinds
+    ///
+    /// # Returns
+    /// One bucket per entry of `caps`, in the same order, followed by the `None` bucket.
+    /// Buckets without items are included.
+    fn take_per_kind(
+        self,
+        caps: &[(ContentKind, usize)],
+        other_cap: usize,
+    ) -> Vec<(Option<ContentKind>, Vec<MDItem>)>;
+
+    /// Reads the given attributes of every item on the calling thread.
+    ///
+    /// # Parameters
+    /// * `attributes` - The attributes to read for every item
+    ///
+    /// # Returns
+    /// One [`HydratedItem`] per item, in the order of the results.
+    fn hydrate(&self, attributes: &[MDItemKey]) -> Vec<HydratedItem>;
+
+    /// Reads the given attributes of every item, spreading the items over one thread per
+    /// available CPU when the `parallel` feature is enabled.
+    ///
+    /// The output is identical to [`hydrate`](Self::hydrate), including its order. Small
+    /// result sets, and all result sets without the `parallel` feature, are read on the
+    /// calling thread. The threads are scoped `std` threads spawned for each call rather than
+    /// a shared pool such as rayon's, which keeps the crate free of dependencies at the cost
+    /// of starting the threads every time.
+    ///
+    /// # Parameters
+    /// * `attributes` - The attributes to read for every item
+    ///
+    /// # Returns
+    /// One [`HydratedItem`] per item, in the order of the results.
+    fn hydrate_parallel(&self, attributes: &[MDItemKey]) -> Vec<HydratedItem>;
+
+    /// Exports the results as a JSON array with one object per item.
+    ///
+    /// See [`write_json_with`](Self::write_json_with) for the format.
+    ///
+    /// # Parameters
+    /// * `attributes` - The attributes to include for every item
+    ///
+    /// # Returns
+    /// A Result containing the JSON text.
+    fn to_json(&self, attributes: &[MDItemKey]) -> Result<String> {
+        let mut json = Vec::new();
+        self.write_json(&mut json, attributes)?;
+        Ok(String::from_utf8(json)?)
+    }
+
+    /// Writes the results as a JSON array with one object per item.
+    ///
+    /// See [`write_json_with`](Self::write_json_with) for the format.
+    ///
+    /// # Parameters
+    /// * `writer` - Where to write the JSON text
+    /// * `attributes` - The attributes to include for every item
+    ///
+    /// # Returns
+    /// A Result that is an error if writing fails.
+    fn write_json<W: Write>(&self, writer: W, attributes: &[MDItemKey]) -> Result<()> {
+        self.write_json_with(writer, attributes, &JsonOptions::default())
+    }
+
+    /// Writes the results as a JSON array with one object per item, item by item.
+    ///
+    /// Every object maps attribute names such as `"kMDItemFSName"` to their values, in
+    /// the order of `attributes`. Attributes an item does not have are omitted. Dates are
+    /// written as RFC 3339 strings in UTC, arrays as JSON arrays.
+    ///
+    /// # Parameters
+    /// * `writer` - Where to write the JSON text
+    /// * `attributes` - The attributes to include for every item
+    /// * `options` - Formatting options, such as truncation of long strings
+    ///
+    /// # Returns
+    /// A Result that is an error if writing fails.
+    fn write_json_with<W: Write>(
+        &self,
+        writer: W,
+        attributes: &[MDItemKey],
+        options: &JsonOptions,
+    ) -> Result<()>;
+}
+
+impl MDQueryResults for Vec<MDItem> {
+    fn dedup_by_path(self) -> Self {
+        let mut seen = HashSet::new();
+        self.into_iter()
+            .filter(|item| item.path().is_none_or(|path| seen.insert(path)))
+            .collect()
+    }
+
+    fn dedup_by_inode(self) -> Self {
+        let mut seen = HashSet::new();
+        self.into_iter()
+            .filter(|item| {
+                item.path()
+                    .is_none_or(|path| seen.insert(FileIdentity::of(path)))
+            })
+            .collect()
+    }
+
+    fn take_per_kind(
+        self,
+        caps: &[(ContentKind, usize)],
+        other_cap: usize,
+    ) -> Vec<(Option<ContentKind>, Vec<MDItem>)> {
+        let mut buckets = caps
+            .iter()
+            .map(|(kind, cap)| (Some(*kind), *cap, Vec::new()))
+            .chain([(None, other_cap, Vec::new())])
+            .collect::<Vec<_>>();
+        for item in self {
+            let bucket = caps
+                .iter()
+                .position(|(kind, _)| item.matches_kind(*kind))
+                .unwrap_or(caps.len());
+            let (_, cap, items) = &mut buckets[bucket];
+            if items.len() < *cap {
+                items.push(item);
+            }
+        }
+        buckets
+            .into_iter()
+            .map(|(kind, _, items)| (kind, items))
+            .collect()
+    }
+
+    fn hydrate(&self, attributes: &[MDItemKey]) -> Vec<HydratedItem> {
+        self.iter()
+            .map(|item| HydratedItem::read(item, attributes))
+            .collect()
+    }
+
+    fn hydrate_parallel(&self, attributes: &[MDItemKey]) -> Vec<HydratedItem> {
+        let threads = if cfg!(feature = "parallel") {
+            thread::available_parallelism().map_or(1, |n| n.get())
+        } else {
+            1
+        };
+        if threads == 1 || self.len() < MIN_PARALLEL_HYDRATION {
+            return self.hydrate(attributes);
+        }
+        let chunk_size = self.len().div_ceil(threads);
+        thread::scope(|scope| {
+            let handles = self
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|item| HydratedItem::read(item, attributes))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        })
+    }
+
+    fn write_json_with<W: Write>(
+        &self,
+        mut writer: W,
+        attributes: &[MDItemKey],
+        options: &JsonOptions,
+    ) -> Result<()> {
+        write!(writer, "[")?;
+        for (i, item) in self.iter().enumerate() {
+            if i > 0 {
+                write!(writer, ",")?;
+            }
+            write!(writer, "{{")?;
+            let mut first = true;
+            for key in attributes {
+                let Some(value) = item.get_attribute_value(key.as_str()) else {
+                    continue;
+                };
+                if !first {
+                    write!(writer, ",")?;
+                }
+                first = false;
+                json::write_string(&mut writer, key.as_str())?;
+                write!(writer, ":")?;
+                json::write_value(&mut writer, &value, options)?;
+            }
+            write!(writer, "}}")?;
+        }
+        write!(writer, "]")?;
+        Ok(())
+    }
+}
+
+/// An item together with attribute values read by [`MDQueryResults::hydrate`] or
+/// [`MDQueryResults::hydrate_parallel`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct HydratedItem {
+    item: MDItem,
+    values: Vec<(&'static str, Option<MDAttributeValue>)>,
+}
+
+impl HydratedItem {
+    fn read(item: &MDItem, attributes: &[MDItemKey]) -> Self {
+        Self {
+            item: item.clone(),
+            values: attributes
+                .iter()
+                .map(|key| (key.as_str(), item.get_attribute_value(key.as_str())))
+                .collect(),
+        }
+    }
+
+    /// The item the values were read from.
+    pub fn item(&self) -> &MDItem {
+        &self.item
+    }
+
+    /// Returns the item, dropping the values.
+    pub fn into_item(self) -> MDItem {
+        self.item
+    }
+
+    /// Returns the value read for `key`.
+    ///
+    /// # Parameters
+    /// * `key` - One of the attributes passed to `hydrate`
+    ///
+    /// # Returns
+    /// The value, or None if the item has no such attribute or it was not read.
+    pub fn get(&self, key: MDItemKey) -> Option<&MDAttributeValue> {
+        let name = key.as_str();
+        self.values
+            .iter()
+            .find(|(key, _)| *key == name)
+            .and_then(|(_, value)| value.as_ref())
+    }
+}
+
+/// An item returned by [`MDQuery::execute_per_scope`](crate::MDQuery::execute_per_scope),
+/// together with the scope it was found in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScopedItem {
+    /// The index of the scope in the `limits` passed to `execute_per_scope`
+    pub scope_index: usize,
+    /// The item found in the scope
+    pub item: MDItem,
+}
+
+/// An item returned by [`MDQuery::search_text`](crate::MDQuery::search_text), together
+/// with its relevance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoredItem {
+    /// How well the item matches the search term, as reported by Spotlight; higher is
+    /// better
+    pub relevance: f64,
+    /// The matching item
+    pub item: MDItem,
+}
+
+/// A change to the results of a live query, delivered by
+/// [`MDQuery::watch_stream`](crate::MDQuery::watch_stream).
+#[cfg(feature = "async")]
+#[derive(Debug, Clone, PartialEq)]
+pub enum MDQueryUpdateEvent {
+    /// Items that started matching the query, such as newly created files
+    Added(Vec<MDItem>),
+    /// Items that still match the query but whose metadata changed
+    Changed(Vec<MDItem>),
+    /// Items that no longer match the query, such as deleted files
+    Removed(Vec<MDItem>),
+}
+
+#[cfg(feature = "async")]
+impl MDQueryUpdateEvent {
+    /// Returns the items of the event, whatever its kind.
+    pub fn items(&self) -> &[MDItem] {
+        match self {
+            Self::Added(items) | Self::Changed(items) | Self::Removed(items) => items,
+        }
+    }
+}
+
+/// Merges the results of one query per scope, keeping the first occurrence of every path.
+#[cfg_attr(
+    any(feature = "fallback", not(target_vendor = "apple")),
+    allow(dead_code)
+)]
+pub(crate) fn merge_scoped(results: Vec<Vec<MDItem>>) -> Vec<ScopedItem> {
+    let mut seen = HashSet::new();
+    results
+        .into_iter()
+        .enumerate()
+        .flat_map(|(scope_index, items)| {
+            items
+                .into_iter()
+                .map(move |item| ScopedItem { scope_index, item })
+        })
+        .filter(|scoped| scoped.item.path().is_none_or(|path| seen.insert(path)))
+        .collect()
+}
+
+/// What identifies a file for [`MDQueryResults::dedup_by_inode`].
+#[derive(PartialEq, Eq, Hash)]
+enum FileIdentity {
+    Inode { device: u64, inode: u64 },
+    Path(PathBuf),
+}
+
+impl FileIdentity {
+    #[cfg(unix)]
+    fn of(path: PathBuf) -> Self {
+        use std::os::unix::fs::MetadataExt;
+
+        match std::fs::symlink_metadata(&path) {
+            Ok(metadata) => Self::Inode {
+                device: metadata.dev(),
+                inode: metadata.ino(),
+            },
+            Err(_) => Self::Path(path),
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn of(path: PathBuf) -> Self {
+        Self::Path(path)
+    }
+}
+
+#[cfg(all(test, target_vendor = "apple", not(feature = "fallback")))]
+mod tests {
+    use super::*;
+    use crate::{MDQuery, MDQueryScope};
+
+    /// Returns the applications in /System/Applications followed by those in its Utilities
+    /// folder, so every utility appears twice.
+    fn overlapping_query() -> Vec<MDItem> {
+        let execute = |scope: &str| {
+            MDQuery::new(
+                "kMDItemFSName = \"*.app\"",
+                [MDQueryScope::Custom(scope.into())],
+                None,
+            )
+            .unwrap()
+            .execute()
+            .unwrap()
+        };
+        let mut items = execute("/System/Applications");
+        let utilities = execute("/System/Applications/Utilities");
+        assert!(!utilities.is_empty());
+        items.extend(utilities);
+        items
+    }
+
+    /// Asserts that `deduped` is `items` with later duplicates removed.
+    fn assert_first_occurrences(items: &[MDItem], deduped: &[MDItem]) {
+        let mut expected = Vec::new();
+        for item in items {
+            if !expected.contains(&item.path()) {
+                expected.push(item.path());
+            }
+        }
+        assert_eq!(
+            deduped.iter().map(MDItem::path).collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_dedup_by_path() {
+        let items = overlapping_query();
+        assert!(!items.is_empty());
+        let deduped = items.clone().dedup_by_path();
+        assert!(deduped.len() < items.len());
+        assert_first_occurrences(&items, &deduped);
+    }
+
+    #[test]
+    fn test_hydrate_parallel() {
+        let items = MDQuery::new(
+            "kMDItemFSName = \"*\"",
+            [MDQueryScope::Custom("/System/Library".into())],
+            Some(2000),
+        )
+        .unwrap()
+        .execute()
+        .unwrap();
+        assert!(items.len() > MIN_PARALLEL_HYDRATION);
+
+        let keys = [
+            MDItemKey::FSName,
+            MDItemKey::ContentType,
+            MDItemKey::Size,
+            MDItemKey::ModificationDate,
+        ];
+        let serial = items.hydrate(&keys);
+        let parallel = items.hydrate_parallel(&keys);
+        assert!(parallel == serial);
+        for (item, hydrated) in items.iter().zip(&parallel) {
+            assert!(hydrated.item() == item);
+            assert_eq!(
+                hydrated.get(MDItemKey::FSName).map(ToString::to_string),
+                item.get_attribute_value(MDItemKey::FSName.as_str())
+                    .map(|value| value.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_take_per_kind() {
+        let items = MDQuery::new(
+            "kMDItemFSName = \"*\"",
+            [MDQueryScope::Custom("/System/Applications".into())],
+            Some(2000),
+        )
+        .unwrap()
+        .execute()
+        .unwrap();
+        let caps = [(ContentKind::Application, 3), (ContentKind::Folder, 2)];
+        let buckets = items.clone().take_per_kind(&caps, 4);
+
+        assert_eq!(
+            buckets.iter().map(|(kind, _)| *kind).collect::<Vec<_>>(),
+            [
+                Some(ContentKind::Application),
+                Some(ContentKind::Folder),
+                None
+            ]
+        );
+        assert_eq!(
+            buckets
+                .iter()
+                .map(|(_, items)| items.len())
+                .collect::<Vec<_>>(),
+            [3, 2, 4]
+        );
+        for (kind, bucket) in &buckets {
+            let expected = items
+                .iter()
+                .filter(|item| {
+                    let first = caps.iter().find(|(kind, _)| item.matches_kind(*kind));
+                    first.map(|(kind, _)| *kind) == *kind
+                })
+                .take(bucket.len())
+                .collect::<Vec<_>>();
+            assert!(bucket.iter().collect::<Vec<_>>() == expected);
+        }
+    }
+
+    #[test]
+    fn test_to_json() {
+        let items = MDQuery::new(
+            "kMDItemFSName = \"Safari.app\"",
+            [MDQueryScope::Custom("/Applications".into())],
+            Some(5),
+        )
+        .unwrap()
+        .execute()
+        .unwrap();
+
+        let json = items
+            .to_json(&[
+                MDItemKey::FSName,
+                MDItemKey::BundleIdentifier,
+                MDItemKey::Authors,
+            ])
+            .unwrap();
+        assert_eq!(
+            json,
+            r#"[{"kMDItemFSName":"Safari.app","kMDItemCFBundleIdentifier":"com.apple.Safari"}]"#
+        );
+    }
+
+    #[test]
+    fn test_dedup_by_inode() {
+        let items = overlapping_query();
+        let deduped = items.clone().dedup_by_inode();
+        assert!(deduped.len() < items.len());
+        assert_first_occurrences(&items, &deduped);
+    }
+}
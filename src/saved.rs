@@ -0,0 +1,762 @@
+use crate::json::JsonValue;
+use crate::{
+    ContentKind, FileSize, FinderLabel, MDItemKey, MDQueryBuilder, MDQueryCompareOp,
+    MDQueryCondition, MDQueryConditionExpression, MDQueryConditionType, MDQueryScope, MDQueryValue,
+    MatchModifiers, RelativeTime, ScopeOptions, SortDirection,
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// The format version written by [`to_json`]; [`from_json`] rejects other versions.
+const VERSION: u64 = 1;
+
+/// A builder method call recorded for [`MDQueryBuilder::to_json`].
+///
+/// Calls are recorded with their arguments rather than the expressions they produce, so a
+/// restored builder applies the escaping and checks of the current version.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Operation {
+    pub(crate) method: &'static str,
+    pub(crate) args: Vec<JsonValue>,
+}
+
+impl Operation {
+    fn save(&self) -> JsonValue {
+        JsonValue::Object(vec![
+            ("method".to_string(), self.method.save()),
+            ("args".to_string(), JsonValue::Array(self.args.clone())),
+        ])
+    }
+}
+
+/// Converts a builder argument to JSON.
+pub(crate) trait Save {
+    fn save(&self) -> JsonValue;
+}
+
+/// Converts JSON written by [`Save`] back to a builder argument.
+trait Restore: Sized {
+    fn restore(value: &JsonValue) -> Option<Self>;
+}
+
+impl<T: Save + ?Sized> Save for &T {
+    fn save(&self) -> JsonValue {
+        (**self).save()
+    }
+}
+
+impl<T: Save> Save for [T] {
+    fn save(&self) -> JsonValue {
+        JsonValue::Array(self.iter().map(Save::save).collect())
+    }
+}
+
+impl<T: Restore> Restore for Vec<T> {
+    fn restore(value: &JsonValue) -> Option<Self> {
+        value.as_array()?.iter().map(T::restore).collect()
+    }
+}
+
+impl Save for str {
+    fn save(&self) -> JsonValue {
+        JsonValue::String(self.to_string())
+    }
+}
+
+impl Restore for String {
+    fn restore(value: &JsonValue) -> Option<Self> {
+        value.as_str().map(str::to_string)
+    }
+}
+
+impl Save for bool {
+    fn save(&self) -> JsonValue {
+        JsonValue::Bool(*self)
+    }
+}
+
+impl Restore for bool {
+    fn restore(value: &JsonValue) -> Option<Self> {
+        match value {
+            JsonValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! saved_integers {
+    ($($ty:ty),*) => {
+        $(
+            impl Save for $ty {
+                fn save(&self) -> JsonValue {
+                    JsonValue::number(self)
+                }
+            }
+
+            impl Restore for $ty {
+                fn restore(value: &JsonValue) -> Option<Self> {
+                    match value {
+                        JsonValue::Number(number) => number.parse().ok(),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+saved_integers!(u8, u16, u32, u64, usize, i64);
+
+/// Finite floats are numbers; NaN and the infinities, which JSON cannot represent, are the
+/// strings "NaN", "inf" and "-inf" so that their argument errors are restored as well.
+impl Save for f64 {
+    fn save(&self) -> JsonValue {
+        if self.is_finite() {
+            JsonValue::number(self)
+        } else {
+            JsonValue::String(self.to_string())
+        }
+    }
+}
+
+impl Restore for f64 {
+    fn restore(value: &JsonValue) -> Option<Self> {
+        match value {
+            JsonValue::Number(number) => number.parse().ok(),
+            JsonValue::String(name) if matches!(name.as_str(), "NaN" | "inf" | "-inf") => {
+                name.parse().ok()
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Seconds and nanoseconds, as a two-element array.
+impl Save for Duration {
+    fn save(&self) -> JsonValue {
+        JsonValue::Array(vec![self.as_secs().save(), self.subsec_nanos().save()])
+    }
+}
+
+impl Restore for Duration {
+    fn restore(value: &JsonValue) -> Option<Self> {
+        let [secs, nanos] = value.as_array()? else {
+            return None;
+        };
+        let nanos = u32::restore(nanos).filter(|nanos| *nanos < 1_000_000_000)?;
+        Some(Duration::new(u64::restore(secs)?, nanos))
+    }
+}
+
+/// An RFC 3339 date-time in UTC, with as many fractional digits as needed.
+impl Save for DateTime<Utc> {
+    fn save(&self) -> JsonValue {
+        JsonValue::String(self.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+    }
+}
+
+impl Restore for DateTime<Utc> {
+    fn restore(value: &JsonValue) -> Option<Self> {
+        let time = DateTime::parse_from_rfc3339(value.as_str()?).ok()?;
+        Some(time.with_timezone(&Utc))
+    }
+}
+
+/// Paths that are not valid UTF-8 are saved lossily.
+impl Save for Path {
+    fn save(&self) -> JsonValue {
+        JsonValue::String(self.to_string_lossy().into_owned())
+    }
+}
+
+impl Restore for PathBuf {
+    fn restore(value: &JsonValue) -> Option<Self> {
+        value.as_str().map(PathBuf::from)
+    }
+}
+
+impl Save for MDItemKey {
+    fn save(&self) -> JsonValue {
+        self.as_str().save()
+    }
+}
+
+impl Restore for MDItemKey {
+    fn restore(value: &JsonValue) -> Option<Self> {
+        value.as_str()?.parse().ok()
+    }
+}
+
+impl Save for MDQueryCompareOp {
+    fn save(&self) -> JsonValue {
+        let symbol = match self {
+            Self::GreaterThan => ">",
+            Self::LessThan => "<",
+            Self::Equal => "==",
+            Self::GreaterThanOrEqual => ">=",
+            Self::LessThanOrEqual => "<=",
+        };
+        symbol.save()
+    }
+}
+
+impl Restore for MDQueryCompareOp {
+    fn restore(value: &JsonValue) -> Option<Self> {
+        match value.as_str()? {
+            ">" => Some(Self::GreaterThan),
+            "<" => Some(Self::LessThan),
+            "==" => Some(Self::Equal),
+            ">=" => Some(Self::GreaterThanOrEqual),
+            "<=" => Some(Self::LessThanOrEqual),
+            _ => None,
+        }
+    }
+}
+
+/// The modifier letters of a query string, such as "cd".
+impl Save for MatchModifiers {
+    fn save(&self) -> JsonValue {
+        let mut letters = String::new();
+        for (set, letter) in [
+            (self.case_insensitive, 'c'),
+            (self.diacritic_insensitive, 'd'),
+            (self.word_based, 'w'),
+        ] {
+            if set {
+                letters.push(letter);
+            }
+        }
+        JsonValue::String(letters)
+    }
+}
+
+impl Restore for MatchModifiers {
+    fn restore(value: &JsonValue) -> Option<Self> {
+        let mut modifiers = MatchModifiers::NONE;
+        for letter in value.as_str()?.chars() {
+            let flag = match letter {
+                'c' => &mut modifiers.case_insensitive,
+                'd' => &mut modifiers.diacritic_insensitive,
+                'w' => &mut modifiers.word_based,
+                _ => return None,
+            };
+            *flag = true;
+        }
+        Some(modifiers)
+    }
+}
+
+impl Save for FileSize {
+    fn save(&self) -> JsonValue {
+        self.as_bytes().save()
+    }
+}
+
+impl Restore for FileSize {
+    fn restore(value: &JsonValue) -> Option<Self> {
+        u64::restore(value).map(FileSize::bytes)
+    }
+}
+
+impl Save for FinderLabel {
+    fn save(&self) -> JsonValue {
+        self.value().save()
+    }
+}
+
+impl Restore for FinderLabel {
+    fn restore(value: &JsonValue) -> Option<Self> {
+        FinderLabel::from_value(u8::restore(value)?)
+    }
+}
+
+impl Save for ScopeOptions {
+    fn save(&self) -> JsonValue {
+        self.bits().save()
+    }
+}
+
+impl Restore for ScopeOptions {
+    fn restore(value: &JsonValue) -> Option<Self> {
+        u32::restore(value).map(ScopeOptions::from_bits)
+    }
+}
+
+impl Save for SortDirection {
+    fn save(&self) -> JsonValue {
+        let name = match self {
+            Self::Ascending => "Ascending",
+            Self::Descending => "Descending",
+        };
+        name.save()
+    }
+}
+
+impl Restore for SortDirection {
+    fn restore(value: &JsonValue) -> Option<Self> {
+        match value.as_str()? {
+            "Ascending" => Some(Self::Ascending),
+            "Descending" => Some(Self::Descending),
+            _ => None,
+        }
+    }
+}
+
+impl Save for ContentKind {
+    fn save(&self) -> JsonValue {
+        let name = match self {
+            Self::Image => "Image",
+            Self::Movie => "Movie",
+            Self::Audio => "Audio",
+            Self::Pdf => "Pdf",
+            Self::Text => "Text",
+            Self::Archive => "Archive",
+            Self::Application => "Application",
+            Self::Folder => "Folder",
+            Self::Presentation => "Presentation",
+            Self::Spreadsheet => "Spreadsheet",
+            Self::SourceCode => "SourceCode",
+            Self::Font => "Font",
+            Self::Email => "Email",
+            Self::Bookmark => "Bookmark",
+            Self::Document => "Document",
+        };
+        name.save()
+    }
+}
+
+impl Restore for ContentKind {
+    fn restore(value: &JsonValue) -> Option<Self> {
+        match value.as_str()? {
+            "Image" => Some(Self::Image),
+            "Movie" => Some(Self::Movie),
+            "Audio" => Some(Self::Audio),
+            "Pdf" => Some(Self::Pdf),
+            "Text" => Some(Self::Text),
+            "Archive" => Some(Self::Archive),
+            "Application" => Some(Self::Application),
+            "Folder" => Some(Self::Folder),
+            "Presentation" => Some(Self::Presentation),
+            "Spreadsheet" => Some(Self::Spreadsheet),
+            "SourceCode" => Some(Self::SourceCode),
+            "Font" => Some(Self::Font),
+            "Email" => Some(Self::Email),
+            "Bookmark" => Some(Self::Bookmark),
+            "Document" => Some(Self::Document),
+            _ => None,
+        }
+    }
+}
+
+/// The variant name, or `{"Custom": path}`.
+impl Save for MDQueryScope {
+    fn save(&self) -> JsonValue {
+        let name = match self {
+            Self::Home => "Home",
+            Self::Computer => "Computer",
+            Self::Network => "Network",
+            Self::AllIndexed => "AllIndexed",
+            Self::ComputerIndexed => "ComputerIndexed",
+            Self::NetworkIndexed => "NetworkIndexed",
+            Self::Custom(path) => return JsonValue::tagged("Custom", path.save()),
+            Self::Documents => "Documents",
+            Self::Downloads => "Downloads",
+            Self::Desktop => "Desktop",
+            Self::Pictures => "Pictures",
+            Self::Movies => "Movies",
+            Self::Music => "Music",
+            Self::Applications => "Applications",
+            Self::ICloudDrive => "ICloudDrive",
+            Self::ICloud => "ICloud",
+        };
+        name.save()
+    }
+}
+
+impl Restore for MDQueryScope {
+    fn restore(value: &JsonValue) -> Option<Self> {
+        if let Some(("Custom", path)) = value.as_tagged() {
+            return PathBuf::restore(path).map(Self::Custom);
+        }
+        match value.as_str()? {
+            "Home" => Some(Self::Home),
+            "Computer" => Some(Self::Computer),
+            "Network" => Some(Self::Network),
+            "AllIndexed" => Some(Self::AllIndexed),
+            "ComputerIndexed" => Some(Self::ComputerIndexed),
+            "NetworkIndexed" => Some(Self::NetworkIndexed),
+            "Documents" => Some(Self::Documents),
+            "Downloads" => Some(Self::Downloads),
+            "Desktop" => Some(Self::Desktop),
+            "Pictures" => Some(Self::Pictures),
+            "Movies" => Some(Self::Movies),
+            "Music" => Some(Self::Music),
+            "Applications" => Some(Self::Applications),
+            "ICloudDrive" => Some(Self::ICloudDrive),
+            "ICloud" => Some(Self::ICloud),
+            _ => None,
+        }
+    }
+}
+
+/// "Now", or the variant name tagging its offset or date-time, such as `{"Today": -7}`.
+impl Save for RelativeTime {
+    fn save(&self) -> JsonValue {
+        let (name, offset) = match self {
+            Self::Now => return "Now".save(),
+            Self::SecondsFromNow(n) => ("SecondsFromNow", n),
+            Self::Today(n) => ("Today", n),
+            Self::ThisWeek(n) => ("ThisWeek", n),
+            Self::ThisMonth(n) => ("ThisMonth", n),
+            Self::ThisYear(n) => ("ThisYear", n),
+            Self::Iso(time) => return JsonValue::tagged("Iso", time.save()),
+        };
+        JsonValue::tagged(name, offset.save())
+    }
+}
+
+impl Restore for RelativeTime {
+    fn restore(value: &JsonValue) -> Option<Self> {
+        if value.as_str() == Some("Now") {
+            return Some(Self::Now);
+        }
+        let (name, value) = value.as_tagged()?;
+        if name == "Iso" {
+            return DateTime::restore(value).map(Self::Iso);
+        }
+        let variant = match name {
+            "SecondsFromNow" => Self::SecondsFromNow,
+            "Today" => Self::Today,
+            "ThisWeek" => Self::ThisWeek,
+            "ThisMonth" => Self::ThisMonth,
+            "ThisYear" => Self::ThisYear,
+            _ => return None,
+        };
+        i64::restore(value).map(variant)
+    }
+}
+
+/// The variant name tagging the value, such as `{"Int": 10}`.
+impl Save for MDQueryValue {
+    fn save(&self) -> JsonValue {
+        match self {
+            Self::Str(value) => JsonValue::tagged("Str", value.save()),
+            Self::Int(value) => JsonValue::tagged("Int", value.save()),
+            Self::Float(value) => JsonValue::tagged("Float", value.save()),
+            Self::Bool(value) => JsonValue::tagged("Bool", value.save()),
+            Self::Date(value) => JsonValue::tagged("Date", value.save()),
+        }
+    }
+}
+
+impl Restore for MDQueryValue {
+    fn restore(value: &JsonValue) -> Option<Self> {
+        match value.as_tagged()? {
+            ("Str", value) => String::restore(value).map(Self::Str),
+            ("Int", value) => i64::restore(value).map(Self::Int),
+            ("Float", value) => f64::restore(value).map(Self::Float),
+            ("Bool", value) => bool::restore(value).map(Self::Bool),
+            ("Date", value) => DateTime::restore(value).map(Self::Date),
+            _ => None,
+        }
+    }
+}
+
+impl Save for MDQueryConditionType {
+    fn save(&self) -> JsonValue {
+        let name = match self {
+            Self::All => "All",
+            Self::Any => "Any",
+        };
+        name.save()
+    }
+}
+
+impl Restore for MDQueryConditionType {
+    fn restore(value: &JsonValue) -> Option<Self> {
+        match value.as_str()? {
+            "All" => Some(Self::All),
+            "Any" => Some(Self::Any),
+            _ => None,
+        }
+    }
+}
+
+/// A builder, as its list of operations.
+impl Save for MDQueryBuilder {
+    fn save(&self) -> JsonValue {
+        save_operations(self.operations())
+    }
+}
+
+impl Restore for MDQueryBuilder {
+    fn restore(value: &JsonValue) -> Option<Self> {
+        replay(value).ok()
+    }
+}
+
+/// A condition taken from a builder is saved as `{"Builder": operations}`, so that it is
+/// rebuilt by the builder methods as well. Other conditions are saved as their type tagging
+/// their expressions, such as `{"Any": ["kMDItemFSSize > 10", {"All": [...]}]}`, where raw
+/// expression strings are kept as written.
+impl Save for MDQueryCondition {
+    fn save(&self) -> JsonValue {
+        if let Some(operations) = &self.operations {
+            return JsonValue::tagged("Builder", save_operations(operations));
+        }
+        let name = match self.condition_type {
+            MDQueryConditionType::All => "All",
+            MDQueryConditionType::Any => "Any",
+        };
+        JsonValue::tagged(name, self.expressions.save())
+    }
+}
+
+impl Restore for MDQueryCondition {
+    fn restore(value: &JsonValue) -> Option<Self> {
+        let (name, value) = value.as_tagged()?;
+        if name == "Builder" {
+            return MDQueryBuilder::restore(value).map(MDQueryCondition::from);
+        }
+        let expressions = Vec::<MDQueryConditionExpression>::restore(value)?;
+        match name {
+            "All" => Some(MDQueryCondition::all(expressions)),
+            "Any" => Some(MDQueryCondition::any(expressions)),
+            _ => None,
+        }
+    }
+}
+
+impl Save for MDQueryConditionExpression {
+    fn save(&self) -> JsonValue {
+        match self {
+            Self::Condition(condition) => condition.save(),
+            Self::Expression(expression) => expression.save(),
+        }
+    }
+}
+
+impl Restore for MDQueryConditionExpression {
+    fn restore(value: &JsonValue) -> Option<Self> {
+        match value {
+            JsonValue::String(expression) => Some(Self::Expression(expression.clone())),
+            _ => MDQueryCondition::restore(value).map(Self::Condition),
+        }
+    }
+}
+
+fn save_operations(operations: &[Operation]) -> JsonValue {
+    JsonValue::Array(operations.iter().map(Operation::save).collect())
+}
+
+/// Converts the operations of a builder to a JSON document.
+pub(crate) fn to_json(operations: &[Operation]) -> String {
+    JsonValue::Object(vec![
+        ("version".to_string(), VERSION.save()),
+        ("operations".to_string(), save_operations(operations)),
+    ])
+    .to_json_string()
+}
+
+/// Rebuilds a builder from a JSON document written by [`to_json`].
+///
+/// # Errors
+/// Returns a description of the first problem found.
+pub(crate) fn from_json(json: &str) -> Result<MDQueryBuilder, String> {
+    let document = JsonValue::parse(json).map_err(|message| format!("invalid JSON: {message}"))?;
+    match document.get("version").and_then(u64::restore) {
+        Some(VERSION) => {}
+        Some(version) => return Err(format!("unsupported version {version}")),
+        None => return Err("missing version".to_string()),
+    }
+    let operations = document
+        .get("operations")
+        .ok_or_else(|| "missing operations".to_string())?;
+    replay(operations)
+}
+
+/// Restores argument `index` of a call to `method`.
+fn argument<T: Restore>(method: &str, index: usize, value: &JsonValue) -> Result<T, String> {
+    T::restore(value).ok_or_else(|| format!("invalid argument {index} of {method}"))
+}
+
+/// Matches a recorded method name, checks and restores its arguments, and applies the call.
+///
+/// Each entry lists the arguments of one method with their restored types.
+macro_rules! replay_calls {
+    ($builder:ident, $method:expr, $args:expr;
+     $($name:literal($($arg:ident: $ty:ty),*) => $call:expr,)*) => {
+        match $method {
+            $($name => {
+                let [$($arg),*] = $args else {
+                    return Err(format!(
+                        "{} takes {} arguments, found {}",
+                        $name,
+                        <[&str]>::len(&[$(stringify!($arg)),*]),
+                        $args.len()
+                    ));
+                };
+                let mut _index = 0;
+                $(
+                    let $arg: $ty = argument($name, _index, $arg)?;
+                    _index += 1;
+                )*
+                $call
+            })*
+            method => return Err(format!("unknown method {:?}", method)),
+        }
+    };
+}
+
+/// Applies a list of operations, as written by [`save_operations`], to a new builder.
+fn replay(operations: &JsonValue) -> Result<MDQueryBuilder, String> {
+    let operations = operations
+        .as_array()
+        .ok_or_else(|| "operations is not an array".to_string())?;
+    let mut builder = MDQueryBuilder::default();
+    for operation in operations {
+        let method = operation
+            .get("method")
+            .and_then(JsonValue::as_str)
+            .ok_or_else(|| "operation without a method".to_string())?;
+        let args = operation
+            .get("args")
+            .and_then(JsonValue::as_array)
+            .ok_or_else(|| format!("{method} without arguments"))?;
+        builder = replay_calls! { builder, method, args;
+            "with_scopes"(scopes: Vec<MDQueryScope>) => builder.with_scopes(scopes),
+            "with_max_count"(max_count: usize) => builder.with_max_count(max_count),
+            "top_k"(k: usize, key: MDItemKey, direction: SortDirection) =>
+                builder.top_k(k, key, direction),
+            "with_scope_options"(options: ScopeOptions) => builder.with_scope_options(options),
+            "from_condition"(condition: MDQueryCondition) =>
+                MDQueryBuilder::from_condition(condition),
+            "condition"(condition: MDQueryCondition) => builder.condition(condition),
+            "merge"(other: MDQueryBuilder, condition_type: MDQueryConditionType) =>
+                builder.merge(other, condition_type),
+            "from_raw"(query: String) => MDQueryBuilder::from_raw(&query),
+            "match_all"() => builder.match_all(),
+            "name_like"(name: String) => builder.name_like(&name),
+            "name_like_with"(name: String, modifiers: MatchModifiers) =>
+                builder.name_like_with(&name, modifiers),
+            "name_is"(name: String) => builder.name_is(&name),
+            "name_is_with"(name: String, modifiers: MatchModifiers) =>
+                builder.name_is_with(&name, modifiers),
+            "name_starts_with"(prefix: String) => builder.name_starts_with(&prefix),
+            "name_ends_with"(suffix: String) => builder.name_ends_with(&suffix),
+            "fs_name_starts_with"(prefix: String) => builder.fs_name_starts_with(&prefix),
+            "fs_name_ends_with"(suffix: String) => builder.fs_name_ends_with(&suffix),
+            "path_starts_with"(dir: PathBuf) => builder.path_starts_with(dir),
+            "exclude_path_prefix"(dir: PathBuf) => builder.exclude_path_prefix(dir),
+            "exclude_system_noise"() => builder.exclude_system_noise(),
+            "exclude_trash"() => builder.exclude_trash(),
+            "exclude_time_machine"() => builder.exclude_time_machine(),
+            "exclude_caches"() => builder.exclude_caches(),
+            "exclude_library"() => builder.exclude_library(),
+            "local_volumes_only"() => builder.local_volumes_only(),
+            "on_volume"(mount_point: PathBuf) => builder.on_volume(mount_point),
+            "compare"(key: MDItemKey, op: MDQueryCompareOp, value: MDQueryValue) =>
+                builder.compare(key, op, value),
+            "time"(key: MDItemKey, op: MDQueryCompareOp, timestamp: i64) =>
+                builder.time(key, op, timestamp),
+            "time_dt"(key: MDItemKey, op: MDQueryCompareOp, time: DateTime<Utc>) =>
+                builder.time_dt(key, op, time),
+            "time_relative"(key: MDItemKey, op: MDQueryCompareOp, time: RelativeTime) =>
+                builder.time_relative(key, op, time),
+            "modified_within_days"(days: u32) => builder.modified_within_days(days),
+            "created_today"() => builder.created_today(),
+            "added_after"(time: DateTime<Utc>) => builder.added_after(time),
+            "added_within_days"(days: u32) => builder.added_within_days(days),
+            "used_within"(duration: Duration) => builder.used_within(duration),
+            "time_in_range"(key: MDItemKey, start: i64, end: i64) =>
+                builder.time_in_range(key, start, end),
+            "size"(op: MDQueryCompareOp, size: FileSize) => builder.size(op, size),
+            "size_of"(key: MDItemKey, op: MDQueryCompareOp, size: FileSize) =>
+                builder.size_of(key, op, size),
+            "size_in_range"(min: FileSize, max: FileSize) => builder.size_in_range(min, max),
+            "size_between"(min: FileSize, max: FileSize) => builder.size_between(min, max),
+            "is_dir"(value: bool) => builder.is_dir(value),
+            "is_dir_by_tree"(value: bool) => builder.is_dir_by_tree(value),
+            "is_app"() => builder.is_app(),
+            "is_package"(value: bool) => builder.is_package(value),
+            "is_framework"() => builder.is_framework(),
+            "exclude_aliases"() => builder.exclude_aliases(),
+            "only_symlinks"() => builder.only_symlinks(),
+            "include_hidden"(value: bool) => builder.include_hidden(value),
+            "title_contains"(text: String) => builder.title_contains(&text),
+            "language"(language: String) => builder.language(&language),
+            "is_password_protected"() => builder.is_password_protected(),
+            "is_screenshot"() => builder.is_screenshot(),
+            "screenshot_type"(capture_type: String) => builder.screenshot_type(&capture_type),
+            "kind"(kind: String) => builder.kind(&kind),
+            "of_kind"(kind: ContentKind) => builder.of_kind(kind),
+            "bundle_identifier"(bundle_id: String) => builder.bundle_identifier(&bundle_id),
+            "app_category"(category: String) => builder.app_category(&category),
+            "has_app_category"(value: bool) => builder.has_app_category(value),
+            "has_architecture"(arch: String) => builder.has_architecture(&arch),
+            "missing_architecture"(arch: String) => builder.missing_architecture(&arch),
+            "version_is"(version: String) => builder.version_is(&version),
+            "author"(author: String) => builder.author(&author),
+            "created_by_app"(app: String) => builder.created_by_app(&app),
+            "pixel_width"(op: MDQueryCompareOp, width: u32) => builder.pixel_width(op, width),
+            "pixel_height"(op: MDQueryCompareOp, height: u32) =>
+                builder.pixel_height(op, height),
+            "bits_per_sample"(op: MDQueryCompareOp, bits: u32) =>
+                builder.bits_per_sample(op, bits),
+            "has_alpha_channel"(value: bool) => builder.has_alpha_channel(value),
+            "color_space"(color_space: String) => builder.color_space(&color_space),
+            "min_resolution"(width: u32, height: u32) => builder.min_resolution(width, height),
+            "min_video_resolution"(width: u32, height: u32) =>
+                builder.min_video_resolution(width, height),
+            "duration"(op: MDQueryCompareOp, duration: Duration) =>
+                builder.duration(op, duration),
+            "owned_by_uid"(uid: u32) => builder.owned_by_uid(uid),
+            "folder_item_count"(op: MDQueryCompareOp, count: u64) =>
+                builder.folder_item_count(op, count),
+            "with_label"(label: FinderLabel) => builder.with_label(label),
+            "use_count"(op: MDQueryCompareOp, count: u64) => builder.use_count(op, count),
+            "page_count"(op: MDQueryCompareOp, pages: u32) => builder.page_count(op, pages),
+            "uses_font"(font: String) => builder.uses_font(&font),
+            "audio_sample_rate"(op: MDQueryCompareOp, hertz: f64) =>
+                builder.audio_sample_rate(op, hertz),
+            "audio_channel_count"(op: MDQueryCompareOp, channels: u32) =>
+                builder.audio_channel_count(op, channels),
+            "total_bit_rate"(op: MDQueryCompareOp, kbps: f64) =>
+                builder.total_bit_rate(op, kbps),
+            "video_bit_rate"(op: MDQueryCompareOp, kbps: f64) =>
+                builder.video_bit_rate(op, kbps),
+            "codec"(codec: String) => builder.codec(&codec),
+            "album"(album: String) => builder.album(&album),
+            "artist"(artist: String) => builder.artist(&artist),
+            "genre"(genre: String) => builder.genre(&genre),
+            "recording_year"(op: MDQueryCompareOp, year: u16) =>
+                builder.recording_year(op, year),
+            "camera_make"(make: String) => builder.camera_make(&make),
+            "camera_model"(model: String) => builder.camera_model(&model),
+            "iso"(op: MDQueryCompareOp, iso: u32) => builder.iso(op, iso),
+            "focal_length"(op: MDQueryCompareOp, millimeters: f64) =>
+                builder.focal_length(op, millimeters),
+            "exposure_time"(op: MDQueryCompareOp, seconds: f64) =>
+                builder.exposure_time(op, seconds),
+            "f_number"(op: MDQueryCompareOp, f_number: f64) => builder.f_number(op, f_number),
+            "downloaded_from_domain"(domain: String) => builder.downloaded_from_domain(&domain),
+            "downloaded_after"(time: DateTime<Utc>) => builder.downloaded_after(time),
+            "finder_comment_contains"(text: String) => builder.finder_comment_contains(&text),
+            "subject_contains"(text: String) => builder.subject_contains(&text),
+            "url_contains"(text: String) => builder.url_contains(&text),
+            "from_email"(address: String) => builder.from_email(&address),
+            "to_email"(address: String) => builder.to_email(&address),
+            "keyword"(keyword: String) => builder.keyword(&keyword),
+            "keyword_with"(keyword: String, modifiers: MatchModifiers) =>
+                builder.keyword_with(&keyword, modifiers),
+            "keywords_any"(keywords: Vec<String>) => builder.keywords_any(
+                &keywords.iter().map(String::as_str).collect::<Vec<_>>()
+            ),
+            "extension"(ext: String) => builder.extension(&ext),
+            "content_type"(content_type: String) => builder.content_type(content_type),
+            "content_type_conforms_to"(uti: String) => builder.content_type_conforms_to(uti),
+        };
+    }
+    Ok(builder)
+}
@@ -0,0 +1,107 @@
+//! The attribute schema Spotlight knows about, for discovering attributes at runtime.
+//!
+//! The schema combines the attributes declared by the system and by every installed
+//! metadata importer, so it lists many more attributes than [`MDItemKey`](crate::MDItemKey).
+//! On platforms without Spotlight the schema is empty.
+//!
+//! ```no_run
+//! use mdquery_rs::schema;
+//!
+//! for name in schema::all_attribute_names() {
+//!     if let Some(display_name) = schema::display_name(&name) {
+//!         println!("{name}: {display_name}");
+//!     }
+//! }
+//! ```
+
+use crate::platform;
+
+/// The type of an attribute's values, as declared in the schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AttributeType {
+    /// Text values
+    String,
+    /// Integer or floating-point values
+    Number,
+    /// Dates
+    Date,
+    /// Boolean values
+    Bool,
+    /// Raw binary data
+    Data,
+    /// A type this crate does not recognize
+    Unknown,
+}
+
+/// What the schema declares about an attribute.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeInfo {
+    /// The type of the attribute's values, or of each element of multivalued attributes
+    pub type_hint: AttributeType,
+    /// Whether the attribute holds an array of values, like `kMDItemAuthors`
+    pub multivalued: bool,
+    /// A localized description of the attribute, if the schema provides one
+    pub description: Option<String>,
+}
+
+/// Returns the names of all attributes in the schema, such as `"kMDItemDisplayName"`.
+///
+/// # Returns
+/// The attribute names, empty if the schema cannot be read.
+pub fn all_attribute_names() -> Vec<String> {
+    platform::schema::all_attribute_names()
+}
+
+/// Returns the name of an attribute as shown to users, localized to the user's language.
+///
+/// # Parameters
+/// * `attribute` - The attribute name, such as `"kMDItemFSSize"`
+///
+/// # Returns
+/// The display name, such as "File size", or None if the attribute has none.
+pub fn display_name(attribute: &str) -> Option<String> {
+    platform::schema::display_name(attribute)
+}
+
+/// Returns the type, multiplicity and description of an attribute.
+///
+/// # Parameters
+/// * `attribute` - The attribute name, such as `"kMDItemAuthors"`
+///
+/// # Returns
+/// The attribute's schema information, or None if the attribute is not in the schema.
+pub fn attribute_info(attribute: &str) -> Option<AttributeInfo> {
+    platform::schema::attribute_info(attribute)
+}
+
+#[cfg(all(test, target_vendor = "apple", not(feature = "fallback")))]
+mod tests {
+    use super::*;
+    use crate::MDItemKey;
+
+    #[test]
+    fn test_display_name_in_schema() {
+        let names = all_attribute_names();
+        assert!(names.iter().any(|name| name == "kMDItemDisplayName"));
+        assert!(display_name("kMDItemDisplayName").is_some_and(|name| !name.is_empty()));
+    }
+
+    #[test]
+    fn test_attribute_info() {
+        let info = attribute_info(MDItemKey::DisplayName.as_str()).unwrap();
+        assert_eq!(info.type_hint, AttributeType::String);
+        assert!(!info.multivalued);
+
+        assert!(
+            attribute_info(MDItemKey::Authors.as_str())
+                .unwrap()
+                .multivalued
+        );
+
+        assert_eq!(
+            attribute_info(MDItemKey::ModificationDate.as_str()).map(|info| info.type_hint),
+            Some(AttributeType::Date)
+        );
+        assert!(attribute_info("kMDItemNoSuchAttribute").is_none());
+    }
+}
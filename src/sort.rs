@@ -0,0 +1,224 @@
+use crate::MDAttributeValue;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// The order of the results selected by
+/// [`MDQueryBuilder::top_k`](crate::MDQueryBuilder::top_k).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SortDirection {
+    /// Smallest, earliest or alphabetically first values first
+    Ascending,
+    /// Largest, latest or alphabetically last values first
+    Descending,
+}
+
+/// Compares two attribute values in ascending order.
+///
+/// Integers and floats compare numerically with each other, and arrays compare element
+/// by element. Values of different kinds are ordered by kind, so the order is total even
+/// for attributes whose values are not all of one type.
+pub(crate) fn compare_values(a: &MDAttributeValue, b: &MDAttributeValue) -> Ordering {
+    use MDAttributeValue::*;
+
+    match (a, b) {
+        (String(a), String(b)) => a.cmp(b),
+        (Integer(a), Integer(b)) => a.cmp(b),
+        (Integer(a), Float(b)) => (*a as f64).total_cmp(b),
+        (Float(a), Integer(b)) => a.total_cmp(&(*b as f64)),
+        (Float(a), Float(b)) => a.total_cmp(b),
+        (Bool(a), Bool(b)) => a.cmp(b),
+        (Date(a), Date(b)) => a.cmp(b),
+        (Array(a), Array(b)) => a
+            .iter()
+            .zip(b)
+            .map(|(a, b)| compare_values(a, b))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| a.len().cmp(&b.len())),
+        _ => kind_rank(a).cmp(&kind_rank(b)),
+    }
+}
+
+fn kind_rank(value: &MDAttributeValue) -> u8 {
+    match value {
+        MDAttributeValue::Integer(_) | MDAttributeValue::Float(_) => 0,
+        MDAttributeValue::Date(_) => 1,
+        MDAttributeValue::Bool(_) => 2,
+        MDAttributeValue::String(_) => 3,
+        MDAttributeValue::Array(_) => 4,
+    }
+}
+
+/// A candidate kept by [`select_top_k`]. Candidates order by their final position, so
+/// the top of the heap is the one that would be dropped first.
+struct Candidate<T> {
+    value: Option<MDAttributeValue>,
+    position: usize,
+    direction: SortDirection,
+    item: T,
+}
+
+impl<T> Ord for Candidate<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let by_value = match (&self.value, &other.value) {
+            (Some(a), Some(b)) => match self.direction {
+                SortDirection::Ascending => compare_values(a, b),
+                SortDirection::Descending => compare_values(b, a),
+            },
+            // Items without the attribute go last in either direction.
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        };
+        by_value.then(self.position.cmp(&other.position))
+    }
+}
+
+impl<T> PartialOrd for Candidate<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> PartialEq for Candidate<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl<T> Eq for Candidate<T> {}
+
+/// Selects the first `k` items in the order of their sort values, keeping at most `k`
+/// items in memory at a time.
+///
+/// Items without a value sort after all items with one, and items with equal values keep
+/// the order they were passed in.
+///
+/// # Parameters
+/// * `items` - The items with their sort values
+/// * `k` - The number of items to keep
+/// * `direction` - Whether the smallest or the largest values come first
+///
+/// # Returns
+/// At most `k` items, sorted.
+#[cfg_attr(
+    any(feature = "fallback", not(target_vendor = "apple")),
+    allow(dead_code)
+)]
+pub(crate) fn select_top_k<T, I>(items: I, k: usize, direction: SortDirection) -> Vec<T>
+where
+    I: IntoIterator<Item = (Option<MDAttributeValue>, T)>,
+{
+    if k == 0 {
+        return Vec::new();
+    }
+    let mut heap = BinaryHeap::with_capacity(k + 1);
+    for (position, (value, item)) in items.into_iter().enumerate() {
+        heap.push(Candidate {
+            value,
+            position,
+            direction,
+            item,
+        });
+        if heap.len() > k {
+            heap.pop();
+        }
+    }
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|candidate| candidate.item)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    /// Sorts all items and keeps the first `k`, the result `select_top_k` must match.
+    fn brute_force(
+        items: &[(Option<MDAttributeValue>, usize)],
+        k: usize,
+        direction: SortDirection,
+    ) -> Vec<usize> {
+        let mut sorted = items.to_vec();
+        sorted.sort_by(|(a, _), (b, _)| match (a, b) {
+            (Some(a), Some(b)) => match direction {
+                SortDirection::Ascending => compare_values(a, b),
+                SortDirection::Descending => compare_values(b, a),
+            },
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        });
+        sorted.into_iter().take(k).map(|(_, item)| item).collect()
+    }
+
+    #[test]
+    fn test_select_top_k_matches_full_sort() {
+        // A deterministic mix of duplicates and missing values.
+        let items = (0..200)
+            .map(|i| {
+                let value = (i * 37 % 91) as i64;
+                let value = (value % 10 != 0).then_some(MDAttributeValue::Integer(value / 3));
+                (value, i)
+            })
+            .collect::<Vec<_>>();
+        for direction in [SortDirection::Ascending, SortDirection::Descending] {
+            for k in [0, 1, 5, 60, 199, 200, 500] {
+                assert_eq!(
+                    select_top_k(items.clone(), k, direction),
+                    brute_force(&items, k, direction),
+                    "k = {}, {:?}",
+                    k,
+                    direction
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_compare_values() {
+        let early = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        let late = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            compare_values(&MDAttributeValue::Integer(2), &MDAttributeValue::Float(2.5)),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_values(
+                &MDAttributeValue::Date(late),
+                &MDAttributeValue::Date(early)
+            ),
+            Ordering::Greater
+        );
+        assert_eq!(compare_values(&"a".into(), &"b".into()), Ordering::Less);
+        assert_eq!(
+            compare_values(
+                &MDAttributeValue::Array(vec!["a".into()]),
+                &MDAttributeValue::Array(vec!["a".into(), "b".into()])
+            ),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_values(&MDAttributeValue::Integer(9), &"1".into()),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn test_missing_values_last() {
+        let items = vec![
+            (None, "none"),
+            (Some(MDAttributeValue::Integer(1)), "one"),
+            (Some(MDAttributeValue::Integer(2)), "two"),
+        ];
+        assert_eq!(
+            select_top_k(items.clone(), 3, SortDirection::Descending),
+            ["two", "one", "none"]
+        );
+        assert_eq!(
+            select_top_k(items, 3, SortDirection::Ascending),
+            ["one", "two", "none"]
+        );
+    }
+}
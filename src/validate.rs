@@ -0,0 +1,450 @@
+use std::fmt::{self, Display};
+
+/// A syntax error found by [`validate_query`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuerySyntaxError {
+    offset: usize,
+    expected: &'static str,
+    found: Option<char>,
+}
+
+impl QuerySyntaxError {
+    /// Returns the byte offset in the query string at which the error was detected.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns a description of the token that was expected at [`offset`](Self::offset).
+    pub fn expected(&self) -> &'static str {
+        self.expected
+    }
+
+    /// Returns the character found at [`offset`](Self::offset), or `None` at the end of
+    /// the query.
+    pub fn found(&self) -> Option<char> {
+        self.found
+    }
+}
+
+impl Display for QuerySyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.found {
+            Some(c) => write!(
+                f,
+                "Invalid query syntax at byte {}: expected {}, found {:?}",
+                self.offset, self.expected, c
+            ),
+            None => write!(
+                f,
+                "Invalid query syntax at byte {}: expected {}, found end of query",
+                self.offset, self.expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for QuerySyntaxError {}
+
+/// Checks a Spotlight query string against the query expression grammar.
+///
+/// Accepts comparisons of an attribute (or `*`) with a quoted string, a number or a
+/// `$time` function, string comparison modifiers (`c`, `d`, `w`), `InRange(attr, low,
+/// high)`, and expressions combined with `&&`/`||` and grouped with parentheses.
+///
+/// This only checks syntax; whether attributes exist or values make sense is left to
+/// Spotlight.
+///
+/// # Parameters
+/// * `query` - The query string to check
+///
+/// # Returns
+/// `Ok(())` if the query is well-formed.
+///
+/// # Errors
+/// Returns a [`QuerySyntaxError`] describing the first problem found.
+pub fn validate_query(query: &str) -> Result<(), QuerySyntaxError> {
+    parse_query(query).map(|_| ())
+}
+
+/// A parsed query expression.
+#[cfg_attr(not(feature = "mock"), allow(dead_code))]
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum QueryNode {
+    And(Vec<QueryNode>),
+    Or(Vec<QueryNode>),
+    Compare {
+        /// The attribute name, or `*` for any attribute.
+        attribute: String,
+        op: QueryOp,
+        value: QueryValue,
+    },
+    InRange {
+        attribute: String,
+        low: QueryValue,
+        high: QueryValue,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum QueryOp {
+    Equal,
+    NotEqual,
+    LessThan,
+    GreaterThan,
+    LessThanOrEqual,
+    GreaterThanOrEqual,
+}
+
+#[cfg_attr(not(feature = "mock"), allow(dead_code))]
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum QueryValue {
+    String {
+        /// The text between the quotes, with escapes left in place so wildcards can be told
+        /// apart from escaped `*`.
+        raw: String,
+        modifiers: String,
+    },
+    Number(f64),
+    Time {
+        function: String,
+        argument: Option<String>,
+    },
+}
+
+/// Parses a query string into a [`QueryNode`] tree; see [`validate_query`] for the grammar.
+pub(crate) fn parse_query(query: &str) -> Result<QueryNode, QuerySyntaxError> {
+    let mut parser = Parser { query, pos: 0 };
+    let node = parser.or_expression()?;
+    parser.skip_whitespace();
+    if parser.pos < query.len() {
+        return Err(parser.error("`&&`, `||` or end of query"));
+    }
+    Ok(node)
+}
+
+struct Parser<'a> {
+    query: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.query[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn error(&self, expected: &'static str) -> QuerySyntaxError {
+        QuerySyntaxError {
+            offset: self.pos,
+            expected,
+            found: self.peek(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let trimmed = self.rest().trim_start();
+        self.pos = self.query.len() - trimmed.len();
+    }
+
+    /// Consumes `token` after optional whitespace if it comes next.
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str, expected: &'static str) -> Result<(), QuerySyntaxError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(expected))
+        }
+    }
+
+    fn or_expression(&mut self) -> Result<QueryNode, QuerySyntaxError> {
+        let mut nodes = vec![self.and_expression()?];
+        while self.eat("||") {
+            nodes.push(self.and_expression()?);
+        }
+        Ok(if nodes.len() == 1 {
+            nodes.remove(0)
+        } else {
+            QueryNode::Or(nodes)
+        })
+    }
+
+    fn and_expression(&mut self) -> Result<QueryNode, QuerySyntaxError> {
+        let mut nodes = vec![self.primary()?];
+        while self.eat("&&") {
+            nodes.push(self.primary()?);
+        }
+        Ok(if nodes.len() == 1 {
+            nodes.remove(0)
+        } else {
+            QueryNode::And(nodes)
+        })
+    }
+
+    fn primary(&mut self) -> Result<QueryNode, QuerySyntaxError> {
+        if self.eat("(") {
+            let node = self.or_expression()?;
+            self.expect(")", "`)`")?;
+            return Ok(node);
+        }
+        self.skip_whitespace();
+        let start = self.pos;
+        if self.identifier() == Some("InRange") {
+            return self.in_range();
+        }
+        self.pos = start;
+        self.comparison()
+    }
+
+    fn in_range(&mut self) -> Result<QueryNode, QuerySyntaxError> {
+        self.expect("(", "`(` after InRange")?;
+        let attribute = self.attribute()?;
+        self.expect(",", "`,`")?;
+        let low = self.value()?;
+        self.expect(",", "`,`")?;
+        let high = self.value()?;
+        self.expect(")", "`)`")?;
+        Ok(QueryNode::InRange {
+            attribute,
+            low,
+            high,
+        })
+    }
+
+    fn comparison(&mut self) -> Result<QueryNode, QuerySyntaxError> {
+        let attribute = self.attribute()?;
+        let op = self.operator()?;
+        let value = self.value()?;
+        Ok(QueryNode::Compare {
+            attribute,
+            op,
+            value,
+        })
+    }
+
+    fn identifier(&mut self) -> Option<&'a str> {
+        let rest = self.rest();
+        if !rest.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            return None;
+        }
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | ':')))
+            .unwrap_or(rest.len());
+        self.pos += len;
+        Some(&rest[..len])
+    }
+
+    fn attribute(&mut self) -> Result<String, QuerySyntaxError> {
+        if self.eat("*") {
+            return Ok("*".to_string());
+        }
+        match self.identifier() {
+            Some(name) => Ok(name.to_string()),
+            None => Err(self.error("attribute name")),
+        }
+    }
+
+    fn operator(&mut self) -> Result<QueryOp, QuerySyntaxError> {
+        // Longer operators first so `==` is not read as `=`.
+        for (token, op) in [
+            ("==", QueryOp::Equal),
+            ("!=", QueryOp::NotEqual),
+            ("<=", QueryOp::LessThanOrEqual),
+            (">=", QueryOp::GreaterThanOrEqual),
+            ("=", QueryOp::Equal),
+            ("<", QueryOp::LessThan),
+            (">", QueryOp::GreaterThan),
+        ] {
+            if self.eat(token) {
+                return Ok(op);
+            }
+        }
+        Err(self.error("comparison operator"))
+    }
+
+    fn value(&mut self) -> Result<QueryValue, QuerySyntaxError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(quote @ ('"' | '\'')) => self.string(quote),
+            Some('$') => self.time(),
+            Some(c) if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => {
+                self.number().map(QueryValue::Number)
+            }
+            _ => Err(self.error("value")),
+        }
+    }
+
+    fn string(&mut self, quote: char) -> Result<QueryValue, QuerySyntaxError> {
+        let start = self.pos;
+        self.pos += quote.len_utf8();
+        let content = self.rest();
+        let mut chars = content.char_indices();
+        let raw = loop {
+            match chars.next() {
+                Some((_, '\\')) => {
+                    chars.next();
+                }
+                Some((i, c)) if c == quote => {
+                    self.pos += i + c.len_utf8();
+                    break &content[..i];
+                }
+                Some(_) => {}
+                None => {
+                    self.pos = start;
+                    return Err(self.error("closing quote for string"));
+                }
+            }
+        };
+        let rest = self.rest();
+        let modifiers = rest
+            .find(|c: char| !matches!(c, 'c' | 'd' | 'w'))
+            .unwrap_or(rest.len());
+        self.pos += modifiers;
+        if self.peek().is_some_and(|c| c.is_ascii_alphanumeric()) {
+            return Err(self.error("string modifier `c`, `d` or `w`"));
+        }
+        Ok(QueryValue::String {
+            raw: raw.to_string(),
+            modifiers: rest[..modifiers].to_string(),
+        })
+    }
+
+    fn number(&mut self) -> Result<f64, QuerySyntaxError> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+')))
+            .unwrap_or(rest.len());
+        let Ok(number) = rest[..len].parse::<f64>() else {
+            return Err(self.error("number"));
+        };
+        self.pos += len;
+        Ok(number)
+    }
+
+    fn time(&mut self) -> Result<QueryValue, QuerySyntaxError> {
+        self.expect("$time.", "`$time.` function")?;
+        let Some(function) = self.identifier() else {
+            return Err(self.error("`$time` function name"));
+        };
+        let is_iso = function == "iso";
+        if !self.rest().starts_with('(') {
+            return if is_iso {
+                Err(self.error("`(` after $time.iso"))
+            } else {
+                Ok(QueryValue::Time {
+                    function: function.to_string(),
+                    argument: None,
+                })
+            };
+        }
+        self.pos += 1;
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest.find(')').unwrap_or(rest.len());
+        if is_iso {
+            if rest[..len].trim().is_empty() {
+                return Err(self.error("date for $time.iso"));
+            }
+            self.pos += len;
+        } else if len > 0 {
+            self.number()?;
+        }
+        self.expect(")", "`)`")?;
+        let argument = rest[..len].trim();
+        Ok(QueryValue::Time {
+            function: function.to_string(),
+            argument: (!argument.is_empty()).then(|| argument.to_string()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offset_of(query: &str) -> usize {
+        validate_query(query).unwrap_err().offset()
+    }
+
+    #[test]
+    fn test_valid_queries() {
+        for query in [
+            "kMDItemFSName = \"Safari.app\"",
+            "kMDItemFSName == \"*.app\"cd && kMDItemFSSize > 1024",
+            "((kMDItemDisplayName == \"my \\\"file*\"cdw) || (* == 'x'))",
+            "InRange(kMDItemFSSize, 1000, 2e6)",
+            "InRange(kMDItemContentModificationDate, $time.iso(2024-03-01T00:00:00+00:00), \
+             $time.now)",
+            "kMDItemLastUsedDate >= $time.today(-7) && kMDItemFSLabel != 6",
+        ] {
+            assert_eq!(validate_query(query), Ok(()), "{query}");
+        }
+    }
+
+    #[test]
+    fn test_parse_query() {
+        assert_eq!(
+            parse_query("a == \"x\\*\"cd || InRange(b, 1, $time.today(-1)) && c != 2"),
+            Ok(QueryNode::Or(vec![
+                QueryNode::Compare {
+                    attribute: "a".to_string(),
+                    op: QueryOp::Equal,
+                    value: QueryValue::String {
+                        raw: "x\\*".to_string(),
+                        modifiers: "cd".to_string(),
+                    },
+                },
+                QueryNode::And(vec![
+                    QueryNode::InRange {
+                        attribute: "b".to_string(),
+                        low: QueryValue::Number(1.0),
+                        high: QueryValue::Time {
+                            function: "today".to_string(),
+                            argument: Some("-1".to_string()),
+                        },
+                    },
+                    QueryNode::Compare {
+                        attribute: "c".to_string(),
+                        op: QueryOp::NotEqual,
+                        value: QueryValue::Number(2.0),
+                    },
+                ]),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_invalid_queries() {
+        assert_eq!(offset_of(""), 0);
+        assert_eq!(offset_of("invalid query syntax !!!"), 8);
+        assert_eq!(offset_of("kMDItemFSName == "), 17);
+        assert_eq!(offset_of("kMDItemFSName == \"Safari"), 17);
+        assert_eq!(offset_of("(kMDItemFSSize > 1"), 18);
+        assert_eq!(offset_of("kMDItemFSSize > 1 &&"), 20);
+        assert_eq!(offset_of("kMDItemFSName == \"a\"x"), 20);
+        assert_eq!(offset_of("InRange(kMDItemFSSize, 1)"), 24);
+        assert_eq!(offset_of("kMDItemFSSize > 1)"), 17);
+        assert_eq!(offset_of("kMDItemFSSize > 12ab"), 16);
+        assert_eq!(offset_of("kMDItemFSSize > $time.iso()"), 26);
+
+        let err = validate_query("kMDItemFSName ~ \"a\"").unwrap_err();
+        assert_eq!(err.offset(), 14);
+        assert_eq!(err.expected(), "comparison operator");
+        assert_eq!(err.found(), Some('~'));
+        assert_eq!(
+            err.to_string(),
+            "Invalid query syntax at byte 14: expected comparison operator, found '~'"
+        );
+    }
+}